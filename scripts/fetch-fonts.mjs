@@ -0,0 +1,36 @@
+// 下载随应用打包的中文字体（Noto Sans SC，SIL Open Font License）到 src-tauri/fonts/
+// 目录中已有字体文件时跳过；打包（tauri build）前自动运行
+import { existsSync, mkdirSync, readdirSync, writeFileSync } from 'node:fs';
+import { dirname, join } from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+const FONT_DIR = join(dirname(fileURLToPath(import.meta.url)), '..', 'src-tauri', 'fonts');
+const FONT_EXTENSIONS = ['.ttf', '.otf', '.ttc'];
+const BASE_URL = 'https://github.com/notofonts/noto-cjk/raw/main/Sans';
+const FILES = [
+    { url: `${BASE_URL}/SubsetOTF/SC/NotoSansSC-Regular.otf`, name: 'NotoSansSC-Regular.otf' },
+    { url: `${BASE_URL}/LICENSE`, name: 'NotoSansSC-OFL.txt' }
+];
+
+function hasFont() {
+    return existsSync(FONT_DIR) && readdirSync(FONT_DIR)
+        .some(name => FONT_EXTENSIONS.some(ext => name.toLowerCase().endsWith(ext)));
+}
+
+async function download({ url, name }) {
+    const response = await fetch(url);
+    if (!response.ok) {
+        throw new Error(`下载 ${url} 失败: HTTP ${response.status}`);
+    }
+    writeFileSync(join(FONT_DIR, name), Buffer.from(await response.arrayBuffer()));
+    console.log(`已下载 ${name}`);
+}
+
+if (hasFont()) {
+    console.log(`${FONT_DIR} 中已有字体文件，跳过下载`);
+} else {
+    mkdirSync(FONT_DIR, { recursive: true });
+    for (const file of FILES) {
+        await download(file);
+    }
+}
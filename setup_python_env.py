@@ -43,8 +43,10 @@ def verify_imports():
         'pandas': 'pandas>=1.5.0',
         'matplotlib': 'matplotlib>=3.6.0', 
         'numpy': 'numpy>=1.24.0',
-        'docx': 'python-docx>=0.8.11',
-        'openpyxl': 'openpyxl>=3.0.10'
+        'openpyxl': 'openpyxl>=3.0.10',
+        'xlrd': 'xlrd>=2.0.1',
+        'odf': 'odfpy>=1.4.1',
+        'pyxlsb': 'pyxlsb>=1.0.10'
     }
     
     print("\n验证模块导入...")
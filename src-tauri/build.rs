@@ -4,7 +4,20 @@ use std::process::Command;
 
 fn main() {
     println!("cargo:rerun-if-changed=python/");
+    println!("cargo:rerun-if-changed=fonts/");
+
+    // 未启用 python 功能时只构建Rust后端，不需要Python脚本和Python环境
+    if env::var_os("CARGO_FEATURE_PYTHON").is_some() {
+        check_python_scripts();
+    }
+
+    // 发布版本必须打包中文字体，否则在没有中文字体的电脑上无法生成图表和报告
+    check_bundled_font();
     
+    tauri_build::build()
+}
+
+fn check_python_scripts() {
     // 确保Python脚本存在
     let python_dir = Path::new("python");
     if !python_dir.exists() {
@@ -23,8 +36,6 @@ fn main() {
     }
     
     println!("Python脚本验证完成");
-    
-    tauri_build::build()
 }
 
 fn check_python_dependencies() {
@@ -32,7 +43,7 @@ fn check_python_dependencies() {
     
     // 尝试导入关键的Python模块
     let python_modules = vec![
-        "pandas", "matplotlib", "numpy", "docx", "openpyxl"
+        "pandas", "numpy", "openpyxl", "xlrd", "odf", "pyxlsb"
     ];
     
     for module in python_modules {
@@ -50,3 +61,22 @@ fn check_python_dependencies() {
         }
     }
 }
+
+fn check_bundled_font() {
+    let has_font = std::fs::read_dir("fonts")
+        .map(|entries| {
+            entries.filter_map(|entry| entry.ok()).any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                [".ttf", ".otf", ".ttc"].iter().any(|ext| name.ends_with(ext))
+            })
+        })
+        .unwrap_or(false);
+
+    if has_font {
+        return;
+    }
+    if env::var("PROFILE").unwrap_or_default() == "release" {
+        panic!("fonts 目录中没有字体文件，请先运行 npm run fetch-fonts");
+    }
+    println!("cargo:warning=fonts 目录中没有字体文件，图表将使用系统中文字体；运行 npm run fetch-fonts 下载打包字体");
+}
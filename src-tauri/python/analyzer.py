@@ -1,16 +1,10 @@
+import calendar
 import datetime
+import json
 import os
 import pandas as pd
-import matplotlib
-# 设置matplotlib为非交互式后端，避免GUI线程问题
-matplotlib.use('Agg')
-import matplotlib.pyplot as plt
 import numpy as np
 
-# 设置中文显示
-plt.rcParams['font.sans-serif'] = ['SimHei']  # 用来正常显示中文标签
-plt.rcParams['axes.unicode_minus'] = False  # 用来正常显示负号
-
 class BorrowingAnalyzer:
     def __init__(self):
         """
@@ -18,38 +12,91 @@ class BorrowingAnalyzer:
         """
         self.data = None
         self.member_data = None
+        self.member_resolver = None
+        self.membership_duration = None
+        # 图表由Rust端绘制：chart_renderer(输出目录, 图表数据JSON) -> {图表类型: 路径}，初始化时设置
+        self.chart_renderer = None
+        # 报告由Rust端写入：report_writer(输出路径, 报告数据JSON)，初始化时设置
+        self.report_writer = None
         self.results = None
     
-    def load_excel_files(self, borrow_paths: list[str], member_path: str, progress_callback=None):
+    @staticmethod
+    def _read_table(path: str, options=None, sheet=None, text_columns=None):
+        """
+        按Rust端识别的格式读取表格
+        :param options: {"format": "csv", "sep": ",", "encoding": "gb18030"} 或 {"format": "excel", "engine": "xlrd"}
+        :param sheet: 工作表名称，为 None 时读取第一个工作表；文本文件忽略该参数
+        :param text_columns: 按文本读取的列（原始表头），如读者证号，避免前导零丢失
+        """
+        options = options or {}
+        dtype = {column: str for column in text_columns or []}
+        if options.get('format') == 'csv':
+            return pd.read_csv(path, sep=options.get('sep', ','), encoding=options.get('encoding', 'utf-8'),
+                               dtype=dtype)
+        return pd.read_excel(path, sheet_name=sheet if sheet is not None else 0, engine=options.get('engine'),
+                             dtype=dtype)
+
+    @staticmethod
+    def _clean_text(value):
+        """单元格文本（去除首尾空白），空值返回 None"""
+        if value is None or (not isinstance(value, str) and pd.isna(value)):
+            return None
+        text = str(value).strip()
+        return text or None
+
+    def load_excel_files(self, borrow_paths: list[str], member_path: str, progress_callback=None,
+                         column_renames=None, read_options=None, entries=None, member_resolver=None,
+                         membership_duration=None):
         """
-        加载Excel数据文件
+        加载数据文件（xlsx/xls/xlsb/ods/csv/tsv）
         :param borrow_paths: 借阅数据文件路径列表
         :param member_path: 读者清单文件路径
         :param progress_callback: 进度回调函数 (total, current, message)
+        :param column_renames: 各文件的列重命名表 {文件路径: {实际表头: 标准表头}}，由Rust端的表头映射生成
+        :param read_options: 各文件的读取参数 {文件路径: {...}}，由Rust端按文件内容识别
+        :param entries: 借阅数据条目 [{"path", "sheet", "name", "renames"}]，每个选中的工作表一个条目；
+                        为 None 时每个文件读取第一个工作表
+        :param member_resolver: 读者匹配函数 (姓名, 读者证号) -> JSON，由Rust端提供（按证号或规范化姓名匹配）；
+                                为 None 时按姓名精确匹配
+        :param membership_duration: 会员时长计算函数 (读者匹配结果JSON, 统计日期) -> JSON，由Rust端提供
         """
+        column_renames = column_renames or {}
+        read_options = read_options or {}
+        if entries is None:
+            entries = [{
+                'path': path,
+                'sheet': None,
+                'name': os.path.splitext(os.path.basename(path))[0],
+                'renames': column_renames.get(path, {}),
+            } for path in borrow_paths]
         try:
             # 读取读者清单
-            self.member_data = pd.read_excel(member_path)
+            self.member_data = self._read_table(member_path, read_options.get(member_path))
+            self.member_data = self.member_data.rename(columns=column_renames.get(member_path, {}))
+            self.member_resolver = member_resolver or self._match_member_by_name
+            self.membership_duration = membership_duration or self._calculate_membership_duration
             
             # 存储所有文件的数据，支持单独分析
             self.file_data_list = []
             self.file_names = []
             
-            total_files = len(borrow_paths)
+            total_files = len(entries)
             
-            for i, path in enumerate(borrow_paths):
+            for i, entry in enumerate(entries):
+                path = entry['path']
                 if progress_callback:
-                    progress_callback(total_files, i, f"正在读取文件 {os.path.basename(path)}")
+                    progress_callback(total_files, i, f"正在读取文件 {entry['name']}")
                 
-                df = pd.read_excel(path)
+                renames = entry.get('renames', {})
+                text_columns = [actual for actual, canonical in renames.items() if canonical == '读者证号']
+                df = self._read_table(path, read_options.get(path), entry.get('sheet'), text_columns)
+                df = df.rename(columns=renames)
                 self.file_data_list.append(df)
-                
-                # 提取文件名（不含扩展名）
-                file_name = os.path.splitext(os.path.basename(path))[0]
-                self.file_names.append(file_name)
+                self.file_names.append(entry['name'])
                 
             # 同时保存合并后的数据（用于整体分析）
             self.data = pd.concat(self.file_data_list, ignore_index=True)
+            self._group_readers()
             
             if progress_callback:
                 progress_callback(total_files, total_files, "数据加载完成")
@@ -57,13 +104,14 @@ class BorrowingAnalyzer:
             return True
             
         except Exception as e:
-            raise Exception(f"加载Excel文件失败: {str(e)}")
+            raise Exception(f"加载数据文件失败: {e!r}") from e
 
-    def analyze_single_file(self, file_index: int, progress_callback=None):
+    def analyze_single_file(self, file_index: int, progress_callback=None, as_of=None):
         """
         分析单个文件的借阅数据
         :param file_index: 文件索引
         :param progress_callback: 进度回调函数 (total, current, message)
+        :param as_of: 统计日期 (YYYY-MM-DD)，为 None 时使用当天
         :return: 分析结果对象
         """
         if not hasattr(self, 'file_data_list') or file_index >= len(self.file_data_list):
@@ -75,7 +123,7 @@ class BorrowingAnalyzer:
         
         try:
             # 调用原有的分析方法
-            result = self.analyze_with_progress(progress_callback)
+            result = self.analyze_with_progress(progress_callback, as_of)
             return result
         finally:
             # 恢复原始数据
@@ -91,11 +139,137 @@ class BorrowingAnalyzer:
             return self.file_names[file_index]
         return f"文件{file_index + 1}"
 
-    def analyze_with_progress(self, progress_callback=None):
+    def _reader_keys(self, data):
+        """每行的 (姓名, 读者证号)，已去除首尾空白"""
+        names = [self._clean_text(v) for v in data['姓名']]
+        if '读者证号' in data.columns:
+            cards = [self._clean_text(v) for v in data['读者证号']]
+        else:
+            cards = [None] * len(names)
+        return list(zip(names, cards))
+
+    def _reader_groups(self, data):
+        """
+        按读者分组：有读者证号的记录按证号分组，名称为 `姓名_证号`；否则按姓名分组。
+        分组按首次出现的顺序排列，姓名和证号都为空的记录不属于任何读者
+        :return: [(名称, 行索引列表, 第一条记录的 (姓名, 读者证号))]
+        """
+        groups = {}
+        for row, (name, card) in zip(data.index, self._reader_keys(data)):
+            if card is not None:
+                key = ('card', card)
+                display = f"{name}_{card}" if name is not None else card
+            elif name is not None:
+                key = ('name', name)
+                display = name
+            else:
+                continue
+            groups.setdefault(key, (display, [], (name, card)))[1].append(row)
+        return list(groups.values())
+
+    def _group_readers(self):
+        """按读者分组合并后的数据"""
+        groups = self._reader_groups(self.data)
+        self.reader_names = [display for display, _, _ in groups]
+        self.reader_rows = [rows for _, rows, _ in groups]
+
+    def _match_member_by_name(self, name, card=None):
+        """未提供Rust端读者匹配函数时使用：按姓名精确匹配读者清单，返回与Rust端相同格式的JSON"""
+        rows = [i for i, member in enumerate(self.member_data['姓名'])
+                if name is not None and self._clean_text(member) == name]
+        identity = {
+            'name': name,
+            'card_number': card,
+            'status': 'unmatched',
+            'matched_by': None,
+            'member_row': None,
+            'registration_date': None,
+            'candidates': [],
+        }
+        if len(rows) == 1:
+            open_date = self.member_data['办卡日期'].iloc[rows[0]]
+            identity.update({
+                'status': 'matched',
+                'matched_by': 'name',
+                'member_row': rows[0] + 2,
+                'registration_date': None if pd.isna(open_date) else pd.to_datetime(open_date).isoformat(),
+            })
+        elif len(rows) > 1:
+            identity['status'] = 'ambiguous'
+            identity['candidates'] = [{'row': i + 2, 'name': name, 'card_number': None} for i in rows]
+        return json.dumps(identity, ensure_ascii=False)
+
+    def get_reader_count(self) -> int:
+        """获取合并数据中的读者数量"""
+        return len(self.reader_rows) if hasattr(self, 'reader_rows') else 0
+
+    def get_reader_name(self, reader_index: int) -> str:
+        """获取指定索引的读者名称"""
+        if hasattr(self, 'reader_names') and reader_index < len(self.reader_names):
+            return self.reader_names[reader_index]
+        return f"读者{reader_index + 1}"
+
+    def analyze_reader(self, reader_index: int, progress_callback=None, as_of=None):
+        """
+        分析单个读者的借阅数据（合并所有文件后按读者分组）
+        :param reader_index: 读者索引
+        :param progress_callback: 进度回调函数 (total, current, message)
+        :param as_of: 统计日期 (YYYY-MM-DD)，为 None 时使用当天
+        :return: 分析结果对象
+        """
+        if not hasattr(self, 'reader_rows') or reader_index >= len(self.reader_rows):
+            raise ValueError("无效的读者索引或未加载数据")
+
+        # 临时设置当前分析的数据为该读者的记录
+        original_data = self.data
+        self.data = original_data.loc[self.reader_rows[reader_index]].reset_index(drop=True)
+
+        try:
+            return self.analyze_with_progress(progress_callback, as_of)
+        finally:
+            # 恢复原始数据
+            self.data = original_data
+
+    @staticmethod
+    def _add_months(date: datetime.date, months: int) -> datetime.date:
+        """加上若干个月，超出月末时取月末"""
+        year, month = divmod(date.month - 1 + months, 12)
+        year += date.year
+        day = min(date.day, calendar.monthrange(year, month + 1)[1])
+        return datetime.date(year, month + 1, day)
+
+    def _calculate_membership_duration(self, reader_identity: str, as_of: str) -> str:
+        """未提供Rust端会员时长计算函数时使用，返回与Rust端相同格式的JSON"""
+        identity = json.loads(reader_identity)
+        as_of_date = datetime.date.fromisoformat(as_of)
+        if identity['status'] != 'matched':
+            return json.dumps({'status': 'unknown', 'as_of': as_of, 'reason': 'reader_not_resolved'})
+        if identity['registration_date'] is None:
+            return json.dumps({'status': 'unknown', 'as_of': as_of, 'reason': 'missing_join_date'})
+        join_date = datetime.date.fromisoformat(identity['registration_date'][:10])
+        if join_date > as_of_date:
+            return json.dumps({'status': 'unknown', 'as_of': as_of, 'reason': 'join_after_as_of'})
+
+        months = (as_of_date.year - join_date.year) * 12 + as_of_date.month - join_date.month
+        if self._add_months(join_date, months) > as_of_date:
+            months -= 1
+        return json.dumps({
+            'status': 'known',
+            'join_date': join_date.isoformat(),
+            'as_of': as_of,
+            'total_days': (as_of_date - join_date).days,
+            'years': months // 12,
+            'months': months % 12,
+            'days': (as_of_date - self._add_months(join_date, months)).days,
+        })
+
+    def analyze_with_progress(self, progress_callback=None, as_of=None):
         """
         分析借阅数据并提供进度回调
         :param progress_callback: 进度回调函数 (total, current, message)
+        :param as_of: 计算会员时长的统计日期 (YYYY-MM-DD)，为 None 时使用当天；相同的日期可以重现之前的结果
         """
+        as_of = as_of or datetime.date.today().isoformat()
         if self.data is None or self.member_data is None:
             raise ValueError("请先加载数据文件")
 
@@ -116,18 +290,17 @@ class BorrowingAnalyzer:
             if progress_callback:
                 progress_callback(100, 30, "计算会员时长")
 
-            # 获取会员时长
-            open_date = None
-            member_name = self.data['姓名'].iloc[0]
-            for i in range(len(self.member_data)):
-                if self.member_data['姓名'].iloc[i] == member_name:
-                    open_date = self.member_data['办卡日期'].iloc[i]
-                    break
-            
-            if open_date is None:
-                total_duration = "未知。请检查读者清单是否包含该读者。"
-            else:
-                total_duration = datetime.datetime.now() - pd.to_datetime(open_date)
+            # 获取会员时长：按第一条记录的读者证号或姓名在读者清单中查找
+            reader_identity = self.member_resolver(*self._reader_keys(self.data)[0])
+            identity = json.loads(reader_identity)
+            total_duration = self.membership_duration(reader_identity, as_of)
+
+            # 未能唯一匹配的读者（每个读者取第一条记录）
+            unresolved_readers = []
+            for _, _, (name, card) in self._reader_groups(self.data):
+                result = self.member_resolver(name, card)
+                if json.loads(result)['status'] != 'matched':
+                    unresolved_readers.append(result)
 
             if progress_callback:
                 progress_callback(100, 40, "计算统计指标")
@@ -147,6 +320,8 @@ class BorrowingAnalyzer:
             # v2功能：按年-月格式统计月度借阅
             self.data['借书年月'] = pd.to_datetime(self.data['借书日期']).dt.strftime('%Y-%m')
             monthly_borrow = self.data.groupby('借书年月')['书名'].count()
+            # 连续的年月时间线，没有借阅的月份补0
+            monthly_timeline = self.month_timeline(monthly_borrow)
             monthly_borrow = monthly_borrow.sort_values(ascending=False)
 
             if progress_callback:
@@ -176,10 +351,11 @@ class BorrowingAnalyzer:
                 progress_callback(100, 80, "分析借阅高峰")
 
             # 借阅高峰期和最多借阅的书
-            # v2更新：借阅高峰期改为年-月格式
-            borrow_peak_yearmonth = self.data.groupby('借书年月')['书名'].count().sort_values(ascending=False).idxmax()
-            # 保留原有的月份高峰期（用于图表）
-            borrow_peak = self.data.groupby('借书月份')['书名'].count().sort_values(ascending=False).idxmax()
+            # 高峰期取时间线上借阅册数最多的年月（并列时取最早的），borrow_peak 为该年月的月份
+            borrow_peak_yearmonth = monthly_timeline.idxmax()
+            borrow_peak = int(borrow_peak_yearmonth.split('-')[1])
+            # 图表数据：各类别借阅册数
+            category_count = self.data.groupby('类别')['书名'].count()
             most_borrowed_book = self.data['书名'].value_counts().idxmax()
             most_borrowed_book_count = self.data['书名'].value_counts().max()
 
@@ -241,10 +417,12 @@ class BorrowingAnalyzer:
             class AnalysisResults:
                 def __init__(self):
                     self.borrow_frequency = borrow_frequency
-                    self.total_duration = str(total_duration)
+                    self.total_duration = total_duration
                     self.total_reading_duration = total_reading_duration
                     self.total_price = total_price
                     self.monthly_borrow = dict(monthly_borrow)
+                    self.monthly_timeline = [(str(month), int(count))
+                                             for month, count in monthly_timeline.items()]
                     self.longest_author = longest_author
                     self.longest_category = longest_category
                     # v2新增字段
@@ -258,11 +436,13 @@ class BorrowingAnalyzer:
                     self.most_borrowed_book_count = most_borrowed_book_count
                     self.full_attendance = full_attendance
                     self.total_full_attendance = total_full_attendance
+                    self.reader_identity = reader_identity
+                    self.unresolved_readers = unresolved_readers
             
             # 保存分析结果为字典（用于内部使用）
             self.results = {
                 'borrow_frequency': borrow_frequency,
-                'total_duration': total_duration,
+                'total_duration': json.loads(total_duration),
                 'total_reading_duration': total_reading_duration,
                 'total_price': total_price,
                 'monthly_duration': monthly_duration,
@@ -270,6 +450,8 @@ class BorrowingAnalyzer:
                 'book_duration': book_duration,
                 'book_duration_ratio': book_duration_ratio,
                 'sorted_duration': sorted_duration,
+                'monthly_timeline': monthly_timeline,
+                'category_count': category_count,
                 # v2新增字段
                 'book_borrow_counts': book_borrow_counts,
                 'most_frequent_author': most_frequent_author,
@@ -285,7 +467,9 @@ class BorrowingAnalyzer:
                 'most_borrowed_book': most_borrowed_book,
                 'most_borrowed_book_count': most_borrowed_book_count,
                 'full_attendance': full_attendance,
-                'total_full_attendance': total_full_attendance
+                'total_full_attendance': total_full_attendance,
+                'reader_identity': identity,
+                'unresolved_readers': [json.loads(result) for result in unresolved_readers]
             }
 
             results_obj = AnalysisResults()
@@ -296,7 +480,7 @@ class BorrowingAnalyzer:
             return results_obj
 
         except Exception as e:
-            raise Exception(f"分析数据失败: {str(e)}")
+            raise Exception(f"分析数据失败: {e!r}") from e
 
     def calculate_actual_weeks(self, year: int, month: int) -> tuple[int, list[str]]:
         """
@@ -355,65 +539,54 @@ class BorrowingAnalyzer:
         }
         return category_dict.get(code, code)
 
+    @staticmethod
+    def month_timeline(monthly_counts):
+        """
+        连续的年月时间线（与Rust后端的 month_timeline 一致）
+        :param monthly_counts: 以 YYYY-MM 为索引的借阅册数
+        :return: 从最早到最晚的月份逐月排列的 Series，没有记录的月份为0
+        """
+        if monthly_counts.empty:
+            return monthly_counts
+        months = pd.period_range(start=monthly_counts.index.min(),
+                                 end=monthly_counts.index.max(), freq='M').strftime('%Y-%m')
+        return monthly_counts.reindex(months, fill_value=0)
+
+    def chart_data(self) -> dict:
+        """
+        图表数据（与Rust后端的 ChartData 一致）
+        :return: {'book_duration': [(书名, 天数)], 'monthly_borrow': [(年月, 册数)], 'category_count': [(类别, 册数)]}
+        """
+        if self.results is None:
+            raise ValueError("请先执行分析")
+
+        try:
+            return {
+                'book_duration': [(str(book), int(duration))
+                                  for book, duration in self.results['sorted_duration'].items()],
+                'monthly_borrow': [(str(month), int(count))
+                                   for month, count in self.results['monthly_timeline'].items()],
+                'category_count': [(str(category), int(count))
+                                   for category, count in self.results['category_count'].items()],
+            }
+        except Exception as e:
+            raise Exception(f"获取图表数据失败: {e!r}") from e
+
     def generate_charts(self, output_dir: str, progress_callback=None) -> dict:
         """
-        生成所有图表并保存到指定目录
+        生成所有图表（PNG和SVG）并保存到指定目录，由Rust端绘制
         :param output_dir: 输出目录路径
         :param progress_callback: 进度回调函数 (total, current, message)
         :return: 包含所有图表文件路径的字典
         """
-        if self.results is None:
-            raise ValueError("请先执行分析")
+        if self.chart_renderer is None:
+            raise ValueError("未设置图表绘制函数")
 
         try:
-            # 确保输出目录存在
-            os.makedirs(output_dir, exist_ok=True)
-            chart_paths = {}
-            
             if progress_callback:
                 progress_callback(100, 0, "开始生成图表")
 
-            # 1. 阅读时长条形图
-            book_duration = self.results['sorted_duration'].head(10)
-            book_duration = book_duration[::-1]
-            plt.figure(figsize=(10, 6))
-            book_duration.plot(kind='barh')
-            plt.title('每本书的阅读时长')
-            plt.xlabel('时长（天）')
-            plt.ylabel('书名')
-            plt.tight_layout()
-            plt.savefig(f"{output_dir}/book_duration.png", transparent=True, dpi=150)
-            plt.close()
-            chart_paths['duration'] = f"{output_dir}/book_duration.png"
-
-            if progress_callback:
-                progress_callback(100, 33, "已生成阅读时长图表")
-
-            # 2. 借阅频次折线图
-            borrow_per_month = self.data.groupby('借书月份')['书名'].count()
-            plt.figure(figsize=(10, 6))
-            borrow_per_month.plot(kind='line', marker='o')
-            plt.title('每月借阅频次')
-            plt.xlabel('月份')
-            plt.ylabel('频次')
-            plt.tight_layout()
-            plt.savefig(f"{output_dir}/borrow_per_month.png", transparent=True, dpi=150)
-            plt.close()
-            chart_paths['monthly'] = f"{output_dir}/borrow_per_month.png"
-
-            if progress_callback:
-                progress_callback(100, 66, "已生成借阅频次图表")
-
-            # 3. 类别阅读册数饼图
-            category_count = self.data.groupby('类别')['书名'].count()
-            plt.figure(figsize=(8, 8))
-            category_count.plot(kind='pie', autopct='%1.1f%%')
-            plt.title('类别阅读册数比例')
-            plt.ylabel('')
-            plt.tight_layout()
-            plt.savefig(f"{output_dir}/category_ratio.png", transparent=True, dpi=150)
-            plt.close()
-            chart_paths['category'] = f"{output_dir}/category_ratio.png"
+            chart_paths = dict(self.chart_renderer(output_dir, json.dumps(self.chart_data(), ensure_ascii=False)))
 
             if progress_callback:
                 progress_callback(100, 100, "图表生成完成")
@@ -421,168 +594,181 @@ class BorrowingAnalyzer:
             return chart_paths
 
         except Exception as e:
-            raise Exception(f"生成图表失败: {str(e)}")
+            raise Exception(f"生成图表失败: {e!r}") from e
 
-    def export_report(self, output_path: str, progress_callback=None):
+    def report_data(self) -> dict:
         """
-        导出分析报告为Word文档
-        :param output_path: 输出文件路径
-        :param progress_callback: 进度回调函数 (total, current, message)
+        报告数据（与Rust后端的 ReportData 一致）
+        :return: 基础统计、每月借书数量、借阅次数和借书时长排序（前十）、全勤月、缺勤月、读者匹配结果及图表数据
         """
         if self.results is None:
             raise ValueError("请先执行分析")
 
         try:
-            from docx import Document
-            import docx.shared
-            doc = Document()
-
-            if progress_callback:
-                progress_callback(100, 0, "开始生成报告")
-
-            # 添加标题
-            doc.add_heading('读书报告分析结果', level=1)
-
-            # 添加基础统计信息（与原型脚本格式一致）
-            if progress_callback:
-                progress_callback(100, 25, "添加基础统计信息")
-
-            # v2更新：报告格式与main_v2.py保持一致
-            basic_info = (
-                f"借阅频次: {self.results['borrow_frequency']}\n"
-                f"总时长: {self.results['total_duration']}\n"
-                f"总价值: {self.results['total_price']} 元\n"
-                f"借阅次数最多的作者: {self.results['most_frequent_author']}\n"
-                f"借阅次数最多的类别: {self.results['most_frequent_category']}\n"
-                f"阅读时长最长的作者: {self.results['longest_author']}\n"
-                f"阅读时长最长的类别: {self.results['longest_category']}\n"
-                f"借阅高峰期: {self.results['borrow_peak_yearmonth']}\n"
-                f"借阅次数最多的书: {self.results['most_borrowed_book']}，共借阅 {self.results['most_borrowed_book_count']} 次\n"
-                f"全勤月总数: {self.results['total_full_attendance']}"
-            )
-            doc.add_paragraph(basic_info)
-
-            # 添加每月借书数量排序（与原型脚本格式一致）
-            if progress_callback:
-                progress_callback(100, 40, "添加月度统计")
-
-            doc.add_heading('每月借书数量排序', level=2)
-            monthly_borrow_text = '\n'.join([f"{month}: {count}"
-                                         for month, count in self.results['monthly_borrow'].items()])
-            doc.add_paragraph(monthly_borrow_text)
-
-            # v2更新：添加书籍借阅次数排序（前十）
-            if progress_callback:
-                progress_callback(100, 55, "添加借阅次数排序")
-
-            doc.add_heading('书籍借阅次数排序（前十）', level=2)
-            borrow_counts_text = '\n'.join([f"{book}: {count}次"
-                                           for book, count in self.results['book_borrow_counts'].head(10).items()])
-            doc.add_paragraph(borrow_counts_text)
-            
-            # 保留借书时长排序（用于图表）
-            doc.add_heading('借书时长排序（前十）', level=2)
-            sorted_duration_text = '\n'.join([f"{book}: {duration}天"
-                                           for book, duration in self.results['sorted_duration'].head(10).items()])
-            doc.add_paragraph(sorted_duration_text)
-
-            # 添加全勤月列表
-            if progress_callback:
-                progress_callback(100, 65, "添加全勤月列表")
-
-            doc.add_heading('全勤月列表', level=2)
-            doc.add_paragraph(', '.join(self.results['full_attendance']))
-
-            # 添加缺勤月列表
-            if progress_callback:
-                progress_callback(100, 70, "添加缺勤月列表")
-            
-            doc.add_heading('缺勤月列表', level=2)
-            if self.results['partial_attendance']:
-                partical_attendance_info = ""
-                for month in self.results['partial_attendance']:
-                    missing_info = self.results['missing_weeks_info'].get(month, {})
-                    partical_attendance_info += f"{month}:\n"
-                    partical_attendance_info += f"  实际借阅周: {', '.join(missing_info.get('recorded_weeks', []))}\n"
-                    partical_attendance_info += f"  修正后借阅周: {', '.join(missing_info.get('corrected_recorded_weeks', []))}\n"
-                    partical_attendance_info += f"  缺失周: {', '.join(missing_info.get('missing_weeks', []))}\n"
-                    partical_attendance_info += f"  总周数: {', '.join(missing_info.get('total_weeks', []))}\n"
-                doc.add_paragraph(partical_attendance_info.strip())
-            else:
-                doc.add_paragraph("无缺勤月")
+            results = self.results
+            missing_weeks_info = results['missing_weeks_info']
+            return {
+                'borrow_frequency': int(results['borrow_frequency']),
+                'total_duration': results['total_duration'],
+                'total_reading_duration': int(results['total_reading_duration']),
+                'total_price': float(results['total_price']),
+                'most_frequent_author': str(results['most_frequent_author']),
+                'most_frequent_category': str(results['most_frequent_category']),
+                'longest_author': str(results['longest_author']),
+                'longest_category': str(results['longest_category']),
+                'borrow_peak_yearmonth': str(results['borrow_peak_yearmonth']),
+                'most_borrowed_book': str(results['most_borrowed_book']),
+                'most_borrowed_book_count': int(results['most_borrowed_book_count']),
+                'borrow_peak': int(results['borrow_peak']),
+                'total_full_attendance': int(results['total_full_attendance']),
+                'monthly_borrow': [(str(month), int(count))
+                                   for month, count in results['monthly_borrow'].items()],
+                'book_borrow_counts': [(str(book), int(count))
+                                       for book, count in results['book_borrow_counts'].head(10).items()],
+                'sorted_duration': [(str(book), int(duration))
+                                    for book, duration in results['sorted_duration'].head(10).items()],
+                'full_attendance': [str(month) for month in results['full_attendance']],
+                'partial_attendance': [
+                    (str(month), {key: [str(week) for week in missing_weeks_info.get(month, {}).get(key, [])]
+                                  for key in ('recorded_weeks', 'corrected_recorded_weeks',
+                                              'missing_weeks', 'total_weeks')})
+                    for month in results['partial_attendance']
+                ],
+                'reader_identity': results['reader_identity'],
+                'unresolved_readers': results['unresolved_readers'],
+                'chart_data': self.chart_data(),
+            }
+        except Exception as e:
+            raise Exception(f"获取报告数据失败: {e!r}") from e
 
-            # 添加图表
-            if progress_callback:
-                progress_callback(100, 75, "生成并添加图表")
+    def analysis_data(self) -> dict:
+        """
+        导出的完整分析结果（与Rust后端的 AnalysisData 一致），排行不截取前十
+        :return: 基础统计、各排序表、全勤月和缺勤月（含缺失周信息）及读者匹配结果
+        """
+        if self.results is None:
+            raise ValueError("请先执行分析")
 
-            # 确保输出目录存在
-            chart_dir = os.path.dirname(output_path)
-            if not chart_dir:
-                chart_dir = "."
-            
-            chart_paths = self.generate_charts(chart_dir)
+        try:
+            results = self.results
+            return {
+                'borrow_frequency': int(results['borrow_frequency']),
+                'total_duration': results['total_duration'],
+                'total_reading_duration': int(results['total_reading_duration']),
+                'total_price': float(results['total_price']),
+                'most_frequent_author': str(results['most_frequent_author']),
+                'most_frequent_category': str(results['most_frequent_category']),
+                'longest_author': str(results['longest_author']),
+                'longest_category': str(results['longest_category']),
+                'borrow_peak_yearmonth': str(results['borrow_peak_yearmonth']),
+                'borrow_peak': int(results['borrow_peak']),
+                'most_borrowed_book': str(results['most_borrowed_book']),
+                'most_borrowed_book_count': int(results['most_borrowed_book_count']),
+                'total_full_attendance': int(results['total_full_attendance']),
+                'monthly_duration': [(int(month), int(duration))
+                                     for month, duration in results['monthly_duration'].items()],
+                'monthly_borrow': [(str(month), int(count))
+                                   for month, count in results['monthly_borrow'].items()],
+                'monthly_timeline': [(str(month), int(count))
+                                     for month, count in results['monthly_timeline'].items()],
+                'book_duration': [(str(book), int(duration))
+                                  for book, duration in results['book_duration'].items()],
+                'book_duration_ratio': [(str(book), float(ratio))
+                                        for book, ratio in results['book_duration_ratio'].items()],
+                'sorted_duration': [(str(book), int(duration))
+                                    for book, duration in results['sorted_duration'].items()],
+                'category_count': [(str(category), int(count))
+                                   for category, count in results['category_count'].items()],
+                'book_borrow_counts': [(str(book), int(count))
+                                       for book, count in results['book_borrow_counts'].items()],
+                'full_attendance': [str(month) for month in results['full_attendance']],
+                'partial_attendance': [str(month) for month in results['partial_attendance']],
+                'missing_weeks_info': {
+                    str(month): {key: [str(week) for week in info.get(key, [])]
+                                 for key in ('recorded_weeks', 'corrected_recorded_weeks',
+                                             'missing_weeks', 'total_weeks')}
+                    for month, info in results['missing_weeks_info'].items()
+                },
+                'reader_identity': results['reader_identity'],
+                'unresolved_readers': results['unresolved_readers'],
+            }
+        except Exception as e:
+            raise Exception(f"获取分析结果失败: {e!r}") from e
 
-            # 每本书的阅读时长
-            doc.add_heading('每本书的阅读时长', level=2)
-            doc.add_picture(chart_paths['duration'],
-                          width=docx.shared.Inches(6),
-                          height=docx.shared.Inches(4))
-            
-            # 每月借阅频次
-            doc.add_heading('每月借阅频次', level=2)
-            doc.add_picture(chart_paths['monthly'],
-                          width=docx.shared.Inches(6),
-                          height=docx.shared.Inches(4))
-            
-            # 类别阅读时长比例
-            doc.add_heading('类别阅读时长比例', level=2)
-            doc.add_picture(chart_paths['category'],
-                          width=docx.shared.Inches(6),
-                          height=docx.shared.Inches(4))
+    def export_report(self, output_path: str, progress_callback=None, report_format=None):
+        """
+        导出分析报告，由Rust端写入
+        :param output_path: 输出文件路径
+        :param progress_callback: 进度回调函数 (total, current, message)
+        :param report_format: 报告格式 (docx/pdf/html/markdown)，为 None 时根据扩展名判断
+        """
+        if self.report_writer is None:
+            raise ValueError("未设置报告写入函数")
 
-            # 保存文档
+        try:
             if progress_callback:
-                progress_callback(100, 90, "保存报告")
+                progress_callback(100, 0, "开始生成报告")
 
-            doc.save(output_path)
+            self.report_writer(output_path, json.dumps(self.report_data(), ensure_ascii=False), report_format)
 
             if progress_callback:
                 progress_callback(100, 100, "报告生成完成")
 
         except Exception as e:
-            raise Exception(f"导出报告失败: {str(e)}")
+            raise Exception(f"导出报告失败: {e!r}") from e
 
-    def export_reports_for_all_files(self, base_output_dir: str, progress_callback=None):
+    def export_reports_for_all_files(self, base_output_dir: str, progress_callback=None, group_by: str = 'file',
+                                     as_of=None, should_cancel=None, on_output=None, report_format: str = 'docx',
+                                     report_extension: str = 'docx'):
         """
-        为所有加载的文件分别生成报告（模拟原型脚本的循环处理逻辑）
+        为所有加载的文件（或所有读者）分别生成报告（模拟原型脚本的循环处理逻辑）
         :param base_output_dir: 基础输出目录
         :param progress_callback: 进度回调函数 (total, current, message)
+        :param group_by: 'file' 每个文件一份报告，'reader' 每位读者一份报告
+        :param as_of: 统计日期 (YYYY-MM-DD)，为 None 时使用当天
+        :param should_cancel: 返回 True 时在下一份报告开始前停止，返回已生成的报告
+        :param on_output: 每份报告生成后以报告路径调用，失败或取消时已生成的报告也能记录下来
+        :param report_format: 报告格式 (docx/pdf/html/markdown)
+        :param report_extension: 报告文件的扩展名，与 report_format 对应
         :return: 生成的报告文件路径列表
         """
         if not hasattr(self, 'file_data_list'):
             raise ValueError("请先加载数据文件")
 
+        if group_by == 'reader':
+            total_files = self.get_reader_count()
+            get_name, analyze = self.get_reader_name, self.analyze_reader
+        else:
+            total_files = len(self.file_data_list)
+            get_name, analyze = self.get_file_name, self.analyze_single_file
+
         try:
             report_paths = []
-            total_files = len(self.file_data_list)
             
             for i in range(total_files):
+                if should_cancel and should_cancel():
+                    if progress_callback:
+                        progress_callback(total_files, i, f"已取消，已生成 {len(report_paths)} 份报告")
+                    return report_paths
+
                 if progress_callback:
-                    progress_callback(total_files, i, f"正在为文件 {self.get_file_name(i)} 生成报告")
+                    progress_callback(total_files, i, f"正在为 {get_name(i)} 生成报告")
                 
                 # 为每个文件创建独立的输出目录
-                file_name = self.get_file_name(i)
+                file_name = get_name(i)
                 file_output_dir = os.path.join(base_output_dir, file_name)
                 os.makedirs(file_output_dir, exist_ok=True)
                 
-                # 分析单个文件
-                self.analyze_single_file(i)
+                # 分析单个文件或读者
+                analyze(i, as_of=as_of)
                 
                 # 生成报告
-                report_path = os.path.join(file_output_dir, f"{file_name}_analysis.docx")
-                self.export_report(report_path)
+                report_path = os.path.join(file_output_dir, f"{file_name}_analysis.{report_extension}")
+                self.export_report(report_path, report_format=report_format)
                 
                 report_paths.append(report_path)
+                if on_output:
+                    on_output(report_path)
             
             if progress_callback:
                 progress_callback(total_files, total_files, "所有报告生成完成")
@@ -590,4 +776,4 @@ class BorrowingAnalyzer:
             return report_paths
 
         except Exception as e:
-            raise Exception(f"批量生成报告失败: {str(e)}")
\ No newline at end of file
+            raise Exception(f"批量生成报告失败: {e!r}") from e
\ No newline at end of file
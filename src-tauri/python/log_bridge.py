@@ -0,0 +1,65 @@
+"""将 Python 的 logging、warnings 和标准输出转发到应用日志。
+
+Windows 发布版没有控制台，print 的内容会丢失，因此统一写入 Rust 端的日志文件。
+"""
+import logging
+import sys
+
+
+class RustLogHandler(logging.Handler):
+    """把 logging 记录交给 Rust 端的 log(level, name, message)"""
+
+    def __init__(self, log):
+        super().__init__()
+        self._log = log
+
+    def emit(self, record):
+        try:
+            self._log(record.levelno, record.name, self.format(record).rstrip())
+        except Exception:
+            self.handleError(record)
+
+
+class RustLogStream:
+    """替代 sys.stdout/sys.stderr，按行写入日志"""
+
+    def __init__(self, log, level, name):
+        self._log = log
+        self._level = level
+        self._name = name
+        self._buffer = ""
+
+    def write(self, text):
+        self._buffer += text
+        while "\n" in self._buffer:
+            line, self._buffer = self._buffer.split("\n", 1)
+            self._emit(line)
+        return len(text)
+
+    def flush(self):
+        self._emit(self._buffer)
+        self._buffer = ""
+
+    def isatty(self):
+        return False
+
+    def _emit(self, line):
+        if line.strip():
+            self._log(self._level, self._name, line.rstrip())
+
+
+def install(log):
+    """安装日志转发，重复调用时替换之前的转发"""
+    handler = RustLogHandler(log)
+    handler.setFormatter(logging.Formatter("%(message)s"))
+
+    root = logging.getLogger()
+    for existing in list(root.handlers):
+        if isinstance(existing, RustLogHandler):
+            root.removeHandler(existing)
+    root.addHandler(handler)
+    root.setLevel(logging.INFO)
+    logging.captureWarnings(True)
+
+    sys.stdout = RustLogStream(log, logging.INFO, "stdout")
+    sys.stderr = RustLogStream(log, logging.WARNING, "stderr")
@@ -0,0 +1,522 @@
+use crate::charts::{self, ChartData};
+use crate::column_mapping::ColumnMapping;
+use crate::data_export::{self, AnalysisData, DataFormat};
+use crate::membership::MembershipDuration;
+#[cfg(feature = "python")]
+use crate::python_analyzer::PythonAnalyzer;
+use crate::reader_identity::ReaderIdentity;
+use crate::report::{self, ReportData, ReportFormat};
+use crate::rust_analyzer::RustAnalyzer;
+use crate::spreadsheet::SheetSelection;
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 分析器后端类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// 通过PyO3调用 `python/analyzer.py`（需要启用 `python` 功能）
+    #[cfg_attr(feature = "python", default)]
+    Python,
+    /// 纯Rust实现，未启用 `python` 功能时为默认后端
+    #[cfg_attr(not(feature = "python"), default)]
+    Rust,
+}
+
+impl std::str::FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "python" => Ok(Self::Python),
+            "rust" => Ok(Self::Rust),
+            other => Err(anyhow::anyhow!("未知的分析器后端: {}", other)),
+        }
+    }
+}
+
+/// 一次分析的结果，两种后端返回相同的结构
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysisResult {
+    pub borrow_frequency: u32,
+    pub total_duration: MembershipDuration,
+    pub total_reading_duration: i64,
+    pub total_price: f64,
+    pub monthly_borrow: HashMap<String, u32>, // v2更新：改为String键支持年-月格式
+    /// 按年月排列的借阅册数，连续且没有借阅的月份为0
+    pub monthly_timeline: Vec<(String, u32)>,
+    // v2新增字段
+    pub most_frequent_author: String,
+    pub most_frequent_category: String,
+    pub book_borrow_counts: HashMap<String, u32>,
+    pub borrow_peak_yearmonth: String,
+    // 保留原有字段
+    pub longest_author: String,
+    pub longest_category: String,
+    pub borrow_peak: u8,
+    pub most_borrowed_book: String,
+    pub most_borrowed_book_count: u32,
+    pub full_attendance: Vec<String>,
+    pub total_full_attendance: u32,
+    /// 读者在读者清单中的匹配结果（按第一条借阅记录）
+    pub reader_identity: ReaderIdentity,
+    /// 数据中未能唯一匹配的读者
+    pub unresolved_readers: Vec<ReaderIdentity>,
+    pub chart_paths: HashMap<String, String>,
+}
+
+/// 批量导出报告的分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGrouping {
+    /// 每个文件（工作表）一份报告
+    #[default]
+    File,
+    /// 每位读者一份报告
+    Reader,
+}
+
+impl ReportGrouping {
+    /// 传给Python端的名称
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReportGrouping::File => "file",
+            ReportGrouping::Reader => "reader",
+        }
+    }
+}
+
+/// 进度回调 (total, current, message)
+pub type ProgressCallback = Box<dyn Fn(u32, u32, String) + Send>;
+
+/// 已生成文件的回调，每写入一个文件调用一次，任务失败或取消时已生成的文件也能记录下来
+pub type OutputCallback = Box<dyn Fn(String) + Send>;
+
+/// 取消标志，由任务管理器设置，分析器在处理下一个文件之前检查
+pub type CancelFlag = Arc<AtomicBool>;
+
+/// 分析器后端接口，`commands.rs` 中的命令只通过该接口访问分析器
+pub trait AnalyzerBackend: Send {
+    /// 后端名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    fn load_data(
+        &self,
+        borrow_paths: Vec<String>,
+        member_path: String,
+        column_mapping: &ColumnMapping,
+        sheet_selection: &SheetSelection,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<()>;
+
+    /// `as_of` 为计算会员时长的统计日期
+    fn analyze(
+        &self,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult>;
+
+    fn analyze_single_file(
+        &self,
+        file_index: u32,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult>;
+
+    /// 最近一次分析结果的图表数据
+    fn chart_data(&self) -> Result<ChartData>;
+
+    /// 绘制最近一次分析结果的图表（PNG和SVG），返回图表类型到PNG路径的映射
+    fn generate_charts(
+        &self,
+        output_dir: String,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<HashMap<String, String>> {
+        charts::render_charts(&self.chart_data()?, Path::new(&output_dir), progress_callback)
+    }
+
+    /// 最近一次分析结果的报告数据
+    fn report_data(&self) -> Result<ReportData>;
+
+    /// 导出最近一次分析结果的报告（Word、PDF、HTML或Markdown），图表绘制到报告所在目录
+    fn export_report(
+        &self,
+        output_path: String,
+        format: ReportFormat,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<()> {
+        report::export_report(&self.report_data()?, Path::new(&output_path), format, progress_callback)
+    }
+
+    /// 最近一次分析的完整结果
+    fn analysis_data(&self) -> Result<AnalysisData>;
+
+    /// 导出最近一次分析的完整结果（JSON或XLSX）
+    fn export_data(
+        &self,
+        output_path: String,
+        format: DataFormat,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<()> {
+        data_export::export_data(
+            &self.analysis_data()?,
+            self.name(),
+            Path::new(&output_path),
+            format,
+            progress_callback,
+        )
+    }
+
+    fn get_file_count(&self) -> Result<u32>;
+
+    fn get_file_name(&self, file_index: u32) -> Result<String>;
+
+    /// 合并数据中的读者数量（按读者证号或姓名分组）
+    fn get_reader_count(&self) -> Result<u32>;
+
+    fn get_reader_name(&self, reader_index: u32) -> Result<String>;
+
+    fn analyze_reader(
+        &self,
+        reader_index: u32,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult>;
+
+    /// 每份报告写入后调用 `on_output`；取消时在当前报告完成后停止并返回 `AnalyzerError::Cancelled`
+    #[allow(clippy::too_many_arguments)]
+    fn export_reports_for_all_files(
+        &self,
+        base_output_dir: String,
+        grouping: ReportGrouping,
+        format: ReportFormat,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+        cancel: Option<CancelFlag>,
+        on_output: Option<OutputCallback>,
+    ) -> Result<Vec<String>>;
+}
+
+/// 创建并初始化指定类型的后端；设置了 `shadow` 时同时运行两个后端（见 `DualAnalyzer`）
+pub fn create_backend(
+    kind: BackendKind,
+    shadow: Option<BackendKind>,
+) -> Result<Box<dyn AnalyzerBackend>> {
+    let primary = create_single_backend(kind)?;
+
+    match shadow {
+        Some(shadow_kind) if shadow_kind != kind => {
+            let shadow = create_single_backend(shadow_kind)?;
+            Ok(Box::new(DualAnalyzer { primary, shadow }))
+        }
+        _ => Ok(primary),
+    }
+}
+
+fn create_single_backend(kind: BackendKind) -> Result<Box<dyn AnalyzerBackend>> {
+    match kind {
+        #[cfg(feature = "python")]
+        BackendKind::Python => {
+            let mut analyzer = PythonAnalyzer::new()?;
+            analyzer.initialize()?;
+            Ok(Box::new(analyzer))
+        }
+        #[cfg(not(feature = "python"))]
+        BackendKind::Python => Err(anyhow::anyhow!(
+            "此版本编译时未启用 python 功能，不包含Python分析器，请使用Rust后端"
+        )),
+        BackendKind::Rust => {
+            let mut analyzer = RustAnalyzer::new()?;
+            analyzer.initialize()?;
+            Ok(Box::new(analyzer))
+        }
+    }
+}
+
+macro_rules! delegate_backend {
+    ($ty:ty, $name:expr) => {
+        impl AnalyzerBackend for $ty {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn load_data(
+                &self,
+                borrow_paths: Vec<String>,
+                member_path: String,
+                column_mapping: &ColumnMapping,
+                sheet_selection: &SheetSelection,
+                progress_callback: Option<ProgressCallback>,
+            ) -> Result<()> {
+                <$ty>::load_data(
+                    self,
+                    borrow_paths,
+                    member_path,
+                    column_mapping,
+                    sheet_selection,
+                    progress_callback,
+                )
+            }
+
+            fn analyze(
+                &self,
+                as_of: NaiveDate,
+                progress_callback: Option<ProgressCallback>,
+            ) -> Result<AnalysisResult> {
+                <$ty>::analyze(self, as_of, progress_callback)
+            }
+
+            fn analyze_single_file(
+                &self,
+                file_index: u32,
+                as_of: NaiveDate,
+                progress_callback: Option<ProgressCallback>,
+            ) -> Result<AnalysisResult> {
+                <$ty>::analyze_single_file(self, file_index, as_of, progress_callback)
+            }
+
+            fn chart_data(&self) -> Result<ChartData> {
+                <$ty>::chart_data(self)
+            }
+
+            fn report_data(&self) -> Result<ReportData> {
+                <$ty>::report_data(self)
+            }
+
+            fn analysis_data(&self) -> Result<AnalysisData> {
+                <$ty>::analysis_data(self)
+            }
+
+            fn get_file_count(&self) -> Result<u32> {
+                <$ty>::get_file_count(self)
+            }
+
+            fn get_file_name(&self, file_index: u32) -> Result<String> {
+                <$ty>::get_file_name(self, file_index)
+            }
+
+            fn get_reader_count(&self) -> Result<u32> {
+                <$ty>::get_reader_count(self)
+            }
+
+            fn get_reader_name(&self, reader_index: u32) -> Result<String> {
+                <$ty>::get_reader_name(self, reader_index)
+            }
+
+            fn analyze_reader(
+                &self,
+                reader_index: u32,
+                as_of: NaiveDate,
+                progress_callback: Option<ProgressCallback>,
+            ) -> Result<AnalysisResult> {
+                <$ty>::analyze_reader(self, reader_index, as_of, progress_callback)
+            }
+
+            fn export_reports_for_all_files(
+                &self,
+                base_output_dir: String,
+                grouping: ReportGrouping,
+                format: ReportFormat,
+                as_of: NaiveDate,
+                progress_callback: Option<ProgressCallback>,
+                cancel: Option<CancelFlag>,
+                on_output: Option<OutputCallback>,
+            ) -> Result<Vec<String>> {
+                <$ty>::export_reports_for_all_files(
+                    self,
+                    base_output_dir,
+                    grouping,
+                    format,
+                    as_of,
+                    progress_callback,
+                    cancel,
+                    on_output,
+                )
+            }
+        }
+    };
+}
+
+#[cfg(feature = "python")]
+delegate_backend!(PythonAnalyzer, "python");
+delegate_backend!(RustAnalyzer, "rust");
+
+/// 迁移期间同时运行两个后端：结果以主后端为准，
+/// 影子后端只用于对比分析结果，其错误和差异仅输出到日志。
+///
+/// 加载数据和各分析命令在主后端完成后，在同一线程中再由影子后端顺序执行一次，
+/// 因此这些命令的耗时约为两个后端之和；图表、报告和导出只使用主后端
+pub struct DualAnalyzer {
+    primary: Box<dyn AnalyzerBackend>,
+    shadow: Box<dyn AnalyzerBackend>,
+}
+
+impl DualAnalyzer {
+    fn compare(&self, context: &str, primary: &AnalysisResult, shadow: Result<AnalysisResult>) {
+        let shadow = match shadow {
+            Ok(shadow) => shadow,
+            Err(e) => {
+                warn!("[{}] 影子后端 {} 执行失败: {:#}", context, self.shadow.name(), e);
+                return;
+            }
+        };
+
+        let (Ok(serde_json::Value::Object(primary)), Ok(serde_json::Value::Object(shadow))) =
+            (serde_json::to_value(primary), serde_json::to_value(&shadow))
+        else {
+            return;
+        };
+
+        let differences: Vec<&String> = primary
+            .iter()
+            .filter(|(key, value)| shadow.get(*key) != Some(*value))
+            .map(|(key, _)| key)
+            .collect();
+
+        if differences.is_empty() {
+            info!("[{}] {} 与 {} 的分析结果一致", context, self.primary.name(), self.shadow.name());
+        } else {
+            for key in differences {
+                warn!(
+                    "[{}] 字段 {} 不一致: {} = {}, {} = {}",
+                    context,
+                    key,
+                    self.primary.name(),
+                    primary[key],
+                    self.shadow.name(),
+                    shadow.get(key).cloned().unwrap_or_default()
+                );
+            }
+        }
+    }
+}
+
+impl AnalyzerBackend for DualAnalyzer {
+    fn name(&self) -> &'static str {
+        self.primary.name()
+    }
+
+    fn load_data(
+        &self,
+        borrow_paths: Vec<String>,
+        member_path: String,
+        column_mapping: &ColumnMapping,
+        sheet_selection: &SheetSelection,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<()> {
+        self.primary.load_data(
+            borrow_paths.clone(),
+            member_path.clone(),
+            column_mapping,
+            sheet_selection,
+            progress_callback,
+        )?;
+        if let Err(e) = self.shadow.load_data(
+            borrow_paths,
+            member_path,
+            column_mapping,
+            sheet_selection,
+            None,
+        ) {
+            warn!("影子后端 {} 加载数据失败: {:#}", self.shadow.name(), e);
+        }
+        Ok(())
+    }
+
+    fn analyze(
+        &self,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult> {
+        let result = self.primary.analyze(as_of, progress_callback)?;
+        self.compare("analyze", &result, self.shadow.analyze(as_of, None));
+        Ok(result)
+    }
+
+    fn analyze_single_file(
+        &self,
+        file_index: u32,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult> {
+        let result = self.primary.analyze_single_file(file_index, as_of, progress_callback)?;
+        let context = format!("analyze_single_file({})", file_index);
+        self.compare(
+            &context,
+            &result,
+            self.shadow.analyze_single_file(file_index, as_of, None),
+        );
+        Ok(result)
+    }
+
+    fn chart_data(&self) -> Result<ChartData> {
+        self.primary.chart_data()
+    }
+
+    fn report_data(&self) -> Result<ReportData> {
+        self.primary.report_data()
+    }
+
+    fn analysis_data(&self) -> Result<AnalysisData> {
+        self.primary.analysis_data()
+    }
+
+    fn get_file_count(&self) -> Result<u32> {
+        self.primary.get_file_count()
+    }
+
+    fn get_file_name(&self, file_index: u32) -> Result<String> {
+        self.primary.get_file_name(file_index)
+    }
+
+    fn get_reader_count(&self) -> Result<u32> {
+        self.primary.get_reader_count()
+    }
+
+    fn get_reader_name(&self, reader_index: u32) -> Result<String> {
+        self.primary.get_reader_name(reader_index)
+    }
+
+    fn analyze_reader(
+        &self,
+        reader_index: u32,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult> {
+        let result = self.primary.analyze_reader(reader_index, as_of, progress_callback)?;
+        let context = format!("analyze_reader({})", reader_index);
+        self.compare(
+            &context,
+            &result,
+            self.shadow.analyze_reader(reader_index, as_of, None),
+        );
+        Ok(result)
+    }
+
+    fn export_reports_for_all_files(
+        &self,
+        base_output_dir: String,
+        grouping: ReportGrouping,
+        format: ReportFormat,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+        cancel: Option<CancelFlag>,
+        on_output: Option<OutputCallback>,
+    ) -> Result<Vec<String>> {
+        self.primary.export_reports_for_all_files(
+            base_output_dir,
+            grouping,
+            format,
+            as_of,
+            progress_callback,
+            cancel,
+            on_output,
+        )
+    }
+}
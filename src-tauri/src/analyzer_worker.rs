@@ -0,0 +1,114 @@
+use crate::analyzer_backend::AnalyzerBackend;
+use crate::error::AnalyzerError;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+use tracing::{error, Span};
+
+type Task = Box<dyn FnOnce(&mut Option<Box<dyn AnalyzerBackend>>) + Send>;
+
+/// 已加载数据的文件名和读者名，加载数据后缓存，查询时不经过工作线程
+#[derive(Debug, Clone, Default)]
+pub struct DataSummary {
+    pub file_names: Vec<String>,
+    pub reader_names: Vec<String>,
+}
+
+impl DataSummary {
+    /// 在工作线程中从分析器读取
+    pub fn collect(analyzer: &dyn AnalyzerBackend) -> anyhow::Result<Self> {
+        let file_names = (0..analyzer.get_file_count()?)
+            .map(|index| analyzer.get_file_name(index))
+            .collect::<anyhow::Result<_>>()?;
+        let reader_names = (0..analyzer.get_reader_count()?)
+            .map(|index| analyzer.get_reader_name(index))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            file_names,
+            reader_names,
+        })
+    }
+}
+
+/// 分析器工作线程：分析器只在该线程中创建和使用，命令按顺序在线程中执行。
+/// 任务 panic 时只影响当前调用，分析器和后续命令不受影响。
+pub struct AnalyzerWorker {
+    sender: Sender<Task>,
+    summary: Mutex<Option<DataSummary>>,
+}
+
+impl AnalyzerWorker {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        thread::Builder::new()
+            .name("analyzer".to_string())
+            .spawn(move || {
+                let mut analyzer = None;
+                for task in receiver {
+                    if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(|| task(&mut analyzer))) {
+                        let message = panic
+                            .downcast_ref::<&str>()
+                            .map(|s| s.to_string())
+                            .or_else(|| panic.downcast_ref::<String>().cloned())
+                            .unwrap_or_default();
+                        error!("分析器任务异常终止: {}", message);
+                    }
+                }
+            })
+            .expect("无法启动分析器工作线程");
+
+        Self {
+            sender,
+            summary: Mutex::new(None),
+        }
+    }
+
+    /// 在工作线程中执行任务并等待结果，会阻塞当前线程，应在任务线程或 `spawn_blocking` 中调用
+    pub fn run<T: Send + 'static>(
+        &self,
+        task: impl FnOnce(&mut Option<Box<dyn AnalyzerBackend>>) -> Result<T, AnalyzerError>
+            + Send
+            + 'static,
+    ) -> Result<T, AnalyzerError> {
+        let (reply, result) = mpsc::sync_channel(1);
+        // 工作线程中的日志沿用调用方的命令和任务上下文
+        let span = Span::current();
+        self.sender
+            .send(Box::new(move |analyzer| {
+                let _span = span.enter();
+                let _ = reply.send(task(analyzer));
+            }))
+            .map_err(|_| AnalyzerError::Internal {
+                message: "分析器工作线程已退出".to_string(),
+            })?;
+        result.recv().map_err(|_| AnalyzerError::Internal {
+            message: "分析器执行过程中发生内部错误".to_string(),
+        })?
+    }
+
+    /// 同 `run`，分析器未初始化时报错
+    pub fn with_analyzer<T: Send + 'static>(
+        &self,
+        task: impl FnOnce(&dyn AnalyzerBackend) -> Result<T, AnalyzerError> + Send + 'static,
+    ) -> Result<T, AnalyzerError> {
+        self.run(|analyzer| {
+            let analyzer = analyzer.as_deref().ok_or(AnalyzerError::NotInitialized)?;
+            task(analyzer)
+        })
+    }
+
+    /// 读取缓存的数据概况，不经过工作线程，导出报告期间也能立即返回
+    pub fn with_summary<T>(
+        &self,
+        query: impl FnOnce(&DataSummary) -> Result<T, AnalyzerError>,
+    ) -> Result<T, AnalyzerError> {
+        let summary = self.summary.lock().unwrap_or_else(|e| e.into_inner());
+        let summary = summary.as_ref().ok_or(AnalyzerError::DataNotLoaded)?;
+        query(summary)
+    }
+
+    pub fn set_summary(&self, summary: Option<DataSummary>) {
+        *self.summary.lock().unwrap_or_else(|e| e.into_inner()) = summary;
+    }
+}
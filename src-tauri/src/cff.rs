@@ -0,0 +1,689 @@
+//! CFF轮廓（OpenType字体的 `CFF ` 表）的子集化，供PDF嵌入字体使用。
+//!
+//! 字形编号、字符集（字形到CID的映射）和FD分配都保持不变：未用到的字形替换为只含 `endchar` 的空字形，
+//! 用到的字形没有调用的子程序替换为只含 `return` 的空子程序，子程序编号不变，字形数据无需改写。
+//! 各结构按固定顺序重新排列，DICT中的偏移量统一用5字节整数编码，便于先确定长度再计算偏移量。
+
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeSet, HashMap};
+
+const ENDCHAR: u8 = 14;
+const RETURN: u8 = 11;
+
+// DICT 操作符（两字节操作符为 `12 << 8 | 第二字节`）
+const OP_CHARSET: u16 = 15;
+const OP_ENCODING: u16 = 16;
+const OP_CHARSTRINGS: u16 = 17;
+const OP_PRIVATE: u16 = 18;
+const OP_SUBRS: u16 = 19;
+const OP_FDARRAY: u16 = 12 << 8 | 36;
+const OP_FDSELECT: u16 = 12 << 8 | 37;
+
+/// 子程序的最大嵌套层数（CFF规范的限制为10）
+const MAX_SUBR_DEPTH: usize = 10;
+
+fn incomplete() -> anyhow::Error {
+    anyhow!("CFF数据不完整")
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Result<u8> {
+    data.get(offset).copied().ok_or_else(incomplete)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    Ok(u16::from_be_bytes([read_u8(data, offset)?, read_u8(data, offset + 1)?]))
+}
+
+fn slice(data: &[u8], start: usize, end: usize) -> Result<&[u8]> {
+    data.get(start..end).ok_or_else(incomplete)
+}
+
+/// 读取从 `start` 开始的 INDEX，返回各项数据和 INDEX 结束的位置
+fn read_index(data: &[u8], start: usize) -> Result<(Vec<&[u8]>, usize)> {
+    let count = read_u16(data, start)? as usize;
+    if count == 0 {
+        return Ok((Vec::new(), start + 2));
+    }
+    let off_size = read_u8(data, start + 2)? as usize;
+    if !(1..=4).contains(&off_size) {
+        return Err(anyhow!("CFF INDEX 的偏移量长度无效: {}", off_size));
+    }
+    let offset = |i: usize| -> Result<usize> {
+        let position = start + 3 + i * off_size;
+        Ok(slice(data, position, position + off_size)?
+            .iter()
+            .fold(0usize, |value, byte| value << 8 | *byte as usize))
+    };
+    // 偏移量从1开始，相对于数据区之前的一个字节
+    let base = start + 3 + (count + 1) * off_size - 1;
+    let mut items = Vec::with_capacity(count);
+    for i in 0..count {
+        items.push(slice(data, base + offset(i)?, base + offset(i + 1)?)?);
+    }
+    Ok((items, base + offset(count)?))
+}
+
+fn write_index<T: AsRef<[u8]>>(items: &[T], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(items.len() as u16).to_be_bytes());
+    if items.is_empty() {
+        return;
+    }
+    let total: usize = items.iter().map(|item| item.as_ref().len()).sum::<usize>() + 1;
+    let off_size = match total {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x1_0000..=0xFF_FFFF => 3,
+        _ => 4,
+    };
+    out.push(off_size as u8);
+    let mut offset = 1usize;
+    for item in items.iter().map(AsRef::as_ref).chain(std::iter::once(&[][..])) {
+        out.extend_from_slice(&offset.to_be_bytes()[std::mem::size_of::<usize>() - off_size..]);
+        offset += item.len();
+    }
+    for item in items {
+        out.extend_from_slice(item.as_ref());
+    }
+}
+
+/// DICT 中的一项：操作符、各操作数的原始字节和整数值（实数的值记为0，只原样写回）
+struct DictEntry {
+    op: u16,
+    operands: Vec<(Vec<u8>, i32)>,
+}
+
+fn parse_dict(data: &[u8]) -> Result<Vec<DictEntry>> {
+    let mut entries = Vec::new();
+    let mut operands = Vec::new();
+    let mut position = 0;
+    while position < data.len() {
+        let b0 = data[position];
+        let (length, value) = match b0 {
+            0..=11 | 13..=21 => {
+                entries.push(DictEntry { op: b0 as u16, operands: std::mem::take(&mut operands) });
+                position += 1;
+                continue;
+            }
+            12 => {
+                let op = 12 << 8 | read_u8(data, position + 1)? as u16;
+                entries.push(DictEntry { op, operands: std::mem::take(&mut operands) });
+                position += 2;
+                continue;
+            }
+            28 => (3, read_u16(data, position + 1)? as i16 as i32),
+            29 => (
+                5,
+                i32::from_be_bytes(slice(data, position + 1, position + 5)?.try_into().unwrap()),
+            ),
+            30 => {
+                // 实数：半字节编码，直到出现 0xF
+                let mut length = 1;
+                loop {
+                    let byte = read_u8(data, position + length)?;
+                    length += 1;
+                    if byte >> 4 == 0xF || byte & 0xF == 0xF {
+                        break;
+                    }
+                }
+                (length, 0)
+            }
+            32..=246 => (1, b0 as i32 - 139),
+            247..=250 => (2, (b0 as i32 - 247) * 256 + read_u8(data, position + 1)? as i32 + 108),
+            251..=254 => (2, -(b0 as i32 - 251) * 256 - read_u8(data, position + 1)? as i32 - 108),
+            _ => return Err(anyhow!("CFF DICT 中的无效字节: {}", b0)),
+        };
+        operands.push((slice(data, position, position + length)?.to_vec(), value));
+        position += length;
+    }
+    Ok(entries)
+}
+
+fn find_operands(entries: &[DictEntry], op: u16) -> Option<Vec<i32>> {
+    entries
+        .iter()
+        .find(|entry| entry.op == op)
+        .map(|entry| entry.operands.iter().map(|(_, value)| *value).collect())
+}
+
+/// 重新编码 DICT，`replace` 中的操作符的操作数改为5字节整数
+fn encode_dict(entries: &[DictEntry], replace: &HashMap<u16, Vec<i32>>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for entry in entries {
+        match replace.get(&entry.op) {
+            Some(values) => {
+                for value in values {
+                    out.push(29);
+                    out.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            None => {
+                for (raw, _) in &entry.operands {
+                    out.extend_from_slice(raw);
+                }
+            }
+        }
+        if entry.op > 0xFF {
+            out.extend_from_slice(&entry.op.to_be_bytes());
+        } else {
+            out.push(entry.op as u8);
+        }
+    }
+    out
+}
+
+/// Private DICT 及其本地子程序
+struct Private<'a> {
+    dict: Vec<DictEntry>,
+    subrs: Vec<&'a [u8]>,
+}
+
+impl<'a> Private<'a> {
+    fn read(data: &'a [u8], operands: &[i32]) -> Result<Self> {
+        let [size, offset] = operands else {
+            return Err(anyhow!("CFF Private 操作数无效"));
+        };
+        let (size, offset) = (*size as usize, *offset as usize);
+        let dict = parse_dict(slice(data, offset, offset + size)?)?;
+        let subrs = match find_operands(&dict, OP_SUBRS).as_deref() {
+            Some([relative]) => read_index(data, offset + *relative as usize)?.0,
+            _ => Vec::new(),
+        };
+        Ok(Self { dict, subrs })
+    }
+
+    /// 写出 Private DICT 和只保留 `used` 的本地子程序，返回 DICT 的长度
+    fn write(&self, used: &BTreeSet<usize>, out: &mut Vec<u8>) -> usize {
+        let has_subrs = find_operands(&self.dict, OP_SUBRS).is_some();
+        let mut replace = HashMap::new();
+        if has_subrs {
+            // 本地子程序紧跟在 DICT 之后，偏移量相对于 DICT 的开头
+            replace.insert(OP_SUBRS, vec![0]);
+            let length = encode_dict(&self.dict, &replace).len();
+            replace.insert(OP_SUBRS, vec![length as i32]);
+        }
+        let dict = encode_dict(&self.dict, &replace);
+        out.extend_from_slice(&dict);
+        if has_subrs {
+            write_index(&prune(&self.subrs, used, RETURN), out);
+        }
+        dict.len()
+    }
+}
+
+/// 未用到的项替换为单字节的空程序
+fn prune(items: &[&[u8]], used: &BTreeSet<usize>, empty: u8) -> Vec<Vec<u8>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| if used.contains(&i) { item.to_vec() } else { vec![empty] })
+        .collect()
+}
+
+/// 字符集、编码和 FDSelect 的原始数据长度
+fn charset_length(data: &[u8], offset: usize, glyphs: usize) -> Result<usize> {
+    let format = read_u8(data, offset)?;
+    if format == 0 {
+        return Ok(1 + 2 * glyphs.saturating_sub(1));
+    }
+    let count_size = match format {
+        1 => 1,
+        2 => 2,
+        _ => return Err(anyhow!("不支持的CFF字符集格式: {}", format)),
+    };
+    // 每个区间：起始SID/CID（2字节）+ 剩余数量，覆盖除 0 号字形外的所有字形
+    let (mut position, mut covered) = (offset + 1, 1);
+    while covered < glyphs {
+        let left = match count_size {
+            1 => read_u8(data, position + 2)? as usize,
+            _ => read_u16(data, position + 2)? as usize,
+        };
+        covered += left + 1;
+        position += 2 + count_size;
+    }
+    Ok(position - offset)
+}
+
+fn encoding_length(data: &[u8], offset: usize) -> Result<usize> {
+    let format = read_u8(data, offset)?;
+    let count = read_u8(data, offset + 1)? as usize;
+    let mut length = match format & 0x7F {
+        0 => 2 + count,
+        1 => 2 + 2 * count,
+        other => return Err(anyhow!("不支持的CFF编码格式: {}", other)),
+    };
+    if format & 0x80 != 0 {
+        length += 1 + 3 * read_u8(data, offset + length)? as usize;
+    }
+    Ok(length)
+}
+
+fn fd_select_length(data: &[u8], offset: usize, glyphs: usize) -> Result<usize> {
+    match read_u8(data, offset)? {
+        0 => Ok(1 + glyphs),
+        3 => Ok(1 + 2 + 3 * read_u16(data, offset + 1)? as usize + 2),
+        other => Err(anyhow!("不支持的CFF FDSelect格式: {}", other)),
+    }
+}
+
+/// 每个字形所属的FD
+fn fd_select(data: &[u8], offset: usize, glyphs: usize) -> Result<Vec<usize>> {
+    match read_u8(data, offset)? {
+        0 => Ok(slice(data, offset + 1, offset + 1 + glyphs)?
+            .iter()
+            .map(|fd| *fd as usize)
+            .collect()),
+        3 => {
+            let ranges = read_u16(data, offset + 1)? as usize;
+            let mut fds = vec![0; glyphs];
+            for i in 0..ranges {
+                let position = offset + 3 + i * 3;
+                let first = read_u16(data, position)? as usize;
+                let fd = read_u8(data, position + 2)? as usize;
+                let end = read_u16(data, position + 3)? as usize;
+                for entry in fds.iter_mut().take(end.min(glyphs)).skip(first) {
+                    *entry = fd;
+                }
+            }
+            Ok(fds)
+        }
+        other => Err(anyhow!("不支持的CFF FDSelect格式: {}", other)),
+    }
+}
+
+/// 子程序编号的偏置（Type 2 charstring 规范）
+fn subr_bias(count: usize) -> i32 {
+    match count {
+        0..=1239 => 107,
+        1240..=33899 => 1131,
+        _ => 32768,
+    }
+}
+
+/// 扫描字形程序，记录调用到的全局和本地子程序。
+/// 只跟踪操作数栈和提示（stem）的数量：`hintmask`/`cntrmask` 之后的掩码字节数取决于提示数量
+struct SubrScanner<'a, 'b> {
+    global: &'b [&'a [u8]],
+    local: &'b [&'a [u8]],
+    used_global: &'b mut BTreeSet<usize>,
+    used_local: &'b mut BTreeSet<usize>,
+    stack: Vec<i32>,
+    stems: usize,
+}
+
+impl SubrScanner<'_, '_> {
+    /// 返回 `true` 表示遇到 `endchar`，字形程序结束
+    fn scan(&mut self, program: &[u8], depth: usize) -> Result<bool> {
+        if depth > MAX_SUBR_DEPTH {
+            return Err(anyhow!("CFF子程序嵌套过深"));
+        }
+        let mut position = 0;
+        while position < program.len() {
+            let b0 = program[position];
+            position += 1;
+            match b0 {
+                28 => {
+                    self.stack.push(read_u16(program, position)? as i16 as i32);
+                    position += 2;
+                }
+                32..=246 => self.stack.push(b0 as i32 - 139),
+                247..=250 => {
+                    self.stack.push((b0 as i32 - 247) * 256 + read_u8(program, position)? as i32 + 108);
+                    position += 1;
+                }
+                251..=254 => {
+                    self.stack.push(-(b0 as i32 - 251) * 256 - read_u8(program, position)? as i32 - 108);
+                    position += 1;
+                }
+                255 => {
+                    // 16.16 定点数，只取整数部分
+                    let value = i32::from_be_bytes(slice(program, position, position + 4)?.try_into().unwrap());
+                    self.stack.push(value >> 16);
+                    position += 4;
+                }
+                // hstem、vstem、hstemhm、vstemhm
+                1 | 3 | 18 | 23 => {
+                    self.stems += self.stack.len() / 2;
+                    self.stack.clear();
+                }
+                // hintmask、cntrmask：之前的操作数是隐含的 vstem
+                19 | 20 => {
+                    self.stems += self.stack.len() / 2;
+                    self.stack.clear();
+                    position += self.stems.div_ceil(8);
+                }
+                10 | 29 => {
+                    let global = b0 == 29;
+                    let subrs = if global { self.global } else { self.local };
+                    let number = self.stack.pop().ok_or_else(|| anyhow!("CFF子程序调用缺少编号"))?;
+                    let index = usize::try_from(number + subr_bias(subrs.len()))
+                        .ok()
+                        .filter(|index| *index < subrs.len())
+                        .ok_or_else(|| anyhow!("CFF子程序编号无效: {}", number))?;
+                    if global {
+                        self.used_global.insert(index);
+                    } else {
+                        self.used_local.insert(index);
+                    }
+                    if self.scan(subrs[index], depth + 1)? {
+                        return Ok(true);
+                    }
+                }
+                RETURN => return Ok(false),
+                ENDCHAR => return Ok(true),
+                12 => {
+                    position += 1;
+                    self.stack.clear();
+                }
+                _ => self.stack.clear(),
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// 只保留 `used` 中的字形（0 号字形始终保留）及其调用的子程序，返回新的 CFF 表
+pub fn subset_cff(data: &[u8], used: impl Iterator<Item = u16>) -> Result<Vec<u8>> {
+    let major = read_u8(data, 0)?;
+    if major != 1 {
+        return Err(anyhow!("不支持的CFF版本: {}", major));
+    }
+    let header_size = read_u8(data, 2)? as usize;
+    let (_, name_end) = read_index(data, header_size)?;
+    let (top_dicts, top_end) = read_index(data, name_end)?;
+    let top = parse_dict(top_dicts.first().ok_or_else(|| anyhow!("CFF缺少 Top DICT"))?)?;
+    let (_, string_end) = read_index(data, top_end)?;
+    let (global_subrs, _) = read_index(data, string_end)?;
+
+    let charstrings_offset = match find_operands(&top, OP_CHARSTRINGS).as_deref() {
+        Some([offset]) => *offset as usize,
+        _ => return Err(anyhow!("CFF缺少 CharStrings")),
+    };
+    let (charstrings, _) = read_index(data, charstrings_offset)?;
+    let glyphs = charstrings.len();
+
+    // CID-keyed 字体的每个FD有自己的 Private DICT；普通字体只有一个
+    let fd_dicts = match find_operands(&top, OP_FDARRAY).as_deref() {
+        Some([offset]) => read_index(data, *offset as usize)?
+            .0
+            .into_iter()
+            .map(parse_dict)
+            .collect::<Result<Vec<_>>>()?,
+        _ => Vec::new(),
+    };
+    let fd_select_offset = find_operands(&top, OP_FDSELECT).and_then(|operands| operands.first().copied());
+    let (privates, glyph_fds) = if fd_dicts.is_empty() {
+        let private = match find_operands(&top, OP_PRIVATE) {
+            Some(operands) => Private::read(data, &operands)?,
+            None => Private { dict: Vec::new(), subrs: Vec::new() },
+        };
+        (vec![private], vec![0; glyphs])
+    } else {
+        let privates = fd_dicts
+            .iter()
+            .map(|dict| match find_operands(dict, OP_PRIVATE) {
+                Some(operands) => Private::read(data, &operands),
+                None => Ok(Private { dict: Vec::new(), subrs: Vec::new() }),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let offset = fd_select_offset.ok_or_else(|| anyhow!("CFF缺少 FDSelect"))? as usize;
+        (privates, fd_select(data, offset, glyphs)?)
+    };
+
+    // 用到的字形及其调用的子程序
+    let keep: BTreeSet<usize> = std::iter::once(0)
+        .chain(used.map(|id| id as usize))
+        .filter(|id| *id < glyphs)
+        .collect();
+    let mut used_global = BTreeSet::new();
+    let mut used_local = vec![BTreeSet::new(); privates.len()];
+    for &id in &keep {
+        let fd = glyph_fds.get(id).copied().filter(|fd| *fd < privates.len()).ok_or_else(incomplete)?;
+        SubrScanner {
+            global: &global_subrs,
+            local: &privates[fd].subrs,
+            used_global: &mut used_global,
+            used_local: &mut used_local[fd],
+            stack: Vec::new(),
+            stems: 0,
+        }
+        .scan(charstrings[id], 0)?;
+    }
+
+    // 字符集、编码和 FDSelect 原样复制（预定义的字符集和编码用 0~2 表示，不是偏移量）
+    let copy_block = |op: u16, length: &dyn Fn(usize) -> Result<usize>| -> Result<Option<&[u8]>> {
+        match find_operands(&top, op).as_deref() {
+            Some([offset]) if *offset > 2 => {
+                let offset = *offset as usize;
+                Ok(Some(slice(data, offset, offset + length(offset)?)?))
+            }
+            _ => Ok(None),
+        }
+    };
+    let charset = copy_block(OP_CHARSET, &|offset| charset_length(data, offset, glyphs))?;
+    let encoding = copy_block(OP_ENCODING, &|offset| encoding_length(data, offset))?;
+    let fd_select_block = match fd_select_offset {
+        Some(offset) if !fd_dicts.is_empty() => {
+            let offset = offset as usize;
+            Some(slice(data, offset, offset + fd_select_length(data, offset, glyphs)?)?)
+        }
+        _ => None,
+    };
+
+    // Private DICT 和本地子程序放在最后，先写出以确定各自的位置和长度
+    let mut private_data = Vec::new();
+    let mut private_ranges = Vec::new();
+    for (private, used) in privates.iter().zip(&used_local) {
+        let start = private_data.len();
+        let length = private.write(used, &mut private_data);
+        private_ranges.push((start, length));
+    }
+
+    let mut new_charstrings = Vec::new();
+    write_index(&prune(&charstrings, &keep, ENDCHAR), &mut new_charstrings);
+    let mut new_global_subrs = Vec::new();
+    write_index(&prune(&global_subrs, &used_global, RETURN), &mut new_global_subrs);
+
+    // 偏移量都是5字节整数，先用占位值确定 Top DICT 和 FD DICT 的长度
+    let mut top_replace: HashMap<u16, Vec<i32>> = HashMap::new();
+    for op in [OP_CHARSTRINGS, OP_CHARSET, OP_ENCODING, OP_FDARRAY, OP_FDSELECT] {
+        let present = match op {
+            OP_CHARSET => charset.is_some(),
+            OP_ENCODING => encoding.is_some(),
+            OP_FDARRAY | OP_FDSELECT => !fd_dicts.is_empty(),
+            _ => true,
+        };
+        if present {
+            top_replace.insert(op, vec![0]);
+        }
+    }
+    if fd_dicts.is_empty() && find_operands(&top, OP_PRIVATE).is_some() {
+        top_replace.insert(OP_PRIVATE, vec![0, 0]);
+    }
+    let mut top_index = Vec::new();
+    write_index(&[encode_dict(&top, &top_replace)], &mut top_index);
+    let fd_array_length = {
+        let mut fd_array = Vec::new();
+        let placeholder: Vec<Vec<u8>> = fd_dicts
+            .iter()
+            .map(|dict| encode_dict(dict, &HashMap::from([(OP_PRIVATE, vec![0, 0])])))
+            .collect();
+        write_index(&placeholder, &mut fd_array);
+        if fd_dicts.is_empty() { 0 } else { fd_array.len() }
+    };
+
+    let head = slice(data, 0, name_end)?;
+    let strings = slice(data, top_end, string_end)?;
+    let mut offset = head.len() + top_index.len() + strings.len() + new_global_subrs.len();
+    let mut place = |length: usize| {
+        let start = offset;
+        offset += length;
+        start as i32
+    };
+    let charset_offset = charset.map(|block| place(block.len()));
+    let encoding_offset = encoding.map(|block| place(block.len()));
+    let fd_select_start = fd_select_block.map(|block| place(block.len()));
+    let charstrings_start = place(new_charstrings.len());
+    let fd_array_start = place(fd_array_length);
+    let private_start = place(private_data.len());
+
+    top_replace.insert(OP_CHARSTRINGS, vec![charstrings_start]);
+    if let Some(offset) = charset_offset {
+        top_replace.insert(OP_CHARSET, vec![offset]);
+    }
+    if let Some(offset) = encoding_offset {
+        top_replace.insert(OP_ENCODING, vec![offset]);
+    }
+    if let Some(offset) = fd_select_start {
+        top_replace.insert(OP_FDSELECT, vec![offset]);
+        top_replace.insert(OP_FDARRAY, vec![fd_array_start]);
+    }
+    let private_operands = |fd: usize| {
+        let (start, length) = private_ranges[fd];
+        vec![length as i32, private_start + start as i32]
+    };
+    if top_replace.contains_key(&OP_PRIVATE) {
+        top_replace.insert(OP_PRIVATE, private_operands(0));
+    }
+
+    let mut out = Vec::with_capacity(offset);
+    out.extend_from_slice(head);
+    write_index(&[encode_dict(&top, &top_replace)], &mut out);
+    out.extend_from_slice(strings);
+    out.extend_from_slice(&new_global_subrs);
+    for block in [charset, encoding, fd_select_block].into_iter().flatten() {
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&new_charstrings);
+    if !fd_dicts.is_empty() {
+        let dicts: Vec<Vec<u8>> = fd_dicts
+            .iter()
+            .enumerate()
+            .map(|(fd, dict)| encode_dict(dict, &HashMap::from([(OP_PRIVATE, private_operands(fd))])))
+            .collect();
+        write_index(&dicts, &mut out);
+    }
+    out.extend_from_slice(&private_data);
+    debug_assert_eq!(out.len(), offset);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ttf_parser::{cff, Face, GlyphId, OutlineBuilder, Tag};
+
+    /// `tests/fixtures/generate_fonts.py` 生成的CID-keyed CFF字体，字形 0~5 为 .notdef、A、B、中、文、C
+    fn fixture_cff() -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fonts/test-cff.otf");
+        let font = std::fs::read(path).unwrap();
+        let face = Face::parse(&font, 0).unwrap();
+        face.raw_face().table(Tag::from_bytes(b"CFF ")).unwrap().to_vec()
+    }
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl OutlineBuilder for Recorder {
+        fn move_to(&mut self, x: f32, y: f32) {
+            self.0.push(format!("M {} {}", x, y));
+        }
+        fn line_to(&mut self, x: f32, y: f32) {
+            self.0.push(format!("L {} {}", x, y));
+        }
+        fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+            self.0.push(format!("Q {} {} {} {}", x1, y1, x, y));
+        }
+        fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+            self.0.push(format!("C {} {} {} {} {} {}", x1, y1, x2, y2, x, y));
+        }
+        fn close(&mut self) {
+            self.0.push("Z".to_string());
+        }
+    }
+
+    fn outline(table: &cff::Table, id: u16) -> Option<Vec<String>> {
+        let mut recorder = Recorder::default();
+        table.outline(GlyphId(id), &mut recorder).ok()?;
+        Some(recorder.0)
+    }
+
+    #[test]
+    fn keeps_used_glyphs_and_their_subroutines() {
+        let data = fixture_cff();
+        // A 和 中；中 通过全局子程序调用FD1的本地子程序
+        let subset = subset_cff(&data, [1u16, 3].into_iter()).unwrap();
+        assert!(subset.len() < data.len());
+
+        let original = cff::Table::parse(&data).unwrap();
+        let table = cff::Table::parse(&subset).expect("子集化后的CFF无法解析");
+        assert_eq!(table.number_of_glyphs(), 6);
+        for id in [0, 1, 3] {
+            let expected = outline(&original, id).unwrap();
+            assert!(!expected.is_empty());
+            assert_eq!(outline(&table, id), Some(expected), "字形 {}", id);
+        }
+        for id in [2, 4, 5] {
+            assert!(outline(&table, id).is_none(), "字形 {} 应为空字形", id);
+        }
+        // 字符集（CID）和FD分配不变
+        for id in 0..6 {
+            assert_eq!(table.glyph_cid(GlyphId(id)), original.glyph_cid(GlyphId(id)));
+        }
+        assert_eq!(table.glyph_cid(GlyphId(3)), Some(20));
+    }
+
+    #[test]
+    fn replaces_unused_programs_with_empty_ones() {
+        let data = fixture_cff();
+        let subset = subset_cff(&data, std::iter::once(1)).unwrap();
+
+        let header_size = subset[2] as usize;
+        let (_, name_end) = read_index(&subset, header_size).unwrap();
+        let (top_dicts, top_end) = read_index(&subset, name_end).unwrap();
+        let (_, string_end) = read_index(&subset, top_end).unwrap();
+        let (global_subrs, _) = read_index(&subset, string_end).unwrap();
+        let top = parse_dict(top_dicts[0]).unwrap();
+        let charstrings_offset = find_operands(&top, OP_CHARSTRINGS).unwrap()[0] as usize;
+        let (charstrings, _) = read_index(&subset, charstrings_offset).unwrap();
+
+        // A 只调用全局子程序0和FD0的本地子程序0
+        assert_eq!(global_subrs.len(), 3);
+        assert!(global_subrs[0].len() > 1);
+        assert_eq!(global_subrs[1], [RETURN]);
+        assert_eq!(global_subrs[2], [RETURN]);
+        assert_eq!(charstrings.len(), 6);
+        assert!(charstrings[0].len() > 1 && charstrings[1].len() > 1);
+        for charstring in &charstrings[2..] {
+            assert_eq!(*charstring, [ENDCHAR]);
+        }
+
+        let fd_array_offset = find_operands(&top, OP_FDARRAY).unwrap()[0] as usize;
+        let (fd_dicts, _) = read_index(&subset, fd_array_offset).unwrap();
+        let local_subrs: Vec<Vec<&[u8]>> = fd_dicts
+            .iter()
+            .map(|dict| {
+                let operands = find_operands(&parse_dict(dict).unwrap(), OP_PRIVATE).unwrap();
+                Private::read(&subset, &operands).unwrap().subrs
+            })
+            .collect();
+        assert!(local_subrs[0][0].len() > 1);
+        assert_eq!(local_subrs[0][1], [RETURN]);
+        assert_eq!(local_subrs[1], [[RETURN], [RETURN]]);
+    }
+
+    #[test]
+    fn hint_masks_are_skipped_when_scanning() {
+        let data = fixture_cff();
+        // 中 的 hintmask 有两个掩码字节，第二个字节 0x1D 若被当作 callgsubr 会调用错误的子程序
+        let subset = subset_cff(&data, std::iter::once(3)).unwrap();
+        let (table, original) = (cff::Table::parse(&subset).unwrap(), cff::Table::parse(&data).unwrap());
+        assert_eq!(outline(&table, 3), outline(&original, 3));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = fixture_cff();
+        assert!(subset_cff(&data[..data.len() / 2], std::iter::once(1)).is_err());
+        assert!(subset_cff(&[2, 0, 5, 4], std::iter::once(1)).is_err());
+    }
+}
@@ -0,0 +1,813 @@
+use crate::analyzer_backend::ProgressCallback;
+use crate::theme::{self, ReportTheme};
+use anyhow::{anyhow, Context, Result};
+use plotters::coord::Shift;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+/// 图表字体名称。中文字体以该名称注册，SVG 中的文字也使用该字体名，由查看器选择系统字体
+const FONT_FAMILY: &str = "sans-serif";
+/// 随应用打包的字体目录名（位于资源目录下）
+pub const FONT_DIR_NAME: &str = "fonts";
+/// 打包字体缺失时依次尝试的系统中文字体
+const SYSTEM_CJK_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wqy-microhei/wqy-microhei.ttc",
+];
+
+/// 静态图表中显示的书目数量（阅读时长最长的前若干本）
+const CHART_TOP_BOOKS: usize = 10;
+
+/// 每月借阅频次图表最多显示的月份标签数量
+const MAX_MONTH_LABELS: usize = 12;
+
+/// 文字、边距和线条尺寸的基准分辨率，主题中的分辨率不同时按比例缩放
+const BASE_DPI: f64 = 150.0;
+
+static FONT_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+static FONTS: Mutex<Option<FontCache>> = Mutex::new(None);
+
+/// 已找到的字体：按主题中指定的字体（未指定时为 `None`）记录查找结果，找不到字体时不记录，下次重新查找
+#[derive(Default)]
+struct FontCache {
+    resolved: HashMap<Option<PathBuf>, PathBuf>,
+    /// 已读取的字体文件内容，注册后在整个运行期间使用
+    loaded: HashMap<PathBuf, &'static [u8]>,
+    /// 当前注册为图表字体的文件
+    registered: Option<PathBuf>,
+}
+
+/// 图表数据，两种后端都从分析结果中提取
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChartData {
+    /// 每本书的阅读时长（天），按时长降序
+    pub book_duration: Vec<(String, i64)>,
+    /// 每月借阅册数（`YYYY-MM`），按年月连续排列，没有借阅的月份为0
+    pub monthly_borrow: Vec<(String, u32)>,
+    /// 各类别借阅册数
+    pub category_count: Vec<(String, u32)>,
+}
+
+/// 前端交互图表的建议图形
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartType {
+    /// 横向条形图
+    Bar,
+    Line,
+    Pie,
+}
+
+/// 一张图表的数据序列，`get_chart_data` 返回该结构的列表，供前端绘制交互图表
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartSeries {
+    /// 与 `generate_charts` 返回的键一致：`duration`/`monthly`/`category`/`year_over_year`
+    pub key: &'static str,
+    pub title: &'static str,
+    pub chart_type: ChartType,
+    /// 标签（书名、月份、类别）的名称
+    pub label_name: &'static str,
+    /// 数值的名称和单位
+    pub value_name: &'static str,
+    pub unit: &'static str,
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+    /// 多条折线的图表（逐年对比）中每条折线的数据，与 `labels` 一一对应，缺少的数据为 `null`；
+    /// 此时 `values` 为空
+    pub groups: Vec<ChartGroup>,
+    /// 截取前 N 项之前的数据项数量
+    pub total: usize,
+}
+
+/// 多条折线图表中的一条折线
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartGroup {
+    pub name: String,
+    pub values: Vec<Option<f64>>,
+}
+
+/// 图表类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChartKind {
+    Duration,
+    Monthly,
+    Category,
+    /// 逐年对比：每年一条折线，只在数据跨越多个年份时生成
+    YearOverYear,
+}
+
+impl ChartKind {
+    const ALL: [ChartKind; 4] = [
+        ChartKind::Duration,
+        ChartKind::Monthly,
+        ChartKind::Category,
+        ChartKind::YearOverYear,
+    ];
+
+    /// 根据数据需要生成的图表
+    fn available(data: &ChartData) -> Vec<ChartKind> {
+        let multi_year = year_over_year(&data.monthly_borrow).len() > 1;
+        Self::ALL
+            .into_iter()
+            .filter(|kind| *kind != ChartKind::YearOverYear || multi_year)
+            .collect()
+    }
+
+    /// `generate_charts` 返回的键
+    fn key(self) -> &'static str {
+        match self {
+            ChartKind::Duration => "duration",
+            ChartKind::Monthly => "monthly",
+            ChartKind::Category => "category",
+            ChartKind::YearOverYear => "year_over_year",
+        }
+    }
+
+    fn file_stem(self) -> &'static str {
+        match self {
+            ChartKind::Duration => "book_duration",
+            ChartKind::Monthly => "borrow_per_month",
+            ChartKind::Category => "category_ratio",
+            ChartKind::YearOverYear => "borrow_year_over_year",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            ChartKind::Duration => "每本书的阅读时长",
+            ChartKind::Monthly => "每月借阅频次",
+            ChartKind::Category => "类别阅读册数比例",
+            ChartKind::YearOverYear => "逐年借阅频次对比",
+        }
+    }
+
+    /// 图片尺寸（像素），由主题中的图表尺寸和分辨率决定
+    fn size(self, theme: &ReportTheme) -> (u32, u32) {
+        match self {
+            ChartKind::Duration | ChartKind::Monthly | ChartKind::YearOverYear => (
+                theme.chart_pixels(theme.chart_width),
+                theme.chart_pixels(theme.chart_height),
+            ),
+            ChartKind::Category => {
+                let size = theme.chart_pixels(theme.pie_chart_size);
+                (size, size)
+            }
+        }
+    }
+
+    fn chart_type(self) -> ChartType {
+        match self {
+            ChartKind::Duration => ChartType::Bar,
+            ChartKind::Monthly | ChartKind::YearOverYear => ChartType::Line,
+            ChartKind::Category => ChartType::Pie,
+        }
+    }
+
+    /// 标签名称、数值名称和单位，与静态图表的坐标轴一致
+    fn axis_names(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            ChartKind::Duration => ("书名", "时长", "天"),
+            ChartKind::Monthly | ChartKind::YearOverYear => ("月份", "频次", "次"),
+            ChartKind::Category => ("类别", "册数", "册"),
+        }
+    }
+
+    fn progress_message(self) -> &'static str {
+        match self {
+            ChartKind::Duration => "已生成阅读时长图表",
+            ChartKind::Monthly => "已生成借阅频次图表",
+            ChartKind::Category => "已生成类别比例图表",
+            ChartKind::YearOverYear => "已生成逐年对比图表",
+        }
+    }
+}
+
+/// 绘制时使用的主题参数：配色和随分辨率缩放的尺寸
+struct ChartStyle {
+    palette: Vec<RGBColor>,
+    scale: f64,
+}
+
+impl ChartStyle {
+    fn from_theme(theme: &ReportTheme) -> Self {
+        Self {
+            palette: theme
+                .palette_rgb()
+                .into_iter()
+                .map(|(r, g, b)| RGBColor(r, g, b))
+                .collect(),
+            scale: theme.chart_dpi as f64 / BASE_DPI,
+        }
+    }
+
+    /// 配色中的第 `index` 个颜色，超出时循环使用
+    fn color(&self, index: usize) -> RGBColor {
+        self.palette[index % self.palette.len()]
+    }
+
+    /// 按分辨率缩放基准分辨率下的像素值
+    fn px(&self, value: u32) -> u32 {
+        ((value as f64 * self.scale).round() as u32).max(1)
+    }
+}
+
+/// 设置打包字体所在目录，应用启动时调用
+pub fn set_font_dir(dir: PathBuf) {
+    *FONT_DIR.lock().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+}
+
+/// 图表的数据序列；`top_n` 限制阅读时长图表的书目数量，未指定时与静态图表一致（前十本）
+pub fn chart_series(data: &ChartData, top_n: Option<usize>) -> Vec<ChartSeries> {
+    ChartKind::available(data)
+        .into_iter()
+        .map(|kind| {
+            let mut groups = Vec::new();
+            let (labels, values, total) = match kind {
+                ChartKind::Duration => {
+                    let top_n = top_n.unwrap_or(CHART_TOP_BOOKS);
+                    let (labels, values) = data
+                        .book_duration
+                        .iter()
+                        .take(top_n)
+                        .map(|(title, days)| (title.clone(), *days as f64))
+                        .unzip();
+                    (labels, values, data.book_duration.len())
+                }
+                ChartKind::Monthly => {
+                    let (labels, values) = data
+                        .monthly_borrow
+                        .iter()
+                        .map(|(month, count)| (month.clone(), *count as f64))
+                        .unzip();
+                    (labels, values, data.monthly_borrow.len())
+                }
+                ChartKind::Category => {
+                    let (labels, values) = data
+                        .category_count
+                        .iter()
+                        .map(|(category, count)| (category.clone(), *count as f64))
+                        .unzip();
+                    (labels, values, data.category_count.len())
+                }
+                ChartKind::YearOverYear => {
+                    let years = year_over_year(&data.monthly_borrow);
+                    groups = years
+                        .iter()
+                        .map(|(year, counts)| ChartGroup {
+                            name: year.to_string(),
+                            values: counts.iter().map(|count| count.map(f64::from)).collect(),
+                        })
+                        .collect();
+                    (month_labels(), Vec::new(), years.len())
+                }
+            };
+            let (label_name, value_name, unit) = kind.axis_names();
+            ChartSeries {
+                key: kind.key(),
+                title: kind.title(),
+                chart_type: kind.chart_type(),
+                label_name,
+                value_name,
+                unit,
+                labels,
+                values,
+                groups,
+                total,
+            }
+        })
+        .collect()
+}
+
+/// 按年份拆分年月时间线，每年12个月：第一个月到最后一个月之间没有数据的月份为0，之外的月份为 `None`
+fn year_over_year(monthly_borrow: &[(String, u32)]) -> Vec<(i32, [Option<u32>; 12])> {
+    let mut months: Vec<(i32, usize, u32)> = monthly_borrow
+        .iter()
+        .filter_map(|(month, count)| {
+            let (year, month) = month.split_once('-')?;
+            let (year, month) = (year.parse::<i32>().ok()?, month.parse::<usize>().ok()?);
+            (1..=12).contains(&month).then_some((year, month, *count))
+        })
+        .collect();
+    months.sort_by_key(|(year, month, _)| (*year, *month));
+    let (Some(&(first_year, first_month, _)), Some(&(last_year, last_month, _))) =
+        (months.first(), months.last())
+    else {
+        return Vec::new();
+    };
+
+    let mut years: Vec<(i32, [Option<u32>; 12])> = (first_year..=last_year)
+        .map(|year| {
+            let counts = std::array::from_fn(|index| {
+                let month = (year, index + 1);
+                (month >= (first_year, first_month) && month <= (last_year, last_month))
+                    .then_some(0)
+            });
+            (year, counts)
+        })
+        .collect();
+    for (year, month, count) in months {
+        let slot = &mut years[(year - first_year) as usize].1[month - 1];
+        *slot = Some(slot.unwrap_or(0) + count);
+    }
+    years
+}
+
+fn month_labels() -> Vec<String> {
+    (1..=12).map(|month| format!("{}月", month)).collect()
+}
+
+/// 按当前报告主题绘制图表，每张图表同时保存为PNG和SVG（同名，扩展名不同），数据跨越多个年份时另外绘制逐年对比图；
+/// 返回图表类型（`duration`/`monthly`/`category`/`year_over_year`）到PNG路径的映射
+pub fn render_charts(
+    data: &ChartData,
+    output_dir: &Path,
+    progress_callback: Option<ProgressCallback>,
+) -> Result<HashMap<String, String>> {
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("无法创建图表目录: {:?}", output_dir))?;
+    let theme = theme::current();
+    load_font(&theme)?;
+    let style = ChartStyle::from_theme(&theme);
+
+    let report = |current: u32, message: &str| {
+        if let Some(callback) = &progress_callback {
+            callback(100, current, message.to_string());
+        }
+    };
+    report(0, "开始生成图表");
+
+    let kinds = ChartKind::available(data);
+    let mut chart_paths = HashMap::new();
+    for (index, kind) in kinds.iter().copied().enumerate() {
+        let png_path = output_dir.join(format!("{}.png", kind.file_stem()));
+        let svg_path = output_dir.join(format!("{}.svg", kind.file_stem()));
+
+        let root = BitMapBackend::new(&png_path, kind.size(&theme)).into_drawing_area();
+        draw_chart(&root, kind, data, &style)
+            .and_then(|()| root.present())
+            .map_err(|e| anyhow!("绘制图表失败 {:?}: {}", png_path, e))?;
+
+        let root = SVGBackend::new(&svg_path, kind.size(&theme)).into_drawing_area();
+        draw_chart(&root, kind, data, &style)
+            .and_then(|()| root.present())
+            .map_err(|e| anyhow!("绘制图表失败 {:?}: {}", svg_path, e))?;
+
+        chart_paths.insert(kind.key().to_string(), png_path.to_string_lossy().to_string());
+        report(
+            ((index + 1) * 100 / kinds.len()) as u32,
+            kind.progress_message(),
+        );
+    }
+
+    info!("图表生成完成: {:?}", output_dir);
+    Ok(chart_paths)
+}
+
+/// 报告中的一张图表
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportChart {
+    /// 与 `generate_charts` 返回的键一致
+    pub key: &'static str,
+    pub title: &'static str,
+    /// PNG路径
+    pub path: PathBuf,
+}
+
+/// 报告中插入的图表，按固定顺序排列，`chart_paths` 为 `render_charts` 的返回值
+pub fn report_charts(chart_paths: &HashMap<String, String>) -> Vec<ReportChart> {
+    ChartKind::ALL
+        .into_iter()
+        .filter_map(|kind| {
+            chart_paths.get(kind.key()).map(|path| ReportChart {
+                key: kind.key(),
+                title: kind.title(),
+                path: PathBuf::from(path),
+            })
+        })
+        .collect()
+}
+
+/// 查找并注册中文字体：依次查找主题中指定的字体、打包的字体和系统字体。
+/// 查找结果按主题中指定的字体缓存，修改主题后使用新的字体。返回字体文件路径，PDF报告嵌入同一字体
+pub fn load_font(theme: &ReportTheme) -> Result<PathBuf> {
+    let mut fonts = FONTS.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = fonts.get_or_insert_with(FontCache::default);
+
+    let path = match cache.resolved.get(&theme.chart_font).filter(|path| path.is_file()) {
+        Some(path) => path.clone(),
+        None => {
+            let path = theme
+                .chart_font
+                .iter()
+                .cloned()
+                .chain(font_candidates())
+                .find(|path| path.is_file())
+                .ok_or_else(|| anyhow!("未找到中文字体，请将字体文件放到 {} 目录", FONT_DIR_NAME))?;
+            cache.resolved.insert(theme.chart_font.clone(), path.clone());
+            path
+        }
+    };
+
+    if cache.registered.as_ref() != Some(&path) {
+        let bytes = match cache.loaded.get(&path) {
+            Some(bytes) => *bytes,
+            None => {
+                let bytes = std::fs::read(&path).with_context(|| format!("无法读取字体 {:?}", path))?;
+                let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+                cache.loaded.insert(path.clone(), bytes);
+                bytes
+            }
+        };
+        if plotters::style::register_font(FONT_FAMILY, FontStyle::Normal, bytes).is_err() {
+            cache.resolved.remove(&theme.chart_font);
+            return Err(anyhow!("无法解析字体: {:?}", path));
+        }
+        cache.registered = Some(path.clone());
+        info!("图表字体: {:?}", path);
+    }
+    Ok(path)
+}
+
+fn font_candidates() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = FONT_DIR.lock().unwrap_or_else(|e| e.into_inner()).clone() {
+        dirs.push(dir);
+    }
+    // 开发模式下的字体目录
+    if let Ok(current_dir) = std::env::current_dir() {
+        dirs.push(current_dir.join("src-tauri").join(FONT_DIR_NAME));
+        dirs.push(current_dir.join(FONT_DIR_NAME));
+    }
+
+    let mut candidates = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut fonts: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| matches!(ext.to_lowercase().as_str(), "ttf" | "otf" | "ttc"))
+            })
+            .collect();
+        fonts.sort();
+        candidates.extend(fonts);
+    }
+    candidates.extend(SYSTEM_CJK_FONTS.iter().map(PathBuf::from));
+    candidates
+}
+
+fn draw_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    kind: ChartKind,
+    data: &ChartData,
+    style: &ChartStyle,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    root.fill(&WHITE)?;
+    match kind {
+        ChartKind::Duration => {
+            let top = data.book_duration.len().min(CHART_TOP_BOOKS);
+            draw_book_duration(root, &data.book_duration[..top], style)
+        }
+        ChartKind::Monthly => draw_monthly_borrow(root, &data.monthly_borrow, style),
+        ChartKind::Category => draw_category_ratio(root, &data.category_count, style),
+        ChartKind::YearOverYear => {
+            draw_year_over_year(root, &year_over_year(&data.monthly_borrow), style)
+        }
+    }
+}
+
+/// 数据为空时只显示标题和提示
+fn draw_empty<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    style: &ChartStyle,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let margin = style.px(30);
+    let area = root
+        .margin(margin, margin, margin, margin)
+        .titled(title, (FONT_FAMILY, style.px(48)))?;
+    let (width, height) = area.dim_in_pixel();
+    area.draw(&Text::new(
+        "暂无数据",
+        (width as i32 / 2, height as i32 / 2),
+        (FONT_FAMILY, style.px(32))
+            .into_font()
+            .color(&BLACK)
+            .pos(Pos::new(HPos::Center, VPos::Center)),
+    ))
+}
+
+/// 阅读时长横向条形图，时长最长的书在最上方
+fn draw_book_duration<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    book_duration: &[(String, i64)],
+    style: &ChartStyle,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let title = ChartKind::Duration.title();
+    if book_duration.is_empty() {
+        return draw_empty(root, title, style);
+    }
+
+    let bars: Vec<&(String, i64)> = book_duration.iter().rev().collect();
+    let max = bars.iter().map(|(_, days)| *days).max().unwrap_or(0).max(1);
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, (FONT_FAMILY, style.px(48)))
+        .margin(style.px(30))
+        .x_label_area_size(style.px(80))
+        .y_label_area_size(style.px(320))
+        .build_cartesian_2d(0..max + max / 10 + 1, (0..bars.len() as i32 - 1).into_segmented())?;
+    chart
+        .configure_mesh()
+        .disable_y_mesh()
+        .x_desc("时长（天）")
+        .y_desc("书名")
+        .label_style((FONT_FAMILY, style.px(24)))
+        .axis_desc_style((FONT_FAMILY, style.px(28)))
+        .y_labels(bars.len())
+        .y_label_formatter(&|value| match value {
+            SegmentValue::CenterOf(index) => bars
+                .get(*index as usize)
+                .map(|(title, _)| shorten(title, 16))
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .draw()?;
+    chart.draw_series(
+        Histogram::horizontal(&chart)
+            .style(style.color(0).filled())
+            .margin(style.px(10))
+            .data(bars.iter().enumerate().map(|(index, (_, days))| (index as i32, *days))),
+    )?;
+    Ok(())
+}
+
+/// 每月借阅频次折线图
+fn draw_monthly_borrow<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    monthly_borrow: &[(String, u32)],
+    style: &ChartStyle,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let title = ChartKind::Monthly.title();
+    if monthly_borrow.is_empty() {
+        return draw_empty(root, title, style);
+    }
+
+    let max = monthly_borrow.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1);
+    let label_step = monthly_borrow.len().div_ceil(MAX_MONTH_LABELS);
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, (FONT_FAMILY, style.px(48)))
+        .margin(style.px(30))
+        .x_label_area_size(style.px(80))
+        .y_label_area_size(style.px(100))
+        .build_cartesian_2d(
+            (0..monthly_borrow.len() as i32 - 1).into_segmented(),
+            0..max + max / 10 + 1,
+        )?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_desc("月份")
+        .y_desc("频次")
+        .label_style((FONT_FAMILY, style.px(24)))
+        .axis_desc_style((FONT_FAMILY, style.px(28)))
+        .x_labels(monthly_borrow.len())
+        .x_label_formatter(&|value| match value {
+            // 月份较多时间隔显示，避免标签重叠
+            SegmentValue::CenterOf(index) if (*index as usize).is_multiple_of(label_step) => {
+                monthly_borrow
+                    .get(*index as usize)
+                    .map(|(month, _)| month.clone())
+                    .unwrap_or_default()
+            }
+            _ => String::new(),
+        })
+        .draw()?;
+
+    let points: Vec<(SegmentValue<i32>, u32)> = monthly_borrow
+        .iter()
+        .enumerate()
+        .map(|(index, (_, count))| (SegmentValue::CenterOf(index as i32), *count))
+        .collect();
+    let line_style = style.color(0).stroke_width(style.px(3));
+    chart.draw_series(LineSeries::new(points.clone(), line_style))?;
+    chart.draw_series(
+        points
+            .into_iter()
+            .map(|point| Circle::new(point, style.px(8), style.color(0).filled())),
+    )?;
+    Ok(())
+}
+
+/// 逐年对比折线图：横轴为1-12月，每年一条折线
+fn draw_year_over_year<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    years: &[(i32, [Option<u32>; 12])],
+    style: &ChartStyle,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let title = ChartKind::YearOverYear.title();
+    let max = years
+        .iter()
+        .flat_map(|(_, counts)| counts.iter().flatten().copied())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, (FONT_FAMILY, style.px(48)))
+        .margin(style.px(30))
+        .x_label_area_size(style.px(80))
+        .y_label_area_size(style.px(100))
+        .build_cartesian_2d((1..12).into_segmented(), 0..max + max / 10 + 1)?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_desc("月份")
+        .y_desc("频次")
+        .label_style((FONT_FAMILY, style.px(24)))
+        .axis_desc_style((FONT_FAMILY, style.px(28)))
+        .x_labels(12)
+        .x_label_formatter(&|value| match value {
+            SegmentValue::CenterOf(month) => format!("{}月", month),
+            _ => String::new(),
+        })
+        .draw()?;
+
+    for (index, (year, counts)) in years.iter().enumerate() {
+        let color = style.color(index);
+        let points: Vec<(SegmentValue<i32>, u32)> = counts
+            .iter()
+            .enumerate()
+            .filter_map(|(month, count)| Some((SegmentValue::CenterOf(month as i32 + 1), (*count)?)))
+            .collect();
+        let line_style = color.stroke_width(style.px(3));
+        let legend_width = style.px(20) as i32;
+        chart
+            .draw_series(LineSeries::new(points.clone(), line_style))?
+            .label(year.to_string())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + legend_width, y)], line_style));
+        chart.draw_series(
+            points
+                .into_iter()
+                .map(|point| Circle::new(point, style.px(8), color.filled())),
+        )?;
+    }
+    chart
+        .configure_series_labels()
+        .label_font((FONT_FAMILY, style.px(24)))
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()
+}
+
+/// 类别册数饼图，扇区上标注百分比
+fn draw_category_ratio<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    category_count: &[(String, u32)],
+    style: &ChartStyle,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let title = ChartKind::Category.title();
+    let category_count: Vec<&(String, u32)> =
+        category_count.iter().filter(|(_, count)| *count > 0).collect();
+    if category_count.is_empty() {
+        return draw_empty(root, title, style);
+    }
+
+    let margin = style.px(30);
+    let area = root
+        .margin(margin, margin, margin, margin)
+        .titled(title, (FONT_FAMILY, style.px(48)))?;
+    let (width, height) = area.dim_in_pixel();
+    let center = (width as i32 / 2, height as i32 / 2);
+    let radius = width.min(height) as f64 * 0.35;
+    let sizes: Vec<f64> = category_count.iter().map(|(_, count)| *count as f64).collect();
+    let labels: Vec<&str> = category_count.iter().map(|(category, _)| category.as_str()).collect();
+    let colors: Vec<RGBColor> = (0..sizes.len()).map(|index| style.color(index)).collect();
+
+    let mut pie = Pie::new(&center, &radius, &sizes, &colors, &labels);
+    pie.label_style((FONT_FAMILY, style.px(28)).into_font().color(&BLACK));
+    pie.percentages((FONT_FAMILY, style.px(24)).into_font().color(&WHITE));
+    area.draw(&pie)
+}
+
+/// 过长的书名截断显示
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        text.chars().take(max_chars).chain(std::iter::once('…')).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn months(entries: &[(&str, u32)]) -> Vec<(String, u32)> {
+        entries.iter().map(|(month, count)| (month.to_string(), *count)).collect()
+    }
+
+    fn series<'a>(all: &'a [ChartSeries], key: &str) -> Option<&'a ChartSeries> {
+        all.iter().find(|series| series.key == key)
+    }
+
+    #[test]
+    fn same_month_of_different_years_stays_separate() {
+        let years = year_over_year(&months(&[("2024-01", 3), ("2024-02", 1), ("2025-01", 5)]));
+        assert_eq!(years.len(), 2);
+        assert_eq!(years[0].0, 2024);
+        assert_eq!(years[0].1[0], Some(3));
+        assert_eq!(years[1].0, 2025);
+        assert_eq!(years[1].1[0], Some(5));
+    }
+
+    #[test]
+    fn months_inside_the_timeline_are_zero_filled() {
+        // 2024-12 和 2025-01 缺失
+        let years = year_over_year(&months(&[("2025-02", 2), ("2024-10", 4), ("2024-11", 1)]));
+
+        let mut expected_2024 = [None; 12];
+        expected_2024[9] = Some(4);
+        expected_2024[10] = Some(1);
+        expected_2024[11] = Some(0);
+        let mut expected_2025 = [None; 12];
+        expected_2025[0] = Some(0);
+        expected_2025[1] = Some(2);
+        assert_eq!(years, vec![(2024, expected_2024), (2025, expected_2025)]);
+
+        assert!(year_over_year(&[]).is_empty());
+        assert!(year_over_year(&months(&[("2024-13", 1), ("未知", 2)])).is_empty());
+    }
+
+    #[test]
+    fn year_over_year_series_has_one_group_per_year() {
+        let data = ChartData {
+            monthly_borrow: months(&[("2024-12", 2), ("2025-01", 0), ("2025-02", 7)]),
+            ..Default::default()
+        };
+        let all = chart_series(&data, None);
+
+        let monthly = series(&all, "monthly").unwrap();
+        assert_eq!(monthly.labels, ["2024-12", "2025-01", "2025-02"]);
+        assert_eq!(monthly.values, [2.0, 0.0, 7.0]);
+
+        let yoy = series(&all, "year_over_year").unwrap();
+        assert_eq!(yoy.labels.len(), 12);
+        assert_eq!(yoy.labels[0], "1月");
+        assert!(yoy.values.is_empty());
+        assert_eq!(yoy.total, 2);
+        let names: Vec<&str> = yoy.groups.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, ["2024", "2025"]);
+        assert_eq!(yoy.groups[0].values[11], Some(2.0));
+        assert_eq!(yoy.groups[0].values[0], None);
+        assert_eq!(yoy.groups[1].values[0], Some(0.0));
+        assert_eq!(yoy.groups[1].values[1], Some(7.0));
+        assert_eq!(yoy.groups[1].values[2], None);
+    }
+
+    #[test]
+    fn single_year_has_no_year_over_year_series() {
+        let data = ChartData {
+            monthly_borrow: months(&[("2025-01", 1), ("2025-02", 2)]),
+            ..Default::default()
+        };
+        let keys: Vec<&str> = chart_series(&data, None).iter().map(|series| series.key).collect();
+        assert_eq!(keys, ["duration", "monthly", "category"]);
+    }
+
+    #[test]
+    fn top_n_truncates_duration_series() {
+        let data = ChartData {
+            book_duration: (0..15).map(|index| (format!("书{}", index), 30 - index)).collect(),
+            ..Default::default()
+        };
+
+        let default = chart_series(&data, None);
+        let duration = series(&default, "duration").unwrap();
+        assert_eq!(duration.labels.len(), CHART_TOP_BOOKS);
+        assert_eq!(duration.total, 15);
+
+        let top = chart_series(&data, Some(3));
+        let duration = series(&top, "duration").unwrap();
+        assert_eq!(duration.labels, ["书0", "书1", "书2"]);
+        assert_eq!(duration.values, [30.0, 29.0, 28.0]);
+        assert_eq!(duration.total, 15);
+
+        let all = chart_series(&data, Some(100));
+        assert_eq!(series(&all, "duration").unwrap().labels.len(), 15);
+    }
+}
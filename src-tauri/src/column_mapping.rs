@@ -0,0 +1,421 @@
+use crate::error::AnalyzerError;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// 表格中的逻辑字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    ReaderName,
+    BookTitle,
+    Author,
+    Category,
+    Price,
+    BorrowDate,
+    ReturnDate,
+    RegistrationDate,
+    CardNumber,
+}
+
+/// 借阅数据必需的字段
+pub const BORROW_COLUMNS: [Column; 7] = [
+    Column::ReaderName,
+    Column::BookTitle,
+    Column::Author,
+    Column::Category,
+    Column::Price,
+    Column::BorrowDate,
+    Column::ReturnDate,
+];
+
+/// 借阅数据的可选字段
+pub const OPTIONAL_BORROW_COLUMNS: [Column; 1] = [Column::CardNumber];
+
+/// 读者清单必需的字段
+pub const MEMBER_COLUMNS: [Column; 2] = [Column::ReaderName, Column::RegistrationDate];
+
+/// 读者清单的可选字段
+pub const OPTIONAL_MEMBER_COLUMNS: [Column; 1] = [Column::CardNumber];
+
+impl Column {
+    /// 所有字段，用于判断模糊匹配是否有歧义
+    const ALL: [Column; 9] = [
+        Column::ReaderName,
+        Column::BookTitle,
+        Column::Author,
+        Column::Category,
+        Column::Price,
+        Column::BorrowDate,
+        Column::ReturnDate,
+        Column::RegistrationDate,
+        Column::CardNumber,
+    ];
+
+    /// 分析器内部使用的标准表头
+    pub fn canonical(self) -> &'static str {
+        match self {
+            Column::ReaderName => "姓名",
+            Column::BookTitle => "书名",
+            Column::Author => "作者",
+            Column::Category => "类别",
+            Column::Price => "定价",
+            Column::BorrowDate => "借书日期",
+            Column::ReturnDate => "还书日期",
+            Column::RegistrationDate => "办卡日期",
+            Column::CardNumber => "读者证号",
+        }
+    }
+
+    /// 内置的常见别名
+    fn default_aliases(self) -> &'static [&'static str] {
+        match self {
+            Column::ReaderName => &["读者姓名", "读者", "借阅人", "借书人"],
+            Column::BookTitle => &["题名", "书刊名", "图书名称", "正题名"],
+            Column::Author => &["责任者", "著者", "作者名"],
+            Column::Category => &["类别代码", "分类", "图书类别", "馆藏类别"],
+            Column::Price => &["价格", "单价", "金额"],
+            Column::BorrowDate => &["借阅日期", "借出日期", "借书时间", "借阅时间"],
+            Column::ReturnDate => &["归还日期", "还回日期", "还书时间", "归还时间"],
+            Column::RegistrationDate => &["办证日期", "注册日期", "开卡日期"],
+            Column::CardNumber => &["借书证号", "借阅证号", "读者卡号", "证号", "卡号"],
+        }
+    }
+}
+
+/// 表头映射配置，可从 JSON/TOML 文件加载，例如：
+///
+/// ```toml
+/// name = "某分馆"
+/// [aliases]
+/// book_title = ["题名"]
+/// borrow_date = ["借阅日期"]
+/// ```
+///
+/// 配置中的别名在内置别名之前匹配，标准表头始终可用。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    /// 配置名称（用于日志）
+    pub name: Option<String>,
+    /// 各字段的附加别名
+    pub aliases: BTreeMap<Column, Vec<String>>,
+    /// 是否启用模糊匹配（包含关系、单字差异）
+    pub fuzzy: bool,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            name: None,
+            aliases: BTreeMap::new(),
+            fuzzy: true,
+        }
+    }
+}
+
+impl ColumnMapping {
+    /// 从 `.json` 或 `.toml` 文件加载映射配置
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("无法读取表头映射配置: {:?}", path))?;
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "json" => serde_json::from_str(&content)
+                .with_context(|| format!("表头映射配置格式错误: {:?}", path)),
+            "toml" => toml::from_str(&content)
+                .with_context(|| format!("表头映射配置格式错误: {:?}", path)),
+            _ => Err(anyhow!("不支持的表头映射配置格式: {:?}", path)),
+        }
+    }
+
+    /// 某字段可接受的所有表头（标准表头、配置别名、内置别名）
+    pub fn candidates(&self, column: Column) -> Vec<String> {
+        let mut candidates = vec![column.canonical().to_string()];
+        if let Some(aliases) = self.aliases.get(&column) {
+            candidates.extend(aliases.iter().cloned());
+        }
+        candidates.extend(column.default_aliases().iter().map(|alias| alias.to_string()));
+        candidates
+    }
+
+    /// 在表头中定位各字段所在的列，缺少字段时报错并列出可接受的表头
+    pub fn resolve(&self, headers: &[String], columns: &[Column]) -> Result<HashMap<Column, usize>> {
+        self.resolve_with_optional(headers, columns, &[])
+    }
+
+    /// 同 `resolve`，另外定位可选字段（找不到时不报错）
+    pub fn resolve_with_optional(
+        &self,
+        headers: &[String],
+        required: &[Column],
+        optional: &[Column],
+    ) -> Result<HashMap<Column, usize>> {
+        let columns: Vec<Column> = required.iter().chain(optional).copied().collect();
+        let (resolved, missing) = self.resolve_partial(headers, &columns);
+        let missing: Vec<Column> = missing
+            .into_iter()
+            .filter(|column| required.contains(column))
+            .collect();
+
+        if missing.is_empty() {
+            Ok(resolved)
+        } else {
+            let descriptions: Vec<String> = missing
+                .iter()
+                .map(|&column| {
+                    format!(
+                        "{}（可接受的表头: {}）",
+                        column.canonical(),
+                        self.candidates(column).join(", ")
+                    )
+                })
+                .collect();
+            Err(AnalyzerError::SchemaError {
+                message: format!("缺少列: {}", descriptions.join("; ")),
+                missing: missing
+                    .iter()
+                    .map(|column| column.canonical().to_string())
+                    .collect(),
+            }
+            .into())
+        }
+    }
+
+    /// 在表头中定位各字段所在的列，返回已找到的列和缺少的字段
+    ///
+    /// 所有字段与表头的匹配按分数从高到低统一分配，每个表头只分配给一个字段，结果与字段顺序无关。
+    /// 模糊匹配只在表头明确属于该字段时采用：表头与其他字段的匹配分数不低于该字段时
+    /// （如与其他字段的标准表头或别名完全一致，或 `日期` 同时包含于 `借书日期` 和 `还书日期`），
+    /// 视为有歧义而不采用。
+    pub fn resolve_partial(
+        &self,
+        headers: &[String],
+        columns: &[Column],
+    ) -> (HashMap<Column, usize>, Vec<Column>) {
+        let normalized: Vec<String> = headers.iter().map(|h| normalize_header(h)).collect();
+
+        let mut matches = Vec::new();
+        for (order, &column) in columns.iter().enumerate() {
+            for (index, header) in normalized.iter().enumerate() {
+                if header.is_empty() {
+                    continue;
+                }
+                let Some((score, rank)) = self.column_score(header, column) else {
+                    continue;
+                };
+                let ambiguous = score < 3
+                    && Column::ALL.iter().any(|&other| {
+                        other != column
+                            && self
+                                .column_score(header, other)
+                                .is_some_and(|(other_score, _)| other_score >= score)
+                    });
+                if !ambiguous {
+                    matches.push((score, rank, index, order, column));
+                }
+            }
+        }
+        // 分数越高越好，同分时取靠前的候选表头、靠前的列
+        matches.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then(a.1.cmp(&b.1))
+                .then(a.2.cmp(&b.2))
+                .then(a.3.cmp(&b.3))
+        });
+
+        let mut resolved = HashMap::new();
+        for (_, _, index, _, column) in matches {
+            if !resolved.contains_key(&column) && !resolved.values().any(|&used| used == index) {
+                resolved.insert(column, index);
+            }
+        }
+        let missing = columns
+            .iter()
+            .copied()
+            .filter(|column| !resolved.contains_key(column))
+            .collect();
+
+        (resolved, missing)
+    }
+
+    /// 生成 {实际表头: 标准表头} 的重命名表，供Python后端使用
+    pub fn renames(
+        &self,
+        headers: &[String],
+        required: &[Column],
+        optional: &[Column],
+    ) -> Result<HashMap<String, String>> {
+        Ok(self
+            .resolve_with_optional(headers, required, optional)?
+            .into_iter()
+            .map(|(column, index)| (headers[index].clone(), column.canonical().to_string()))
+            .collect())
+    }
+
+    /// 表头与某字段的最佳匹配：分数和候选表头的序号（同分时取靠前的候选表头）
+    fn column_score(&self, header: &str, column: Column) -> Option<(u8, usize)> {
+        self.candidates(column)
+            .iter()
+            .enumerate()
+            .filter_map(|(rank, candidate)| {
+                self.match_score(header, &normalize_header(candidate))
+                    .map(|score| (score, rank))
+            })
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+    }
+
+    /// 匹配分数：3 = 完全一致，2 = 包含关系，1 = 仅差一个字
+    fn match_score(&self, header: &str, candidate: &str) -> Option<u8> {
+        if header == candidate {
+            return Some(3);
+        }
+        if !self.fuzzy {
+            return None;
+        }
+        if header.contains(candidate) || candidate.contains(header) {
+            return Some(2);
+        }
+        if header.chars().count() >= 3 && edit_distance(header, candidate) <= 1 {
+            return Some(1);
+        }
+        None
+    }
+}
+
+/// 表头规范化：去除空白和括号中的说明，全角字符转半角，英文转小写
+fn normalize_header(header: &str) -> String {
+    let mut normalized = String::new();
+    let mut depth = 0;
+    for c in header.chars().map(to_half_width) {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = (depth - 1).max(0),
+            c if c.is_whitespace() || depth > 0 => {}
+            c => normalized.extend(c.to_lowercase()),
+        }
+    }
+    normalized
+}
+
+pub(crate) fn to_half_width(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '（' => '(',
+        '）' => ')',
+        '【' => '[',
+        '】' => ']',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        c => c,
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(headers: &[&str]) -> Vec<String> {
+        headers.iter().map(|header| header.to_string()).collect()
+    }
+
+    #[test]
+    fn normalizes_headers() {
+        assert_eq!(normalize_header(" 借书 日期（必填）"), "借书日期");
+        assert_eq!(normalize_header("【说明】定价"), "定价");
+        assert_eq!(normalize_header("ＩＳＢＮ"), "isbn");
+    }
+
+    #[test]
+    fn resolves_aliases_and_fuzzy_headers() {
+        let mapping = ColumnMapping::default();
+        let headers = headers(&["读者姓名", "题名", "第一作者", "分类", "价格(元)", "借书日其", "归还日期"]);
+        let resolved = mapping.resolve(&headers, &BORROW_COLUMNS).unwrap();
+        assert_eq!(resolved[&Column::ReaderName], 0);
+        assert_eq!(resolved[&Column::BookTitle], 1);
+        assert_eq!(resolved[&Column::Author], 2);
+        assert_eq!(resolved[&Column::Category], 3);
+        assert_eq!(resolved[&Column::Price], 4);
+        assert_eq!(resolved[&Column::BorrowDate], 5);
+        assert_eq!(resolved[&Column::ReturnDate], 6);
+    }
+
+    #[test]
+    fn rejects_fuzzy_matches_of_other_columns() {
+        let mapping = ColumnMapping::default();
+        // 还书日期与借书日期只差一个字，但它是还书日期的标准表头
+        let (resolved, missing) = mapping.resolve_partial(&headers(&["还书日期"]), &[Column::BorrowDate]);
+        assert!(resolved.is_empty());
+        assert_eq!(missing, vec![Column::BorrowDate]);
+
+        // 读者证号包含别名“读者”，但它是读者证号字段
+        let (resolved, _) = mapping.resolve_partial(&headers(&["读者证号"]), &[Column::ReaderName]);
+        assert!(resolved.is_empty());
+    }
+
+    #[test]
+    fn rejects_ambiguous_fuzzy_matches() {
+        let mapping = ColumnMapping::default();
+        let columns = [Column::BorrowDate, Column::ReturnDate];
+        let (resolved, missing) = mapping.resolve_partial(&headers(&["日期"]), &columns);
+        assert!(resolved.is_empty());
+        assert_eq!(missing, columns.to_vec());
+
+        // 与借书日期、还书日期都只差一个字
+        let (resolved, _) = mapping.resolve_partial(&headers(&["续书日期"]), &columns);
+        assert!(resolved.is_empty());
+    }
+
+    #[test]
+    fn assignment_does_not_depend_on_column_order() {
+        let mapping = ColumnMapping::default();
+        let headers = headers(&["借书人", "借书证号"]);
+        for columns in [
+            [Column::ReaderName, Column::CardNumber],
+            [Column::CardNumber, Column::ReaderName],
+        ] {
+            let (resolved, missing) = mapping.resolve_partial(&headers, &columns);
+            assert!(missing.is_empty());
+            assert_eq!(resolved[&Column::ReaderName], 0);
+            assert_eq!(resolved[&Column::CardNumber], 1);
+        }
+    }
+
+    #[test]
+    fn configured_aliases_and_strict_mode() {
+        let mapping = ColumnMapping {
+            aliases: BTreeMap::from([(Column::BookTitle, vec!["文献名".to_string()])]),
+            fuzzy: false,
+            ..ColumnMapping::default()
+        };
+        let (resolved, missing) =
+            mapping.resolve_partial(&headers(&["文献名", "借书日其"]), &[Column::BookTitle, Column::BorrowDate]);
+        assert_eq!(resolved[&Column::BookTitle], 0);
+        assert_eq!(missing, vec![Column::BorrowDate]);
+
+        let error = mapping.resolve(&headers(&["文献名"]), &[Column::Price]).unwrap_err();
+        assert!(error.to_string().contains("定价"));
+    }
+}
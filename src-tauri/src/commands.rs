@@ -1,157 +1,535 @@
-use crate::python_analyzer::{PythonAnalyzer, AnalysisResult};
-use std::sync::Mutex;
-use tauri::State;
+use crate::analyzer_backend::{create_backend, ReportGrouping};
+use crate::analyzer_worker::{AnalyzerWorker, DataSummary};
+use crate::charts::{self, ChartSeries};
+use crate::column_mapping::ColumnMapping;
+use crate::config::AppConfig;
+use crate::data_export::DataFormat;
+use crate::diagnostics::{self, InputSelection};
+use crate::error::AnalyzerError;
+use crate::jobs::{self, JobInfo, JobRegistry};
+use crate::logging;
+use crate::progress::ProgressStage;
+use crate::report::ReportFormat;
+use crate::spreadsheet::{self, SheetSelection};
+use crate::validation::{self, ValidationReport};
+use chrono::{Local, NaiveDate};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_opener::OpenerExt;
+use tracing::instrument;
 
 // 全局状态管理
 pub struct AppState {
-    pub analyzer: Mutex<Option<PythonAnalyzer>>,
+    pub config: AppConfig,
+    /// 分析器在独立的工作线程中运行，命令不会长时间占用锁
+    pub analyzer: AnalyzerWorker,
+    pub jobs: Arc<JobRegistry>,
+    /// 最近一次分析的输入文件（诊断包使用）
+    pub last_inputs: Mutex<Option<InputSelection>>,
 }
 
-impl Default for AppState {
-    fn default() -> Self {
+impl AppState {
+    pub fn new(config: AppConfig) -> Self {
         Self {
-            analyzer: Mutex::new(None),
+            config,
+            analyzer: AnalyzerWorker::spawn(),
+            jobs: Arc::new(JobRegistry::new()),
+            last_inputs: Mutex::new(None),
         }
     }
 }
 
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new(AppConfig::default())
+    }
+}
+
 #[tauri::command]
-pub async fn initialize_analyzer(state: State<'_, AppState>) -> Result<(), String> {
-    let mut analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    match PythonAnalyzer::new() {
-        Ok(mut analyzer) => {
-            if let Err(e) = analyzer.initialize() {
-                return Err(format!("初始化Python分析器失败: {}", e));
+#[instrument(skip_all)]
+pub async fn initialize_analyzer(app: AppHandle, state: State<'_, AppState>) -> Result<(), AnalyzerError> {
+    let kind = state.config.analyzer_backend;
+    let shadow = state.config.shadow_backend;
+    state.analyzer.set_summary(None);
+
+    // 分析器在工作线程中创建，等待期间不占用异步运行时线程
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<AppState>().analyzer.run(move |analyzer| {
+            match create_backend(kind, shadow) {
+                Ok(backend) => {
+                    *analyzer = Some(backend);
+                    Ok(())
+                }
+                Err(e) => Err(AnalyzerError::classify(e, |message| AnalyzerError::Internal {
+                    message: format!("初始化分析器失败: {}", message),
+                })),
             }
-            *analyzer_guard = Some(analyzer);
-            Ok(())
-        }
-        Err(e) => Err(format!("创建Python分析器失败: {}", e)),
+        })
+    })
+    .await
+    .map_err(|e| AnalyzerError::Internal {
+        message: format!("初始化分析器失败: {}", e),
+    })
+    .and_then(|result| result);
+
+    if let Err(e) = &result {
+        diagnostics::record_error("initialize_analyzer", e);
+    }
+    result
+}
+
+// 会员时长的统计日期，未指定时使用当天
+fn as_of_date(as_of: Option<NaiveDate>) -> NaiveDate {
+    as_of.unwrap_or_else(|| Local::now().date_naive())
+}
+
+// 表头映射：优先使用本次指定的配置，其次是启动配置中的默认配置
+fn load_column_mapping(
+    column_profile: Option<String>,
+    state: &AppState,
+) -> Result<ColumnMapping, AnalyzerError> {
+    let profile = column_profile
+        .map(std::path::PathBuf::from)
+        .or_else(|| state.config.column_profile.clone());
+    match profile {
+        Some(path) => ColumnMapping::load(&path).map_err(|e| {
+            AnalyzerError::classify(e, |message| AnalyzerError::ConfigError {
+                message: format!("加载表头映射失败: {}", message),
+            })
+        }),
+        None => Ok(ColumnMapping::default()),
     }
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
+pub async fn validate_inputs(
+    reader_list_path: String,
+    borrow_paths: Vec<String>,
+    column_profile: Option<String>,
+    sheet_selection: Option<SheetSelection>,
+    state: State<'_, AppState>,
+) -> Result<ValidationReport, AnalyzerError> {
+    let column_mapping = load_column_mapping(column_profile, &state)?;
+
+    // 读取表格期间不占用异步运行时线程
+    tauri::async_runtime::spawn_blocking(move || {
+        validation::validate_inputs(
+            &borrow_paths,
+            &reader_list_path,
+            &column_mapping,
+            &sheet_selection.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| AnalyzerError::Internal {
+        message: format!("校验输入文件失败: {}", e),
+    })
+}
+
+// 列出各文件的工作表名称，文本文件（CSV/TSV）返回空列表
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn list_sheets(
+    paths: Vec<String>,
+) -> Result<std::collections::HashMap<String, Vec<String>>, AnalyzerError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        paths
+            .into_iter()
+            .map(|path| match spreadsheet::sheet_names(&path) {
+                Ok(names) => Ok((path, names)),
+                Err(e) => Err(AnalyzerError::classify(e, |message| AnalyzerError::IoError {
+                    message: format!("读取工作表列表失败: {}", message),
+                })),
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| AnalyzerError::Internal {
+        message: format!("读取工作表列表失败: {}", e),
+    })
+    .and_then(|result| result)
+}
+
+// 分析任务：加载数据并分析，立即返回任务ID，结果在任务结束事件中返回
+#[tauri::command]
+#[instrument(skip_all)]
 pub async fn analyze_files(
     reader_list_path: String,
     borrow_paths: Vec<String>,
+    column_profile: Option<String>,
+    sheet_selection: Option<SheetSelection>,
+    as_of: Option<NaiveDate>,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<AnalysisResult, String> {
-    let analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    let analyzer = analyzer_guard
-        .as_ref()
-        .ok_or("分析器未初始化，请先调用 initialize_analyzer")?;
-
-    // 加载数据
-    if let Err(e) = analyzer.load_data(borrow_paths, reader_list_path, None) {
-        return Err(format!("加载数据失败: {}", e));
-    }
+) -> Result<String, AnalyzerError> {
+    let column_mapping = load_column_mapping(column_profile, &state)?;
+    let sheet_selection = sheet_selection.unwrap_or_default();
+    let as_of = as_of_date(as_of);
+    *state.last_inputs.lock().unwrap_or_else(|e| e.into_inner()) = Some(InputSelection {
+        borrow_paths: borrow_paths.clone(),
+        member_path: reader_list_path.clone(),
+        sheet_selection: sheet_selection.clone(),
+        column_mapping: column_mapping.clone(),
+    });
 
-    // 执行分析
-    match analyzer.analyze(None) {
-        Ok(result) => Ok(result),
-        Err(e) => Err(format!("分析失败: {}", e)),
-    }
+    Ok(jobs::spawn(&app, &state.jobs, "analyze_files", move |job| {
+        let state = job.app().state::<AppState>();
+
+        // 加载数据，同时缓存文件名和读者名供查询命令使用
+        job.check_cancelled()?;
+        state.analyzer.set_summary(None);
+        let progress = job.progress(ProgressStage::LoadData);
+        let summary = state.analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .load_data(
+                    borrow_paths,
+                    reader_list_path,
+                    &column_mapping,
+                    &sheet_selection,
+                    Some(progress),
+                )
+                .map_err(|e| AnalyzerError::analysis("加载数据失败", e))?;
+            DataSummary::collect(analyzer).map_err(|e| AnalyzerError::analysis("加载数据失败", e))
+        })?;
+        state.analyzer.set_summary(Some(summary));
+
+        // 执行分析
+        job.check_cancelled()?;
+        let progress = job.progress(ProgressStage::Analyze);
+        state.analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .analyze(as_of, Some(progress))
+                .map_err(|e| AnalyzerError::analysis("分析失败", e))
+        })
+    }))
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn generate_charts(
     output_dir: String,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    let analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    let analyzer = analyzer_guard
-        .as_ref()
-        .ok_or("分析器未初始化，请先调用 initialize_analyzer")?;
-
-    match analyzer.generate_charts(output_dir, None) {
-        Ok(chart_paths) => Ok(chart_paths),
-        Err(e) => Err(format!("生成图表失败: {}", e)),
-    }
+) -> Result<String, AnalyzerError> {
+    Ok(jobs::spawn(&app, &state.jobs, "generate_charts", move |job| {
+        job.check_cancelled()?;
+        let progress = job.progress(ProgressStage::GenerateCharts);
+        let chart_paths = job.app().state::<AppState>().analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .generate_charts(output_dir, Some(progress))
+                .map_err(|e| AnalyzerError::export("生成图表失败", e))
+        })?;
+        job.add_outputs(chart_paths.values().cloned());
+        Ok(chart_paths)
+    }))
+}
+
+// 最近一次分析结果的图表数据序列，供前端绘制交互图表；`top_n` 为阅读时长图表显示的书目数量
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_chart_data(
+    top_n: Option<usize>,
+    app: AppHandle,
+) -> Result<Vec<ChartSeries>, AnalyzerError> {
+    // 等待工作线程期间不占用异步运行时线程
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<AppState>().analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .chart_data()
+                .map(|data| charts::chart_series(&data, top_n))
+                .map_err(|e| AnalyzerError::analysis("获取图表数据失败", e))
+        })
+    })
+    .await
+    .map_err(|e| AnalyzerError::Internal {
+        message: format!("获取图表数据失败: {}", e),
+    })
+    .and_then(|result| result)
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn export_report(
     output_path: String,
+    format: Option<ReportFormat>,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    let analyzer = analyzer_guard
-        .as_ref()
-        .ok_or("分析器未初始化，请先调用 initialize_analyzer")?;
-
-    match analyzer.export_report(output_path, None) {
-        Ok(()) => Ok(()),
-        Err(e) => Err(format!("导出报告失败: {}", e)),
-    }
+) -> Result<String, AnalyzerError> {
+    // 未指定格式时根据扩展名判断（.docx、.pdf、.html 或 .md）
+    let format = ReportFormat::resolve(format, Path::new(&output_path))
+        .map_err(|e| AnalyzerError::export("导出报告失败", e))?;
+
+    Ok(jobs::spawn(&app, &state.jobs, "export_report", move |job| {
+        job.check_cancelled()?;
+        let progress = job.progress(ProgressStage::ExportReport);
+        let path = output_path.clone();
+        job.app().state::<AppState>().analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .export_report(path, format, Some(progress))
+                .map_err(|e| AnalyzerError::export("导出报告失败", e))
+        })?;
+        job.add_outputs([output_path]);
+        Ok(())
+    }))
+}
+
+// 导出最近一次分析的完整结果，供其他工具读取
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn export_data(
+    output_path: String,
+    format: Option<DataFormat>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, AnalyzerError> {
+    // 未指定格式时根据扩展名判断（.json 或 .xlsx）
+    let format = DataFormat::resolve(format, Path::new(&output_path))
+        .map_err(|e| AnalyzerError::export("导出数据失败", e))?;
+
+    Ok(jobs::spawn(&app, &state.jobs, "export_data", move |job| {
+        job.check_cancelled()?;
+        let progress = job.progress(ProgressStage::ExportData);
+        let path = output_path.clone();
+        job.app().state::<AppState>().analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .export_data(path, format, Some(progress))
+                .map_err(|e| AnalyzerError::export("导出数据失败", e))
+        })?;
+        job.add_outputs([output_path]);
+        Ok(())
+    }))
 }
 
+// 查询命令读取加载数据时缓存的概况，不等待工作线程中正在执行的分析或导出
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_file_count(
     state: State<'_, AppState>,
-) -> Result<u32, String> {
-    let analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    let analyzer = analyzer_guard
-        .as_ref()
-        .ok_or("分析器未初始化，请先调用 initialize_analyzer")?;
-
-    match analyzer.get_file_count() {
-        Ok(count) => Ok(count),
-        Err(e) => Err(format!("获取文件数量失败: {}", e)),
-    }
+) -> Result<u32, AnalyzerError> {
+    state
+        .analyzer
+        .with_summary(|summary| Ok(summary.file_names.len() as u32))
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_file_name(
     file_index: u32,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    let analyzer = analyzer_guard
-        .as_ref()
-        .ok_or("分析器未初始化，请先调用 initialize_analyzer")?;
-
-    match analyzer.get_file_name(file_index) {
-        Ok(name) => Ok(name),
-        Err(e) => Err(format!("获取文件名失败: {}", e)),
-    }
+) -> Result<String, AnalyzerError> {
+    state
+        .analyzer
+        .with_summary(|summary| {
+            summary
+                .file_names
+                .get(file_index as usize)
+                .cloned()
+                .ok_or_else(|| AnalyzerError::AnalysisFailed {
+                    message: format!("获取文件名失败: 文件索引超出范围: {}", file_index),
+                })
+        })
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn analyze_single_file(
     file_index: u32,
+    as_of: Option<NaiveDate>,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<AnalysisResult, String> {
-    let analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    let analyzer = analyzer_guard
-        .as_ref()
-        .ok_or("分析器未初始化，请先调用 initialize_analyzer")?;
-
-    match analyzer.analyze_single_file(file_index, None) {
-        Ok(result) => Ok(result),
-        Err(e) => Err(format!("分析单文件失败: {}", e)),
-    }
+) -> Result<String, AnalyzerError> {
+    let as_of = as_of_date(as_of);
+
+    Ok(jobs::spawn(&app, &state.jobs, "analyze_single_file", move |job| {
+        job.check_cancelled()?;
+        let progress = job.progress(ProgressStage::Analyze);
+        job.app().state::<AppState>().analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .analyze_single_file(file_index, as_of, Some(progress))
+                .map_err(|e| AnalyzerError::analysis("分析单文件失败", e))
+        })
+    }))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_reader_count(
+    state: State<'_, AppState>,
+) -> Result<u32, AnalyzerError> {
+    state
+        .analyzer
+        .with_summary(|summary| Ok(summary.reader_names.len() as u32))
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_reader_name(
+    reader_index: u32,
+    state: State<'_, AppState>,
+) -> Result<String, AnalyzerError> {
+    state
+        .analyzer
+        .with_summary(|summary| {
+            summary
+                .reader_names
+                .get(reader_index as usize)
+                .cloned()
+                .ok_or_else(|| AnalyzerError::AnalysisFailed {
+                    message: format!("获取读者名称失败: 读者索引超出范围: {}", reader_index),
+                })
+        })
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn analyze_reader(
+    reader_index: u32,
+    as_of: Option<NaiveDate>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, AnalyzerError> {
+    let as_of = as_of_date(as_of);
+
+    Ok(jobs::spawn(&app, &state.jobs, "analyze_reader", move |job| {
+        job.check_cancelled()?;
+        let progress = job.progress(ProgressStage::Analyze);
+        job.app().state::<AppState>().analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .analyze_reader(reader_index, as_of, Some(progress))
+                .map_err(|e| AnalyzerError::analysis("分析读者失败", e))
+        })
+    }))
+}
+
+// 批量导出任务：取消后在当前报告完成时停止，已生成的报告逐个记录在任务信息的 outputs 中
+#[tauri::command]
+#[instrument(skip_all)]
 pub async fn export_reports_for_all_files(
     base_output_dir: String,
+    group_by: Option<ReportGrouping>,
+    format: Option<ReportFormat>,
+    as_of: Option<NaiveDate>,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<Vec<String>, String> {
-    let analyzer_guard = state.analyzer.lock().map_err(|e| e.to_string())?;
-    
-    let analyzer = analyzer_guard
-        .as_ref()
-        .ok_or("分析器未初始化，请先调用 initialize_analyzer")?;
-
-    match analyzer.export_reports_for_all_files(base_output_dir, None) {
-        Ok(report_paths) => Ok(report_paths),
-        Err(e) => Err(format!("批量导出报告失败: {}", e)),
-    }
-}
\ No newline at end of file
+) -> Result<String, AnalyzerError> {
+    let grouping = group_by.unwrap_or_default();
+    // 未指定格式时生成Word报告
+    let format = format.unwrap_or_default();
+    let as_of = as_of_date(as_of);
+
+    Ok(jobs::spawn(&app, &state.jobs, "export_reports_for_all_files", move |job| {
+        job.check_cancelled()?;
+        let progress = job.progress(ProgressStage::ExportReports);
+        let cancel = job.cancel_flag();
+        let on_output = job.output_recorder();
+        job.app().state::<AppState>().analyzer.with_analyzer(move |analyzer| {
+            analyzer
+                .export_reports_for_all_files(
+                    base_output_dir,
+                    grouping,
+                    format,
+                    as_of,
+                    Some(progress),
+                    Some(cancel),
+                    Some(on_output),
+                )
+                .map_err(|e| AnalyzerError::export("批量导出报告失败", e))
+        })
+    }))
+}
+
+// 请求取消任务
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn cancel_job(job_id: String, state: State<'_, AppState>) -> Result<(), AnalyzerError> {
+    state.jobs.cancel(&job_id)
+}
+
+// 列出运行中和已结束的任务
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn list_jobs(state: State<'_, AppState>) -> Result<Vec<JobInfo>, AnalyzerError> {
+    Ok(state.jobs.list())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_job(job_id: String, state: State<'_, AppState>) -> Result<JobInfo, AnalyzerError> {
+    state
+        .jobs
+        .get(&job_id)
+        .ok_or(AnalyzerError::JobNotFound { job_id })
+}
+
+// 导出诊断包（zip），包含日志、运行环境、最近的错误和匿名化的输入表头，返回诊断包路径
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn export_diagnostics(
+    output_path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, AnalyzerError> {
+    let log_dir = app.path().app_log_dir().ok();
+    let inputs = state
+        .last_inputs
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        diagnostics::export_diagnostics(
+            std::path::Path::new(&output_path),
+            log_dir.as_deref(),
+            inputs.as_ref(),
+        )
+        .map(|()| output_path)
+        .map_err(|e| AnalyzerError::export("导出诊断包失败", e))
+    })
+    .await
+    .map_err(|e| AnalyzerError::Internal {
+        message: format!("导出诊断包失败: {}", e),
+    })?
+}
+
+/// 界面中查看日志时默认读取的行数
+const DEFAULT_TAIL_LINES: usize = 200;
+
+fn log_dir(app: &AppHandle) -> Result<std::path::PathBuf, AnalyzerError> {
+    app.path().app_log_dir().map_err(|e| AnalyzerError::IoError {
+        message: format!("无法获取日志目录: {}", e),
+    })
+}
+
+// 用系统默认程序打开最新的日志文件，返回日志文件路径
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn open_log(app: AppHandle) -> Result<String, AnalyzerError> {
+    let path = logging::latest_log_file(&log_dir(&app)?).map_err(|e| {
+        AnalyzerError::classify(e, |message| AnalyzerError::IoError { message })
+    })?;
+    let path = path.to_string_lossy().to_string();
+    app.opener()
+        .open_path(path.clone(), None::<&str>)
+        .map_err(|e| AnalyzerError::IoError {
+            message: format!("无法打开日志文件 {}: {}", path, e),
+        })?;
+    Ok(path)
+}
+
+// 读取最新日志文件的最后 `lines` 行（默认200行）
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn tail_log(lines: Option<usize>, app: AppHandle) -> Result<String, AnalyzerError> {
+    let log_dir = log_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        logging::tail(&log_dir, lines.unwrap_or(DEFAULT_TAIL_LINES))
+            .map_err(|e| AnalyzerError::classify(e, |message| AnalyzerError::IoError { message }))
+    })
+    .await
+    .map_err(|e| AnalyzerError::Internal {
+        message: format!("读取日志失败: {}", e),
+    })
+    .and_then(|result| result)
+}
@@ -0,0 +1,65 @@
+use crate::analyzer_backend::BackendKind;
+use crate::theme::ReportTheme;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// 配置文件名（位于应用配置目录下）
+pub const CONFIG_FILE_NAME: &str = "config.json";
+/// 覆盖分析器后端的环境变量
+const BACKEND_ENV: &str = "READERREPORT_ANALYZER_BACKEND";
+/// 覆盖影子后端的环境变量
+const SHADOW_BACKEND_ENV: &str = "READERREPORT_SHADOW_BACKEND";
+
+/// 应用启动配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// 使用的分析器后端
+    pub analyzer_backend: BackendKind,
+    /// 迁移期间在主后端之后顺序运行、用于对比结果的影子后端（加载和分析的耗时约为两个后端之和）
+    pub shadow_backend: Option<BackendKind>,
+    /// 默认的表头映射配置文件（JSON/TOML）
+    pub column_profile: Option<PathBuf>,
+    /// 报告主题（标题、页眉页脚、标志、配色、字体和图表尺寸）
+    pub report_theme: ReportTheme,
+}
+
+impl AppConfig {
+    /// 从配置文件加载，文件不存在或无法解析时使用默认配置；环境变量优先
+    pub fn load(path: &Path) -> Self {
+        let mut config = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("配置文件解析失败，使用默认配置: {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+
+        config.report_theme = config.report_theme.normalize(path.parent());
+
+        if let Some(kind) = env_backend(BACKEND_ENV) {
+            config.analyzer_backend = kind;
+        }
+        if let Some(kind) = env_backend(SHADOW_BACKEND_ENV) {
+            config.shadow_backend = Some(kind);
+        }
+
+        info!(
+            "分析器后端: {:?}，影子后端: {:?}",
+            config.analyzer_backend, config.shadow_backend
+        );
+        config
+    }
+}
+
+fn env_backend(name: &str) -> Option<BackendKind> {
+    let value = std::env::var(name).ok()?;
+    match value.parse() {
+        Ok(kind) => Some(kind),
+        Err(e) => {
+            warn!("忽略环境变量 {}: {}", name, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,549 @@
+use crate::analyzer_backend::ProgressCallback;
+use crate::membership::MembershipDuration;
+use crate::reader_identity::ReaderIdentity;
+use crate::rust_analyzer::MissingWeeksInfo;
+use crate::xlsx::{self, Cell, Sheet};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+/// 导出文件的格式标识
+pub const DATA_FORMAT_NAME: &str = "readerreport-analysis";
+/// 导出文件的格式版本，字段的含义或结构变化时增加
+pub const DATA_FORMAT_VERSION: u32 = 1;
+
+/// `unresolved_readers` 工作表的列，与 `ReaderIdentity` 的字段一致
+const READER_COLUMNS: [&str; 7] = [
+    "name",
+    "card_number",
+    "status",
+    "matched_by",
+    "member_row",
+    "registration_date",
+    "candidates",
+];
+
+/// 数据导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    #[default]
+    Json,
+    /// Excel工作簿，每个表一个工作表
+    Xlsx,
+}
+
+impl DataFormat {
+    /// 根据文件扩展名判断导出格式
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Ok(Self::Json),
+            "xlsx" => Ok(Self::Xlsx),
+            _ => Err(anyhow!(
+                "无法根据扩展名判断导出格式: {:?}，支持 .json 和 .xlsx",
+                path
+            )),
+        }
+    }
+
+    /// 指定了格式时使用指定的格式，否则根据扩展名判断
+    pub fn resolve(format: Option<Self>, path: &Path) -> Result<Self> {
+        match format {
+            Some(format) => Ok(format),
+            None => Self::from_path(path),
+        }
+    }
+}
+
+/// 完整分析结果，对应Python版本的 `self.results` 字典，两种后端都从最近一次分析结果中提取
+///
+/// 与 `ReportData` 不同，排行不截取前十；排序字段的顺序与 `AnalysisDetails` 一致。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisData {
+    pub borrow_frequency: u32,
+    pub total_duration: MembershipDuration,
+    /// 所有借阅的借阅时长之和（天）
+    pub total_reading_duration: i64,
+    pub total_price: f64,
+    pub most_frequent_author: String,
+    pub most_frequent_category: String,
+    pub longest_author: String,
+    pub longest_category: String,
+    pub borrow_peak_yearmonth: String,
+    /// 借阅最多的月份（1-12）
+    pub borrow_peak: u8,
+    pub most_borrowed_book: String,
+    pub most_borrowed_book_count: u32,
+    pub total_full_attendance: u32,
+    /// 每个自然月（1-12）的借阅时长之和，按时长降序
+    pub monthly_duration: Vec<(u32, i64)>,
+    /// 每月借书数量，按数量降序
+    pub monthly_borrow: Vec<(String, u32)>,
+    /// 按年月排列的借阅册数，没有借阅的月份为0
+    pub monthly_timeline: Vec<(String, u32)>,
+    /// 每本书的借阅时长之和，按书名排列
+    pub book_duration: Vec<(String, i64)>,
+    /// 每本书的借阅时长占总借阅时长的百分比，按书名排列
+    pub book_duration_ratio: Vec<(String, f64)>,
+    /// 每本书的借阅时长之和，按时长降序
+    pub sorted_duration: Vec<(String, i64)>,
+    pub category_count: Vec<(String, u32)>,
+    /// 每本书的借阅次数，按次数降序
+    pub book_borrow_counts: Vec<(String, u32)>,
+    pub full_attendance: Vec<String>,
+    pub partial_attendance: Vec<String>,
+    /// 缺勤月的周信息，键为 `partial_attendance` 中的年月
+    pub missing_weeks_info: BTreeMap<String, MissingWeeksInfo>,
+    pub reader_identity: ReaderIdentity,
+    pub unresolved_readers: Vec<ReaderIdentity>,
+}
+
+/// 导出文件的内容：格式标识、版本和生成信息，分析结果位于 `analysis`
+#[derive(Debug, Serialize)]
+struct DataExport<'a> {
+    format: &'static str,
+    version: u32,
+    generated_at: String,
+    backend: &'a str,
+    analysis: &'a AnalysisData,
+}
+
+/// 导出完整分析结果：JSON为单个文件，XLSX为每个表一个工作表的工作簿
+pub fn export_data(
+    data: &AnalysisData,
+    backend: &str,
+    output_path: &Path,
+    format: DataFormat,
+    progress_callback: Option<ProgressCallback>,
+) -> Result<()> {
+    let report = |current: u32, message: &str| {
+        if let Some(callback) = &progress_callback {
+            callback(100, current, message.to_string());
+        }
+    };
+    report(0, "开始导出数据");
+
+    if let Some(parent) = output_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("无法创建导出目录: {:?}", parent))?;
+    }
+
+    let export = DataExport {
+        format: DATA_FORMAT_NAME,
+        version: DATA_FORMAT_VERSION,
+        generated_at: chrono::Local::now().to_rfc3339(),
+        backend,
+        analysis: data,
+    };
+
+    report(50, "写入数据");
+    match format {
+        DataFormat::Json => {
+            let json = serde_json::to_string_pretty(&export)?;
+            std::fs::write(output_path, json)
+                .with_context(|| format!("无法保存数据: {:?}", output_path))?;
+        }
+        DataFormat::Xlsx => xlsx::write_xlsx(&sheets(&export)?, output_path)
+            .with_context(|| format!("无法保存数据: {:?}", output_path))?,
+    }
+
+    report(100, "数据导出完成");
+    info!("数据导出完成: {:?}", output_path);
+    Ok(())
+}
+
+/// 工作簿中的工作表：`summary` 为导出信息和所有单项统计，其余每个表一个工作表，
+/// 工作表名称和列名与JSON中的字段名一致
+fn sheets(export: &DataExport) -> Result<Vec<Sheet>> {
+    let data = export.analysis;
+
+    let mut missing_weeks = Vec::new();
+    for (month, info) in &data.missing_weeks_info {
+        for week in &info.total_weeks {
+            missing_weeks.push(vec![
+                Cell::from(month.as_str()),
+                Cell::from(week.as_str()),
+                Cell::from(info.recorded_weeks.contains(week)),
+                Cell::from(info.corrected_recorded_weeks.contains(week)),
+                Cell::from(info.missing_weeks.contains(week)),
+            ]);
+        }
+    }
+
+    let mut unresolved_readers = Vec::new();
+    for reader in &data.unresolved_readers {
+        let mut fields = Vec::new();
+        flatten(&serde_json::to_value(reader)?, "", &[], &mut fields);
+        unresolved_readers.push(
+            READER_COLUMNS
+                .iter()
+                .map(|column| {
+                    fields
+                        .iter()
+                        .find(|(field, _)| field == column)
+                        .map(|(_, value)| value.clone())
+                        .unwrap_or(Cell::Empty)
+                })
+                .collect(),
+        );
+    }
+
+    let mut sheets = vec![
+        sheet("monthly_borrow", &["year_month", "count"], pairs(&data.monthly_borrow)),
+        sheet("monthly_timeline", &["year_month", "count"], pairs(&data.monthly_timeline)),
+        sheet(
+            "monthly_duration",
+            &["month", "days"],
+            data.monthly_duration
+                .iter()
+                .map(|(month, days)| vec![Cell::from(*month), Cell::from(*days)])
+                .collect(),
+        ),
+        sheet("book_duration", &["title", "days"], pairs(&data.book_duration)),
+        sheet("book_duration_ratio", &["title", "percent"], pairs(&data.book_duration_ratio)),
+        sheet("sorted_duration", &["rank", "title", "days"], ranked(&data.sorted_duration)),
+        sheet("category_count", &["category", "count"], pairs(&data.category_count)),
+        sheet("book_borrow_counts", &["rank", "title", "count"], ranked(&data.book_borrow_counts)),
+        sheet(
+            "full_attendance",
+            &["year_month"],
+            data.full_attendance.iter().map(|month| vec![Cell::from(month.as_str())]).collect(),
+        ),
+        sheet(
+            "partial_attendance",
+            &[
+                "year_month",
+                "recorded_weeks",
+                "corrected_recorded_weeks",
+                "missing_weeks",
+                "total_weeks",
+            ],
+            data.partial_attendance
+                .iter()
+                .map(|month| {
+                    let info = data.missing_weeks_info.get(month).cloned().unwrap_or_default();
+                    vec![
+                        Cell::from(month.as_str()),
+                        Cell::from(info.recorded_weeks.len() as u32),
+                        Cell::from(info.corrected_recorded_weeks.len() as u32),
+                        Cell::from(info.missing_weeks.len() as u32),
+                        Cell::from(info.total_weeks.len() as u32),
+                    ]
+                })
+                .collect(),
+        ),
+        sheet(
+            "missing_weeks_info",
+            &["year_month", "week", "recorded", "corrected_recorded", "missing"],
+            missing_weeks,
+        ),
+        sheet("unresolved_readers", &READER_COLUMNS, unresolved_readers),
+    ];
+
+    // 有单独工作表的字段不再写入概要
+    let tables: Vec<&str> = sheets.iter().map(|sheet| sheet.name.as_str()).collect();
+    let mut fields = Vec::new();
+    flatten(&serde_json::to_value(data)?, "", &tables, &mut fields);
+
+    let mut summary = vec![
+        vec![Cell::from("format"), Cell::from(export.format)],
+        vec![Cell::from("version"), Cell::from(export.version)],
+        vec![Cell::from("generated_at"), Cell::from(export.generated_at.as_str())],
+        vec![Cell::from("backend"), Cell::from(export.backend)],
+    ];
+    summary.extend(fields.into_iter().map(|(field, value)| vec![Cell::from(field), value]));
+    summary.push(vec![
+        Cell::from("total_duration.description"),
+        Cell::from(data.total_duration.describe()),
+    ]);
+    sheets.insert(0, sheet("summary", &["field", "value"], summary));
+    Ok(sheets)
+}
+
+fn sheet(name: &str, headers: &[&str], rows: Vec<Vec<Cell>>) -> Sheet {
+    Sheet {
+        name: name.to_string(),
+        headers: headers.iter().map(|header| header.to_string()).collect(),
+        rows,
+    }
+}
+
+/// 排行表：名次（从1开始）、名称和数值
+fn ranked<V: Copy + Into<Cell>>(entries: &[(String, V)]) -> Vec<Vec<Cell>> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, (title, value))| {
+            vec![Cell::from(index as u32 + 1), Cell::from(title.as_str()), (*value).into()]
+        })
+        .collect()
+}
+
+fn pairs<K, V>(entries: &[(K, V)]) -> Vec<Vec<Cell>>
+where
+    K: AsRef<str>,
+    V: Copy + Into<Cell>,
+{
+    entries
+        .iter()
+        .map(|(key, value)| vec![Cell::from(key.as_ref()), (*value).into()])
+        .collect()
+}
+
+/// 将对象展开为 `字段.子字段` 和单元格，数组以JSON文字写入；跳过 `skip` 中的顶层字段
+fn flatten(value: &Value, prefix: &str, skip: &[&str], fields: &mut Vec<(String, Cell)>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for (key, value) in map {
+        let field = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            _ if prefix.is_empty() && skip.contains(&key.as_str()) => {}
+            Value::Object(_) => flatten(value, &field, skip, fields),
+            Value::Array(_) => fields.push((field, Cell::from(value.to_string()))),
+            Value::Null => fields.push((field, Cell::Empty)),
+            Value::Bool(value) => fields.push((field, Cell::from(*value))),
+            Value::Number(number) => {
+                fields.push((field, number.as_f64().map(Cell::Number).unwrap_or(Cell::Empty)))
+            }
+            Value::String(text) => fields.push((field, Cell::from(text.as_str()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader_identity::{MatchMethod, MatchStatus};
+    use calamine::{open_workbook, Data, Reader, Xlsx};
+    use chrono::NaiveDate;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("readerreport-export-{}-{}", std::process::id(), name))
+    }
+
+    fn identity(name: &str, status: MatchStatus) -> ReaderIdentity {
+        ReaderIdentity {
+            name: Some(name.to_string()),
+            card_number: None,
+            status,
+            matched_by: (status == MatchStatus::Matched).then_some(MatchMethod::Name),
+            member_row: (status == MatchStatus::Matched).then_some(2),
+            registration_date: None,
+            candidates: Vec::new(),
+        }
+    }
+
+    /// 借阅时长全为0的读者：`book_duration_ratio` 为NaN
+    fn sample_data() -> AnalysisData {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        AnalysisData {
+            borrow_frequency: 2,
+            total_duration: MembershipDuration::new(date(2024, 1, 15), date(2025, 3, 20)),
+            total_reading_duration: 0,
+            total_price: 59.5,
+            most_frequent_author: "刘慈欣".to_string(),
+            most_frequent_category: "I".to_string(),
+            longest_author: "刘慈欣".to_string(),
+            longest_category: "I".to_string(),
+            borrow_peak_yearmonth: "2025-01".to_string(),
+            borrow_peak: 1,
+            most_borrowed_book: "<三体> & 球状闪电".to_string(),
+            most_borrowed_book_count: 2,
+            total_full_attendance: 0,
+            monthly_duration: vec![(1, 0)],
+            monthly_borrow: vec![("2025-01".to_string(), 2)],
+            monthly_timeline: vec![("2025-01".to_string(), 2)],
+            book_duration: vec![("<三体> & 球状闪电".to_string(), 0)],
+            book_duration_ratio: vec![("<三体> & 球状闪电".to_string(), f64::NAN)],
+            sorted_duration: vec![("<三体> & 球状闪电".to_string(), 0)],
+            category_count: vec![("I".to_string(), 2)],
+            book_borrow_counts: vec![("<三体> & 球状闪电".to_string(), 2)],
+            full_attendance: Vec::new(),
+            partial_attendance: vec!["2025-01".to_string()],
+            missing_weeks_info: BTreeMap::from([(
+                "2025-01".to_string(),
+                MissingWeeksInfo {
+                    recorded_weeks: vec!["2".to_string()],
+                    corrected_recorded_weeks: vec!["2".to_string()],
+                    missing_weeks: vec!["1".to_string(), "3".to_string()],
+                    total_weeks: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+                },
+            )]),
+            reader_identity: identity("张三", MatchStatus::Matched),
+            unresolved_readers: vec![identity("李四", MatchStatus::Unmatched)],
+        }
+    }
+
+    fn sample_export(data: &AnalysisData) -> DataExport<'_> {
+        DataExport {
+            format: DATA_FORMAT_NAME,
+            version: DATA_FORMAT_VERSION,
+            generated_at: "2025-03-20T10:00:00+08:00".to_string(),
+            backend: "Rust",
+            analysis: data,
+        }
+    }
+
+    #[test]
+    fn format_is_resolved_from_extension() {
+        assert_eq!(DataFormat::from_path(Path::new("a/数据.JSON")).unwrap(), DataFormat::Json);
+        assert_eq!(DataFormat::from_path(Path::new("数据.xlsx")).unwrap(), DataFormat::Xlsx);
+        assert!(DataFormat::from_path(Path::new("数据.csv")).is_err());
+        assert_eq!(
+            DataFormat::resolve(Some(DataFormat::Xlsx), Path::new("数据.json")).unwrap(),
+            DataFormat::Xlsx
+        );
+    }
+
+    #[test]
+    fn json_export_wraps_analysis_in_envelope() {
+        let path = temp_path("data.json");
+        let mut data = sample_data();
+        data.book_duration_ratio[0].1 = 12.5;
+        export_data(&data, "Rust", &path, DataFormat::Json, None).unwrap();
+
+        let json: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["format"], DATA_FORMAT_NAME);
+        assert_eq!(json["version"], DATA_FORMAT_VERSION);
+        assert_eq!(json["backend"], "Rust");
+        assert!(chrono::DateTime::parse_from_rfc3339(json["generated_at"].as_str().unwrap()).is_ok());
+
+        let analysis: AnalysisData = serde_json::from_value(json["analysis"].clone()).unwrap();
+        assert_eq!(analysis, data);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn workbook_has_one_sheet_per_table() {
+        let data = sample_data();
+        let sheets = sheets(&sample_export(&data)).unwrap();
+
+        let layout: Vec<(&str, Vec<&str>)> = sheets
+            .iter()
+            .map(|sheet| {
+                (sheet.name.as_str(), sheet.headers.iter().map(String::as_str).collect())
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                ("summary", vec!["field", "value"]),
+                ("monthly_borrow", vec!["year_month", "count"]),
+                ("monthly_timeline", vec!["year_month", "count"]),
+                ("monthly_duration", vec!["month", "days"]),
+                ("book_duration", vec!["title", "days"]),
+                ("book_duration_ratio", vec!["title", "percent"]),
+                ("sorted_duration", vec!["rank", "title", "days"]),
+                ("category_count", vec!["category", "count"]),
+                ("book_borrow_counts", vec!["rank", "title", "count"]),
+                ("full_attendance", vec!["year_month"]),
+                (
+                    "partial_attendance",
+                    vec![
+                        "year_month",
+                        "recorded_weeks",
+                        "corrected_recorded_weeks",
+                        "missing_weeks",
+                        "total_weeks"
+                    ]
+                ),
+                (
+                    "missing_weeks_info",
+                    vec!["year_month", "week", "recorded", "corrected_recorded", "missing"]
+                ),
+                ("unresolved_readers", READER_COLUMNS.to_vec()),
+            ]
+        );
+
+        let rows = |name: &str| &sheets.iter().find(|sheet| sheet.name == name).unwrap().rows;
+        assert_eq!(
+            rows("book_borrow_counts"),
+            &vec![vec![Cell::from(1_u32), Cell::from("<三体> & 球状闪电"), Cell::from(2_u32)]]
+        );
+        assert_eq!(
+            rows("partial_attendance"),
+            &vec![vec![
+                Cell::from("2025-01"),
+                Cell::from(1_u32),
+                Cell::from(1_u32),
+                Cell::from(2_u32),
+                Cell::from(3_u32),
+            ]]
+        );
+        assert_eq!(rows("missing_weeks_info").len(), 3);
+        assert_eq!(rows("missing_weeks_info")[0][4], Cell::from(true));
+        assert_eq!(rows("unresolved_readers")[0][0], Cell::from("李四"));
+        assert_eq!(rows("unresolved_readers")[0][2], Cell::from("unmatched"));
+        assert_eq!(rows("unresolved_readers")[0][3], Cell::Empty);
+    }
+
+    #[test]
+    fn summary_excludes_tabled_fields() {
+        let data = sample_data();
+        let sheets = sheets(&sample_export(&data)).unwrap();
+        let summary = &sheets[0];
+        let fields: Vec<&Cell> = summary.rows.iter().map(|row| &row[0]).collect();
+
+        for sheet in &sheets[1..] {
+            assert!(
+                !fields.iter().any(|field| matches!(field,
+                    Cell::Text(text) if text == &sheet.name || text.starts_with(&format!("{}.", sheet.name)))),
+                "概要中不应包含 {}",
+                sheet.name
+            );
+        }
+
+        let value = |name: &str| {
+            summary
+                .rows
+                .iter()
+                .find(|row| row[0] == Cell::from(name))
+                .map(|row| row[1].clone())
+                .unwrap_or_else(|| panic!("概要中缺少 {}", name))
+        };
+        assert_eq!(value("format"), Cell::from(DATA_FORMAT_NAME));
+        assert_eq!(value("version"), Cell::from(DATA_FORMAT_VERSION));
+        assert_eq!(value("backend"), Cell::from("Rust"));
+        assert_eq!(value("total_price"), Cell::from(59.5));
+        assert_eq!(value("total_duration.status"), Cell::from("known"));
+        assert_eq!(value("total_duration.years"), Cell::from(1_u32));
+        assert_eq!(value("reader_identity.name"), Cell::from("张三"));
+        assert_eq!(value("reader_identity.candidates"), Cell::from("[]"));
+        assert_eq!(value("reader_identity.card_number"), Cell::Empty);
+        assert_eq!(value("total_duration.description"), Cell::from(data.total_duration.describe()));
+    }
+
+    #[test]
+    fn xlsx_export_writes_nan_ratio_as_text() {
+        let path = temp_path("data.xlsx");
+        let data = sample_data();
+        export_data(&data, "Rust", &path, DataFormat::Xlsx, None).unwrap();
+
+        let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+        assert_eq!(workbook.sheet_names().len(), 13);
+
+        let ratio = workbook.worksheet_range("book_duration_ratio").unwrap();
+        assert_eq!(ratio.get_value((1, 0)), Some(&Data::String("<三体> & 球状闪电".to_string())));
+        assert_eq!(ratio.get_value((1, 1)), Some(&Data::String("NaN".to_string())));
+
+        let summary = workbook.worksheet_range("summary").unwrap();
+        assert_eq!(summary.get_value((0, 0)), Some(&Data::String("field".to_string())));
+        assert_eq!(summary.get_value((1, 1)), Some(&Data::String(DATA_FORMAT_NAME.to_string())));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,402 @@
+use crate::column_mapping::{
+    Column, ColumnMapping, BORROW_COLUMNS, MEMBER_COLUMNS, OPTIONAL_BORROW_COLUMNS,
+    OPTIONAL_MEMBER_COLUMNS,
+};
+use crate::error::AnalyzerError;
+#[cfg(feature = "python")]
+use crate::python_analyzer;
+use crate::spreadsheet::{self, SheetSelection};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::{error, info};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// 保留的最近错误数量
+const MAX_RECENT_ERRORS: usize = 20;
+
+static RECENT_ERRORS: Mutex<VecDeque<ErrorRecord>> = Mutex::new(VecDeque::new());
+
+/// 命令失败的记录，写入诊断包的 `errors.json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorRecord {
+    pub time: DateTime<Local>,
+    pub command: String,
+    pub error: AnalyzerError,
+}
+
+/// 记录命令失败（包括Python调用栈），供导出诊断包使用
+pub fn record_error(command: &str, error: &AnalyzerError) {
+    error!("{} 失败 [{}]: {}", command, error.code(), error);
+    let mut errors = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+    if errors.len() == MAX_RECENT_ERRORS {
+        errors.pop_front();
+    }
+    errors.push_back(ErrorRecord {
+        time: Local::now(),
+        command: command.to_string(),
+        error: error.clone(),
+    });
+}
+
+/// 最近一次分析的输入文件，用于在诊断包中列出表头
+#[derive(Debug, Clone, Default)]
+pub struct InputSelection {
+    pub borrow_paths: Vec<String>,
+    pub member_path: String,
+    pub sheet_selection: SheetSelection,
+    pub column_mapping: ColumnMapping,
+}
+
+/// 匿名化的输入文件信息：文件名和工作表名替换为序号，只保留表头
+#[derive(Debug, Serialize)]
+struct InputHeaders {
+    file: String,
+    format: String,
+    sheet: Option<String>,
+    headers: Vec<String>,
+    /// 已识别的字段及其所在列（从0开始）
+    resolved: BTreeMap<Column, usize>,
+    missing: Vec<Column>,
+    error: Option<String>,
+}
+
+/// 导出诊断包（zip）：日志、运行环境、Python环境、最近的错误和匿名化的输入表头。
+/// 日志和错误信息中的输入文件路径同样替换为匿名文件名（见 [`Anonymizer`]）
+pub fn export_diagnostics(
+    output_path: &Path,
+    log_dir: Option<&Path>,
+    inputs: Option<&InputSelection>,
+) -> Result<()> {
+    let file = File::create(output_path)
+        .with_context(|| format!("无法创建诊断包: {:?}", output_path))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    let anonymizer = Anonymizer::new(inputs);
+
+    #[cfg(feature = "python")]
+    let python = python_analyzer::python_environment()
+        .unwrap_or_else(|e| serde_json::json!({ "error": format!("{:#}", e) }));
+    #[cfg(not(feature = "python"))]
+    let python = serde_json::json!({ "error": "编译时未启用 python 功能" });
+    let environment = serde_json::json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "created_at": Local::now(),
+        "python": python,
+    });
+    zip.start_file("environment.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&environment)?.as_bytes())?;
+
+    let errors: Vec<ErrorRecord> = RECENT_ERRORS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect();
+    let errors = anonymizer.apply_json(serde_json::to_value(&errors)?);
+    zip.start_file("errors.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&errors)?.as_bytes())?;
+
+    if let Some(inputs) = inputs {
+        zip.start_file("inputs.json", options)?;
+        let headers = input_headers(inputs, &anonymizer);
+        zip.write_all(serde_json::to_string_pretty(&headers)?.as_bytes())?;
+    }
+
+    if let Some(log_dir) = log_dir.filter(|dir| dir.is_dir()) {
+        for entry in std::fs::read_dir(log_dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let log = std::fs::read(&path)?;
+            zip.start_file(format!("logs/{}", name), options)?;
+            zip.write_all(anonymizer.apply(&String::from_utf8_lossy(&log)).as_bytes())?;
+        }
+    }
+
+    zip.finish()?;
+    info!("诊断包已导出: {:?}", output_path);
+    Ok(())
+}
+
+fn input_headers(inputs: &InputSelection, anonymizer: &Anonymizer) -> Vec<InputHeaders> {
+    let mut result = vec![describe_input(
+        member_name(&inputs.member_path),
+        &inputs.member_path,
+        None,
+        &inputs.column_mapping,
+        &MEMBER_COLUMNS,
+        &OPTIONAL_MEMBER_COLUMNS,
+        anonymizer,
+    )];
+
+    for (index, path) in inputs.borrow_paths.iter().enumerate() {
+        let file = borrow_name(index, path);
+        let sheets = spreadsheet::resolve_entries(
+            std::slice::from_ref(path),
+            &inputs.sheet_selection,
+        );
+        match sheets {
+            Ok(entries) => result.extend(entries.iter().enumerate().map(|(sheet_index, entry)| {
+                describe_input(
+                    file.clone(),
+                    path,
+                    entry.sheet.as_deref().map(|sheet| (sheet_index, sheet)),
+                    &inputs.column_mapping,
+                    &BORROW_COLUMNS,
+                    &OPTIONAL_BORROW_COLUMNS,
+                    anonymizer,
+                )
+            })),
+            Err(e) => result.push(InputHeaders {
+                error: Some(anonymizer.apply(&format!("{:#}", e))),
+                file,
+                format: String::new(),
+                sheet: None,
+                headers: Vec::new(),
+                resolved: BTreeMap::new(),
+                missing: Vec::new(),
+            }),
+        }
+    }
+    result
+}
+
+fn describe_input(
+    file: String,
+    path: &str,
+    sheet: Option<(usize, &str)>,
+    column_mapping: &ColumnMapping,
+    required: &[Column],
+    optional: &[Column],
+    anonymizer: &Anonymizer,
+) -> InputHeaders {
+    let mut input = InputHeaders {
+        file,
+        format: spreadsheet::detect_format(path)
+            .map(|format| format!("{:?}", format))
+            .unwrap_or_default(),
+        sheet: sheet.map(|(index, _)| format!("sheet_{}", index + 1)),
+        headers: Vec::new(),
+        resolved: BTreeMap::new(),
+        missing: Vec::new(),
+        error: None,
+    };
+
+    match spreadsheet::read_sheet_headers(path, sheet.map(|(_, name)| name)) {
+        Ok(headers) => {
+            let columns: Vec<Column> = required.iter().chain(optional).copied().collect();
+            let (resolved, missing) = column_mapping.resolve_partial(&headers, &columns);
+            input.resolved = resolved.into_iter().collect();
+            input.missing = missing
+                .into_iter()
+                .filter(|column| required.contains(column))
+                .collect();
+            input.headers = headers;
+        }
+        Err(e) => input.error = Some(anonymizer.apply(&format!("{:#}", e))),
+    }
+    input
+}
+
+fn member_name(path: &str) -> String {
+    anonymous_name("member", path)
+}
+
+fn borrow_name(index: usize, path: &str) -> String {
+    anonymous_name(&format!("borrow_{}", index + 1), path)
+}
+
+// 匿名文件名：序号加原扩展名，如 `borrow_1.xlsx`
+fn anonymous_name(label: &str, path: &str) -> String {
+    match Path::new(path).extension() {
+        Some(extension) => format!("{}.{}", label, extension.to_string_lossy()),
+        None => label.to_string(),
+    }
+}
+
+/// 写入诊断包的文本中的路径替换：输入文件的完整路径和文件名替换为匿名文件名，用户目录替换为 `~`。
+/// 日志常用 `{:?}` 输出路径，Windows路径中的反斜杠会被转义，转义后的形式也一并替换。
+struct Anonymizer {
+    /// 按长度从长到短排列，同一位置优先替换最长的匹配
+    replacements: Vec<(String, String)>,
+}
+
+impl Anonymizer {
+    fn new(inputs: Option<&InputSelection>) -> Self {
+        let mut files = Vec::new();
+        if let Some(inputs) = inputs {
+            files.push((inputs.member_path.as_str(), member_name(&inputs.member_path)));
+            for (index, path) in inputs.borrow_paths.iter().enumerate() {
+                files.push((path.as_str(), borrow_name(index, path)));
+            }
+        }
+
+        let mut replacements = Vec::new();
+        for (path, file) in files {
+            replacements.push((path.to_string(), file.clone()));
+            replacements.push((debug_escaped(path), file.clone()));
+            if let Some(name) = Path::new(path).file_name() {
+                replacements.push((name.to_string_lossy().to_string(), file));
+            }
+        }
+        if let Some(home) = home_dir() {
+            replacements.push((debug_escaped(&home), "~".to_string()));
+            replacements.push((home, "~".to_string()));
+        }
+        Self::with_replacements(replacements)
+    }
+
+    fn with_replacements(mut replacements: Vec<(String, String)>) -> Self {
+        // 过短的字符串（如根目录 `/`）会误伤普通文本
+        replacements.retain(|(from, _)| from.chars().count() > 1);
+        replacements.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        replacements.dedup_by(|a, b| a.0 == b.0);
+        Anonymizer { replacements }
+    }
+
+    /// 单遍扫描替换，替换结果不会被再次匹配（如 `borrow_1.csv` 中的 `1.csv`）
+    fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            match self.replacements.iter().find(|(from, _)| rest.starts_with(from.as_str())) {
+                Some((from, to)) => {
+                    result.push_str(to);
+                    rest = &rest[from.len()..];
+                }
+                None => {
+                    result.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        result
+    }
+
+    /// 替换JSON中所有的字符串
+    fn apply_json(&self, value: serde_json::Value) -> serde_json::Value {
+        use serde_json::Value;
+        match value {
+            Value::String(text) => Value::String(self.apply(&text)),
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.apply_json(v)).collect()),
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, v)| (key, self.apply_json(v)))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+// `{:?}` 输出的字符串去掉两侧引号
+fn debug_escaped(text: &str) -> String {
+    let debug = format!("{:?}", text);
+    debug[1..debug.len() - 1].to_string()
+}
+
+fn home_dir() -> Option<String> {
+    ["HOME", "USERPROFILE"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|home| !home.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::path::PathBuf;
+    use zip::ZipArchive;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("readerreport-diagnostics-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn anonymizer_replaces_longest_match_in_one_pass() {
+        let anonymizer = Anonymizer::with_replacements(vec![
+            (r"C:\data\1.csv".to_string(), "borrow_1.csv".to_string()),
+            (debug_escaped(r"C:\data\1.csv"), "borrow_1.csv".to_string()),
+            ("1.csv".to_string(), "borrow_1.csv".to_string()),
+            ("/".to_string(), "~".to_string()),
+        ]);
+        assert_eq!(
+            anonymizer.apply(r#"无法读取 C:\data\1.csv 和 "C:\\data\\1.csv"，另见 1.csv / 借阅"#),
+            r#"无法读取 borrow_1.csv 和 "borrow_1.csv"，另见 borrow_1.csv / 借阅"#
+        );
+        assert_eq!(
+            anonymizer.apply_json(serde_json::json!({ "message": "1.csv", "count": 1 })),
+            serde_json::json!({ "message": "borrow_1.csv", "count": 1 })
+        );
+    }
+
+    #[test]
+    fn export_removes_input_paths_from_logs_and_errors() {
+        let dir = temp_dir();
+        let member_path = dir.join("某某图书馆读者清单.xlsx").to_string_lossy().to_string();
+        let borrow_path = dir.join("张三借阅记录.csv").to_string_lossy().to_string();
+
+        let log_dir = dir.join("logs");
+        std::fs::create_dir_all(&log_dir).unwrap();
+        std::fs::write(
+            log_dir.join("app.log"),
+            format!(
+                "借阅数据文件: {:?}\n读者清单文件: {}\n",
+                vec![&borrow_path],
+                member_path
+            ),
+        )
+        .unwrap();
+        record_error(
+            "validate_inputs",
+            &AnalyzerError::input_missing(
+                format!("文件不存在: {}", borrow_path),
+                Some(&borrow_path),
+            ),
+        );
+
+        let inputs = InputSelection {
+            borrow_paths: vec![borrow_path.clone()],
+            member_path: member_path.clone(),
+            ..Default::default()
+        };
+        let output = dir.join("diagnostics.zip");
+        export_diagnostics(&output, Some(&log_dir), Some(&inputs)).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let mut contents = BTreeMap::new();
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).unwrap();
+            let mut text = String::new();
+            entry.read_to_string(&mut text).unwrap();
+            contents.insert(entry.name().to_string(), text);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for (name, text) in &contents {
+            for secret in ["某某图书馆读者清单", "张三借阅记录", dir.to_str().unwrap()] {
+                assert!(!text.contains(secret), "{} 中包含 {}", name, secret);
+            }
+        }
+        assert!(contents["logs/app.log"].contains("borrow_1.csv"));
+        assert!(contents["logs/app.log"].contains("member.xlsx"));
+        assert!(contents["errors.json"].contains("文件不存在: borrow_1.csv"));
+        assert!(contents["inputs.json"].contains("borrow_1.csv"));
+    }
+}
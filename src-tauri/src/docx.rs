@@ -0,0 +1,572 @@
+use crate::report::{self, ReportBlock, ReportDocument};
+use crate::theme::ReportTheme;
+use anyhow::{anyhow, Context, Result};
+use image::ImageFormat;
+use std::fs::File;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use tracing::warn;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// 图片尺寸单位 EMU（English Metric Unit）
+const EMU_PER_INCH: f64 = 914_400.0;
+const EMU_PER_CM: f64 = 360_000.0;
+/// 图表在报告中的宽度（英寸），高度按图片比例计算
+const CHART_WIDTH_INCHES: f64 = 6.0;
+/// 1毫米对应的 twip（1/20 磅），页面尺寸和表格列宽使用该单位
+const TWIPS_PER_MM: f64 = 1440.0 / 25.4;
+
+const NAMESPACES: &str = concat!(
+    r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" "#,
+    r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" "#,
+    r#"xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing" "#,
+    r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" "#,
+    r#"xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture""#,
+);
+pub(crate) const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
+const REL_OFFICE_DOCUMENT: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument";
+const REL_CORE_PROPERTIES: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties";
+const REL_STYLES: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles";
+const REL_HEADER: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/header";
+const REL_FOOTER: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/footer";
+const REL_IMAGE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/image";
+
+/// 将报告写入Word文档（`.docx`）：标题和小标题使用主题配色，统计信息使用Word表格，
+/// 图表嵌入为图片，页眉页脚包含主题中的标志和文字
+pub fn write_docx(document: &ReportDocument, theme: &ReportTheme, output_path: &Path) -> Result<()> {
+    let (r, g, b) = theme.accent_rgb();
+    let accent = format!("{:02X}{:02X}{:02X}", r, g, b);
+    let page = PageLayout::from_theme(theme);
+
+    let mut package = Package::default();
+    let mut document_rels = Relationships::default();
+    document_rels.add(REL_STYLES, "styles.xml".to_string());
+
+    let mut body = paragraph(Some("Heading1"), &document.title);
+    for block in &document.blocks {
+        match block {
+            ReportBlock::Heading(text) => body.push_str(&paragraph(Some("Heading2"), text)),
+            ReportBlock::Paragraph(text) => body.push_str(&paragraph(None, text)),
+            ReportBlock::Table { headers, rows } => {
+                body.push_str(&table(headers, rows, &accent, page.text_width))
+            }
+            ReportBlock::Image { title, path, .. } => {
+                let image = package.add_image(path)?;
+                let rel_id = document_rels.add(REL_IMAGE, format!("media/{}", image.name));
+                let width = CHART_WIDTH_INCHES * EMU_PER_INCH;
+                let height = width * image.height as f64 / image.width as f64;
+                let drawing = package.drawing(&rel_id, title, width, height);
+                body.push_str(&format!(
+                    r#"<w:p><w:pPr><w:jc w:val="center"/></w:pPr>{}</w:p>"#,
+                    drawing
+                ));
+            }
+        }
+    }
+    // 正文不能以表格结尾
+    if body.ends_with("</w:tbl>") {
+        body.push_str("<w:p/>");
+    }
+
+    let mut section = String::new();
+    if let Some(header) = header(&mut package, theme)? {
+        let rel_id = document_rels.add(REL_HEADER, "header1.xml".to_string());
+        section.push_str(&format!(r#"<w:headerReference w:type="default" r:id="{}"/>"#, rel_id));
+        package.parts.push(header);
+    }
+    if let Some(footer_text) = theme.footer_text.as_deref().filter(|text| !text.trim().is_empty()) {
+        let rel_id = document_rels.add(REL_FOOTER, "footer1.xml".to_string());
+        section.push_str(&format!(r#"<w:footerReference w:type="default" r:id="{}"/>"#, rel_id));
+        package.parts.push(Part {
+            name: "word/footer1.xml",
+            content_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.footer+xml",
+            xml: format!(
+                r#"{}<w:ftr {}><w:p><w:pPr><w:pStyle w:val="Footer"/><w:jc w:val="center"/></w:pPr>{}</w:p></w:ftr>"#,
+                XML_DECLARATION,
+                NAMESPACES,
+                runs(footer_text)
+            ),
+            rels: None,
+        });
+    }
+    section.push_str(&page.to_xml());
+
+    package.parts.push(Part {
+        name: "word/document.xml",
+        content_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml",
+        xml: format!(
+            r#"{}<w:document {}><w:body>{}<w:sectPr>{}</w:sectPr></w:body></w:document>"#,
+            XML_DECLARATION, NAMESPACES, body, section
+        ),
+        rels: Some(document_rels),
+    });
+    package.parts.push(Part {
+        name: "word/styles.xml",
+        content_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml",
+        xml: styles(theme, &accent),
+        rels: None,
+    });
+    package.parts.push(Part {
+        name: "docProps/core.xml",
+        content_type: "application/vnd.openxmlformats-package.core-properties+xml",
+        xml: format!(
+            r#"{}<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{}</dc:title><dcterms:created xsi:type="dcterms:W3CDTF">{}</dcterms:created></cp:coreProperties>"#,
+            XML_DECLARATION,
+            escape(&document.title),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+        ),
+        rels: None,
+    });
+
+    package.write(output_path)
+}
+
+/// 页面尺寸和页边距（twip），由主题中的纸张大小和页边距决定
+struct PageLayout {
+    width: u32,
+    height: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+    left: u32,
+    /// 正文宽度，表格列宽按各列内容长度分配
+    text_width: u32,
+}
+
+impl PageLayout {
+    fn from_theme(theme: &ReportTheme) -> Self {
+        let twips = |mm: f64| (mm * TWIPS_PER_MM).round() as u32;
+        let (width, height) = theme.page_size.dimensions_mm();
+        let margins = theme.page_margins_mm;
+        Self {
+            width: twips(width),
+            height: twips(height),
+            top: twips(margins.top),
+            right: twips(margins.right),
+            bottom: twips(margins.bottom),
+            left: twips(margins.left),
+            text_width: twips(width - margins.left - margins.right),
+        }
+    }
+
+    /// 页眉和页脚距页面边缘的距离为页边距的一半
+    fn to_xml(&self) -> String {
+        format!(
+            r#"<w:pgSz w:w="{}" w:h="{}"/><w:pgMar w:top="{}" w:right="{}" w:bottom="{}" w:left="{}" w:header="{}" w:footer="{}" w:gutter="0"/>"#,
+            self.width,
+            self.height,
+            self.top,
+            self.right,
+            self.bottom,
+            self.left,
+            self.top / 2,
+            self.bottom / 2
+        )
+    }
+}
+
+/// 页眉：标志图片和页眉文字，都未设置时没有页眉
+fn header(package: &mut Package, theme: &ReportTheme) -> Result<Option<Part>> {
+    let logo = theme.logo.as_deref().filter(|logo| {
+        let exists = logo.is_file();
+        if !exists {
+            warn!("报告主题中的标志图片不存在: {:?}", logo);
+        }
+        exists
+    });
+    let header_text = theme.header_text.as_deref().filter(|text| !text.trim().is_empty());
+    if logo.is_none() && header_text.is_none() {
+        return Ok(None);
+    }
+
+    let mut rels = Relationships::default();
+    let mut content = String::new();
+    if let Some(logo) = logo {
+        let image = package.add_image(logo)?;
+        let rel_id = rels.add(REL_IMAGE, format!("media/{}", image.name));
+        let height = theme.logo_height_cm * EMU_PER_CM;
+        let width = height * image.width as f64 / image.height as f64;
+        content.push_str(&package.drawing(&rel_id, "logo", width, height));
+    }
+    if let Some(header_text) = header_text {
+        if logo.is_some() {
+            content.push_str(&runs(&format!("  {}", header_text)));
+        } else {
+            content.push_str(&runs(header_text));
+        }
+    }
+
+    Ok(Some(Part {
+        name: "word/header1.xml",
+        content_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.header+xml",
+        xml: format!(
+            r#"{}<w:hdr {}><w:p><w:pPr><w:pStyle w:val="Header"/></w:pPr>{}</w:p></w:hdr>"#,
+            XML_DECLARATION, NAMESPACES, content
+        ),
+        rels: Some(rels),
+    }))
+}
+
+/// 样式：正文字体使用主题中的报告字体（包括中文字体），标题使用主题配色的第一个颜色
+fn styles(theme: &ReportTheme, accent: &str) -> String {
+    let fonts = theme
+        .report_font
+        .as_deref()
+        .map(|font| {
+            let font = escape(font);
+            format!(
+                r#"<w:rFonts w:ascii="{0}" w:hAnsi="{0}" w:eastAsia="{0}" w:cs="{0}"/>"#,
+                font
+            )
+        })
+        .unwrap_or_default();
+    let heading = |id: &str, name: &str, level: u32, size: u32| {
+        format!(
+            r#"<w:style w:type="paragraph" w:styleId="{id}"><w:name w:val="{name}"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:uiPriority w:val="9"/><w:qFormat/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="240" w:after="120"/><w:outlineLvl w:val="{level}"/></w:pPr><w:rPr><w:b/><w:bCs/><w:color w:val="{accent}"/><w:sz w:val="{size}"/><w:szCs w:val="{size}"/></w:rPr></w:style>"#
+        )
+    };
+    let border = |side: &str| format!(r#"<w:{side} w:val="single" w:sz="4" w:space="0" w:color="auto"/>"#);
+    let borders: String = ["top", "left", "bottom", "right", "insideH", "insideV"]
+        .iter()
+        .map(|side| border(side))
+        .collect();
+
+    format!(
+        concat!(
+            r#"{declaration}<w:styles {namespaces}>"#,
+            r#"<w:docDefaults><w:rPrDefault><w:rPr>{fonts}<w:sz w:val="21"/><w:szCs w:val="21"/><w:lang w:val="en-US" w:eastAsia="zh-CN"/></w:rPr></w:rPrDefault>"#,
+            r#"<w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="276" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>"#,
+            r#"<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>"#,
+            "{heading1}{heading2}",
+            r#"<w:style w:type="paragraph" w:styleId="Header"><w:name w:val="header"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="0"/></w:pPr><w:rPr><w:sz w:val="18"/><w:szCs w:val="18"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="paragraph" w:styleId="Footer"><w:name w:val="footer"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="0"/></w:pPr><w:rPr><w:sz w:val="18"/><w:szCs w:val="18"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/><w:tblPr><w:tblInd w:w="0" w:type="dxa"/><w:tblCellMar><w:top w:w="0" w:type="dxa"/><w:left w:w="108" w:type="dxa"/><w:bottom w:w="0" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>"#,
+            r#"<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:basedOn w:val="TableNormal"/><w:pPr><w:spacing w:before="40" w:after="40" w:line="240" w:lineRule="auto"/></w:pPr><w:tblPr><w:tblBorders>{borders}</w:tblBorders></w:tblPr></w:style>"#,
+            "</w:styles>"
+        ),
+        declaration = XML_DECLARATION,
+        namespaces = NAMESPACES,
+        fonts = fonts,
+        heading1 = heading("Heading1", "heading 1", 0, 32),
+        heading2 = heading("Heading2", "heading 2", 1, 26),
+        borders = borders,
+    )
+}
+
+/// 段落，`style` 为段落样式ID
+fn paragraph(style: Option<&str>, text: &str) -> String {
+    let properties = style
+        .map(|style| format!(r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, style))
+        .unwrap_or_default();
+    format!("<w:p>{}{}</w:p>", properties, runs(text))
+}
+
+/// 文字，换行转换为 `<w:br/>`
+fn runs(text: &str) -> String {
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| format!(r#"<w:t xml:space="preserve">{}</w:t>"#, escape(line)))
+        .collect();
+    format!("<w:r>{}</w:r>", lines.join("<w:br/>"))
+}
+
+/// 表格：表头行使用主题配色作为底色并在每页重复，列宽按各列内容的显示宽度分配
+fn table(headers: &[String], rows: &[Vec<String>], accent: &str, text_width: u32) -> String {
+    let weights = report::column_weights(headers, rows);
+    let total_weight: f64 = weights.iter().sum();
+    let widths: Vec<u32> = weights
+        .iter()
+        .map(|weight| (text_width as f64 * weight / total_weight) as u32)
+        .collect();
+
+    let cell = |text: &str, width: u32, header: bool| {
+        if header {
+            format!(
+                r#"<w:tc><w:tcPr><w:tcW w:w="{}" w:type="dxa"/><w:shd w:val="clear" w:color="auto" w:fill="{}"/></w:tcPr><w:p><w:r><w:rPr><w:b/><w:color w:val="FFFFFF"/></w:rPr><w:t xml:space="preserve">{}</w:t></w:r></w:p></w:tc>"#,
+                width,
+                accent,
+                escape(text)
+            )
+        } else {
+            format!(
+                r#"<w:tc><w:tcPr><w:tcW w:w="{}" w:type="dxa"/></w:tcPr>{}</w:tc>"#,
+                width,
+                paragraph(None, text)
+            )
+        }
+    };
+
+    let mut xml = String::from(
+        r#"<w:tbl><w:tblPr><w:tblStyle w:val="TableGrid"/><w:tblW w:w="5000" w:type="pct"/><w:tblLook w:val="04A0" w:firstRow="1" w:lastRow="0" w:firstColumn="1" w:lastColumn="0" w:noHBand="0" w:noVBand="1"/></w:tblPr><w:tblGrid>"#,
+    );
+    for width in &widths {
+        xml.push_str(&format!(r#"<w:gridCol w:w="{}"/>"#, width));
+    }
+    xml.push_str("</w:tblGrid><w:tr><w:trPr><w:tblHeader/></w:trPr>");
+    for (header, width) in headers.iter().zip(&widths) {
+        xml.push_str(&cell(header, *width, true));
+    }
+    xml.push_str("</w:tr>");
+    for row in rows {
+        xml.push_str("<w:tr><w:trPr><w:cantSplit/></w:trPr>");
+        for (column, width) in widths.iter().enumerate() {
+            xml.push_str(&cell(row.get(column).map(String::as_str).unwrap_or(""), *width, false));
+        }
+        xml.push_str("</w:tr>");
+    }
+    xml.push_str("</w:tbl>");
+    xml
+}
+
+/// XML转义，并去掉XML中不允许的控制字符（来自表格数据的书名可能包含）
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 文档部件及其关系
+struct Part {
+    name: &'static str,
+    content_type: &'static str,
+    xml: String,
+    rels: Option<Relationships>,
+}
+
+#[derive(Default)]
+struct Relationships(Vec<(String, &'static str, String)>);
+
+impl Relationships {
+    /// 添加关系，返回关系ID
+    fn add(&mut self, kind: &'static str, target: String) -> String {
+        let id = format!("rId{}", self.0.len() + 1);
+        self.0.push((id.clone(), kind, target));
+        id
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = format!(
+            r#"{}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+            XML_DECLARATION
+        );
+        for (id, kind, target) in &self.0 {
+            xml.push_str(&format!(
+                r#"<Relationship Id="{}" Type="{}" Target="{}"/>"#,
+                id,
+                kind,
+                escape(target)
+            ));
+        }
+        xml.push_str("</Relationships>");
+        xml
+    }
+}
+
+/// 嵌入的图片（位于 `word/media`）
+struct Image {
+    name: String,
+    bytes: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Default)]
+struct Package {
+    parts: Vec<Part>,
+    images: Vec<Image>,
+    /// 文档中图形对象的编号，需要在整个文档中唯一
+    drawing_count: u32,
+}
+
+impl Package {
+    /// 读取PNG或JPEG图片并加入文档
+    fn add_image(&mut self, path: &Path) -> Result<&Image> {
+        let format = ImageFormat::from_path(path).ok();
+        let extension = match format {
+            Some(ImageFormat::Png) => "png",
+            Some(ImageFormat::Jpeg) => "jpeg",
+            _ => return Err(anyhow!("不支持的图片格式（仅支持PNG和JPEG）: {:?}", path)),
+        };
+        let format = format.unwrap();
+        let bytes = std::fs::read(path).with_context(|| format!("无法读取图片: {:?}", path))?;
+        let (width, height) = image::io::Reader::with_format(Cursor::new(&bytes), format)
+            .into_dimensions()
+            .with_context(|| format!("无法读取图片尺寸: {:?}", path))?;
+        if width == 0 || height == 0 {
+            return Err(anyhow!("图片尺寸无效: {:?}", path));
+        }
+
+        self.images.push(Image {
+            name: format!("image{}.{}", self.images.len() + 1, extension),
+            bytes,
+            width,
+            height,
+        });
+        Ok(self.images.last().unwrap())
+    }
+
+    /// 嵌入式图片，`width`、`height` 单位为EMU
+    fn drawing(&mut self, rel_id: &str, name: &str, width: f64, height: f64) -> String {
+        self.drawing_count += 1;
+        let (id, cx, cy) = (self.drawing_count, width.round() as u64, height.round() as u64);
+        let name = escape(name);
+        format!(
+            concat!(
+                r#"<w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0"><wp:extent cx="{cx}" cy="{cy}"/>"#,
+                r#"<wp:docPr id="{id}" name="{name}"/><wp:cNvGraphicFramePr><a:graphicFrameLocks noChangeAspect="1"/></wp:cNvGraphicFramePr>"#,
+                r#"<a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic>"#,
+                r#"<pic:nvPicPr><pic:cNvPr id="{id}" name="{name}"/><pic:cNvPicPr/></pic:nvPicPr>"#,
+                r#"<pic:blipFill><a:blip r:embed="{rel_id}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>"#,
+                r#"<pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr>"#,
+                r#"</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"#
+            ),
+            cx = cx,
+            cy = cy,
+            id = id,
+            name = name,
+            rel_id = rel_id,
+        )
+    }
+
+    fn write(self, output_path: &Path) -> Result<()> {
+        let file = File::create(output_path)
+            .with_context(|| format!("无法创建文件: {:?}", output_path))?;
+        let mut zip = ZipWriter::new(file);
+        let options = SimpleFileOptions::default();
+
+        let mut content_types = format!(
+            concat!(
+                r#"{}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+                r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+                r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+                r#"<Default Extension="png" ContentType="image/png"/>"#,
+                r#"<Default Extension="jpeg" ContentType="image/jpeg"/>"#
+            ),
+            XML_DECLARATION
+        );
+        for part in &self.parts {
+            content_types.push_str(&format!(
+                r#"<Override PartName="/{}" ContentType="{}"/>"#,
+                part.name, part.content_type
+            ));
+        }
+        content_types.push_str("</Types>");
+        zip.start_file("[Content_Types].xml", options)?;
+        zip.write_all(content_types.as_bytes())?;
+
+        let mut package_rels = Relationships::default();
+        package_rels.add(REL_OFFICE_DOCUMENT, "word/document.xml".to_string());
+        package_rels.add(REL_CORE_PROPERTIES, "docProps/core.xml".to_string());
+        zip.start_file("_rels/.rels", options)?;
+        zip.write_all(package_rels.to_xml().as_bytes())?;
+
+        for part in &self.parts {
+            zip.start_file(part.name, options)?;
+            zip.write_all(part.xml.as_bytes())?;
+            if let Some(rels) = &part.rels {
+                let (dir, file_name) = part.name.rsplit_once('/').unwrap_or(("", part.name));
+                zip.start_file(format!("{}/_rels/{}.rels", dir, file_name), options)?;
+                zip.write_all(rels.to_xml().as_bytes())?;
+            }
+        }
+
+        for image in &self.images {
+            zip.start_file(format!("word/media/{}", image.name), options)?;
+            zip.write_all(&image.bytes)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> String {
+        let mut text = String::new();
+        archive
+            .by_name(name)
+            .unwrap_or_else(|e| panic!("文档中缺少 {}: {}", name, e))
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn writes_heading_table_and_image() {
+        let dir = std::env::temp_dir().join(format!("readerreport-docx-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let chart = dir.join("chart.png");
+        image::RgbImage::from_pixel(40, 20, image::Rgb([31, 119, 180])).save(&chart).unwrap();
+        let output = dir.join("report.docx");
+
+        let document = ReportDocument {
+            title: "读书报告".to_string(),
+            blocks: vec![
+                ReportBlock::Heading("统计 <汇总> & 排行".to_string()),
+                ReportBlock::Table {
+                    headers: vec!["书名".to_string(), "次数".to_string()],
+                    rows: vec![vec!["\"三体\" & <球状闪电>".to_string(), "3".to_string()]],
+                },
+                ReportBlock::Image {
+                    title: "阅读时长".to_string(),
+                    path: chart.clone(),
+                    series: None,
+                },
+            ],
+        };
+        let theme = ReportTheme {
+            footer_text: Some("第一页".to_string()),
+            ..Default::default()
+        };
+        write_docx(&document, &theme, &output).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let xml = read_entry(&mut archive, "word/document.xml");
+        assert!(xml.contains(r#"<w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t xml:space="preserve">读书报告</w:t>"#));
+        assert!(xml.contains("统计 &lt;汇总&gt; &amp; 排行"));
+        assert!(xml.contains("<w:tbl>"));
+        assert!(xml.contains("&quot;三体&quot; &amp; &lt;球状闪电&gt;"));
+        assert!(!xml.contains("<球状闪电>"));
+        assert!(xml.contains(r#"<w:footerReference w:type="default""#));
+
+        // 图片：文档中的关系ID指向 word/media 中的图片，宽高比与图片一致
+        let rels = read_entry(&mut archive, "word/_rels/document.xml.rels");
+        let image_rel = rels
+            .split("<Relationship ")
+            .find(|rel| rel.contains(REL_IMAGE))
+            .expect("缺少图片关系");
+        assert!(image_rel.contains(r#"Target="media/image1.png""#));
+        let rel_id = image_rel.split('"').nth(1).unwrap();
+        assert!(xml.contains(&format!(r#"<a:blip r:embed="{}"/>"#, rel_id)));
+        let width = (CHART_WIDTH_INCHES * EMU_PER_INCH).round() as u64;
+        assert!(xml.contains(&format!(r#"<wp:extent cx="{}" cy="{}"/>"#, width, width / 2)));
+        assert!(xml.contains(r#"name="阅读时长""#));
+
+        let mut image = Vec::new();
+        archive.by_name("word/media/image1.png").unwrap().read_to_end(&mut image).unwrap();
+        assert_eq!(image, std::fs::read(&chart).unwrap());
+        assert!(read_entry(&mut archive, "[Content_Types].xml").contains(r#"<Default Extension="png""#));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,172 @@
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use thiserror::Error;
+#[cfg(feature = "python")]
+use tracing::error;
+
+/// 返回给前端的错误。序列化为 `{"code", "message", ...}`，
+/// `code` 是稳定的机器可读代码，前端据此本地化提示和给出修复建议。
+///
+/// 分析器内部仍使用 `anyhow`，需要区分的错误以 `AnalyzerError` 构造后转换为 `anyhow::Error`，
+/// 命令返回前通过 [`AnalyzerError::classify`] 从错误链中还原。
+#[derive(Debug, Clone, Error)]
+pub enum AnalyzerError {
+    #[error("分析器未初始化，请先调用 initialize_analyzer")]
+    NotInitialized,
+    #[error("尚未加载数据，请先调用 analyze_files")]
+    DataNotLoaded,
+    /// 未选择输入文件或文件不存在
+    #[error("{message}")]
+    InputMissing { message: String, path: Option<String> },
+    /// 表头缺少必需的列，`missing` 为缺少列的标准表头
+    #[error("{message}")]
+    SchemaError { message: String, missing: Vec<String> },
+    /// 表头映射等配置文件无法读取或解析
+    #[error("{message}")]
+    ConfigError { message: String },
+    #[error("Python异常 {exception_type}: {message}")]
+    PythonException {
+        exception_type: String,
+        message: String,
+        traceback: Option<String>,
+    },
+    #[error("{message}")]
+    IoError { message: String },
+    #[error("{message}")]
+    AnalysisFailed { message: String },
+    #[error("{message}")]
+    ExportError { message: String },
+    #[error("任务已取消")]
+    Cancelled,
+    #[error("任务不存在: {job_id}")]
+    JobNotFound { job_id: String },
+    #[error("{message}")]
+    Internal { message: String },
+}
+
+impl AnalyzerError {
+    /// 稳定的错误代码
+    pub fn code(&self) -> &'static str {
+        match self {
+            AnalyzerError::NotInitialized => "not_initialized",
+            AnalyzerError::DataNotLoaded => "data_not_loaded",
+            AnalyzerError::InputMissing { .. } => "input_missing",
+            AnalyzerError::SchemaError { .. } => "schema_error",
+            AnalyzerError::ConfigError { .. } => "config_error",
+            AnalyzerError::PythonException { .. } => "python_exception",
+            AnalyzerError::IoError { .. } => "io_error",
+            AnalyzerError::AnalysisFailed { .. } => "analysis_failed",
+            AnalyzerError::ExportError { .. } => "export_error",
+            AnalyzerError::Cancelled => "cancelled",
+            AnalyzerError::JobNotFound { .. } => "job_not_found",
+            AnalyzerError::Internal { .. } => "internal",
+        }
+    }
+
+    pub fn input_missing(message: impl Into<String>, path: Option<&str>) -> Self {
+        AnalyzerError::InputMissing {
+            message: message.into(),
+            path: path.map(str::to_string),
+        }
+    }
+
+    /// 从错误链中还原错误类型：`AnalyzerError`、Python异常和IO错误保留原类型，
+    /// 其余错误由 `fallback` 根据完整的错误信息构造
+    pub fn classify(error: anyhow::Error, fallback: impl FnOnce(String) -> Self) -> Self {
+        let message = format!("{:#}", error);
+        for cause in error.chain() {
+            if let Some(e) = cause.downcast_ref::<AnalyzerError>() {
+                // 保留外层的上下文（如文件名）
+                return match e.clone() {
+                    AnalyzerError::InputMissing { path, .. } => {
+                        AnalyzerError::InputMissing { message, path }
+                    }
+                    AnalyzerError::SchemaError { missing, .. } => {
+                        AnalyzerError::SchemaError { message, missing }
+                    }
+                    e => e,
+                };
+            }
+            #[cfg(feature = "python")]
+            if let Some(e) = cause.downcast_ref::<PyErr>() {
+                return Self::from_python(e);
+            }
+            if cause.downcast_ref::<std::io::Error>().is_some() {
+                return AnalyzerError::IoError { message };
+            }
+        }
+        fallback(message)
+    }
+
+    /// 分析过程中的错误，`context` 如 "加载数据失败"
+    pub fn analysis(context: &str, error: anyhow::Error) -> Self {
+        Self::classify(error, |message| AnalyzerError::AnalysisFailed {
+            message: format!("{}: {}", context, message),
+        })
+    }
+
+    /// 导出报告或图表时的错误
+    pub fn export(context: &str, error: anyhow::Error) -> Self {
+        Self::classify(error, |message| AnalyzerError::ExportError {
+            message: format!("{}: {}", context, message),
+        })
+    }
+
+    /// Python异常：异常类型、信息和完整的调用栈（包括 `raise ... from` 等链式异常）。
+    /// `analyzer.py` 用 `raise Exception(...) from e` 包装原始异常，异常类型取最初引发的异常。
+    #[cfg(feature = "python")]
+    pub fn from_python(error: &PyErr) -> Self {
+        Python::with_gil(|py| {
+            let mut root = error.clone_ref(py);
+            while let Some(cause) = root.cause(py) {
+                root = cause;
+            }
+            let exception_type = root
+                .get_type_bound(py)
+                .qualname()
+                .map(|name| name.to_string())
+                .unwrap_or_else(|_| "Exception".to_string());
+            let message = error.value_bound(py).to_string();
+            let traceback = py
+                .import_bound("traceback")
+                .and_then(|traceback| {
+                    traceback.call_method1("format_exception", (error.value_bound(py),))
+                })
+                .and_then(|lines| lines.extract::<Vec<String>>())
+                .map(|lines| lines.concat())
+                .ok();
+            if let Some(traceback) = &traceback {
+                error!("Python异常:\n{}", traceback);
+            }
+            AnalyzerError::PythonException {
+                exception_type,
+                message,
+                traceback,
+            }
+        })
+    }
+}
+
+impl Serialize for AnalyzerError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            AnalyzerError::InputMissing { path, .. } => map.serialize_entry("path", path)?,
+            AnalyzerError::SchemaError { missing, .. } => map.serialize_entry("missing", missing)?,
+            AnalyzerError::PythonException {
+                exception_type,
+                traceback,
+                ..
+            } => {
+                map.serialize_entry("exception_type", exception_type)?;
+                map.serialize_entry("traceback", traceback)?;
+            }
+            AnalyzerError::JobNotFound { job_id } => map.serialize_entry("job_id", job_id)?,
+            _ => {}
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,515 @@
+use crate::charts::{ChartSeries, ChartType};
+use crate::report::{ReportBlock, ReportDocument};
+use crate::theme::ReportTheme;
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::ImageFormat;
+use std::fmt::Write;
+use std::path::Path;
+use tracing::warn;
+
+/// 报告正文的字体，主题中指定的字体优先
+const FONT_FAMILY: &str =
+    r#""Microsoft YaHei", "PingFang SC", "Noto Sans CJK SC", "Source Han Sans SC", sans-serif"#;
+
+/// 交互图表的宽度（SVG坐标），显示时随页面宽度缩放
+const SVG_WIDTH: f64 = 720.0;
+const SVG_FONT_SIZE: f64 = 12.0;
+
+const STYLESHEET: &str = r#"
+* { box-sizing: border-box; }
+body { margin: 0; background: #f4f5f7; color: #222; font-family: var(--font); font-size: 15px; line-height: 1.6; }
+.report { max-width: 960px; margin: 24px auto; padding: 32px 40px; background: #fff; box-shadow: 0 1px 4px rgba(0, 0, 0, 0.12); }
+.report-header { display: flex; align-items: center; gap: 12px; padding-bottom: 8px; margin-bottom: 16px; border-bottom: 1px solid #ddd; color: #666; font-size: 13px; }
+.report-footer { margin-top: 32px; padding-top: 8px; border-top: 1px solid #ddd; color: #666; font-size: 13px; text-align: center; }
+h1 { color: var(--accent); font-size: 26px; margin: 8px 0 16px; }
+h2 { color: var(--accent); font-size: 19px; margin: 28px 0 10px; }
+.table-wrap { overflow-x: auto; }
+table { border-collapse: collapse; width: 100%; font-size: 14px; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: var(--accent); color: #fff; }
+tbody tr:nth-child(even) { background: #f7f8fa; }
+figure.chart { margin: 8px 0 16px; text-align: center; }
+figure.chart img, figure.chart svg { max-width: 100%; height: auto; }
+.chart text { font-family: var(--font); fill: #333; }
+.chart .axis { stroke: #888; stroke-width: 1; }
+.chart .grid { stroke: #e3e3e3; stroke-width: 1; }
+.chart .mark { cursor: pointer; transition: opacity 0.15s; }
+.chart .mark:hover { opacity: 0.7; stroke: #222; stroke-width: 1.5; }
+@media (max-width: 640px) {
+  .report { margin: 0; padding: 16px; box-shadow: none; }
+}
+@media print {
+  body { background: #fff; font-size: 11pt; }
+  .report { max-width: none; margin: 0; padding: 0; box-shadow: none; }
+  h1, h2 { break-after: avoid; }
+  tr, figure.chart { break-inside: avoid; }
+  thead { display: table-header-group; }
+  .table-wrap { overflow: visible; }
+  th { -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+  .chart .mark:hover { opacity: 1; stroke: none; }
+}
+"#;
+
+/// 将报告写入单个HTML文件：样式内嵌，图表和标志以base64嵌入，不引用任何外部资源，可以离线打开和打印。
+/// 主题启用 `interactive_charts` 时图表绘制为可交互的SVG（悬停时显示数值）
+pub fn write_html(document: &ReportDocument, theme: &ReportTheme, output_path: &Path) -> Result<()> {
+    let mut body = String::new();
+    body.push_str(&header(theme)?);
+    writeln!(body, "<h1>{}</h1>", escape(&document.title))?;
+
+    let palette = theme.palette_rgb();
+    for block in &document.blocks {
+        match block {
+            ReportBlock::Heading(text) => writeln!(body, "<h2>{}</h2>", escape(text))?,
+            ReportBlock::Paragraph(text) => {
+                writeln!(body, "<p>{}</p>", escape(text).replace('\n', "<br>"))?
+            }
+            ReportBlock::Table { headers, rows } => body.push_str(&table(headers, rows)),
+            ReportBlock::Image {
+                title,
+                path,
+                series,
+            } => {
+                let chart = match series {
+                    Some(series) if theme.interactive_charts => svg_chart(series, &palette),
+                    _ => format!(
+                        r#"<img src="{}" alt="{}">"#,
+                        data_uri(path)?,
+                        escape(title)
+                    ),
+                };
+                writeln!(body, r#"<figure class="chart">{}</figure>"#, chart)?;
+            }
+        }
+    }
+
+    if let Some(text) = theme.footer_text.as_deref().filter(|text| !text.trim().is_empty()) {
+        writeln!(body, r#"<footer class="report-footer">{}</footer>"#, escape(text))?;
+    }
+
+    let html = format!(
+        concat!(
+            "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n",
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+            "<meta name=\"generator\" content=\"ReaderReport\">\n<title>{}</title>\n",
+            "<style>{}{}</style>\n</head>\n<body>\n<article class=\"report\">\n{}</article>\n</body>\n</html>\n"
+        ),
+        escape(&document.title),
+        variables(theme),
+        STYLESHEET,
+        body
+    );
+    std::fs::write(output_path, html).with_context(|| format!("无法写入文件: {:?}", output_path))
+}
+
+/// 随主题变化的样式：字体、主题色，以及打印时的纸张大小和页边距
+fn variables(theme: &ReportTheme) -> String {
+    let font = match theme.report_font.as_deref() {
+        // 字体名称放在CSS字符串中，去掉可能破坏样式的字符
+        Some(font) => format!(
+            "\"{}\", {}",
+            font.chars()
+                .filter(|c| !matches!(c, '"' | '\\' | '<' | '>' | ';' | '{' | '}'))
+                .collect::<String>(),
+            FONT_FAMILY
+        ),
+        None => FONT_FAMILY.to_string(),
+    };
+    let (width, height) = theme.page_size.dimensions_mm();
+    let margins = theme.page_margins_mm;
+    format!(
+        "\n:root {{ --accent: {}; --font: {}; }}\n@page {{ size: {}mm {}mm; margin: {}mm {}mm {}mm {}mm; }}",
+        hex_color(theme.accent_rgb()),
+        font,
+        width,
+        height,
+        margins.top,
+        margins.right,
+        margins.bottom,
+        margins.left
+    )
+}
+
+fn header(theme: &ReportTheme) -> Result<String> {
+    let mut content = String::new();
+    match theme.logo.as_deref() {
+        Some(logo) if logo.is_file() => write!(
+            content,
+            r#"<img src="{}" alt="" style="height: {}cm">"#,
+            data_uri(logo)?,
+            theme.logo_height_cm
+        )?,
+        Some(logo) => warn!("报告主题中的标志图片不存在: {:?}", logo),
+        None => {}
+    }
+    if let Some(text) = theme.header_text.as_deref().filter(|text| !text.trim().is_empty()) {
+        write!(content, "<span>{}</span>", escape(text))?;
+    }
+    if content.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!(r#"<header class="report-header">{}</header>"#, content) + "\n")
+}
+
+fn table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let cells = |tag: &str, row: &[String]| -> String {
+        row.iter()
+            .map(|cell| format!("<{tag}>{}</{tag}>", escape(cell)))
+            .collect()
+    };
+    let mut html = format!(
+        "<div class=\"table-wrap\"><table>\n<thead><tr>{}</tr></thead>\n<tbody>\n",
+        cells("th", headers)
+    );
+    for row in rows {
+        html.push_str(&format!("<tr>{}</tr>\n", cells("td", row)));
+    }
+    html.push_str("</tbody></table></div>\n");
+    html
+}
+
+/// 图片文件转换为 `data:` URI
+pub(crate) fn data_uri(path: &Path) -> Result<String> {
+    let mime = match ImageFormat::from_path(path) {
+        Ok(ImageFormat::Png) => "image/png",
+        Ok(ImageFormat::Jpeg) => "image/jpeg",
+        _ => return Err(anyhow!("不支持的图片格式（需要PNG或JPEG）: {:?}", path)),
+    };
+    let bytes = std::fs::read(path).with_context(|| format!("无法读取图片: {:?}", path))?;
+    Ok(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
+
+/// HTML/SVG 文本转义，并去掉XML不允许的控制字符
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn hex_color((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// 按数据序列绘制SVG图表，每个数据点都带有提示文字（`<title>`），悬停时高亮并显示数值
+pub(crate) fn svg_chart(series: &ChartSeries, palette: &[(u8, u8, u8)]) -> String {
+    let color = |index: usize| hex_color(palette[index % palette.len()]);
+    let (height, content) = match series.chart_type {
+        ChartType::Bar => bar_chart(series, &color(0)),
+        ChartType::Line => line_chart(series, &color),
+        ChartType::Pie => pie_chart(series, &color),
+    };
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" width="{}" height="{}" font-size="{}" role="img" aria-label="{}">{}</svg>"#,
+        SVG_WIDTH,
+        height,
+        SVG_WIDTH,
+        height,
+        SVG_FONT_SIZE,
+        escape(series.title),
+        content
+    )
+}
+
+fn tooltip(series: &ChartSeries, label: &str, value: f64) -> String {
+    format!(
+        "<title>{}：{}{}</title>",
+        escape(label),
+        format_value(value),
+        escape(series.unit)
+    )
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+/// 文字的大致显示宽度（中文等非ASCII字符按一个字号计算，其余按半个字号）
+fn text_width(text: &str) -> f64 {
+    text.chars()
+        .map(|c| if c.is_ascii() { 0.55 } else { 1.0 })
+        .sum::<f64>()
+        * SVG_FONT_SIZE
+}
+
+/// 截断过长的标签，完整的标签在提示文字中显示
+fn truncate(text: &str, max_width: f64) -> String {
+    if text_width(text) <= max_width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    for c in text.chars() {
+        if text_width(&truncated) + text_width(&c.to_string()) + SVG_FONT_SIZE > max_width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated + "…"
+}
+
+/// 坐标轴的最大值和刻度间隔（1、2、5 × 10^n，约5个刻度）
+fn axis_scale(max: f64) -> (f64, f64) {
+    let max = max.max(1.0);
+    let raw = max / 5.0;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(raw)
+        .max(1.0);
+    ((max / step).ceil() * step, step)
+}
+
+/// 横向条形图，返回图表高度和SVG内容
+fn bar_chart(series: &ChartSeries, color: &str) -> (f64, String) {
+    const ROW: f64 = 26.0;
+    const TOP: f64 = 10.0;
+    let label_width = series
+        .labels
+        .iter()
+        .map(|label| text_width(label))
+        .fold(0.0, f64::max)
+        .min(220.0)
+        + 10.0;
+    let plot_left = label_width;
+    let plot_width = SVG_WIDTH - plot_left - 60.0;
+    let (max, step) = axis_scale(series.values.iter().copied().fold(0.0, f64::max));
+    let plot_height = series.labels.len() as f64 * ROW;
+    let height = TOP + plot_height + 40.0;
+
+    let mut svg = String::new();
+    let mut tick = 0.0;
+    while tick <= max {
+        let x = plot_left + plot_width * tick / max;
+        svg.push_str(&format!(
+            r#"<line class="grid" x1="{x:.1}" y1="{TOP}" x2="{x:.1}" y2="{:.1}"/><text x="{x:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+            TOP + plot_height,
+            TOP + plot_height + 16.0,
+            format_value(tick)
+        ));
+        tick += step;
+    }
+    for (index, (label, value)) in series.labels.iter().zip(&series.values).enumerate() {
+        let y = TOP + index as f64 * ROW;
+        let width = plot_width * value / max;
+        svg.push_str(&format!(
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+            plot_left - 6.0,
+            y + ROW / 2.0,
+            escape(&truncate(label, label_width - 10.0))
+        ));
+        svg.push_str(&format!(
+            r#"<rect class="mark" x="{plot_left:.1}" y="{:.1}" width="{width:.1}" height="{:.1}" fill="{color}">{}</rect>"#,
+            y + 5.0,
+            ROW - 10.0,
+            tooltip(series, label, *value)
+        ));
+        svg.push_str(&format!(
+            r#"<text x="{:.1}" y="{:.1}" dominant-baseline="middle">{}</text>"#,
+            plot_left + width + 4.0,
+            y + ROW / 2.0,
+            format_value(*value)
+        ));
+    }
+    svg.push_str(&format!(
+        r#"<line class="axis" x1="{plot_left:.1}" y1="{TOP}" x2="{plot_left:.1}" y2="{:.1}"/><text x="{:.1}" y="{:.1}" text-anchor="middle">{}（{}）</text>"#,
+        TOP + plot_height,
+        plot_left + plot_width / 2.0,
+        height - 4.0,
+        escape(series.value_name),
+        escape(series.unit)
+    ));
+    (height, svg)
+}
+
+/// 折线图：单条折线（`values`）或每组一条折线（`groups`，缺少的数据处断开），返回图表高度和SVG内容
+fn line_chart(series: &ChartSeries, color: &dyn Fn(usize) -> String) -> (f64, String) {
+    const LEFT: f64 = 50.0;
+    const RIGHT: f64 = 20.0;
+    const TOP: f64 = 16.0;
+    const PLOT_HEIGHT: f64 = 260.0;
+    let lines: Vec<(String, Vec<Option<f64>>)> = if series.groups.is_empty() {
+        vec![(
+            series.value_name.to_string(),
+            series.values.iter().copied().map(Some).collect(),
+        )]
+    } else {
+        series
+            .groups
+            .iter()
+            .map(|group| (group.name.clone(), group.values.clone()))
+            .collect()
+    };
+    let max = lines
+        .iter()
+        .flat_map(|(_, values)| values.iter().flatten())
+        .copied()
+        .fold(0.0, f64::max);
+    let (max, step) = axis_scale(max);
+    let count = series.labels.len().max(1);
+    let plot_width = SVG_WIDTH - LEFT - RIGHT;
+    let x = |index: usize| {
+        if count == 1 {
+            LEFT + plot_width / 2.0
+        } else {
+            LEFT + plot_width * index as f64 / (count - 1) as f64
+        }
+    };
+    let y = |value: f64| TOP + PLOT_HEIGHT * (1.0 - value / max);
+    let bottom = TOP + PLOT_HEIGHT;
+
+    let mut svg = String::new();
+    let mut tick = 0.0;
+    while tick <= max {
+        svg.push_str(&format!(
+            r#"<line class="grid" x1="{LEFT}" y1="{0:.1}" x2="{1:.1}" y2="{0:.1}"/><text x="{2:.1}" y="{0:.1}" text-anchor="end" dominant-baseline="middle">{3}</text>"#,
+            y(tick),
+            LEFT + plot_width,
+            LEFT - 6.0,
+            format_value(tick)
+        ));
+        tick += step;
+    }
+    // 标签过多时间隔显示，最多约12个
+    let every = count.div_ceil(12);
+    for (index, label) in series.labels.iter().enumerate().step_by(every) {
+        svg.push_str(&format!(
+            r#"<text x="{0:.1}" y="{1:.1}" text-anchor="end" transform="rotate(-40 {0:.1} {1:.1})">{2}</text>"#,
+            x(index),
+            bottom + 14.0,
+            escape(label)
+        ));
+    }
+    svg.push_str(&format!(
+        r#"<line class="axis" x1="{LEFT}" y1="{bottom:.1}" x2="{:.1}" y2="{bottom:.1}"/><line class="axis" x1="{LEFT}" y1="{TOP}" x2="{LEFT}" y2="{bottom:.1}"/>"#,
+        LEFT + plot_width
+    ));
+
+    for (line, (name, values)) in lines.iter().enumerate() {
+        let color = color(line);
+        // 缺少数据的位置断开折线
+        let mut path = String::new();
+        let mut pen_down = false;
+        for (index, value) in values.iter().enumerate() {
+            match value {
+                Some(value) => {
+                    path.push_str(&format!(
+                        "{}{:.1},{:.1} ",
+                        if pen_down { "L" } else { "M" },
+                        x(index),
+                        y(*value)
+                    ));
+                    pen_down = true;
+                }
+                None => pen_down = false,
+            }
+        }
+        svg.push_str(&format!(
+            r#"<path d="{}" fill="none" stroke="{color}" stroke-width="2"/>"#,
+            path.trim_end()
+        ));
+        for (index, value) in values.iter().enumerate() {
+            let (Some(value), Some(label)) = (value, series.labels.get(index)) else {
+                continue;
+            };
+            let label = if series.groups.is_empty() {
+                label.clone()
+            } else {
+                format!("{} {}", name, label)
+            };
+            svg.push_str(&format!(
+                r#"<circle class="mark" cx="{:.1}" cy="{:.1}" r="4" fill="{color}">{}</circle>"#,
+                x(index),
+                y(*value),
+                tooltip(series, &label, *value)
+            ));
+        }
+    }
+
+    let mut height = bottom + 60.0;
+    if !series.groups.is_empty() {
+        // 图例
+        let mut legend_x = LEFT;
+        for (line, (name, _)) in lines.iter().enumerate() {
+            svg.push_str(&format!(
+                r#"<rect x="{legend_x:.1}" y="{height:.1}" width="12" height="12" fill="{}"/><text x="{:.1}" y="{:.1}">{}</text>"#,
+                color(line),
+                legend_x + 16.0,
+                height + 10.5,
+                escape(name)
+            ));
+            legend_x += 28.0 + text_width(name);
+        }
+        height += 24.0;
+    }
+    (height, svg)
+}
+
+/// 饼图，图例中显示数值和比例，返回图表高度和SVG内容
+fn pie_chart(series: &ChartSeries, color: &dyn Fn(usize) -> String) -> (f64, String) {
+    const RADIUS: f64 = 140.0;
+    const CENTER: (f64, f64) = (170.0, 160.0);
+    const LEGEND_ROW: f64 = 22.0;
+    let total: f64 = series.values.iter().sum();
+    let height = (CENTER.1 * 2.0).max(series.labels.len() as f64 * LEGEND_ROW + 20.0);
+
+    let (cx, cy) = CENTER;
+    let mut svg = String::new();
+    let mut angle: f64 = -std::f64::consts::FRAC_PI_2;
+    for (index, (label, value)) in series.labels.iter().zip(&series.values).enumerate() {
+        let color = color(index);
+        let ratio = if total > 0.0 { value / total } else { 0.0 };
+        let title = format!(
+            "<title>{}：{}{}（{:.1}%）</title>",
+            escape(label),
+            format_value(*value),
+            escape(series.unit),
+            ratio * 100.0
+        );
+        if ratio >= 0.9999 {
+            svg.push_str(&format!(
+                r#"<circle class="mark" cx="{cx}" cy="{cy}" r="{RADIUS}" fill="{color}">{title}</circle>"#
+            ));
+        } else if ratio > 0.0 {
+            let end = angle + ratio * std::f64::consts::TAU;
+            svg.push_str(&format!(
+                r##"<path class="mark" d="M{cx},{cy} L{:.2},{:.2} A{RADIUS},{RADIUS} 0 {} 1 {:.2},{:.2} Z" fill="{color}" stroke="#fff" stroke-width="1">{title}</path>"##,
+                cx + RADIUS * angle.cos(),
+                cy + RADIUS * angle.sin(),
+                u8::from(ratio > 0.5),
+                cx + RADIUS * end.cos(),
+                cy + RADIUS * end.sin(),
+            ));
+            angle = end;
+        }
+
+        let legend_x = cx * 2.0 + 20.0;
+        let legend_y = 10.0 + index as f64 * LEGEND_ROW;
+        svg.push_str(&format!(
+            r#"<rect x="{legend_x:.1}" y="{legend_y:.1}" width="12" height="12" fill="{color}"/><text x="{:.1}" y="{:.1}">{} {}{}（{:.1}%）</text>"#,
+            legend_x + 18.0,
+            legend_y + 10.5,
+            escape(&truncate(label, 200.0)),
+            format_value(*value),
+            escape(series.unit),
+            ratio * 100.0
+        ));
+    }
+    (height, svg)
+}
@@ -0,0 +1,401 @@
+use crate::analyzer_backend::{CancelFlag, OutputCallback, ProgressCallback};
+use crate::diagnostics;
+use crate::error::AnalyzerError;
+use crate::progress::{self, ProgressEvent, ProgressStage};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, info_span, warn};
+
+/// 任务结束事件名称，载荷为任务的最终状态 `JobInfo`
+pub const JOB_EVENT: &str = "analysis://job";
+/// 保留的已结束任务数量，超出时丢弃最早结束的任务
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 任务信息，`list_jobs`/`get_job` 返回该结构
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    /// 启动任务的命令名称
+    pub command: String,
+    pub status: JobStatus,
+    pub started_at: DateTime<Local>,
+    pub finished_at: Option<DateTime<Local>>,
+    /// 最近一次进度事件
+    pub progress: Option<ProgressEvent>,
+    /// 已生成的文件；任务取消或失败时为已完成的部分
+    pub outputs: Vec<String>,
+    /// 命令的返回值（任务完成时）
+    pub result: Option<serde_json::Value>,
+    pub error: Option<AnalyzerError>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancelled: CancelFlag,
+}
+
+/// 任务管理器：记录运行中和已结束的任务
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Mutex<Vec<JobEntry>>,
+    next_id: AtomicU64,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记新任务，返回任务ID和取消标志
+    pub fn start(&self, command: &str) -> (String, CancelFlag) {
+        let id = format!("job-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let entry = JobEntry {
+            info: JobInfo {
+                id: id.clone(),
+                command: command.to_string(),
+                status: JobStatus::Running,
+                started_at: Local::now(),
+                finished_at: None,
+                progress: None,
+                outputs: Vec::new(),
+                result: None,
+                error: None,
+            },
+            cancelled: cancelled.clone(),
+        };
+        self.lock().push(entry);
+        (id, cancelled)
+    }
+
+    /// 请求取消任务，任务在当前文件或阶段结束后停止；任务已结束时不做任何操作
+    pub fn cancel(&self, id: &str) -> Result<(), AnalyzerError> {
+        let jobs = self.lock();
+        let entry = jobs
+            .iter()
+            .find(|entry| entry.info.id == id)
+            .ok_or_else(|| AnalyzerError::JobNotFound {
+                job_id: id.to_string(),
+            })?;
+        if entry.info.status == JobStatus::Running {
+            entry.cancelled.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobInfo> {
+        self.lock()
+            .iter()
+            .find(|entry| entry.info.id == id)
+            .map(|entry| entry.info.clone())
+    }
+
+    /// 所有任务，按启动顺序排列
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.lock().iter().map(|entry| entry.info.clone()).collect()
+    }
+
+    /// 创建逐个记录任务已生成文件的回调
+    fn output_recorder(self: &Arc<Self>, id: &str) -> OutputCallback {
+        let registry = self.clone();
+        let id = id.to_string();
+        Box::new(move |output| registry.update(&id, |info| info.outputs.push(output)))
+    }
+
+    fn update(&self, id: &str, update: impl FnOnce(&mut JobInfo)) {
+        if let Some(entry) = self.lock().iter_mut().find(|entry| entry.info.id == id) {
+            update(&mut entry.info);
+        }
+    }
+
+    /// 记录任务结束，状态由结果决定（出错时参考取消标志），返回最终状态
+    fn finish(
+        &self,
+        id: &str,
+        outcome: Result<serde_json::Value, AnalyzerError>,
+    ) -> Option<JobInfo> {
+        let mut jobs = self.lock();
+        let entry = jobs.iter_mut().find(|entry| entry.info.id == id)?;
+        let info = &mut entry.info;
+        info.finished_at = Some(Local::now());
+        // 已完成的任务即使之后请求了取消也记为完成；出错时请求过取消则记为取消
+        info.status = match &outcome {
+            Ok(_) => JobStatus::Completed,
+            Err(AnalyzerError::Cancelled) => JobStatus::Cancelled,
+            Err(_) if entry.cancelled.load(Ordering::Relaxed) => JobStatus::Cancelled,
+            Err(_) => JobStatus::Failed,
+        };
+        match outcome {
+            Ok(result) => info.result = Some(result),
+            Err(e) => info.error = Some(e),
+        }
+        let finished = info.clone();
+
+        let finished_count = jobs
+            .iter()
+            .filter(|entry| entry.info.status != JobStatus::Running)
+            .count();
+        if finished_count > MAX_FINISHED_JOBS {
+            if let Some(oldest) = jobs
+                .iter()
+                .position(|entry| entry.info.status != JobStatus::Running)
+            {
+                jobs.remove(oldest);
+            }
+        }
+        Some(finished)
+    }
+
+    // 任务线程 panic 导致锁中毒时继续使用其中的数据
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<JobEntry>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 任务线程中使用的上下文：发送进度、检查取消、记录生成的文件
+pub struct JobContext {
+    app: AppHandle,
+    registry: Arc<JobRegistry>,
+    id: String,
+    cancelled: CancelFlag,
+}
+
+impl JobContext {
+    pub fn app(&self) -> &AppHandle {
+        &self.app
+    }
+
+    pub fn cancel_flag(&self) -> CancelFlag {
+        self.cancelled.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 阶段之间调用：已请求取消时返回错误，结束任务
+    pub fn check_cancelled(&self) -> Result<(), AnalyzerError> {
+        if self.is_cancelled() {
+            Err(AnalyzerError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 创建某一阶段的进度回调：发送进度事件并记录到任务信息中
+    pub fn progress(&self, stage: ProgressStage) -> ProgressCallback {
+        let emit = progress::emitter(&self.app, &self.id, stage);
+        let registry = self.registry.clone();
+        let id = self.id.clone();
+        Box::new(move |total, current, message| {
+            registry.update(&id, |info| {
+                info.progress = Some(ProgressEvent {
+                    job_id: id.clone(),
+                    stage,
+                    current,
+                    total,
+                    message: message.clone(),
+                })
+            });
+            emit(total, current, message);
+        })
+    }
+
+    /// 创建逐个记录已生成文件的回调，任务中途失败或取消时已生成的文件仍在 `outputs` 中
+    pub fn output_recorder(&self) -> OutputCallback {
+        self.registry.output_recorder(&self.id)
+    }
+
+    /// 记录已生成的文件
+    pub fn add_outputs(&self, outputs: impl IntoIterator<Item = String>) {
+        self.registry
+            .update(&self.id, |info| info.outputs.extend(outputs));
+    }
+}
+
+/// 在后台线程中运行任务并立即返回任务ID；任务结束时发送 `analysis://job` 事件
+pub fn spawn<T, F>(
+    app: &AppHandle,
+    registry: &Arc<JobRegistry>,
+    command: &'static str,
+    task: F,
+) -> String
+where
+    T: Serialize,
+    F: FnOnce(&JobContext) -> Result<T, AnalyzerError> + Send + 'static,
+{
+    let (id, cancelled) = registry.start(command);
+    let context = JobContext {
+        app: app.clone(),
+        registry: registry.clone(),
+        id: id.clone(),
+        cancelled,
+    };
+
+    // 任务中的日志都带有任务ID和命令名称
+    let span = info_span!("job", id = %id, command);
+    tauri::async_runtime::spawn_blocking(move || {
+        let _span = span.enter();
+        info!("任务开始");
+        // 任务 panic 时记为失败，不影响其他任务
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| task(&context)))
+            .unwrap_or_else(|_| {
+                Err(AnalyzerError::Internal {
+                    message: "任务执行过程中发生内部错误".to_string(),
+                })
+            })
+            .and_then(|result| {
+                serde_json::to_value(result).map_err(|e| AnalyzerError::Internal {
+                    message: format!("序列化任务结果失败: {}", e),
+                })
+            });
+        if let Err(e) = &outcome {
+            diagnostics::record_error(command, e);
+        }
+        if let Some(info) = context.registry.finish(&context.id, outcome) {
+            info!(status = ?info.status, "任务结束");
+            if let Err(e) = context.app.emit(JOB_EVENT, info) {
+                warn!("发送任务事件失败: {}", e);
+            }
+        }
+    });
+
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 模拟分步执行的任务：每一步开始前检查取消，结束后记录生成的文件
+    fn run_steps(
+        registry: &Arc<JobRegistry>,
+        id: &str,
+        cancelled: &CancelFlag,
+        steps: &[&str],
+        before_step: impl Fn(usize),
+    ) -> Result<serde_json::Value, AnalyzerError> {
+        let record = registry.output_recorder(id);
+        for (index, step) in steps.iter().enumerate() {
+            before_step(index);
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(AnalyzerError::Cancelled);
+            }
+            record(step.to_string());
+        }
+        Ok(serde_json::json!(steps.len()))
+    }
+
+    #[test]
+    fn cancel_before_first_step() {
+        let registry = Arc::new(JobRegistry::new());
+        let (id, cancelled) = registry.start("generate_report");
+        registry.cancel(&id).unwrap();
+
+        let outcome = run_steps(&registry, &id, &cancelled, &["a.png", "b.png"], |_| {});
+        let info = registry.finish(&id, outcome).unwrap();
+
+        assert_eq!(info.status, JobStatus::Cancelled);
+        assert!(info.outputs.is_empty());
+        assert!(matches!(info.error, Some(AnalyzerError::Cancelled)));
+        assert!(info.result.is_none());
+        assert!(info.finished_at.is_some());
+    }
+
+    #[test]
+    fn cancel_after_last_step_reports_completed() {
+        let registry = Arc::new(JobRegistry::new());
+        let (id, cancelled) = registry.start("generate_report");
+
+        let outcome = run_steps(&registry, &id, &cancelled, &["a.png", "b.png"], |_| {});
+        // 所有步骤已完成、任务尚未结束时请求取消
+        registry.cancel(&id).unwrap();
+        assert!(cancelled.load(Ordering::Relaxed));
+        let info = registry.finish(&id, outcome).unwrap();
+
+        assert_eq!(info.status, JobStatus::Completed);
+        assert_eq!(info.outputs, ["a.png", "b.png"]);
+        assert_eq!(info.result, Some(serde_json::json!(2)));
+        assert!(info.error.is_none());
+
+        // 任务结束后再请求取消不改变状态
+        registry.cancel(&id).unwrap();
+        assert_eq!(registry.get(&id).unwrap().status, JobStatus::Completed);
+    }
+
+    #[test]
+    fn outputs_recorded_before_cancellation_are_kept() {
+        let registry = Arc::new(JobRegistry::new());
+        let (id, cancelled) = registry.start("generate_report");
+
+        let outcome = run_steps(&registry, &id, &cancelled, &["a.png", "b.png", "c.png"], |index| {
+            if index == 2 {
+                registry.cancel(&id).unwrap();
+            }
+        });
+        let info = registry.finish(&id, outcome).unwrap();
+
+        assert_eq!(info.status, JobStatus::Cancelled);
+        assert_eq!(info.outputs, ["a.png", "b.png"]);
+        assert_eq!(registry.get(&id).unwrap().outputs, ["a.png", "b.png"]);
+    }
+
+    #[test]
+    fn errors_after_cancel_request_are_reported_as_cancelled() {
+        let registry = Arc::new(JobRegistry::new());
+        let (failed, _) = registry.start("export_data");
+        let (cancelled_id, _) = registry.start("export_data");
+        registry.cancel(&cancelled_id).unwrap();
+
+        let error = || AnalyzerError::Internal {
+            message: "写入失败".to_string(),
+        };
+        assert_eq!(registry.finish(&failed, Err(error())).unwrap().status, JobStatus::Failed);
+        assert_eq!(
+            registry.finish(&cancelled_id, Err(error())).unwrap().status,
+            JobStatus::Cancelled
+        );
+    }
+
+    #[test]
+    fn oldest_finished_jobs_are_evicted() {
+        let registry = Arc::new(JobRegistry::new());
+        let (running, _) = registry.start("analyze");
+        let ids: Vec<String> = (0..=MAX_FINISHED_JOBS)
+            .map(|_| {
+                let (id, _) = registry.start("export_data");
+                registry.finish(&id, Ok(serde_json::Value::Null)).unwrap();
+                id
+            })
+            .collect();
+
+        let jobs = registry.list();
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+        assert_eq!(jobs[0].id, running);
+        assert_eq!(jobs[0].status, JobStatus::Running);
+        assert_eq!(jobs[1].id, ids[1]);
+
+        assert!(registry.get(&ids[0]).is_none());
+        assert!(matches!(
+            registry.cancel(&ids[0]),
+            Err(AnalyzerError::JobNotFound { job_id }) if job_id == ids[0]
+        ));
+        assert!(registry.finish(&ids[0], Ok(serde_json::Value::Null)).is_none());
+        assert!(registry.cancel(&running).is_ok());
+        assert!(registry.cancel("job-unknown").is_err());
+    }
+}
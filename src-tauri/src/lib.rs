@@ -1,23 +1,85 @@
-mod python_analyzer;
+pub mod analyzer_backend;
+mod analyzer_worker;
+mod cff;
+pub mod charts;
+pub mod column_mapping;
+mod config;
+pub mod data_export;
+mod diagnostics;
+pub mod docx;
+pub mod error;
+pub mod html;
+mod jobs;
+mod logging;
+pub mod membership;
+pub mod pdf;
+mod progress;
+pub mod report;
+#[cfg(feature = "python")]
+pub mod python_analyzer;
+pub mod reader_identity;
+pub mod rust_analyzer;
+pub mod spreadsheet;
+pub mod template;
+pub mod theme;
 mod commands;
+mod validation;
+pub mod xlsx;
 
 use commands::AppState;
+use config::{AppConfig, CONFIG_FILE_NAME};
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(AppState::default())
+        .setup(|app| {
+            // 最先初始化日志，之后的输出都写入应用日志目录
+            logging::init(app.path().app_log_dir().ok().as_deref());
+
+            // 打包的中文字体（图表使用）
+            if let Ok(dir) = app.path().resource_dir() {
+                charts::set_font_dir(dir.join(charts::FONT_DIR_NAME));
+            }
+
+            // 用户报告模板
+            if let Ok(dir) = app.path().app_data_dir() {
+                report::set_template_dir(dir.join(report::TEMPLATE_DIR_NAME));
+            }
+
+            // 启动时根据配置选择分析器后端
+            let config = match app.path().app_config_dir() {
+                Ok(dir) => AppConfig::load(&dir.join(CONFIG_FILE_NAME)),
+                Err(_) => AppConfig::default(),
+            };
+            theme::set_current(config.report_theme.clone());
+            app.manage(AppState::new(config));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::initialize_analyzer,
+            commands::validate_inputs,
+            commands::list_sheets,
             commands::analyze_files,
             commands::generate_charts,
+            commands::get_chart_data,
             commands::export_report,
+            commands::export_data,
             commands::get_file_count,
             commands::get_file_name,
             commands::analyze_single_file,
-            commands::export_reports_for_all_files
+            commands::get_reader_count,
+            commands::get_reader_name,
+            commands::analyze_reader,
+            commands::export_reports_for_all_files,
+            commands::cancel_job,
+            commands::list_jobs,
+            commands::get_job,
+            commands::export_diagnostics,
+            commands::open_log,
+            commands::tail_log
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
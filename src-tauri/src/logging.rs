@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use std::path::{Path, PathBuf};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::{self, format::Writer, time::FormatTime};
+use tracing_subscriber::prelude::*;
+
+/// 日志文件名前缀，按天滚动，如 `readerreport.2024-01-01.log`
+const LOG_FILE_PREFIX: &str = "readerreport";
+const LOG_FILE_SUFFIX: &str = "log";
+/// 保留的日志文件数量（天）
+const MAX_LOG_FILES: usize = 7;
+/// 覆盖日志级别的环境变量，如 `debug`
+const LOG_LEVEL_ENV: &str = "READERREPORT_LOG";
+
+/// 本地时间，与报告中的时间一致
+struct LocalTime;
+
+impl FormatTime for LocalTime {
+    fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
+        write!(w, "{}", Local::now().format("%Y-%m-%d %H:%M:%S%.3f"))
+    }
+}
+
+/// 初始化日志：写入 `log_dir` 下按天滚动的日志文件，同时输出到控制台。
+/// Windows 发布版没有控制台，日志文件是唯一的输出。
+pub fn init(log_dir: Option<&Path>) {
+    let level = std::env::var(LOG_LEVEL_ENV)
+        .ok()
+        .and_then(|value| value.parse::<LevelFilter>().ok())
+        .unwrap_or(LevelFilter::INFO);
+
+    let mut appender_error = None;
+    let file_layer = log_dir
+        .and_then(|dir| {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                appender_error = Some(format!("无法创建日志目录 {:?}: {}", dir, e));
+                return None;
+            }
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_FILE_PREFIX)
+                .filename_suffix(LOG_FILE_SUFFIX)
+                .max_log_files(MAX_LOG_FILES)
+                .build(dir)
+                .map_err(|e| appender_error = Some(format!("无法创建日志文件 {:?}: {}", dir, e)))
+                .ok()
+        })
+        .map(|appender| {
+            fmt::layer()
+                .with_writer(appender)
+                .with_ansi(false)
+                .with_timer(LocalTime)
+        });
+    let console_layer = fmt::layer().with_timer(LocalTime);
+
+    if tracing_subscriber::registry()
+        .with(file_layer)
+        .with(console_layer)
+        .with(level)
+        .try_init()
+        .is_err()
+    {
+        return;
+    }
+
+    if let Some(e) = appender_error {
+        tracing::warn!("{}", e);
+    }
+    tracing::info!(
+        "应用启动，版本 {}，日志目录: {:?}",
+        env!("CARGO_PKG_VERSION"),
+        log_dir
+    );
+}
+
+/// 最新的日志文件
+pub fn latest_log_file(log_dir: &Path) -> Result<PathBuf> {
+    let entries = std::fs::read_dir(log_dir)
+        .with_context(|| format!("无法读取日志目录: {:?}", log_dir))?;
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        })
+        .max_by_key(|path| path.metadata().and_then(|meta| meta.modified()).ok())
+        .ok_or_else(|| anyhow!("日志目录中没有日志文件: {:?}", log_dir))
+}
+
+/// 读取最新日志文件的最后 `max_lines` 行
+pub fn tail(log_dir: &Path, max_lines: usize) -> Result<String> {
+    let path = latest_log_file(log_dir)?;
+    let content = std::fs::read(&path).with_context(|| format!("无法读取日志文件: {:?}", path))?;
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    Ok(lines[start..].join("\n"))
+}
@@ -0,0 +1,165 @@
+use crate::reader_identity::{MatchStatus, ReaderIdentity};
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// 无法计算会员时长的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownReason {
+    /// 读者未能在读者清单中唯一匹配，详见 `reader_identity`
+    ReaderNotResolved,
+    /// 读者清单中该读者的办卡日期为空
+    MissingJoinDate,
+    /// 办卡日期晚于统计日期
+    JoinAfterAsOf,
+}
+
+/// 会员时长：从办卡日期到统计日期（`as_of`），按自然日计算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MembershipDuration {
+    Known {
+        join_date: NaiveDate,
+        as_of: NaiveDate,
+        total_days: i64,
+        /// `join_date` 加上 `years` 年 `months` 个月 `days` 天等于 `as_of`
+        years: u32,
+        months: u32,
+        days: u32,
+    },
+    Unknown {
+        as_of: NaiveDate,
+        reason: UnknownReason,
+    },
+}
+
+impl MembershipDuration {
+    pub fn new(join_date: NaiveDate, as_of: NaiveDate) -> Self {
+        if join_date > as_of {
+            return MembershipDuration::Unknown {
+                as_of,
+                reason: UnknownReason::JoinAfterAsOf,
+            };
+        }
+
+        // 不超过统计日期的最大整月数；月末办卡时按月末对齐（如 1月31日 + 1个月 = 2月28日）
+        let mut total_months =
+            (as_of.year() - join_date.year()) * 12 + as_of.month() as i32 - join_date.month() as i32;
+        let add_months = |months: i32| join_date + Months::new(months as u32);
+        if add_months(total_months) > as_of {
+            total_months -= 1;
+        }
+        let days = (as_of - add_months(total_months)).num_days();
+
+        MembershipDuration::Known {
+            join_date,
+            as_of,
+            total_days: (as_of - join_date).num_days(),
+            years: (total_months / 12) as u32,
+            months: (total_months % 12) as u32,
+            days: days as u32,
+        }
+    }
+
+    /// 按读者匹配结果计算：只有唯一匹配且办卡日期不为空时才能计算
+    pub fn for_reader(identity: &ReaderIdentity, as_of: NaiveDate) -> Self {
+        match (identity.status, identity.registration_date) {
+            (MatchStatus::Matched, Some(join_date)) => Self::new(join_date.date(), as_of),
+            (MatchStatus::Matched, None) => MembershipDuration::Unknown {
+                as_of,
+                reason: UnknownReason::MissingJoinDate,
+            },
+            _ => MembershipDuration::Unknown {
+                as_of,
+                reason: UnknownReason::ReaderNotResolved,
+            },
+        }
+    }
+
+    /// 报告中使用的文字描述，如 `1年2个月3天（共 428 天）`
+    pub fn describe(&self) -> String {
+        match self {
+            MembershipDuration::Known {
+                total_days,
+                years,
+                months,
+                days,
+                ..
+            } => format!("{}年{}个月{}天（共 {} 天）", years, months, days, total_days),
+            MembershipDuration::Unknown { .. } => "未知".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader_identity::MatchMethod;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn parts(duration: MembershipDuration) -> (i64, u32, u32, u32) {
+        match duration {
+            MembershipDuration::Known {
+                total_days,
+                years,
+                months,
+                days,
+                ..
+            } => (total_days, years, months, days),
+            MembershipDuration::Unknown { reason, .. } => panic!("会员时长未知: {:?}", reason),
+        }
+    }
+
+    #[test]
+    fn counts_whole_years_months_and_days() {
+        assert_eq!(parts(MembershipDuration::new(date(2024, 3, 15), date(2025, 5, 18))), (429, 1, 2, 3));
+        assert_eq!(parts(MembershipDuration::new(date(2025, 1, 1), date(2025, 1, 1))), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn aligns_month_end_join_dates() {
+        // 1月31日 + 1个月 = 2月28日
+        assert_eq!(parts(MembershipDuration::new(date(2025, 1, 31), date(2025, 2, 28))), (28, 0, 1, 0));
+        assert_eq!(parts(MembershipDuration::new(date(2025, 1, 31), date(2025, 2, 27))), (27, 0, 0, 27));
+        assert_eq!(parts(MembershipDuration::new(date(2025, 1, 31), date(2025, 3, 30))), (58, 0, 1, 30));
+    }
+
+    #[test]
+    fn handles_leap_years() {
+        assert_eq!(parts(MembershipDuration::new(date(2024, 2, 29), date(2025, 2, 28))), (365, 1, 0, 0));
+        assert_eq!(parts(MembershipDuration::new(date(2024, 2, 29), date(2028, 2, 29))), (1461, 4, 0, 0));
+        assert_eq!(parts(MembershipDuration::new(date(2024, 2, 28), date(2024, 3, 1))), (2, 0, 0, 2));
+    }
+
+    #[test]
+    fn unknown_when_join_date_is_unusable() {
+        let as_of = date(2025, 1, 1);
+        assert_eq!(
+            MembershipDuration::new(date(2025, 1, 2), as_of),
+            MembershipDuration::Unknown { as_of, reason: UnknownReason::JoinAfterAsOf }
+        );
+
+        let mut identity = ReaderIdentity {
+            name: Some("张三".to_string()),
+            card_number: None,
+            status: MatchStatus::Matched,
+            matched_by: Some(MatchMethod::Name),
+            member_row: Some(2),
+            registration_date: None,
+            candidates: Vec::new(),
+        };
+        assert_eq!(
+            MembershipDuration::for_reader(&identity, as_of),
+            MembershipDuration::Unknown { as_of, reason: UnknownReason::MissingJoinDate }
+        );
+        identity.status = MatchStatus::Ambiguous;
+        assert_eq!(
+            MembershipDuration::for_reader(&identity, as_of),
+            MembershipDuration::Unknown { as_of, reason: UnknownReason::ReaderNotResolved }
+        );
+        assert_eq!(MembershipDuration::for_reader(&identity, as_of).describe(), "未知");
+    }
+}
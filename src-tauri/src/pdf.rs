@@ -0,0 +1,1199 @@
+use crate::cff;
+use crate::charts;
+use crate::report::{self, ReportBlock, ReportDocument};
+use crate::theme::ReportTheme;
+use anyhow::{anyhow, Context, Result};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::path::Path;
+use tracing::{info, warn};
+use ttf_parser::{Face, GlyphId, Tag};
+
+/// 1毫米、1厘米对应的磅数（PDF的长度单位）
+const PT_PER_MM: f64 = 72.0 / 25.4;
+const PT_PER_CM: f64 = 72.0 / 2.54;
+/// 图表在报告中的宽度（与Word报告一致），正文宽度不足时缩小
+const CHART_WIDTH: f64 = 6.0 * 72.0;
+
+/// 字号（磅）
+const TITLE_SIZE: f64 = 18.0;
+const HEADING_SIZE: f64 = 13.0;
+const BODY_SIZE: f64 = 10.5;
+const TABLE_SIZE: f64 = 9.5;
+const HEADER_SIZE: f64 = 9.0;
+/// 行高与字号之比
+const LINE_SPACING: f64 = 1.4;
+/// 段落、标题和表格之后的间距
+const BLOCK_SPACING: f64 = 8.0;
+const HEADING_SPACING: f64 = 12.0;
+const CELL_PADDING: f64 = 4.0;
+
+type Rgb = (f64, f64, f64);
+
+const BLACK: Rgb = (0.0, 0.0, 0.0);
+const WHITE: Rgb = (1.0, 1.0, 1.0);
+const GRAY: Rgb = (0.45, 0.45, 0.45);
+const BORDER: Rgb = (0.6, 0.6, 0.6);
+
+/// 将报告写入PDF：嵌入中文字体（只包含用到的字形），统计信息绘制为表格，
+/// 纸张大小、页边距、配色、页眉页脚和标志取自报告主题
+pub fn write_pdf(document: &ReportDocument, theme: &ReportTheme, output_path: &Path) -> Result<()> {
+    let font_path = charts::load_font(theme)?;
+    let font = PdfFont::load(&font_path)?;
+
+    let mut layout = Layout::new(theme, font);
+    layout.lay_out(document)?;
+    layout.add_headers_and_footers(theme)?;
+
+    let bytes = layout.into_pdf(&document.title)?;
+    std::fs::write(output_path, bytes).with_context(|| format!("无法写入文件: {:?}", output_path))?;
+    info!("PDF报告字体: {:?}", font_path);
+    Ok(())
+}
+
+fn rgb((r, g, b): (u8, u8, u8)) -> Rgb {
+    (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0)
+}
+
+/// 页面上的绘制操作，坐标原点在页面左下角
+enum Op {
+    /// 文字，`y` 为基线位置
+    Text {
+        x: f64,
+        y: f64,
+        size: f64,
+        color: Rgb,
+        bold: bool,
+        text: String,
+    },
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        fill: Option<Rgb>,
+        stroke: Option<Rgb>,
+    },
+    Image {
+        index: usize,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+}
+
+/// 按页排版：从页面顶部向下依次放置内容，空间不足时换页
+struct Layout {
+    font: PdfFont,
+    images: Vec<PdfImage>,
+    accent: Rgb,
+    page_width: f64,
+    page_height: f64,
+    margin_top: f64,
+    margin_bottom: f64,
+    left: f64,
+    right: f64,
+    pages: Vec<Vec<Op>>,
+    /// 当前位置（距页面底部）
+    y: f64,
+}
+
+impl Layout {
+    fn new(theme: &ReportTheme, font: PdfFont) -> Self {
+        let (width, height) = theme.page_size.dimensions_mm();
+        let margins = theme.page_margins_mm;
+        let page_height = height * PT_PER_MM;
+        Self {
+            font,
+            images: Vec::new(),
+            accent: rgb(theme.accent_rgb()),
+            page_width: width * PT_PER_MM,
+            page_height,
+            margin_top: margins.top * PT_PER_MM,
+            margin_bottom: margins.bottom * PT_PER_MM,
+            left: margins.left * PT_PER_MM,
+            right: (width - margins.right) * PT_PER_MM,
+            pages: vec![Vec::new()],
+            y: page_height - margins.top * PT_PER_MM,
+        }
+    }
+
+    fn content_width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    fn content_top(&self) -> f64 {
+        self.page_height - self.margin_top
+    }
+
+    fn at_page_top(&self) -> bool {
+        self.y >= self.content_top()
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = self.content_top();
+    }
+
+    /// 当前页剩余空间不足 `height` 时换页（页面顶部时不再换页）
+    fn ensure(&mut self, height: f64) {
+        if self.y - height < self.margin_bottom && !self.at_page_top() {
+            self.new_page();
+        }
+    }
+
+    fn push(&mut self, op: Op) {
+        self.pages.last_mut().unwrap().push(op);
+    }
+
+    fn line_height(size: f64) -> f64 {
+        size * LINE_SPACING
+    }
+
+    /// 行框顶部为 `top` 时文字的基线位置（文字在行框中垂直居中）
+    fn baseline(&self, top: f64, size: f64) -> f64 {
+        let text_height = (self.font.ascender - self.font.descender) * size;
+        top - (Self::line_height(size) - text_height) / 2.0 - self.font.ascender * size
+    }
+
+    fn lay_out(&mut self, document: &ReportDocument) -> Result<()> {
+        self.heading(&document.title, TITLE_SIZE, Self::line_height(BODY_SIZE) * 3.0);
+        for (index, block) in document.blocks.iter().enumerate() {
+            match block {
+                ReportBlock::Heading(text) => {
+                    // 标题与后面的内容放在同一页
+                    let following = match document.blocks.get(index + 1) {
+                        Some(ReportBlock::Image { path, .. }) => self.image_size(path)?.1,
+                        _ => Self::line_height(TABLE_SIZE) * 2.0 + CELL_PADDING * 4.0,
+                    };
+                    self.heading(text, HEADING_SIZE, following);
+                }
+                ReportBlock::Paragraph(text) => self.paragraph(text),
+                ReportBlock::Table { headers, rows } => self.table(headers, rows),
+                ReportBlock::Image { path, .. } => self.image(path)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn heading(&mut self, text: &str, size: f64, following: f64) {
+        if !self.at_page_top() {
+            self.y -= HEADING_SPACING;
+        }
+        let lines = self.font.wrap(text, size, self.content_width());
+        let height = lines.len() as f64 * Self::line_height(size);
+        self.ensure(height + following.min(self.content_top() - self.margin_bottom - height));
+        for line in lines {
+            let baseline = self.baseline(self.y, size);
+            self.push(Op::Text {
+                x: self.left,
+                y: baseline,
+                size,
+                color: self.accent,
+                bold: true,
+                text: line,
+            });
+            self.y -= Self::line_height(size);
+        }
+        self.y -= BLOCK_SPACING / 2.0;
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        for line in self.font.wrap(text, BODY_SIZE, self.content_width()) {
+            self.ensure(Self::line_height(BODY_SIZE));
+            let baseline = self.baseline(self.y, BODY_SIZE);
+            self.push(Op::Text {
+                x: self.left,
+                y: baseline,
+                size: BODY_SIZE,
+                color: BLACK,
+                bold: false,
+                text: line,
+            });
+            self.y -= Self::line_height(BODY_SIZE);
+        }
+        self.y -= BLOCK_SPACING;
+    }
+
+    /// 表格：表头使用主题色，跨页时在新页面重复表头
+    fn table(&mut self, headers: &[String], rows: &[Vec<String>]) {
+        let weights = report::column_weights(headers, rows);
+        let total_weight: f64 = weights.iter().sum();
+        let widths: Vec<f64> = weights
+            .iter()
+            .map(|weight| self.content_width() * weight / total_weight)
+            .collect();
+
+        let header = self.wrap_row(headers, &widths);
+        let rows: Vec<Vec<Vec<String>>> = rows.iter().map(|row| self.wrap_row(row, &widths)).collect();
+
+        let first_row = rows.first().map(|row| Self::row_height(row)).unwrap_or(0.0);
+        self.ensure(Self::row_height(&header) + first_row);
+        self.row(&header, &widths, true);
+        for row in &rows {
+            if self.y - Self::row_height(row) < self.margin_bottom {
+                self.new_page();
+                self.row(&header, &widths, true);
+            }
+            self.row(row, &widths, false);
+        }
+        self.y -= BLOCK_SPACING;
+    }
+
+    fn wrap_row(&mut self, cells: &[String], widths: &[f64]) -> Vec<Vec<String>> {
+        widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| {
+                let text = cells.get(column).map(String::as_str).unwrap_or("");
+                self.font.wrap(text, TABLE_SIZE, width - CELL_PADDING * 2.0)
+            })
+            .collect()
+    }
+
+    fn row_height(cells: &[Vec<String>]) -> f64 {
+        let lines = cells.iter().map(Vec::len).max().unwrap_or(1).max(1);
+        lines as f64 * Self::line_height(TABLE_SIZE) + CELL_PADDING * 2.0
+    }
+
+    fn row(&mut self, cells: &[Vec<String>], widths: &[f64], header: bool) {
+        let height = Self::row_height(cells);
+        let mut x = self.left;
+        for (lines, width) in cells.iter().zip(widths) {
+            self.push(Op::Rect {
+                x,
+                y: self.y - height,
+                width: *width,
+                height,
+                fill: header.then_some(self.accent),
+                stroke: Some(BORDER),
+            });
+            let mut top = self.y - CELL_PADDING;
+            for line in lines {
+                let baseline = self.baseline(top, TABLE_SIZE);
+                self.push(Op::Text {
+                    x: x + CELL_PADDING,
+                    y: baseline,
+                    size: TABLE_SIZE,
+                    color: if header { WHITE } else { BLACK },
+                    bold: header,
+                    text: line.clone(),
+                });
+                top -= Self::line_height(TABLE_SIZE);
+            }
+            x += width;
+        }
+        self.y -= height;
+    }
+
+    /// 图表在页面上的尺寸：宽度与Word报告一致，超出正文区域时等比缩小
+    fn image_size(&mut self, path: &Path) -> Result<(f64, f64)> {
+        let index = self.load_image(path)?;
+        let image = &self.images[index];
+        let mut width = CHART_WIDTH.min(self.content_width());
+        let mut height = width * image.height as f64 / image.width as f64;
+        let max_height = self.content_top() - self.margin_bottom;
+        if height > max_height {
+            width *= max_height / height;
+            height = max_height;
+        }
+        Ok((width, height))
+    }
+
+    fn image(&mut self, path: &Path) -> Result<()> {
+        let index = self.load_image(path)?;
+        let (width, height) = self.image_size(path)?;
+        self.ensure(height);
+        self.push(Op::Image {
+            index,
+            x: self.left + (self.content_width() - width) / 2.0,
+            y: self.y - height,
+            width,
+            height,
+        });
+        self.y -= height + BLOCK_SPACING;
+        Ok(())
+    }
+
+    /// 读取图片（同一路径只读取一次），返回图片序号
+    fn load_image(&mut self, path: &Path) -> Result<usize> {
+        if let Some(index) = self.images.iter().position(|image| image.path == path) {
+            return Ok(index);
+        }
+        self.images.push(PdfImage::load(path)?);
+        Ok(self.images.len() - 1)
+    }
+
+    /// 在每页的上下页边距中绘制页眉（标志和文字）和页脚（文字和页码）
+    fn add_headers_and_footers(&mut self, theme: &ReportTheme) -> Result<()> {
+        let logo = match theme.logo.as_deref() {
+            Some(logo) if logo.is_file() => Some(self.load_image(logo)?),
+            Some(logo) => {
+                warn!("报告主题中的标志图片不存在: {:?}", logo);
+                None
+            }
+            None => None,
+        };
+        let header_text = theme.header_text.clone().filter(|text| !text.trim().is_empty());
+        let footer_text = theme.footer_text.clone().filter(|text| !text.trim().is_empty());
+
+        let header_center = self.page_height - self.margin_top / 2.0;
+        let footer_center = self.margin_bottom / 2.0;
+        let text_offset = (self.font.ascender + self.font.descender) * HEADER_SIZE / 2.0;
+        let total = self.pages.len();
+
+        for page in 0..total {
+            let mut ops = Vec::new();
+            let mut x = self.left;
+            if let Some(index) = logo {
+                let image = &self.images[index];
+                let height = (theme.logo_height_cm * PT_PER_CM).min(self.margin_top * 0.8);
+                let width = height * image.width as f64 / image.height as f64;
+                ops.push(Op::Image {
+                    index,
+                    x,
+                    y: header_center - height / 2.0,
+                    width,
+                    height,
+                });
+                x += width + 6.0;
+            }
+            if let Some(text) = &header_text {
+                ops.push(Op::Text {
+                    x,
+                    y: header_center - text_offset,
+                    size: HEADER_SIZE,
+                    color: GRAY,
+                    bold: false,
+                    text: text.clone(),
+                });
+            }
+
+            if let Some(text) = &footer_text {
+                let width = self.font.width(text, HEADER_SIZE);
+                ops.push(Op::Text {
+                    x: (self.page_width - width) / 2.0,
+                    y: footer_center - text_offset,
+                    size: HEADER_SIZE,
+                    color: GRAY,
+                    bold: false,
+                    text: text.clone(),
+                });
+            }
+            let page_number = format!("{} / {}", page + 1, total);
+            let width = self.font.width(&page_number, HEADER_SIZE);
+            ops.push(Op::Text {
+                x: self.right - width,
+                y: footer_center - text_offset,
+                size: HEADER_SIZE,
+                color: GRAY,
+                bold: false,
+                text: page_number,
+            });
+
+            self.pages[page].extend(ops);
+        }
+        Ok(())
+    }
+
+    /// 生成页面内容流，并写出完整的PDF文件
+    fn into_pdf(mut self, title: &str) -> Result<Vec<u8>> {
+        let contents: Vec<Vec<u8>> = std::mem::take(&mut self.pages)
+            .iter()
+            .map(|ops| self.content_stream(ops))
+            .collect();
+
+        let mut writer = PdfWriter::new();
+        let catalog_id = writer.reserve();
+        let pages_id = writer.reserve();
+        let font_id = self.font.write(&mut writer)?;
+
+        let mut image_ids = Vec::new();
+        for image in &self.images {
+            image_ids.push(image.write(&mut writer)?);
+        }
+        let images: String = image_ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| format!("/Im{} {} 0 R ", index, id))
+            .collect();
+        let resources = format!("<< /Font << /F1 {} 0 R >> /XObject << {}>> >>", font_id, images);
+
+        let mut page_ids = Vec::new();
+        for content in contents {
+            let content_id = writer.stream("", &content)?;
+            page_ids.push(writer.object(format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources {} /Contents {} 0 R >>",
+                pages_id, self.page_width, self.page_height, resources, content_id
+            )));
+        }
+
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+        writer.set(
+            pages_id,
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), page_ids.len()),
+        );
+        writer.set(catalog_id, format!("<< /Type /Catalog /Pages {} 0 R >>", pages_id));
+        let info_id = writer.object(format!(
+            "<< /Title {} /Producer (ReaderReport) /CreationDate (D:{}) >>",
+            text_string(title),
+            chrono::Local::now().format("%Y%m%d%H%M%S")
+        ));
+        Ok(writer.finish(catalog_id, info_id))
+    }
+
+    fn content_stream(&mut self, ops: &[Op]) -> Vec<u8> {
+        let color = |(r, g, b): Rgb| format!("{:.3} {:.3} {:.3}", r, g, b);
+        let mut content = String::new();
+        for op in ops {
+            match op {
+                Op::Text {
+                    x,
+                    y,
+                    size,
+                    color: fill,
+                    bold,
+                    text,
+                } => {
+                    // 字体没有粗体，粗体文字用描边加粗
+                    let render = if *bold {
+                        format!("{} RG {:.2} w 2 Tr", color(*fill), size * 0.03)
+                    } else {
+                        "0 Tr".to_string()
+                    };
+                    content.push_str(&format!(
+                        "BT /F1 {:.2} Tf {} rg {} {:.2} {:.2} Td <{}> Tj ET\n",
+                        size,
+                        color(*fill),
+                        render,
+                        x,
+                        y,
+                        self.font.encode(text)
+                    ));
+                }
+                Op::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill,
+                    stroke,
+                } => {
+                    let paint = match (fill, stroke) {
+                        (Some(_), Some(_)) => "B",
+                        (Some(_), None) => "f",
+                        (None, Some(_)) => "S",
+                        (None, None) => "n",
+                    };
+                    content.push_str("q 0.5 w ");
+                    if let Some(fill) = fill {
+                        content.push_str(&format!("{} rg ", color(*fill)));
+                    }
+                    if let Some(stroke) = stroke {
+                        content.push_str(&format!("{} RG ", color(*stroke)));
+                    }
+                    content.push_str(&format!(
+                        "{:.2} {:.2} {:.2} {:.2} re {} Q\n",
+                        x, y, width, height, paint
+                    ));
+                }
+                Op::Image {
+                    index,
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    content.push_str(&format!(
+                        "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{} Do Q\n",
+                        width, height, x, y, index
+                    ));
+                }
+            }
+        }
+        content.into_bytes()
+    }
+}
+
+/// PDF文本字符串（UTF-16BE），用于文档信息中的标题
+fn text_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        hex.push_str(&format!("{:04X}", unit));
+    }
+    hex.push('>');
+    hex
+}
+
+/// 嵌入的字体，只保留用到的字形（TrueType轮廓见 [`subset_glyf`]，CFF轮廓见 [`cff::subset_cff`]）
+struct PdfFont {
+    data: Vec<u8>,
+    index: u32,
+    name: String,
+    units_per_em: f64,
+    /// 以字号为单位的上升和下降高度（下降为负值）
+    ascender: f64,
+    descender: f64,
+    cap_height: f64,
+    bbox: [i16; 4],
+    cff: bool,
+    /// 字符到 (字形编号, 宽度) 的缓存，宽度以字号为单位
+    glyphs: HashMap<char, (u16, f64)>,
+    /// 用到的字形编号到字符的映射（用于复制文字时还原字符）
+    used: BTreeMap<u16, char>,
+}
+
+impl PdfFont {
+    fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("无法读取字体 {:?}", path))?;
+        // 字体集合（.ttc）使用第一个字体
+        let index = 0;
+        let face = Face::parse(&data, index).map_err(|e| anyhow!("无法解析字体 {:?}: {}", path, e))?;
+        let tables = face.tables();
+        let cff = tables.glyf.is_none();
+        if cff && tables.cff.is_none() {
+            return Err(anyhow!("不支持的字体格式（需要TrueType或CFF轮廓）: {:?}", path));
+        }
+
+        let units_per_em = face.units_per_em() as f64;
+        let name = face
+            .names()
+            .into_iter()
+            .find(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME && name.is_unicode())
+            .and_then(|name| name.to_string())
+            .map(|name| name.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect::<String>())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "ReportFont".to_string());
+        let bbox = face.global_bounding_box();
+
+        Ok(Self {
+            index,
+            name,
+            units_per_em,
+            ascender: face.ascender() as f64 / units_per_em,
+            descender: face.descender() as f64 / units_per_em,
+            cap_height: face.capital_height().unwrap_or(face.ascender()) as f64 / units_per_em,
+            bbox: [bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max],
+            cff,
+            glyphs: HashMap::new(),
+            used: BTreeMap::new(),
+            data,
+        })
+    }
+
+    fn face(&self) -> Face<'_> {
+        // 加载时已经解析成功
+        Face::parse(&self.data, self.index).unwrap()
+    }
+
+    /// 字符的字形编号和宽度，字体中没有的字符使用 0 号字形
+    fn glyph(&mut self, c: char) -> (u16, f64) {
+        if let Some(glyph) = self.glyphs.get(&c) {
+            return *glyph;
+        }
+        let face = self.face();
+        let id = face.glyph_index(c).unwrap_or(GlyphId(0));
+        let advance = face.glyph_hor_advance(id).unwrap_or(0) as f64 / self.units_per_em;
+        self.glyphs.insert(c, (id.0, advance));
+        (id.0, advance)
+    }
+
+    fn width(&mut self, text: &str, size: f64) -> f64 {
+        text.chars().map(|c| self.glyph(c).1).sum::<f64>() * size
+    }
+
+    /// 按宽度折行：中文可以在任意字符处断行，英文尽量在空格处断行
+    fn wrap(&mut self, text: &str, size: f64, max_width: f64) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line: Vec<char> = Vec::new();
+            let mut width = 0.0;
+            // 最后一个可以断行的位置（该位置之前的字符留在本行）
+            let mut last_break = None;
+            for c in paragraph.chars() {
+                let advance = self.glyph(c).1 * size;
+                if width + advance > max_width && !line.is_empty() {
+                    let split = match last_break {
+                        Some(split) if split > 0 && !c.is_whitespace() => split,
+                        _ => line.len(),
+                    };
+                    let rest = line.split_off(split);
+                    lines.push(line.iter().collect::<String>().trim_end().to_string());
+                    line = rest.into_iter().skip_while(|c| c.is_whitespace()).collect();
+                    width = line.iter().map(|c| self.glyph(*c).1 * size).sum();
+                    last_break = None;
+                    if c.is_whitespace() && line.is_empty() {
+                        continue;
+                    }
+                }
+                line.push(c);
+                width += advance;
+                if c.is_whitespace() || !c.is_ascii() {
+                    last_break = Some(line.len());
+                }
+            }
+            lines.push(line.into_iter().collect());
+        }
+        lines
+    }
+
+    /// 文字编码为PDF中的十六进制字形编号（CID），并记录用到的字形
+    fn encode(&mut self, text: &str) -> String {
+        let mut hex = String::new();
+        for c in text.chars() {
+            let (id, _) = self.glyph(c);
+            self.used.entry(id).or_insert(c);
+            hex.push_str(&format!("{:04X}", self.cid(id)));
+        }
+        hex
+    }
+
+    /// 字形编号对应的CID：TrueType字体与字形编号相同；CID-keyed的CFF字体取自字体的字符集
+    fn cid(&self, id: u16) -> u16 {
+        if !self.cff {
+            return id;
+        }
+        self.face()
+            .tables()
+            .cff
+            .and_then(|cff| cff.glyph_cid(GlyphId(id)))
+            .unwrap_or(id)
+    }
+
+    /// 写入字体相关的对象，返回 Type0 字体对象的编号
+    fn write(&self, writer: &mut PdfWriter) -> Result<u32> {
+        let program = self.font_program()?;
+        // 子集字体的名称需要加6个大写字母的前缀
+        let base_name = format!("RRSUBS+{}", self.name);
+        let (subtype, file_key, file_extra, cid_map) = if self.cff {
+            ("CIDFontType0", "FontFile3", "/Subtype /OpenType", "")
+        } else {
+            ("CIDFontType2", "FontFile2", "", "/CIDToGIDMap /Identity")
+        };
+        let file_id = writer.stream(file_extra, &program)?;
+
+        let scale = |value: i16| (value as f64 * 1000.0 / self.units_per_em).round();
+        let descriptor_id = writer.object(format!(
+            "<< /Type /FontDescriptor /FontName /{} /Flags 4 /FontBBox [{} {} {} {}] /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /{} {} 0 R >>",
+            base_name,
+            scale(self.bbox[0]),
+            scale(self.bbox[1]),
+            scale(self.bbox[2]),
+            scale(self.bbox[3]),
+            (self.ascender * 1000.0).round(),
+            (self.descender * 1000.0).round(),
+            (self.cap_height * 1000.0).round(),
+            file_key,
+            file_id
+        ));
+
+        let face = self.face();
+        let widths: String = self
+            .used
+            .keys()
+            .map(|id| {
+                let advance = face.glyph_hor_advance(GlyphId(*id)).unwrap_or(0) as f64;
+                format!("{} [{}] ", self.cid(*id), (advance * 1000.0 / self.units_per_em).round())
+            })
+            .collect();
+        let cid_font_id = writer.object(format!(
+            "<< /Type /Font /Subtype /{} /BaseFont /{} /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> /FontDescriptor {} 0 R /DW 1000 /W [{}] {} >>",
+            subtype, base_name, descriptor_id, widths, cid_map
+        ));
+
+        let to_unicode_id = writer.stream("", self.to_unicode().as_bytes())?;
+        Ok(writer.object(format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+            base_name, cid_font_id, to_unicode_id
+        )))
+    }
+
+    /// CID到Unicode的映射，使PDF中的文字可以复制和搜索
+    fn to_unicode(&self) -> String {
+        let mut cmap = String::from(concat!(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n",
+            "/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n",
+            "/CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n",
+            "1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n"
+        ));
+        let entries: Vec<(u16, char)> = self.used.iter().map(|(id, c)| (self.cid(*id), *c)).collect();
+        for chunk in entries.chunks(100) {
+            cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+            for (cid, c) in chunk {
+                let unicode: String = c.encode_utf16(&mut [0; 2]).iter().map(|unit| format!("{:04X}", unit)).collect();
+                cmap.push_str(&format!("<{:04X}> <{}>\n", cid, unicode));
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+        cmap
+    }
+
+    /// 嵌入的字体数据：从字体集合中取出单个字体，只保留用到的字形和PDF需要的表
+    fn font_program(&self) -> Result<Vec<u8>> {
+        let face = self.face();
+        let raw = face.raw_face();
+        let table = |record: &ttf_parser::TableRecord| -> Result<&[u8]> {
+            let start = record.offset as usize;
+            self.data
+                .get(start..start + record.length as usize)
+                .ok_or_else(|| anyhow!("字体数据不完整"))
+        };
+        let find = |tag: &[u8; 4]| -> Result<Option<&[u8]>> {
+            match raw.table_records.into_iter().find(|record| record.tag == Tag::from_bytes(tag)) {
+                Some(record) => Ok(Some(table(&record)?)),
+                None => Ok(None),
+            }
+        };
+
+        if self.cff {
+            // PDF中的OpenType字体按CID取字形，GSUB、GPOS等排版表用不到
+            const KEEP: [&[u8; 4]; 8] = [b"head", b"hhea", b"hmtx", b"maxp", b"OS/2", b"name", b"post", b"cmap"];
+            let cff = find(b"CFF ")?.ok_or_else(|| anyhow!("字体缺少 CFF 表"))?;
+            let cff = cff::subset_cff(cff, self.used.keys().copied()).unwrap_or_else(|e| {
+                warn!("CFF字体子集化失败，嵌入完整的字形数据: {:#}", e);
+                cff.to_vec()
+            });
+            let mut tables = vec![(Tag::from_bytes(b"CFF "), cff)];
+            for tag in KEEP {
+                if let Some(data) = find(tag)? {
+                    tables.push((Tag::from_bytes(tag), data.to_vec()));
+                }
+            }
+            return Ok(build_sfnt(b"OTTO", tables));
+        }
+
+        // PDF中的TrueType字体只需要以下表，cmap等表由 CIDToGIDMap 和 ToUnicode 代替
+        const KEEP: [&[u8; 4]; 8] = [b"head", b"hhea", b"hmtx", b"maxp", b"cvt ", b"fpgm", b"prep", b"OS/2"];
+        let head = find(b"head")?.ok_or_else(|| anyhow!("字体缺少 head 表"))?;
+        let glyf = find(b"glyf")?.ok_or_else(|| anyhow!("字体缺少 glyf 表"))?;
+        let loca = find(b"loca")?.ok_or_else(|| anyhow!("字体缺少 loca 表"))?;
+        let (glyf, loca) = subset_glyf(
+            glyf,
+            loca,
+            read_u16(head, 50)? != 0,
+            face.number_of_glyphs(),
+            self.used.keys().copied(),
+        )?;
+
+        let mut tables = vec![
+            (Tag::from_bytes(b"glyf"), glyf),
+            (Tag::from_bytes(b"loca"), loca),
+        ];
+        for tag in KEEP {
+            let Some(data) = find(tag)? else {
+                continue;
+            };
+            let mut data = data.to_vec();
+            if tag == b"head" {
+                // 新的 loca 表使用长格式，校验和调整值置零
+                data[8..12].copy_from_slice(&[0; 4]);
+                data[50..52].copy_from_slice(&1u16.to_be_bytes());
+            }
+            tables.push((Tag::from_bytes(tag), data));
+        }
+        Ok(build_sfnt(&[0, 1, 0, 0], tables))
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| anyhow!("字体数据不完整"))
+}
+
+/// 只保留用到的字形（及复合字形引用的字形）的 glyf 表，字形编号保持不变，返回新的 glyf 和长格式的 loca 表
+fn subset_glyf(
+    glyf: &[u8],
+    loca: &[u8],
+    long_loca: bool,
+    number_of_glyphs: u16,
+    used: impl Iterator<Item = u16>,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let offset = |id: u16| -> Result<usize> {
+        if long_loca {
+            let index = id as usize * 4;
+            loca.get(index..index + 4)
+                .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+                .ok_or_else(|| anyhow!("字体数据不完整"))
+        } else {
+            Ok(read_u16(loca, id as usize * 2)? as usize * 2)
+        }
+    };
+    let glyph = |id: u16| -> Result<&[u8]> {
+        let (start, end) = (offset(id)?, offset(id + 1)?);
+        glyf.get(start..end.max(start)).ok_or_else(|| anyhow!("字体数据不完整"))
+    };
+
+    // 0 号字形（缺失字形）始终保留；复合字形引用的字形也需要保留
+    let mut keep = BTreeSet::from([0u16]);
+    let mut pending: Vec<u16> = used.filter(|id| *id < number_of_glyphs).collect();
+    while let Some(id) = pending.pop() {
+        if !keep.insert(id) && id != 0 {
+            continue;
+        }
+        let data = glyph(id)?;
+        if data.len() < 10 || i16::from_be_bytes([data[0], data[1]]) >= 0 {
+            continue;
+        }
+        // 复合字形：依次读取各组成部分
+        let mut position = 10;
+        loop {
+            let flags = read_u16(data, position)?;
+            let component = read_u16(data, position + 2)?;
+            if component < number_of_glyphs && !keep.contains(&component) {
+                pending.push(component);
+            }
+            position += 4 + if flags & 0x0001 != 0 { 4 } else { 2 };
+            position += match flags {
+                flags if flags & 0x0008 != 0 => 2,
+                flags if flags & 0x0040 != 0 => 4,
+                flags if flags & 0x0080 != 0 => 8,
+                _ => 0,
+            };
+            if flags & 0x0020 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity((number_of_glyphs as usize + 1) * 4);
+    for id in 0..number_of_glyphs {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if keep.contains(&id) {
+            new_glyf.extend_from_slice(glyph(id)?);
+            // 每个字形按4字节对齐
+            while new_glyf.len() % 4 != 0 {
+                new_glyf.push(0);
+            }
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+    Ok((new_glyf, new_loca))
+}
+
+/// 由各个表组装字体文件（表目录按标签排序）
+fn build_sfnt(version: &[u8; 4], mut tables: Vec<(Tag, Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| tag.as_u32());
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.max(1).leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut font = Vec::new();
+    font.extend_from_slice(version);
+    for value in [count, search_range, entry_selector, count * 16 - search_range] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, data) in &tables {
+        font.extend_from_slice(&tag.as_u32().to_be_bytes());
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        font.extend_from_slice(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut bytes = [0u8; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(bytes))
+    })
+}
+
+/// 嵌入的图片（RGB），有透明度时另外保存为软蒙版
+struct PdfImage {
+    path: std::path::PathBuf,
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+    alpha: Option<Vec<u8>>,
+}
+
+impl PdfImage {
+    fn load(path: &Path) -> Result<Self> {
+        let image = image::open(path)
+            .with_context(|| format!("无法读取图片: {:?}", path))?
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return Err(anyhow!("图片尺寸无效: {:?}", path));
+        }
+        let pixels = image.into_raw();
+        let rgb = pixels.chunks(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+        let alpha: Vec<u8> = pixels.chunks(4).map(|pixel| pixel[3]).collect();
+        Ok(Self {
+            path: path.to_path_buf(),
+            width,
+            height,
+            rgb,
+            alpha: alpha.iter().any(|value| *value < 255).then_some(alpha),
+        })
+    }
+
+    fn write(&self, writer: &mut PdfWriter) -> Result<u32> {
+        let mask = match &self.alpha {
+            Some(alpha) => format!(
+                "/SMask {} 0 R",
+                writer.stream(
+                    &format!(
+                        "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8",
+                        self.width, self.height
+                    ),
+                    alpha
+                )?
+            ),
+            None => String::new(),
+        };
+        writer.stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 {}",
+                self.width, self.height, mask
+            ),
+            &self.rgb,
+        )
+    }
+}
+
+/// PDF对象的写入：对象按编号记录偏移量，最后写出交叉引用表
+struct PdfWriter {
+    buffer: Vec<u8>,
+    /// 各对象（编号从1开始）在文件中的偏移量
+    offsets: Vec<Option<usize>>,
+}
+
+impl PdfWriter {
+    fn new() -> Self {
+        Self {
+            // 第二行的非ASCII字符表明文件包含二进制数据
+            buffer: b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec(),
+            offsets: Vec::new(),
+        }
+    }
+
+    /// 预留对象编号，之后用 `set` 写入
+    fn reserve(&mut self) -> u32 {
+        self.offsets.push(None);
+        self.offsets.len() as u32
+    }
+
+    fn set(&mut self, id: u32, body: String) {
+        self.set_bytes(id, body.as_bytes());
+    }
+
+    fn set_bytes(&mut self, id: u32, body: &[u8]) {
+        self.offsets[id as usize - 1] = Some(self.buffer.len());
+        self.buffer.extend_from_slice(format!("{} 0 obj\n", id).as_bytes());
+        self.buffer.extend_from_slice(body);
+        self.buffer.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn object(&mut self, body: String) -> u32 {
+        let id = self.reserve();
+        self.set(id, body);
+        id
+    }
+
+    /// 压缩后写入流对象，`entries` 为字典中除长度和压缩方式以外的条目
+    fn stream(&mut self, entries: &str, data: &[u8]) -> Result<u32> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+
+        let mut body = format!(
+            "<< {} /Length {} /Filter /FlateDecode >>\nstream\n",
+            entries,
+            compressed.len()
+        )
+        .into_bytes();
+        body.extend_from_slice(&compressed);
+        body.extend_from_slice(b"\nendstream");
+
+        let id = self.reserve();
+        self.set_bytes(id, &body);
+        Ok(id)
+    }
+
+    fn finish(mut self, root: u32, info: u32) -> Vec<u8> {
+        let xref_offset = self.buffer.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            xref.push_str(&format!("{:010} 00000 n \n", offset.unwrap_or(0)));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            root,
+            info,
+            xref_offset
+        ));
+        self.buffer.extend_from_slice(xref.as_bytes());
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 检查交叉引用表中的每个偏移量都指向对应的对象，`startxref` 指向交叉引用表
+    fn check_structure(bytes: &[u8]) {
+        assert!(bytes.starts_with(b"%PDF-1.7\n"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+        let text = String::from_utf8_lossy(bytes);
+
+        let tail = &text[text.rfind("startxref\n").expect("缺少 startxref")..];
+        let xref_offset: usize = tail.lines().nth(1).unwrap().parse().unwrap();
+        assert!(bytes[xref_offset..].starts_with(b"xref\n"));
+
+        let mut lines = bytes[xref_offset..].split(|&b| b == b'\n').skip(1);
+        let header = String::from_utf8_lossy(lines.next().unwrap()).to_string();
+        let count: usize = header.split(' ').nth(1).unwrap().parse().unwrap();
+        assert_eq!(lines.next().unwrap(), b"0000000000 65535 f ");
+        for id in 1..count {
+            let entry = String::from_utf8_lossy(lines.next().unwrap()).to_string();
+            assert!(entry.ends_with(" 00000 n "), "{}", entry);
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(
+                bytes[offset..].starts_with(format!("{} 0 obj\n", id).as_bytes()),
+                "对象 {} 的偏移量 {} 不正确",
+                id,
+                offset
+            );
+        }
+
+        let trailer = String::from_utf8_lossy(lines.next().unwrap()).to_string();
+        assert_eq!(trailer, "trailer");
+        let dictionary = String::from_utf8_lossy(lines.next().unwrap()).to_string();
+        assert!(dictionary.contains(&format!("/Size {} ", count)), "{}", dictionary);
+    }
+
+    #[test]
+    fn writer_produces_valid_xref_and_trailer() {
+        let mut writer = PdfWriter::new();
+        let catalog = writer.reserve();
+        let info = writer.object("<< /Title (test) >>".to_string());
+        let content = writer.stream("", b"BT ET").unwrap();
+        writer.set(catalog, format!("<< /Type /Catalog /Content {} 0 R >>", content));
+
+        let bytes = writer.finish(catalog, info);
+        check_structure(&bytes);
+        assert!(String::from_utf8_lossy(&bytes).contains(&format!("/Root {} 0 R /Info {} 0 R", catalog, info)));
+    }
+
+    /// `tests/fixtures/generate_fonts.py` 生成的测试字体，字形 0~5 为 .notdef、A、B、中、文、C
+    fn fixture_font(name: &str) -> PdfFont {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fonts").join(name);
+        PdfFont::load(&path).unwrap()
+    }
+
+    struct OutlineSink;
+
+    impl ttf_parser::OutlineBuilder for OutlineSink {
+        fn move_to(&mut self, _: f32, _: f32) {}
+        fn line_to(&mut self, _: f32, _: f32) {}
+        fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {}
+        fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+        fn close(&mut self) {}
+    }
+
+    /// 有轮廓的字形编号
+    fn outlined_glyphs(program: &[u8]) -> Vec<u16> {
+        let face = Face::parse(program, 0).expect("嵌入的字体无法解析");
+        (0..face.number_of_glyphs())
+            .filter(|id| face.outline_glyph(GlyphId(*id), &mut OutlineSink).is_some())
+            .collect()
+    }
+
+    /// 写入字体对象后的PDF文本
+    fn written_font(font: &PdfFont) -> String {
+        let mut writer = PdfWriter::new();
+        let catalog = writer.reserve();
+        let font_id = font.write(&mut writer).unwrap();
+        writer.set(catalog, format!("<< /Type /Catalog /Font {} 0 R >>", font_id));
+        let info = writer.object("<< >>".to_string());
+        let bytes = writer.finish(catalog, info);
+        check_structure(&bytes);
+        String::from_utf8_lossy(&bytes).to_string()
+    }
+
+    #[test]
+    fn truetype_font_keeps_used_and_component_glyphs() {
+        let mut font = fixture_font("test-glyf.ttf");
+        assert!(!font.cff);
+        // TrueType字体的CID就是字形编号
+        assert_eq!(font.encode("A中"), "00010003");
+        assert_eq!(font.cid(3), 3);
+
+        // 中 是引用 B 和 C 的复合字形，B、C 随之保留；文 没有用到
+        assert_eq!(outlined_glyphs(&font.font_program().unwrap()), [0, 1, 2, 3, 5]);
+        assert_eq!(outlined_glyphs(&font.data), [0, 1, 2, 3, 4, 5]);
+
+        assert!(font
+            .to_unicode()
+            .contains("2 beginbfchar\n<0001> <0041>\n<0003> <4E2D>\nendbfchar\n"));
+        let text = written_font(&font);
+        assert!(text.contains("/BaseFont /RRSUBS+RRTestGlyf"));
+        assert!(text.contains("/Subtype /CIDFontType2"));
+        assert!(text.contains("/CIDToGIDMap /Identity"));
+        assert!(text.contains("/W [1 [600] 3 [1000] ]"));
+    }
+
+    #[test]
+    fn cff_font_uses_charset_cids_and_keeps_used_glyphs() {
+        let mut font = fixture_font("test-cff.otf");
+        assert!(font.cff);
+        // 字形编号 1、3 在字符集中的CID为 10、20
+        assert_eq!(font.encode("A中"), "000A0014");
+
+        let program = font.font_program().unwrap();
+        assert_eq!(outlined_glyphs(&program), [0, 1, 3]);
+        assert!(program.len() < font.data.len());
+        let face = Face::parse(&program, 0).unwrap();
+        assert_eq!(face.tables().cff.unwrap().glyph_cid(GlyphId(3)), Some(20));
+
+        assert!(font.to_unicode().contains("<000A> <0041>\n<0014> <4E2D>\n"));
+        let text = written_font(&font);
+        assert!(text.contains("/BaseFont /RRSUBS+RRTestCFF"));
+        assert!(text.contains("/Subtype /CIDFontType0"));
+        assert!(text.contains("/FontFile3"));
+        assert!(text.contains("/W [10 [600] 20 [1000] ]"));
+    }
+
+    #[test]
+    #[ignore = "需要 fonts 目录中的中文字体，先运行 npm run fetch-fonts"]
+    fn writes_report_with_embedded_font() {
+        let document = ReportDocument {
+            title: "Reading Report 阅读报告".to_string(),
+            blocks: vec![
+                ReportBlock::Heading("Summary 概要".to_string()),
+                ReportBlock::Paragraph("Books borrowed: 12 借阅册数".to_string()),
+                ReportBlock::Table {
+                    headers: vec!["Title 书名".to_string(), "Count".to_string()],
+                    rows: vec![vec!["A | B".to_string(), "3".to_string()]],
+                },
+            ],
+        };
+        let path = std::env::temp_dir().join(format!("readerreport-pdf-{}.pdf", std::process::id()));
+        write_pdf(&document, &ReportTheme::default(), &path).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        check_structure(&bytes);
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("/Type /Catalog"));
+        assert!(text.contains("/Subtype /Type0"));
+        assert!(text.contains("/FontFile"));
+    }
+}
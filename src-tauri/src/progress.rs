@@ -0,0 +1,47 @@
+use crate::analyzer_backend::ProgressCallback;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+/// 进度事件名称，前端通过 `listen('analysis://progress', ...)` 订阅
+pub const PROGRESS_EVENT: &str = "analysis://progress";
+
+/// 任务所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStage {
+    LoadData,
+    Analyze,
+    GenerateCharts,
+    ExportReport,
+    ExportReports,
+    ExportData,
+}
+
+/// 进度事件，`current`/`total` 与分析器的进度回调一致
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEvent {
+    pub job_id: String,
+    pub stage: ProgressStage,
+    pub current: u32,
+    pub total: u32,
+    pub message: String,
+}
+
+/// 创建将分析器进度转发为 `analysis://progress` 事件的回调
+pub fn emitter(app: &AppHandle, job_id: &str, stage: ProgressStage) -> ProgressCallback {
+    let app = app.clone();
+    let job_id = job_id.to_string();
+    Box::new(move |total, current, message| {
+        let event = ProgressEvent {
+            job_id: job_id.clone(),
+            stage,
+            current,
+            total,
+            message,
+        };
+        if let Err(e) = app.emit(PROGRESS_EVENT, event) {
+            warn!("发送进度事件失败: {}", e);
+        }
+    })
+}
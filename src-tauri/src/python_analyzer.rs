@@ -1,31 +1,27 @@
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::Path;
+use std::sync::Arc;
 use anyhow::{Result, anyhow, Context};
+use crate::analyzer_backend::{
+    AnalysisResult, CancelFlag, OutputCallback, ProgressCallback, ReportGrouping,
+};
+use crate::charts::{self, ChartData};
+use crate::column_mapping::{
+    ColumnMapping, BORROW_COLUMNS, MEMBER_COLUMNS, OPTIONAL_BORROW_COLUMNS, OPTIONAL_MEMBER_COLUMNS,
+};
+use crate::data_export::AnalysisData;
+use crate::error::AnalyzerError;
+use crate::membership::MembershipDuration;
+use crate::reader_identity::{ReaderIdentity, ReaderIdentityResolver};
+use crate::report::{self, ReportData, ReportFormat};
+use crate::rust_analyzer::read_member_file;
+use crate::spreadsheet::{detect_format, read_headers, read_sheet_headers, resolve_entries, SheetSelection};
+use chrono::NaiveDate;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AnalysisResult {
-    pub borrow_frequency: u32,
-    pub total_duration: String,
-    pub total_reading_duration: i64,
-    pub total_price: f64,
-    pub monthly_borrow: HashMap<String, u32>, // v2更新：改为String键支持年-月格式
-    // v2新增字段
-    pub most_frequent_author: String,
-    pub most_frequent_category: String,
-    pub book_borrow_counts: HashMap<String, u32>,
-    pub borrow_peak_yearmonth: String,
-    // 保留原有字段
-    pub longest_author: String,
-    pub longest_category: String,
-    pub borrow_peak: u8,
-    pub most_borrowed_book: String,
-    pub most_borrowed_book_count: u32,
-    pub full_attendance: Vec<String>,
-    pub total_full_attendance: u32,
-    pub chart_paths: HashMap<String, String>,
-}
+use pyo3::types::{PyCFunction, PyDict, PyList, PyTuple};
+use tracing::info;
 
 pub struct PythonAnalyzer {
     initialized: bool,
@@ -118,7 +114,7 @@ impl PythonAnalyzer {
             // 获取Python脚本路径
             let python_path = self.get_python_path()?;
             
-            println!("添加Python路径: {}", python_path);
+            info!("添加Python路径: {}", python_path);
             
             // 检查路径是否已在sys.path中
             let paths: Vec<String> = path.extract()?;
@@ -126,17 +122,24 @@ impl PythonAnalyzer {
                 path.call_method1("insert", (0, python_path))?;
             }
 
+            // 将Python的logging、warnings和标准输出转发到应用日志
+            install_log_bridge(py)?;
+
             // 导入正式的分析器
             let analyzer_module = py.import_bound("analyzer")?;
             
             // 创建BorrowingAnalyzer实例
             let analyzer_class = analyzer_module.getattr("BorrowingAnalyzer")?;
             let analyzer_instance = analyzer_class.call0()?;
+            // 导出报告时的图表由Rust端绘制，与Rust后端一致
+            analyzer_instance.setattr("chart_renderer", chart_renderer(py)?)?;
+            // 批量导出时的报告由Rust端写入，与Rust后端一致
+            analyzer_instance.setattr("report_writer", report_writer(py)?)?;
             
             self.analyzer_instance = Some(analyzer_instance.unbind());
             self.initialized = true;
             
-            println!("Python分析器初始化成功");
+            info!("Python分析器初始化成功");
             Ok(())
         })
     }
@@ -145,44 +148,126 @@ impl PythonAnalyzer {
         &self,
         borrow_paths: Vec<String>,
         member_path: String,
-        _progress_callback: Option<Box<dyn Fn(u32, u32, String)>>,
+        column_mapping: &ColumnMapping,
+        sheet_selection: &SheetSelection,
+        progress_callback: Option<ProgressCallback>,
     ) -> Result<()> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
         // 验证文件路径
         if borrow_paths.is_empty() {
-            return Err(anyhow!("请至少选择一个借阅数据文件"));
+            return Err(AnalyzerError::input_missing("请至少选择一个借阅数据文件", None).into());
         }
 
         if member_path.is_empty() {
-            return Err(anyhow!("请选择读者清单文件"));
+            return Err(AnalyzerError::input_missing("请选择读者清单文件", None).into());
         }
 
         // 验证文件是否存在
         for path in &borrow_paths {
             if !std::path::Path::new(path).exists() {
-                return Err(anyhow!("借阅数据文件不存在: {}", path));
+                return Err(AnalyzerError::input_missing(
+                    format!("借阅数据文件不存在: {}", path),
+                    Some(path),
+                )
+                .into());
             }
         }
         
         if !std::path::Path::new(&member_path).exists() {
-            return Err(anyhow!("读者清单文件不存在: {}", member_path));
+            return Err(AnalyzerError::input_missing(
+                format!("读者清单文件不存在: {}", member_path),
+                Some(&member_path),
+            )
+            .into());
+        }
+
+        // 每个选中的工作表作为一个条目，附带按表头映射生成的列重命名表 {实际表头: 标准表头}
+        let mut entries = Vec::new();
+        for entry in resolve_entries(&borrow_paths, sheet_selection)? {
+            let headers = read_sheet_headers(&entry.path, entry.sheet.as_deref())?;
+            let renames = column_mapping
+                .renames(&headers, &BORROW_COLUMNS, &OPTIONAL_BORROW_COLUMNS)
+                .with_context(|| format!("文件 {} 表头不符合要求", entry.name))?;
+            entries.push((entry, renames));
         }
 
+        // 读者清单的列重命名表 {文件路径: {实际表头: 标准表头}}
+        let mut column_renames = HashMap::new();
+        let member_headers = read_headers(&member_path)?;
+        let member_renames = column_mapping
+            .renames(&member_headers, &MEMBER_COLUMNS, &OPTIONAL_MEMBER_COLUMNS)
+            .with_context(|| format!("文件 {} 表头不符合要求", member_path))?;
+        column_renames.insert(member_path.clone(), member_renames);
+
+        // 按文件内容识别格式和编码 {文件路径: pandas读取参数}
+        let mut read_options = HashMap::new();
+        for path in borrow_paths.iter().chain(std::iter::once(&member_path)) {
+            read_options.insert(path.clone(), detect_format(path)?.pandas_options());
+        }
+
+        // 读者匹配由Rust端完成，Python端通过 member_resolver(姓名, 读者证号) 获取JSON格式的匹配结果
+        let resolver = Arc::new(ReaderIdentityResolver::new(read_member_file(
+            &member_path,
+            column_mapping,
+        )?));
+
         Python::with_gil(|py| {
             let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
             
             // 转换borrow_paths为Python列表
             let py_borrow_paths = PyList::new_bound(py, &borrow_paths);
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item("column_renames", column_renames)?;
+            kwargs.set_item("read_options", read_options)?;
+            let py_entries = PyList::empty_bound(py);
+            for (entry, renames) in &entries {
+                let py_entry = PyDict::new_bound(py);
+                py_entry.set_item("path", &entry.path)?;
+                py_entry.set_item("sheet", &entry.sheet)?;
+                py_entry.set_item("name", &entry.name)?;
+                py_entry.set_item("renames", renames)?;
+                py_entries.append(py_entry)?;
+            }
+            kwargs.set_item("entries", py_entries)?;
+            let member_resolver = PyCFunction::new_closure_bound(
+                py,
+                Some(c"member_resolver"),
+                None,
+                move |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<String> {
+                    let (name, card_number): (Option<String>, Option<String>) = args.extract()?;
+                    let identity = resolver.resolve(name.as_deref(), card_number.as_deref());
+                    serde_json::to_string(&identity).map_err(|e| PyValueError::new_err(e.to_string()))
+                },
+            )?;
+            kwargs.set_item("member_resolver", member_resolver)?;
+            // 会员时长 membership_duration(读者匹配结果JSON, 统计日期) -> JSON，与Rust后端的计算方式一致
+            let membership_duration = PyCFunction::new_closure_bound(
+                py,
+                Some(c"membership_duration"),
+                None,
+                |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<String> {
+                    let (identity, as_of): (String, String) = args.extract()?;
+                    let identity: ReaderIdentity = serde_json::from_str(&identity)
+                        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                    let as_of: NaiveDate = as_of
+                        .parse()
+                        .map_err(|e: chrono::ParseError| PyValueError::new_err(e.to_string()))?;
+                    serde_json::to_string(&MembershipDuration::for_reader(&identity, as_of))
+                        .map_err(|e| PyValueError::new_err(e.to_string()))
+                },
+            )?;
+            kwargs.set_item("membership_duration", membership_duration)?;
+            set_progress_kwarg(&kwargs, progress_callback)?;
             
             // 调用load_excel_files方法
-            analyzer.call_method1("load_excel_files", (&py_borrow_paths, &member_path))?;
+            analyzer.call_method("load_excel_files", (&py_borrow_paths, &member_path), Some(&kwargs))?;
             
-            println!("数据加载完成");
-            println!("借阅数据文件: {:?}", borrow_paths);
-            println!("读者清单文件: {}", member_path);
+            info!("数据加载完成");
+            info!("借阅数据文件: {:?}", borrow_paths);
+            info!("读者清单文件: {}", member_path);
             
             Ok(())
         })
@@ -190,150 +275,80 @@ impl PythonAnalyzer {
 
     pub fn analyze(
         &self,
-        _progress_callback: Option<Box<dyn Fn(u32, u32, String)>>,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
     ) -> Result<AnalysisResult> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
         Python::with_gil(|py| {
             let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
             
             // 调用analyze_with_progress方法
-            let results = analyzer.call_method0("analyze_with_progress")?;
-            
-            // 提取结果
-            let borrow_frequency: u32 = results.getattr("borrow_frequency")?.extract()?;
-            let total_duration: String = results.getattr("total_duration")?.to_string();
-            let total_reading_duration: i64 = results.getattr("total_reading_duration")?.extract()?;
-            let total_price: f64 = results.getattr("total_price")?.extract()?;
-            // v2新增字段
-            let most_frequent_author: String = results.getattr("most_frequent_author")?.extract()?;
-            let most_frequent_category: String = results.getattr("most_frequent_category")?.extract()?;
-            let borrow_peak_yearmonth: String = results.getattr("borrow_peak_yearmonth")?.extract()?;
-            // 保留原有字段
-            let longest_author: String = results.getattr("longest_author")?.extract()?;
-            let longest_category: String = results.getattr("longest_category")?.extract()?;
-            let borrow_peak: u8 = results.getattr("borrow_peak")?.extract()?;
-            let most_borrowed_book: String = results.getattr("most_borrowed_book")?.extract()?;
-            let most_borrowed_book_count: u32 = results.getattr("most_borrowed_book_count")?.extract()?;
-            let total_full_attendance: u32 = results.getattr("total_full_attendance")?.extract()?;
-            
-            // 提取full_attendance列表
-            let full_attendance_py = results.getattr("full_attendance")?;
-            let full_attendance: Vec<String> = full_attendance_py.extract()?;
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item("as_of", as_of.to_string())?;
+            set_progress_kwarg(&kwargs, progress_callback)?;
+            let results = analyzer.call_method("analyze_with_progress", (), Some(&kwargs))?;
             
-            // v2更新：提取monthly_borrow字典（年-月格式）
-            let monthly_borrow_py = results.getattr("monthly_borrow")?;
-            let monthly_borrow_dict = monthly_borrow_py.downcast::<PyDict>()
-                .map_err(|e| anyhow!("无法将monthly_borrow转换为字典: {:?}", e))?;
-            let mut monthly_borrow = HashMap::new();
-            for (key, value) in monthly_borrow_dict.iter() {
-                let month: String = key.extract()?; // v2更新：改为String类型
-                let count: u32 = value.extract()?;
-                monthly_borrow.insert(month, count);
-            }
+            let result = extract_analysis_result(&results)?;
 
-            // v2新增：提取book_borrow_counts字典
-            let book_borrow_counts_py = results.getattr("book_borrow_counts")?;
-            let book_borrow_counts_dict = book_borrow_counts_py.downcast::<PyDict>()
-                .map_err(|e| anyhow!("无法将book_borrow_counts转换为字典: {:?}", e))?;
-            let mut book_borrow_counts = HashMap::new();
-            for (key, value) in book_borrow_counts_dict.iter() {
-                let book_name: String = key.extract()?;
-                let count: u32 = value.extract()?;
-                book_borrow_counts.insert(book_name, count);
-            }
-            
-            let result = AnalysisResult {
-                borrow_frequency,
-                total_duration,
-                total_reading_duration,
-                total_price,
-                monthly_borrow,
-                // v2新增字段
-                most_frequent_author,
-                most_frequent_category,
-                book_borrow_counts,
-                borrow_peak_yearmonth,
-                // 保留原有字段
-                longest_author,
-                longest_category,
-                borrow_peak,
-                most_borrowed_book,
-                most_borrowed_book_count,
-                full_attendance,
-                total_full_attendance,
-                chart_paths: HashMap::new(),
-            };
-
-            println!("数据分析完成");
+            info!("数据分析完成");
             Ok(result)
         })
     }
 
-    pub fn generate_charts(
-        &self,
-        output_dir: String,
-        _progress_callback: Option<Box<dyn Fn(u32, u32, String)>>,
-    ) -> Result<HashMap<String, String>> {
+    pub fn chart_data(&self) -> Result<ChartData> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
-        // 确保输出目录存在
-        std::fs::create_dir_all(&output_dir)?;
-
         Python::with_gil(|py| {
             let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
-            
-            // 调用generate_charts方法
-            let chart_paths_py = analyzer.call_method1("generate_charts", (output_dir.clone(),))?;
-            
-            // 转换Python字典为Rust HashMap
-            let chart_paths_dict = chart_paths_py.downcast::<PyDict>()
-                .map_err(|e| anyhow!("无法将chart_paths转换为字典: {:?}", e))?;
-            let mut chart_paths = HashMap::new();
-            
-            for (key, value) in chart_paths_dict.iter() {
-                let chart_type: String = key.extract()?;
-                let chart_path: String = value.extract()?;
-                chart_paths.insert(chart_type, chart_path);
-            }
-
-            println!("图表生成完成: {}", output_dir);
-            Ok(chart_paths)
+            let data = analyzer.call_method0("chart_data")?;
+            Ok(ChartData {
+                book_duration: data.get_item("book_duration")?.extract()?,
+                monthly_borrow: data.get_item("monthly_borrow")?.extract()?,
+                category_count: data.get_item("category_count")?.extract()?,
+            })
         })
     }
 
-    pub fn export_report(
-        &self,
-        output_path: String,
-        _progress_callback: Option<Box<dyn Fn(u32, u32, String)>>,
-    ) -> Result<()> {
+    pub fn report_data(&self) -> Result<ReportData> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
-        // 确保输出目录存在
-        if let Some(parent) = std::path::Path::new(&output_path).parent() {
-            std::fs::create_dir_all(parent)?;
+        Python::with_gil(|py| {
+            let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
+            let data = analyzer.call_method0("report_data")?;
+            let json: String = py
+                .import_bound("json")?
+                .call_method1("dumps", (data,))?
+                .extract()?;
+            serde_json::from_str(&json).context("无法解析报告数据")
+        })
+    }
+
+    pub fn analysis_data(&self) -> Result<AnalysisData> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
         Python::with_gil(|py| {
             let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
-            
-            // 调用export_report方法
-            analyzer.call_method1("export_report", (output_path.clone(),))?;
-            
-            println!("报告导出完成: {}", output_path);
-            Ok(())
+            let data = analyzer.call_method0("analysis_data")?;
+            let json: String = py
+                .import_bound("json")?
+                .call_method1("dumps", (data,))?
+                .extract()?;
+            serde_json::from_str(&json).context("无法解析分析结果")
         })
     }
 
     pub fn get_file_count(&self) -> Result<u32> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
         Python::with_gil(|py| {
@@ -345,7 +360,7 @@ impl PythonAnalyzer {
 
     pub fn get_file_name(&self, file_index: u32) -> Result<String> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
         Python::with_gil(|py| {
@@ -358,95 +373,89 @@ impl PythonAnalyzer {
     pub fn analyze_single_file(
         &self,
         file_index: u32,
-        _progress_callback: Option<Box<dyn Fn(u32, u32, String)>>,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
     ) -> Result<AnalysisResult> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
         Python::with_gil(|py| {
             let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
             
             // 调用analyze_single_file方法
-            let results = analyzer.call_method1("analyze_single_file", (file_index,))?;
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item("as_of", as_of.to_string())?;
+            set_progress_kwarg(&kwargs, progress_callback)?;
+            let results = analyzer.call_method("analyze_single_file", (file_index,), Some(&kwargs))?;
             
-            // 提取结果 (使用相同的提取逻辑)
-            let borrow_frequency: u32 = results.getattr("borrow_frequency")?.extract()?;
-            let total_duration: String = results.getattr("total_duration")?.to_string();
-            let total_reading_duration: i64 = results.getattr("total_reading_duration")?.extract()?;
-            let total_price: f64 = results.getattr("total_price")?.extract()?;
-            // v2新增字段
-            let most_frequent_author: String = results.getattr("most_frequent_author")?.extract()?;
-            let most_frequent_category: String = results.getattr("most_frequent_category")?.extract()?;
-            let borrow_peak_yearmonth: String = results.getattr("borrow_peak_yearmonth")?.extract()?;
-            // 保留原有字段
-            let longest_author: String = results.getattr("longest_author")?.extract()?;
-            let longest_category: String = results.getattr("longest_category")?.extract()?;
-            let borrow_peak: u8 = results.getattr("borrow_peak")?.extract()?;
-            let most_borrowed_book: String = results.getattr("most_borrowed_book")?.extract()?;
-            let most_borrowed_book_count: u32 = results.getattr("most_borrowed_book_count")?.extract()?;
-            let total_full_attendance: u32 = results.getattr("total_full_attendance")?.extract()?;
-            
-            // 提取full_attendance列表
-            let full_attendance_py = results.getattr("full_attendance")?;
-            let full_attendance: Vec<String> = full_attendance_py.extract()?;
-            
-            // v2更新：提取monthly_borrow字典（年-月格式）
-            let monthly_borrow_py = results.getattr("monthly_borrow")?;
-            let monthly_borrow_dict = monthly_borrow_py.downcast::<PyDict>()
-                .map_err(|e| anyhow!("无法将monthly_borrow转换为字典: {:?}", e))?;
-            let mut monthly_borrow = HashMap::new();
-            for (key, value) in monthly_borrow_dict.iter() {
-                let month: String = key.extract()?; // v2更新：改为String类型
-                let count: u32 = value.extract()?;
-                monthly_borrow.insert(month, count);
-            }
+            let result = extract_analysis_result(&results)?;
 
-            // v2新增：提取book_borrow_counts字典
-            let book_borrow_counts_py = results.getattr("book_borrow_counts")?;
-            let book_borrow_counts_dict = book_borrow_counts_py.downcast::<PyDict>()
-                .map_err(|e| anyhow!("无法将book_borrow_counts转换为字典: {:?}", e))?;
-            let mut book_borrow_counts = HashMap::new();
-            for (key, value) in book_borrow_counts_dict.iter() {
-                let book_name: String = key.extract()?;
-                let count: u32 = value.extract()?;
-                book_borrow_counts.insert(book_name, count);
-            }
-            
-            let result = AnalysisResult {
-                borrow_frequency,
-                total_duration,
-                total_reading_duration,
-                total_price,
-                monthly_borrow,
-                // v2新增字段
-                most_frequent_author,
-                most_frequent_category,
-                book_borrow_counts,
-                borrow_peak_yearmonth,
-                // 保留原有字段
-                longest_author,
-                longest_category,
-                borrow_peak,
-                most_borrowed_book,
-                most_borrowed_book_count,
-                full_attendance,
-                total_full_attendance,
-                chart_paths: HashMap::new(),
-            };
-
-            println!("单文件分析完成: 文件索引 {}", file_index);
+            info!("单文件分析完成: 文件索引 {}", file_index);
+            Ok(result)
+        })
+    }
+
+    pub fn get_reader_count(&self) -> Result<u32> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        Python::with_gil(|py| {
+            let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
+            let count: u32 = analyzer.call_method0("get_reader_count")?.extract()?;
+            Ok(count)
+        })
+    }
+
+    pub fn get_reader_name(&self, reader_index: u32) -> Result<String> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        Python::with_gil(|py| {
+            let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
+            let name: String = analyzer.call_method1("get_reader_name", (reader_index,))?.extract()?;
+            Ok(name)
+        })
+    }
+
+    pub fn analyze_reader(
+        &self,
+        reader_index: u32,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        Python::with_gil(|py| {
+            let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item("as_of", as_of.to_string())?;
+            set_progress_kwarg(&kwargs, progress_callback)?;
+            let results = analyzer.call_method("analyze_reader", (reader_index,), Some(&kwargs))?;
+            let result = extract_analysis_result(&results)?;
+
+            info!("读者分析完成: 读者索引 {}", reader_index);
             Ok(result)
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn export_reports_for_all_files(
         &self,
         base_output_dir: String,
-        _progress_callback: Option<Box<dyn Fn(u32, u32, String)>>,
+        grouping: ReportGrouping,
+        format: ReportFormat,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+        cancel: Option<CancelFlag>,
+        on_output: Option<OutputCallback>,
     ) -> Result<Vec<String>> {
         if !self.initialized {
-            return Err(anyhow!("Python分析器未初始化"));
+            return Err(AnalyzerError::NotInitialized.into());
         }
 
         // 确保基础输出目录存在
@@ -456,17 +465,259 @@ impl PythonAnalyzer {
             let analyzer = self.analyzer_instance.as_ref().unwrap().bind(py);
             
             // 调用export_reports_for_all_files方法
-            let report_paths_py = analyzer.call_method1("export_reports_for_all_files", (base_output_dir.clone(),))?;
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item("group_by", grouping.as_str())?;
+            kwargs.set_item("report_format", format.as_str())?;
+            kwargs.set_item("report_extension", format.extension())?;
+            kwargs.set_item("as_of", as_of.to_string())?;
+            set_progress_kwarg(&kwargs, progress_callback)?;
+            // Python端取消时返回已生成的报告，记录是否因取消而提前停止
+            let stopped = Arc::new(AtomicBool::new(false));
+            if let Some(cancel) = cancel {
+                let stopped = stopped.clone();
+                let should_cancel = PyCFunction::new_closure_bound(
+                    py,
+                    Some(c"should_cancel"),
+                    None,
+                    move |_args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| {
+                        let cancelled = cancel.load(Ordering::Relaxed);
+                        if cancelled {
+                            stopped.store(true, Ordering::Relaxed);
+                        }
+                        cancelled
+                    },
+                )?;
+                kwargs.set_item("should_cancel", should_cancel)?;
+            }
+            if let Some(on_output) = on_output {
+                let on_output = PyCFunction::new_closure_bound(
+                    py,
+                    Some(c"on_output"),
+                    None,
+                    move |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+                        let (path,): (String,) = args.extract()?;
+                        on_output(path);
+                        Ok(())
+                    },
+                )?;
+                kwargs.set_item("on_output", on_output)?;
+            }
+            let report_paths_py = analyzer.call_method(
+                "export_reports_for_all_files",
+                (base_output_dir.clone(),),
+                Some(&kwargs),
+            )?;
             
             // 转换Python列表为Rust Vec
             let report_paths: Vec<String> = report_paths_py.extract()?;
+            if stopped.load(Ordering::Relaxed) {
+                return Err(AnalyzerError::Cancelled.into());
+            }
             
-            println!("所有文件报告生成完成，共 {} 个文件", report_paths.len());
+            info!("所有报告生成完成，共 {} 个文件", report_paths.len());
             Ok(report_paths)
         })
     }
 }
 
+/// Python运行环境信息（诊断包使用）：版本、解释器路径、`sys.path` 和已安装的包版本
+pub fn python_environment() -> Result<serde_json::Value> {
+    Python::with_gil(|py| {
+        let sys = py.import_bound("sys")?;
+        let version: String = sys.getattr("version")?.extract()?;
+        let executable: String = sys.getattr("executable")?.extract()?;
+        let sys_path: Vec<String> = sys.getattr("path")?.extract()?;
+
+        let mut packages = std::collections::BTreeMap::new();
+        let metadata = py.import_bound("importlib.metadata")?;
+        for distribution in metadata.call_method0("distributions")?.iter()? {
+            let distribution = distribution?;
+            let name: Option<String> = distribution.getattr("metadata")?.get_item("Name")?.extract()?;
+            let version: String = distribution.getattr("version")?.extract()?;
+            if let Some(name) = name {
+                packages.insert(name, version);
+            }
+        }
+
+        Ok(serde_json::json!({
+            "version": version,
+            "executable": executable,
+            "sys_path": sys_path,
+            "packages": packages,
+        }))
+    })
+}
+
+/// 将进度回调包装为Python函数 `progress_callback(total, current, message)`，作为关键字参数传给Python
+fn set_progress_kwarg(
+    kwargs: &Bound<'_, PyDict>,
+    progress_callback: Option<ProgressCallback>,
+) -> PyResult<()> {
+    let Some(callback) = progress_callback else {
+        return Ok(());
+    };
+
+    let callback = PyCFunction::new_closure_bound(
+        kwargs.py(),
+        Some(c"progress_callback"),
+        None,
+        move |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+            let (total, current, message): (u32, u32, String) = args.extract()?;
+            callback(total, current, message);
+            Ok(())
+        },
+    )?;
+    kwargs.set_item("progress_callback", callback)
+}
+
+/// 安装 `log_bridge`：Python端通过 `log(levelno, name, message)` 写入应用日志，目标为 `python`
+fn install_log_bridge(py: Python<'_>) -> PyResult<()> {
+    let log = PyCFunction::new_closure_bound(
+        py,
+        Some(c"log"),
+        None,
+        |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+            let (level, name, message): (u32, String, String) = args.extract()?;
+            // 与Python logging的级别对应：ERROR=40, WARNING=30, INFO=20, DEBUG=10
+            match level {
+                40.. => tracing::error!(target: "python", logger = %name, "{}", message),
+                30..=39 => tracing::warn!(target: "python", logger = %name, "{}", message),
+                20..=29 => tracing::info!(target: "python", logger = %name, "{}", message),
+                _ => tracing::debug!(target: "python", logger = %name, "{}", message),
+            }
+            Ok(())
+        },
+    )?;
+    py.import_bound("log_bridge")?.call_method1("install", (log,))?;
+    Ok(())
+}
+
+/// 图表绘制函数 `chart_renderer(output_dir, chart_data_json) -> {图表类型: 路径}`
+fn chart_renderer(py: Python<'_>) -> PyResult<Bound<'_, PyCFunction>> {
+    PyCFunction::new_closure_bound(
+        py,
+        Some(c"chart_renderer"),
+        None,
+        |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<HashMap<String, String>> {
+            let (output_dir, data): (String, String) = args.extract()?;
+            let data: ChartData =
+                serde_json::from_str(&data).map_err(|e| PyValueError::new_err(e.to_string()))?;
+            charts::render_charts(&data, Path::new(&output_dir), None)
+                .map_err(|e| PyRuntimeError::new_err(format!("{:#}", e)))
+        },
+    )
+}
+
+/// 报告写入函数 `report_writer(output_path, report_data_json, report_format=None)`，使用当前报告主题；
+/// 未指定格式（`docx`/`pdf`/`html`/`markdown`）时根据扩展名判断
+fn report_writer(py: Python<'_>) -> PyResult<Bound<'_, PyCFunction>> {
+    PyCFunction::new_closure_bound(
+        py,
+        Some(c"report_writer"),
+        None,
+        |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+            let (output_path, data, format): (String, String, Option<String>) = args.extract()?;
+            let data: ReportData =
+                serde_json::from_str(&data).map_err(|e| PyValueError::new_err(e.to_string()))?;
+            let format = format
+                .map(|format| serde_json::from_value(serde_json::Value::String(format)))
+                .transpose()
+                .map_err(|e| PyValueError::new_err(format!("未知的报告格式: {}", e)))?;
+            let output_path = Path::new(&output_path);
+            let format = ReportFormat::resolve(format, output_path)
+                .map_err(|e| PyValueError::new_err(format!("{:#}", e)))?;
+            report::export_report(&data, output_path, format, None)
+                .map_err(|e| PyRuntimeError::new_err(format!("{:#}", e)))
+        },
+    )
+}
+
+/// 从Python分析结果对象中提取 `AnalysisResult`
+fn extract_analysis_result(results: &Bound<'_, PyAny>) -> Result<AnalysisResult> {
+    let borrow_frequency: u32 = results.getattr("borrow_frequency")?.extract()?;
+    // 会员时长由Rust端的 membership_duration 计算，原样返回JSON
+    let total_duration_json: String = results.getattr("total_duration")?.extract()?;
+    let total_duration: MembershipDuration = serde_json::from_str(&total_duration_json)
+        .context("无法解析total_duration")?;
+    let total_reading_duration: i64 = results.getattr("total_reading_duration")?.extract()?;
+    let total_price: f64 = results.getattr("total_price")?.extract()?;
+    // v2新增字段
+    let most_frequent_author: String = results.getattr("most_frequent_author")?.extract()?;
+    let most_frequent_category: String = results.getattr("most_frequent_category")?.extract()?;
+    let borrow_peak_yearmonth: String = results.getattr("borrow_peak_yearmonth")?.extract()?;
+    // 保留原有字段
+    let longest_author: String = results.getattr("longest_author")?.extract()?;
+    let longest_category: String = results.getattr("longest_category")?.extract()?;
+    let borrow_peak: u8 = results.getattr("borrow_peak")?.extract()?;
+    let most_borrowed_book: String = results.getattr("most_borrowed_book")?.extract()?;
+    let most_borrowed_book_count: u32 = results.getattr("most_borrowed_book_count")?.extract()?;
+    let total_full_attendance: u32 = results.getattr("total_full_attendance")?.extract()?;
+    
+    // 提取full_attendance列表
+    let full_attendance_py = results.getattr("full_attendance")?;
+    let full_attendance: Vec<String> = full_attendance_py.extract()?;
+
+    // 读者匹配结果由Rust端的 member_resolver 生成，原样返回JSON
+    let reader_identity_json: String = results.getattr("reader_identity")?.extract()?;
+    let reader_identity: ReaderIdentity = serde_json::from_str(&reader_identity_json)
+        .context("无法解析reader_identity")?;
+    let unresolved_readers_json: Vec<String> = results.getattr("unresolved_readers")?.extract()?;
+    let unresolved_readers = unresolved_readers_json
+        .iter()
+        .map(|json| serde_json::from_str(json))
+        .collect::<serde_json::Result<Vec<ReaderIdentity>>>()
+        .context("无法解析unresolved_readers")?;
+    
+    // v2更新：提取monthly_borrow字典（年-月格式）
+    let monthly_borrow_py = results.getattr("monthly_borrow")?;
+    let monthly_borrow_dict = monthly_borrow_py.downcast::<PyDict>()
+        .map_err(|e| anyhow!("无法将monthly_borrow转换为字典: {:?}", e))?;
+    let mut monthly_borrow = HashMap::new();
+    for (key, value) in monthly_borrow_dict.iter() {
+        let month: String = key.extract()?; // v2更新：改为String类型
+        let count: u32 = value.extract()?;
+        monthly_borrow.insert(month, count);
+    }
+
+    let monthly_timeline: Vec<(String, u32)> = results.getattr("monthly_timeline")?.extract()?;
+
+    // v2新增：提取book_borrow_counts字典
+    let book_borrow_counts_py = results.getattr("book_borrow_counts")?;
+    let book_borrow_counts_dict = book_borrow_counts_py.downcast::<PyDict>()
+        .map_err(|e| anyhow!("无法将book_borrow_counts转换为字典: {:?}", e))?;
+    let mut book_borrow_counts = HashMap::new();
+    for (key, value) in book_borrow_counts_dict.iter() {
+        let book_name: String = key.extract()?;
+        let count: u32 = value.extract()?;
+        book_borrow_counts.insert(book_name, count);
+    }
+    
+    Ok(AnalysisResult {
+        borrow_frequency,
+        total_duration,
+        total_reading_duration,
+        total_price,
+        monthly_borrow,
+        monthly_timeline,
+        // v2新增字段
+        most_frequent_author,
+        most_frequent_category,
+        book_borrow_counts,
+        borrow_peak_yearmonth,
+        // 保留原有字段
+        longest_author,
+        longest_category,
+        borrow_peak,
+        most_borrowed_book,
+        most_borrowed_book_count,
+        full_attendance,
+        total_full_attendance,
+        reader_identity,
+        unresolved_readers,
+        chart_paths: HashMap::new(),
+    })
+}
+
 impl Default for PythonAnalyzer {
     fn default() -> Self {
         Self::new().unwrap()
@@ -0,0 +1,314 @@
+use crate::column_mapping::to_half_width;
+use crate::rust_analyzer::{group_by_reader, BorrowRecord, MemberRecord};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// 读者在读者清单中的匹配状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchStatus {
+    /// 找到唯一对应的读者
+    Matched,
+    /// 有多名读者符合条件，无法确定是哪一位
+    Ambiguous,
+    /// 读者清单中没有该读者
+    Unmatched,
+}
+
+/// 匹配依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMethod {
+    CardNumber,
+    Name,
+}
+
+/// 读者清单中的候选读者，行号为Excel中的行号（表头为第1行）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemberCandidate {
+    pub row: u32,
+    pub name: Option<String>,
+    pub card_number: Option<String>,
+}
+
+/// 借阅数据中的读者与读者清单的匹配结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReaderIdentity {
+    /// 借阅数据中的姓名和读者证号（已去除首尾空白）
+    pub name: Option<String>,
+    pub card_number: Option<String>,
+    pub status: MatchStatus,
+    pub matched_by: Option<MatchMethod>,
+    /// 匹配到的读者清单行号
+    pub member_row: Option<u32>,
+    pub registration_date: Option<NaiveDateTime>,
+    /// 状态为 `Ambiguous` 时符合条件的所有读者
+    pub candidates: Vec<MemberCandidate>,
+}
+
+/// 在读者清单中查找借阅数据中的读者。
+///
+/// 借阅数据有读者证号且读者清单中有相同证号时按证号匹配；否则按规范化后的姓名匹配
+/// （去除空白、全角转半角、繁体转简体），此时读者清单中证号不同的读者不参与匹配。
+pub struct ReaderIdentityResolver {
+    members: Vec<MemberRecord>,
+    by_card: HashMap<String, Vec<usize>>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl ReaderIdentityResolver {
+    pub fn new(members: Vec<MemberRecord>) -> Self {
+        let mut by_card: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, member) in members.iter().enumerate() {
+            if let Some(card) = member.card_number.as_deref().map(normalize_card_number) {
+                if !card.is_empty() {
+                    by_card.entry(card).or_default().push(index);
+                }
+            }
+            if let Some(name) = member.name.as_deref().map(normalize_name) {
+                if !name.is_empty() {
+                    by_name.entry(name).or_default().push(index);
+                }
+            }
+        }
+
+        Self {
+            members,
+            by_card,
+            by_name,
+        }
+    }
+
+    pub fn resolve(&self, name: Option<&str>, card_number: Option<&str>) -> ReaderIdentity {
+        let name = name.map(str::trim).filter(|s| !s.is_empty());
+        let card_number = card_number.map(str::trim).filter(|s| !s.is_empty());
+        let card_key = card_number.map(normalize_card_number);
+
+        if let Some(rows) = card_key.as_ref().and_then(|card| self.by_card.get(card)) {
+            return self.identity(name, card_number, rows.clone(), MatchMethod::CardNumber);
+        }
+
+        let rows = name
+            .map(normalize_name)
+            .and_then(|name| self.by_name.get(&name))
+            .map(|rows| {
+                rows.iter()
+                    .copied()
+                    // 证号不同的读者不是同一人
+                    .filter(|&index| card_key.is_none() || self.members[index].card_number.is_none())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.identity(name, card_number, rows, MatchMethod::Name)
+    }
+
+    /// 按一条借阅记录的姓名和读者证号查找
+    pub fn resolve_record(&self, record: &BorrowRecord) -> ReaderIdentity {
+        self.resolve(record.name.as_deref(), record.card_number.as_deref())
+    }
+
+    /// 借阅数据中未能唯一匹配的读者（按读者分组，每组取第一条记录）
+    pub fn unresolved_readers(&self, data: &[BorrowRecord]) -> Vec<ReaderIdentity> {
+        group_by_reader(data)
+            .iter()
+            .map(|reader| self.resolve_record(&reader.records[0]))
+            .filter(|identity| identity.status != MatchStatus::Matched)
+            .collect()
+    }
+
+    fn identity(
+        &self,
+        name: Option<&str>,
+        card_number: Option<&str>,
+        rows: Vec<usize>,
+        method: MatchMethod,
+    ) -> ReaderIdentity {
+        let mut identity = ReaderIdentity {
+            name: name.map(str::to_string),
+            card_number: card_number.map(str::to_string),
+            status: MatchStatus::Unmatched,
+            matched_by: None,
+            member_row: None,
+            registration_date: None,
+            candidates: Vec::new(),
+        };
+
+        match rows.as_slice() {
+            [] => {}
+            [index] => {
+                let member = &self.members[*index];
+                identity.status = MatchStatus::Matched;
+                identity.matched_by = Some(method);
+                identity.member_row = Some(member.row);
+                identity.registration_date = member.registration_date;
+            }
+            _ => {
+                identity.status = MatchStatus::Ambiguous;
+                identity.candidates = rows
+                    .iter()
+                    .map(|&index| {
+                        let member = &self.members[index];
+                        MemberCandidate {
+                            row: member.row,
+                            name: member.name.clone(),
+                            card_number: member.card_number.clone(),
+                        }
+                    })
+                    .collect();
+            }
+        }
+        identity
+    }
+}
+
+/// 姓名规范化：去除所有空白，全角转半角，繁体转简体，英文转小写
+pub fn normalize_name(name: &str) -> String {
+    let table = traditional_to_simplified();
+    name.chars()
+        .map(to_half_width)
+        .filter(|c| !c.is_whitespace())
+        .map(|c| table.get(&c).copied().unwrap_or(c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 读者证号规范化：去除所有空白，全角转半角，英文转大写（保留前导零）
+pub fn normalize_card_number(card_number: &str) -> String {
+    card_number
+        .chars()
+        .map(to_half_width)
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+fn traditional_to_simplified() -> &'static HashMap<char, char> {
+    static TABLE: OnceLock<HashMap<char, char>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        TRADITIONAL
+            .concat()
+            .chars()
+            .zip(SIMPLIFIED.concat().chars())
+            .collect()
+    })
+}
+
+/// 姓名中常见的繁体字，与 `SIMPLIFIED` 逐字对应
+const TRADITIONAL: &[&str] = &[
+    "倫偉傑傳僑價儀億優儲兒內兩冊則剛劃劉劍勁動務勝勞勢勳勵匯區協卻厲",
+    "參吳呂員問啟喬單嚴國圍園圓圖團堅報場塊壯壽夢奪奮婦婭媽嫻嬌孫學實",
+    "寧審寫寶將對導屬島嶄嶺嶽巖帥師幫廠廣廳張強彈彌彎彥徑從復徵悅愛態",
+    "慶憑憲憶應懷戀戰戶揚擁擇據擴攝敵數斂斷時暉暢曄曆曉曠書會朧東條棟",
+    "楊楓業榮構樂樓標樹橋機檢櫻權歐歡歲歷殘氣決沖況涼淚淵淺湯溫滄滿漁",
+    "漢漸潔潛澤濃濟濤濱瀅瀏瀟瀾灑灣災為烏無煒熱燁燈營燦燾爍爭爾牆狀猶",
+    "獎獨獻現琺瑋瑤瑩瑪璉璣環璽瓊產畢畫異當療發皚盡監盤盧眾碩確碼礦禎",
+    "禕禪禮種稱積穎穩窮竇競筆節範築簡籃糧紀約紅紋純紛紡細紹組結絕絡絢",
+    "給統絲綉經綜綠維綱網綺綿緒線緣緩緯練縉縣縱總繡繩繼續羅義習聖聞聯",
+    "聰聲職聽肅膽臉臨與興舉艱荊莊華萊萬葉葦蒼蓋蓮蔣蔥蕭薑藍藝藥蘆蘇蘊",
+    "蘋蘭處虛號蟲衆術衛補裝見規視親覺觀訊訓記許詠試詩誌語誠說誰誼調談",
+    "論諸諾謀謙講謝謹證識譚譜議護讀變讓豐豔貓貞負財貢貨貫貴費賀資賈賓",
+    "賜賞賢質賴購贈贊趕趙躍車軍軒軟較載輔輕輝輪輯輸轉辦辭農連週進運過",
+    "達遠選遺邁還邊邏郵鄉鄒鄧鄭醫釋釗針鈞鈴鈺鉑銀銅銘銳鋒鋪鋼錄錢錦錯",
+    "鍵鍾鎔鎮鏈鏗鏡鐘鐵鑄鑒長門閃開閑間閔閣閱闊闖關陣陰陳陸陽隊階際隨",
+    "險隱雖雙雜雞離難雲電霧靈靚靜韋韓韻響頁頂項順須頌預頒領頤頭頰頻顆",
+    "題額顏願類顧顯風颯飄飛飯飾餘館馬馮駐駿騎騰驅驕驗驚體髮鬥鬱魚魯鮑",
+    "鮮鯨鳥鳳鳴鴨鴻鴿鵑鵬鶯鶴鷹鸞麗麥黃點黨齊齒齡龍龐龔龜",
+];
+
+const SIMPLIFIED: &[&str] = &[
+    "伦伟杰传侨价仪亿优储儿内两册则刚划刘剑劲动务胜劳势勋励汇区协却厉",
+    "参吴吕员问启乔单严国围园圆图团坚报场块壮寿梦夺奋妇娅妈娴娇孙学实",
+    "宁审写宝将对导属岛崭岭岳岩帅师帮厂广厅张强弹弥弯彦径从复征悦爱态",
+    "庆凭宪忆应怀恋战户扬拥择据扩摄敌数敛断时晖畅晔历晓旷书会胧东条栋",
+    "杨枫业荣构乐楼标树桥机检樱权欧欢岁历残气决冲况凉泪渊浅汤温沧满渔",
+    "汉渐洁潜泽浓济涛滨滢浏潇澜洒湾灾为乌无炜热烨灯营灿焘烁争尔墙状犹",
+    "奖独献现珐玮瑶莹玛琏玑环玺琼产毕画异当疗发皑尽监盘卢众硕确码矿祯",
+    "祎禅礼种称积颖稳穷窦竞笔节范筑简篮粮纪约红纹纯纷纺细绍组结绝络绚",
+    "给统丝绣经综绿维纲网绮绵绪线缘缓纬练缙县纵总绣绳继续罗义习圣闻联",
+    "聪声职听肃胆脸临与兴举艰荆庄华莱万叶苇苍盖莲蒋葱萧姜蓝艺药芦苏蕴",
+    "苹兰处虚号虫众术卫补装见规视亲觉观讯训记许咏试诗志语诚说谁谊调谈",
+    "论诸诺谋谦讲谢谨证识谭谱议护读变让丰艳猫贞负财贡货贯贵费贺资贾宾",
+    "赐赏贤质赖购赠赞赶赵跃车军轩软较载辅轻辉轮辑输转办辞农连周进运过",
+    "达远选遗迈还边逻邮乡邹邓郑医释钊针钧铃钰铂银铜铭锐锋铺钢录钱锦错",
+    "键钟镕镇链铿镜钟铁铸鉴长门闪开闲间闵阁阅阔闯关阵阴陈陆阳队阶际随",
+    "险隐虽双杂鸡离难云电雾灵靓静韦韩韵响页顶项顺须颂预颁领颐头颊频颗",
+    "题额颜愿类顾显风飒飘飞饭饰余馆马冯驻骏骑腾驱骄验惊体发斗郁鱼鲁鲍",
+    "鲜鲸鸟凤鸣鸭鸿鸽鹃鹏莺鹤鹰鸾丽麦黄点党齐齿龄龙庞龚龟",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn member(row: u32, name: &str, card_number: Option<&str>) -> MemberRecord {
+        MemberRecord {
+            row,
+            name: Some(name.to_string()),
+            registration_date: NaiveDate::from_ymd_opt(2020, 1, row).and_then(|d| d.and_hms_opt(0, 0, 0)),
+            card_number: card_number.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn normalizes_traditional_and_full_width_names() {
+        assert_eq!(normalize_name("陳 偉傑"), "陈伟杰");
+        assert_eq!(normalize_name("ＡＬＩＣＥ　Ｗａｎｇ"), "alicewang");
+        assert_eq!(normalize_card_number(" ａ００１２ "), "A0012");
+    }
+
+    #[test]
+    fn matches_by_normalized_name() {
+        let resolver = ReaderIdentityResolver::new(vec![member(2, "陈伟杰", None)]);
+        let identity = resolver.resolve(Some(" 陳偉傑 "), None);
+        assert_eq!(identity.status, MatchStatus::Matched);
+        assert_eq!(identity.matched_by, Some(MatchMethod::Name));
+        assert_eq!(identity.member_row, Some(2));
+        assert_eq!(identity.name.as_deref(), Some("陳偉傑"));
+    }
+
+    #[test]
+    fn card_number_takes_precedence_over_name() {
+        let resolver = ReaderIdentityResolver::new(vec![
+            member(2, "张三", Some("0001")),
+            member(3, "张三", Some("0002")),
+        ]);
+        let identity = resolver.resolve(Some("张三"), Some("０００２"));
+        assert_eq!(identity.status, MatchStatus::Matched);
+        assert_eq!(identity.matched_by, Some(MatchMethod::CardNumber));
+        assert_eq!(identity.member_row, Some(3));
+    }
+
+    #[test]
+    fn duplicate_names_are_ambiguous() {
+        let resolver = ReaderIdentityResolver::new(vec![
+            member(2, "张三", None),
+            member(3, "張三", None),
+            member(4, "李四", None),
+        ]);
+        let identity = resolver.resolve(Some("张三"), None);
+        assert_eq!(identity.status, MatchStatus::Ambiguous);
+        assert_eq!(identity.member_row, None);
+        let rows: Vec<u32> = identity.candidates.iter().map(|c| c.row).collect();
+        assert_eq!(rows, vec![2, 3]);
+    }
+
+    #[test]
+    fn members_with_other_card_numbers_are_not_matched_by_name() {
+        let resolver = ReaderIdentityResolver::new(vec![
+            member(2, "张三", Some("0001")),
+            member(3, "张三", None),
+        ]);
+        let identity = resolver.resolve(Some("张三"), Some("9999"));
+        assert_eq!(identity.status, MatchStatus::Matched);
+        assert_eq!(identity.matched_by, Some(MatchMethod::Name));
+        assert_eq!(identity.member_row, Some(3));
+
+        let resolver = ReaderIdentityResolver::new(vec![member(2, "张三", Some("0001"))]);
+        assert_eq!(resolver.resolve(Some("张三"), Some("9999")).status, MatchStatus::Unmatched);
+        assert_eq!(resolver.resolve(None, None).status, MatchStatus::Unmatched);
+    }
+}
@@ -0,0 +1,600 @@
+use crate::analyzer_backend::ProgressCallback;
+use crate::charts::{self, ChartData, ChartSeries, ReportChart};
+use crate::docx;
+use crate::html;
+use crate::membership::MembershipDuration;
+use crate::pdf;
+use crate::reader_identity::ReaderIdentity;
+use crate::rust_analyzer::MissingWeeksInfo;
+use crate::template::Template;
+use crate::theme::{self, ReportTheme};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// 报告中排行榜显示的条目数量
+pub const REPORT_TOP_ENTRIES: usize = 10;
+
+/// 报告模板目录名（位于应用数据目录下）
+pub const TEMPLATE_DIR_NAME: &str = "templates";
+/// Markdown模板的文件名：Markdown、Word和PDF报告（以及没有HTML模板时的HTML报告）都由该模板生成
+pub const MARKDOWN_TEMPLATE: &str = "report.md";
+/// HTML模板的文件名，模板目录中有该文件时HTML报告直接由它生成
+pub const HTML_TEMPLATE: &str = "report.html";
+/// 模板目录中内置默认模板的副本，复制为 `report.md` 后修改即可自定义报告；该文件本身不会被读取
+pub const EXAMPLE_MARKDOWN_TEMPLATE: &str = "report.example.md";
+/// 内置的默认模板，生成的报告与原来固定格式的报告相同
+pub const DEFAULT_MARKDOWN_TEMPLATE: &str = include_str!("../templates/report.md");
+
+/// 用户模板目录，应用启动时设置
+static TEMPLATE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// 报告数据，两种后端都从最近一次分析结果中提取，由Rust端写入报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportData {
+    pub borrow_frequency: u32,
+    pub total_duration: MembershipDuration,
+    /// 所有借阅的借阅时长之和（天）
+    pub total_reading_duration: i64,
+    pub total_price: f64,
+    pub most_frequent_author: String,
+    pub most_frequent_category: String,
+    pub longest_author: String,
+    pub longest_category: String,
+    pub borrow_peak_yearmonth: String,
+    pub most_borrowed_book: String,
+    pub most_borrowed_book_count: u32,
+    /// 借阅最多的月份（1-12）
+    pub borrow_peak: u8,
+    pub total_full_attendance: u32,
+    /// 每月借书数量，按数量降序
+    pub monthly_borrow: Vec<(String, u32)>,
+    /// 借阅次数最多的书（前十）
+    pub book_borrow_counts: Vec<(String, u32)>,
+    /// 借书时长最长的书（前十，天）
+    pub sorted_duration: Vec<(String, i64)>,
+    pub full_attendance: Vec<String>,
+    /// 缺勤月及其周信息，按年月排列
+    pub partial_attendance: Vec<(String, MissingWeeksInfo)>,
+    pub reader_identity: ReaderIdentity,
+    pub unresolved_readers: Vec<ReaderIdentity>,
+    pub chart_data: ChartData,
+}
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    /// Word文档
+    #[default]
+    Docx,
+    Pdf,
+    /// 单个HTML文件，样式和图表都内嵌在文件中
+    Html,
+    /// Markdown文件，图表引用报告所在目录中的图片
+    Markdown,
+}
+
+impl ReportFormat {
+    /// 根据文件扩展名判断报告格式
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "docx" => Ok(Self::Docx),
+            "pdf" => Ok(Self::Pdf),
+            "html" | "htm" => Ok(Self::Html),
+            "md" | "markdown" => Ok(Self::Markdown),
+            _ => Err(anyhow!(
+                "无法根据扩展名判断报告格式: {:?}，支持 .docx、.pdf、.html 和 .md",
+                path
+            )),
+        }
+    }
+
+    /// 指定了格式时使用指定的格式，否则根据扩展名判断
+    pub fn resolve(format: Option<Self>, path: &Path) -> Result<Self> {
+        match format {
+            Some(format) => Ok(format),
+            None => Self::from_path(path),
+        }
+    }
+
+    /// 与序列化结果一致的格式名称
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Docx => "docx",
+            Self::Pdf => "pdf",
+            Self::Html => "html",
+            Self::Markdown => "markdown",
+        }
+    }
+
+    /// 该格式的报告文件扩展名（不含 `.`）
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            format => format.as_str(),
+        }
+    }
+}
+
+/// 与输出格式无关的报告内容，各格式的写入器按顺序输出其中的内容块
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportDocument {
+    pub title: String,
+    pub blocks: Vec<ReportBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportBlock {
+    /// 小节标题
+    Heading(String),
+    Paragraph(String),
+    /// 表格，第一行为表头
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// 图表图片（PNG）及其数据序列，HTML报告可以用数据序列绘制交互图表
+    Image {
+        title: String,
+        path: PathBuf,
+        series: Option<ChartSeries>,
+    },
+}
+
+impl ReportDocument {
+    /// 由模板生成的Markdown组织报告内容。支持的格式：第一个一级标题为报告标题（没有时使用 `default_title`），
+    /// 其他标题为小标题；带分隔行的表格；单独一行的图片 `![标题](路径)`（相对路径相对于 `base_dir`）；
+    /// 其余非空行为段落，段落内的换行保留。反斜杠转义的标点还原为原字符
+    pub fn from_markdown(
+        markdown: &str,
+        default_title: &str,
+        base_dir: &Path,
+        charts: &[ReportChart],
+        series: &[ChartSeries],
+    ) -> Self {
+        let mut title = None;
+        let mut blocks = Vec::new();
+        let mut paragraph: Vec<String> = Vec::new();
+        let flush = |paragraph: &mut Vec<String>, blocks: &mut Vec<ReportBlock>| {
+            if !paragraph.is_empty() {
+                blocks.push(ReportBlock::Paragraph(paragraph.join("\n")));
+                paragraph.clear();
+            }
+        };
+
+        let mut lines = markdown.lines().map(str::trim).peekable();
+        while let Some(line) = lines.next() {
+            if line.is_empty() {
+                flush(&mut paragraph, &mut blocks);
+            } else if let Some((level, text)) = markdown_heading(line) {
+                flush(&mut paragraph, &mut blocks);
+                if level == 1 && title.is_none() {
+                    title = Some(text);
+                } else {
+                    blocks.push(ReportBlock::Heading(text));
+                }
+            } else if line.starts_with('|') && lines.peek().is_some_and(|next| is_table_separator(next)) {
+                flush(&mut paragraph, &mut blocks);
+                lines.next();
+                let headers = table_cells(line);
+                let mut rows = Vec::new();
+                while let Some(row) = lines.next_if(|next| next.starts_with('|')) {
+                    let mut cells = table_cells(row);
+                    cells.resize(headers.len(), String::new());
+                    rows.push(cells);
+                }
+                blocks.push(ReportBlock::Table { headers, rows });
+            } else if let Some((alt, target)) = markdown_image(line) {
+                flush(&mut paragraph, &mut blocks);
+                let path = base_dir.join(target);
+                let series = charts
+                    .iter()
+                    .find(|chart| chart.path == path)
+                    .and_then(|chart| series.iter().find(|series| series.key == chart.key))
+                    .cloned();
+                blocks.push(ReportBlock::Image {
+                    title: alt,
+                    path,
+                    series,
+                });
+            } else {
+                paragraph.push(unescape_markdown(line));
+            }
+        }
+        flush(&mut paragraph, &mut blocks);
+
+        Self {
+            title: title.unwrap_or_else(|| default_title.to_string()),
+            blocks,
+        }
+    }
+}
+
+/// `#` 开头的标题行，返回级别和标题文字
+fn markdown_heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6)
+        .contains(&level)
+        .then(|| (level, unescape_markdown(text.trim())))
+}
+
+/// 表格的分隔行，如 `| --- | :---: |`
+fn is_table_separator(line: &str) -> bool {
+    let cells = split_table_row(line);
+    !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let cell = cell.trim().trim_start_matches(':').trim_end_matches(':');
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    split_table_row(line)
+        .iter()
+        .map(|cell| unescape_markdown(cell.trim()))
+        .collect()
+}
+
+/// 按未转义的 `|` 拆分表格行，去掉首尾的 `|`
+fn split_table_row(line: &str) -> Vec<&str> {
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => {
+                cells.push(&line[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = &line[start..];
+    if !last.trim().is_empty() {
+        cells.push(last);
+    }
+    cells
+}
+
+/// 单独一行的图片 `![标题](路径)`，返回标题和路径
+fn markdown_image(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix("![")?.strip_suffix(')')?;
+    let mut escaped = false;
+    for (index, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ']' => {
+                let target = rest[index + 1..].strip_prefix('(')?;
+                return Some((unescape_markdown(&rest[..index]), unescape_markdown(target.trim())));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 还原反斜杠转义的ASCII标点
+fn unescape_markdown(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(next) if c == '\\' && next.is_ascii_punctuation() => {
+                result.push(*next);
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// 表格各列的相对宽度：按各列内容的显示宽度（中文等非ASCII字符按两个字符计算）分配，
+/// 限制在 4 到 40 个字符之间，避免过窄或过宽
+pub fn column_weights(headers: &[String], rows: &[Vec<String>]) -> Vec<f64> {
+    (0..headers.len())
+        .map(|column| {
+            std::iter::once(&headers[column])
+                .chain(rows.iter().filter_map(|row| row.get(column)))
+                .map(|text| text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum::<usize>())
+                .max()
+                .unwrap_or(0)
+                .clamp(4, 40) as f64
+        })
+        .collect()
+}
+
+/// 设置用户模板目录，应用启动时调用。内置的默认模板同时写入其中的 `report.example.md`，作为自定义模板的起点。
+/// 该文件不会被读取，每次启动都更新为当前版本的内置模板；自定义的模板是同一目录中的 `report.md`
+pub fn set_template_dir(dir: PathBuf) {
+    let example_path = dir.join(EXAMPLE_MARKDOWN_TEMPLATE);
+    let unchanged = std::fs::read_to_string(&example_path)
+        .is_ok_and(|example| example == DEFAULT_MARKDOWN_TEMPLATE);
+    if !unchanged {
+        if let Err(e) = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(&example_path, DEFAULT_MARKDOWN_TEMPLATE))
+        {
+            warn!("无法写入默认报告模板 {:?}: {}", example_path, e);
+        }
+    }
+    *TEMPLATE_DIR.lock().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+}
+
+/// 模板目录中的用户模板，文件不存在时返回 `None`
+fn user_template(name: &str) -> Result<Option<Template>> {
+    let dir = TEMPLATE_DIR.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match dir.map(|dir| dir.join(name)) {
+        Some(path) if path.is_file() => {
+            info!("使用自定义报告模板: {:?}", path);
+            Template::load(&path).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// 用户的Markdown模板，没有时使用内置的默认模板
+fn markdown_template() -> Result<Template> {
+    match user_template(MARKDOWN_TEMPLATE)? {
+        Some(template) => Ok(template),
+        None => Template::parse(MARKDOWN_TEMPLATE, DEFAULT_MARKDOWN_TEMPLATE),
+    }
+}
+
+/// 模板变量：报告数据的所有字段，其中排行榜和缺勤月转换为带名称的对象；另有报告标题、生成时间、主题和图表。
+/// `embed` 为 `true` 时（HTML模板）图表和标志另外提供 `data:` URI，图表提供可交互的SVG
+pub fn template_context(
+    data: &ReportData,
+    theme: &ReportTheme,
+    charts: &[ReportChart],
+    embed: bool,
+) -> Result<Value> {
+    let mut context = serde_json::to_value(data)?;
+    let Value::Object(fields) = &mut context else {
+        return Err(anyhow!("报告数据不是对象"));
+    };
+
+    if let Some(Value::Object(duration)) = fields.get_mut("total_duration") {
+        duration.insert("description".to_string(), json!(data.total_duration.describe()));
+    }
+    fields.insert(
+        "monthly_borrow".to_string(),
+        data.monthly_borrow
+            .iter()
+            .map(|(month, count)| json!({ "month": month, "count": count }))
+            .collect(),
+    );
+    fields.insert(
+        "book_borrow_counts".to_string(),
+        data.book_borrow_counts
+            .iter()
+            .enumerate()
+            .map(|(index, (title, count))| json!({ "rank": index + 1, "title": title, "count": count }))
+            .collect(),
+    );
+    fields.insert(
+        "sorted_duration".to_string(),
+        data.sorted_duration
+            .iter()
+            .enumerate()
+            .map(|(index, (title, days))| json!({ "rank": index + 1, "title": title, "days": days }))
+            .collect(),
+    );
+    fields.insert(
+        "partial_attendance".to_string(),
+        data.partial_attendance
+            .iter()
+            .map(|(month, info)| {
+                json!({
+                    "month": month,
+                    "recorded_weeks": info.recorded_weeks,
+                    "corrected_recorded_weeks": info.corrected_recorded_weeks,
+                    "missing_weeks": info.missing_weeks,
+                    "total_weeks": info.total_weeks,
+                })
+            })
+            .collect(),
+    );
+
+    let palette = theme.palette_rgb();
+    let (r, g, b) = theme.accent_rgb();
+    let mut theme_value = serde_json::to_value(theme)?;
+    theme_value["accent"] = json!(format!("#{:02x}{:02x}{:02x}", r, g, b));
+    if embed {
+        if let Some(logo) = theme.logo.as_deref().filter(|logo| logo.is_file()) {
+            theme_value["logo_data_uri"] = json!(html::data_uri(logo)?);
+        }
+    }
+
+    let series = charts::chart_series(&data.chart_data, None);
+    let mut chart_values = Vec::new();
+    for chart in charts {
+        let mut value = json!({
+            "key": chart.key,
+            "title": chart.title,
+            "path": chart.path,
+            "file_name": chart.path.file_name().map(|name| name.to_string_lossy()),
+        });
+        if embed {
+            value["data_uri"] = json!(html::data_uri(&chart.path)?);
+            if let Some(series) = series.iter().find(|series| series.key == chart.key) {
+                value["svg"] = json!(html::svg_chart(series, &palette));
+            }
+        }
+        chart_values.push(value);
+    }
+    fields.insert(
+        "chart_paths".to_string(),
+        charts.iter().map(|chart| (chart.key.to_string(), json!(chart.path))).collect(),
+    );
+    fields.insert("charts".to_string(), Value::Array(chart_values));
+    fields.insert("title".to_string(), json!(theme.title));
+    fields.insert(
+        "generated_at".to_string(),
+        json!(chrono::Local::now().format("%Y-%m-%d %H:%M").to_string()),
+    );
+    fields.insert("theme".to_string(), theme_value);
+    Ok(context)
+}
+
+/// 导出报告：图表绘制到报告所在目录，主题使用当前配置，报告内容由模板生成
+pub fn export_report(
+    data: &ReportData,
+    output_path: &Path,
+    format: ReportFormat,
+    progress_callback: Option<ProgressCallback>,
+) -> Result<()> {
+    let report = |current: u32, message: &str| {
+        if let Some(callback) = &progress_callback {
+            callback(100, current, message.to_string());
+        }
+    };
+    report(0, "开始生成报告");
+
+    let chart_dir = match output_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(chart_dir)
+        .with_context(|| format!("无法创建报告目录: {:?}", chart_dir))?;
+
+    report(25, "生成图表");
+    let chart_paths = charts::render_charts(&data.chart_data, chart_dir, None)?;
+
+    report(75, "添加统计信息和图表");
+    let theme = theme::current();
+    let charts = charts::report_charts(&chart_paths);
+
+    report(90, "保存报告");
+    write_report(data, &theme, &charts, chart_dir, output_path, format)
+        .with_context(|| format!("无法保存报告: {:?}", output_path))?;
+
+    report(100, "报告生成完成");
+    info!("报告导出完成: {:?}", output_path);
+    Ok(())
+}
+
+fn write_report(
+    data: &ReportData,
+    theme: &ReportTheme,
+    charts: &[ReportChart],
+    chart_dir: &Path,
+    output_path: &Path,
+    format: ReportFormat,
+) -> Result<()> {
+    let write = |content: String| {
+        std::fs::write(output_path, content).with_context(|| format!("无法写入文件: {:?}", output_path))
+    };
+
+    // HTML模板直接生成HTML报告
+    if format == ReportFormat::Html {
+        if let Some(template) = user_template(HTML_TEMPLATE)? {
+            return write(template.render(&template_context(data, theme, charts, true)?)?);
+        }
+    }
+
+    let markdown = markdown_template()?.render(&template_context(data, theme, charts, false)?)?;
+    if format == ReportFormat::Markdown {
+        return write(markdown);
+    }
+    let series = charts::chart_series(&data.chart_data, None);
+    let document = ReportDocument::from_markdown(&markdown, &theme.title, chart_dir, charts, &series);
+    match format {
+        ReportFormat::Docx => docx::write_docx(&document, theme, output_path),
+        ReportFormat::Pdf => pdf::write_pdf(&document, theme, output_path),
+        ReportFormat::Html | ReportFormat::Markdown => html::write_html(&document, theme, output_path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render_document(source: &str, context: serde_json::Value) -> ReportDocument {
+        let markdown = Template::parse(MARKDOWN_TEMPLATE, source).unwrap().render(&context).unwrap();
+        ReportDocument::from_markdown(&markdown, "默认标题", Path::new("charts"), &[], &[])
+    }
+
+    #[test]
+    fn template_dir_holds_an_example_and_the_user_template() {
+        let dir = std::env::temp_dir().join(format!("readerreport-templates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let example = dir.join(EXAMPLE_MARKDOWN_TEMPLATE);
+        // 旧版本留下的副本更新为当前的内置模板
+        std::fs::write(&example, "旧版本的模板").unwrap();
+
+        set_template_dir(dir.clone());
+        assert_eq!(std::fs::read_to_string(&example).unwrap(), DEFAULT_MARKDOWN_TEMPLATE);
+        // 只有副本时使用内置模板
+        assert!(user_template(MARKDOWN_TEMPLATE).unwrap().is_none());
+
+        std::fs::write(dir.join(MARKDOWN_TEMPLATE), "# {{ title }}").unwrap();
+        let template = markdown_template().unwrap();
+        *TEMPLATE_DIR.lock().unwrap() = None;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(template.render(&json!({ "title": "自定义" })).unwrap(), "# 自定义");
+    }
+
+    #[test]
+    fn interpolated_values_do_not_change_the_structure() {
+        let source = "# {{ title }}\n\n## {{ heading }}\n\n| 书名 | 次数 |\n| --- | --- |\n\
+                      {% for book in books %}| {{ book }} | 1 |\n{% endfor %}{{ note }}\n\n\
+                      ![{{ chart }}]({{ file }})\n";
+        let context = json!({
+            "title": "# A | B",
+            "heading": "![x](y.png)",
+            "books": ["C | D", "---", "1. \\ *E*"],
+            "note": "| --- |\nline",
+            "chart": "图表 [1]",
+            "file": "a_b.png",
+        });
+        let document = render_document(source, context);
+
+        assert_eq!(document.title, "# A | B");
+        assert_eq!(
+            document.blocks,
+            vec![
+                ReportBlock::Heading("![x](y.png)".to_string()),
+                ReportBlock::Table {
+                    headers: vec!["书名".to_string(), "次数".to_string()],
+                    rows: vec![
+                        vec!["C | D".to_string(), "1".to_string()],
+                        vec!["---".to_string(), "1".to_string()],
+                        vec!["1. \\ *E*".to_string(), "1".to_string()],
+                    ],
+                },
+                ReportBlock::Paragraph("| --- | line".to_string()),
+                ReportBlock::Image {
+                    title: "图表 [1]".to_string(),
+                    path: Path::new("charts").join("a_b.png"),
+                    series: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn values_at_line_start_are_not_table_separators() {
+        let source = "| 项目 |\n{{ separator }}\n";
+        let document = render_document(source, json!({ "separator": "| --- |" }));
+        assert_eq!(document.title, "默认标题");
+        assert_eq!(document.blocks, vec![ReportBlock::Paragraph("| 项目 |\n| --- |".to_string())]);
+
+        let document = render_document("| 项目 |\n{{ separator }}\n", json!({ "separator": "---" }));
+        assert_eq!(document.blocks, vec![ReportBlock::Paragraph("| 项目 |\n---".to_string())]);
+    }
+}
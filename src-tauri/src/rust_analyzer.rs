@@ -0,0 +1,1273 @@
+use crate::analyzer_backend::{
+    AnalysisResult, CancelFlag, OutputCallback, ProgressCallback, ReportGrouping,
+};
+use crate::charts::ChartData;
+use crate::column_mapping::{
+    Column, ColumnMapping, BORROW_COLUMNS, MEMBER_COLUMNS, OPTIONAL_BORROW_COLUMNS,
+    OPTIONAL_MEMBER_COLUMNS,
+};
+use crate::data_export::AnalysisData;
+use crate::error::AnalyzerError;
+use crate::membership::MembershipDuration;
+use crate::reader_identity::{ReaderIdentity, ReaderIdentityResolver};
+use crate::report::{self, ReportData, ReportFormat, REPORT_TOP_ENTRIES};
+use crate::spreadsheet::{read_first_sheet, read_sheet, resolve_entries, SheetEntry, SheetSelection};
+use anyhow::{anyhow, Context, Result};
+use calamine::{Data, DataType, Range};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use tracing::info;
+
+/// 单条借阅记录
+#[derive(Debug, Clone)]
+pub struct BorrowRecord {
+    pub name: Option<String>,
+    pub book_title: Option<String>,
+    pub author: Option<String>,
+    pub category: Option<String>,
+    pub price: Option<f64>,
+    pub borrow_date: NaiveDateTime,
+    pub return_date: Option<NaiveDateTime>,
+    /// 读者证号（可选列）
+    pub card_number: Option<String>,
+}
+
+impl BorrowRecord {
+    /// 借阅时长（天），与 pandas 的 `Timedelta.days` 一样向下取整
+    pub fn duration_days(&self) -> Option<i64> {
+        self.return_date
+            .map(|return_date| floor_days(return_date - self.borrow_date))
+    }
+}
+
+/// 同一读者的借阅记录
+#[derive(Debug, Clone)]
+pub struct ReaderGroup {
+    /// 显示名称：有读者证号时为 `姓名_证号`，否则为姓名
+    pub name: String,
+    pub records: Vec<BorrowRecord>,
+}
+
+/// 读者清单中的一条记录
+#[derive(Debug, Clone)]
+pub struct MemberRecord {
+    /// Excel中的行号（表头为第1行）
+    pub row: u32,
+    pub name: Option<String>,
+    pub registration_date: Option<NaiveDateTime>,
+    /// 读者证号（可选列）
+    pub card_number: Option<String>,
+}
+
+/// 缺勤月的周信息
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MissingWeeksInfo {
+    pub recorded_weeks: Vec<String>,
+    pub corrected_recorded_weeks: Vec<String>,
+    pub missing_weeks: Vec<String>,
+    pub total_weeks: Vec<String>,
+}
+
+/// 完整分析结果，对应Python版本的 `self.results` 字典
+///
+/// 排序字段均保持 pandas `sort_values(ascending=False)` 的顺序：
+/// 按值降序，并列时保持原有顺序。
+#[derive(Debug, Clone)]
+pub struct AnalysisDetails {
+    pub borrow_frequency: u32,
+    pub total_duration: MembershipDuration,
+    pub total_reading_duration: i64,
+    pub total_price: f64,
+    pub monthly_duration: Vec<(u32, i64)>,
+    pub monthly_borrow: Vec<(String, u32)>,
+    pub book_duration: Vec<(String, i64)>,
+    pub book_duration_ratio: Vec<(String, f64)>,
+    pub sorted_duration: Vec<(String, i64)>,
+    /// 按年月排列的借阅册数，从第一个月到最后一个月连续，没有借阅的月份为0
+    pub monthly_timeline: Vec<(String, u32)>,
+    /// 各类别借阅册数，按类别排列
+    pub category_count: Vec<(String, u32)>,
+    pub book_borrow_counts: Vec<(String, u32)>,
+    pub most_frequent_author: String,
+    pub most_frequent_category: String,
+    pub borrow_peak_yearmonth: String,
+    pub partial_attendance: Vec<String>,
+    pub missing_weeks_info: BTreeMap<String, MissingWeeksInfo>,
+    pub longest_author: String,
+    pub longest_category: String,
+    pub borrow_peak: u8,
+    pub most_borrowed_book: String,
+    pub most_borrowed_book_count: u32,
+    pub full_attendance: Vec<String>,
+    pub total_full_attendance: u32,
+    pub reader_identity: ReaderIdentity,
+    pub unresolved_readers: Vec<ReaderIdentity>,
+}
+
+impl AnalysisDetails {
+    /// 转换为返回给前端的 `AnalysisResult`
+    pub fn to_result(&self) -> AnalysisResult {
+        AnalysisResult {
+            borrow_frequency: self.borrow_frequency,
+            total_duration: self.total_duration.clone(),
+            total_reading_duration: self.total_reading_duration,
+            total_price: self.total_price,
+            monthly_borrow: self.monthly_borrow.iter().cloned().collect(),
+            monthly_timeline: self.monthly_timeline.clone(),
+            most_frequent_author: self.most_frequent_author.clone(),
+            most_frequent_category: self.most_frequent_category.clone(),
+            // 与Python版本一致，只返回前十
+            book_borrow_counts: self.book_borrow_counts.iter().take(10).cloned().collect(),
+            borrow_peak_yearmonth: self.borrow_peak_yearmonth.clone(),
+            longest_author: self.longest_author.clone(),
+            longest_category: self.longest_category.clone(),
+            borrow_peak: self.borrow_peak,
+            most_borrowed_book: self.most_borrowed_book.clone(),
+            most_borrowed_book_count: self.most_borrowed_book_count,
+            full_attendance: self.full_attendance.clone(),
+            total_full_attendance: self.total_full_attendance,
+            reader_identity: self.reader_identity.clone(),
+            unresolved_readers: self.unresolved_readers.clone(),
+            chart_paths: HashMap::new(),
+        }
+    }
+
+    /// 图表数据，与Python版本的 `chart_data()` 一致
+    pub fn chart_data(&self) -> ChartData {
+        ChartData {
+            book_duration: self.sorted_duration.clone(),
+            monthly_borrow: self.monthly_timeline.clone(),
+            category_count: self.category_count.clone(),
+        }
+    }
+
+    /// 报告数据，与Python版本的 `report_data()` 一致
+    pub fn report_data(&self) -> ReportData {
+        ReportData {
+            borrow_frequency: self.borrow_frequency,
+            total_duration: self.total_duration.clone(),
+            total_reading_duration: self.total_reading_duration,
+            total_price: self.total_price,
+            most_frequent_author: self.most_frequent_author.clone(),
+            most_frequent_category: self.most_frequent_category.clone(),
+            longest_author: self.longest_author.clone(),
+            longest_category: self.longest_category.clone(),
+            borrow_peak_yearmonth: self.borrow_peak_yearmonth.clone(),
+            most_borrowed_book: self.most_borrowed_book.clone(),
+            most_borrowed_book_count: self.most_borrowed_book_count,
+            borrow_peak: self.borrow_peak,
+            total_full_attendance: self.total_full_attendance,
+            monthly_borrow: self.monthly_borrow.clone(),
+            book_borrow_counts: self.book_borrow_counts.iter().take(REPORT_TOP_ENTRIES).cloned().collect(),
+            sorted_duration: self.sorted_duration.iter().take(REPORT_TOP_ENTRIES).cloned().collect(),
+            full_attendance: self.full_attendance.clone(),
+            partial_attendance: self
+                .partial_attendance
+                .iter()
+                .map(|month| {
+                    let info = self.missing_weeks_info.get(month).cloned().unwrap_or_default();
+                    (month.clone(), info)
+                })
+                .collect(),
+            reader_identity: self.reader_identity.clone(),
+            unresolved_readers: self.unresolved_readers.clone(),
+            chart_data: self.chart_data(),
+        }
+    }
+
+    /// 导出的完整分析结果，与Python版本的 `analysis_data()` 一致
+    pub fn analysis_data(&self) -> AnalysisData {
+        AnalysisData {
+            borrow_frequency: self.borrow_frequency,
+            total_duration: self.total_duration.clone(),
+            total_reading_duration: self.total_reading_duration,
+            total_price: self.total_price,
+            most_frequent_author: self.most_frequent_author.clone(),
+            most_frequent_category: self.most_frequent_category.clone(),
+            longest_author: self.longest_author.clone(),
+            longest_category: self.longest_category.clone(),
+            borrow_peak_yearmonth: self.borrow_peak_yearmonth.clone(),
+            borrow_peak: self.borrow_peak,
+            most_borrowed_book: self.most_borrowed_book.clone(),
+            most_borrowed_book_count: self.most_borrowed_book_count,
+            total_full_attendance: self.total_full_attendance,
+            monthly_duration: self.monthly_duration.clone(),
+            monthly_borrow: self.monthly_borrow.clone(),
+            monthly_timeline: self.monthly_timeline.clone(),
+            book_duration: self.book_duration.clone(),
+            book_duration_ratio: self.book_duration_ratio.clone(),
+            sorted_duration: self.sorted_duration.clone(),
+            category_count: self.category_count.clone(),
+            book_borrow_counts: self.book_borrow_counts.clone(),
+            full_attendance: self.full_attendance.clone(),
+            partial_attendance: self.partial_attendance.clone(),
+            missing_weeks_info: self.missing_weeks_info.clone(),
+            reader_identity: self.reader_identity.clone(),
+            unresolved_readers: self.unresolved_readers.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct AnalyzerState {
+    member_resolver: Option<ReaderIdentityResolver>,
+    file_data_list: Vec<Vec<BorrowRecord>>,
+    file_names: Vec<String>,
+    readers: Vec<ReaderGroup>,
+    results: Option<AnalysisDetails>,
+}
+
+/// 纯Rust实现的借阅分析器，接口与 `PythonAnalyzer` 保持一致
+pub struct RustAnalyzer {
+    initialized: bool,
+    state: RefCell<AnalyzerState>,
+}
+
+impl RustAnalyzer {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            initialized: false,
+            state: RefCell::new(AnalyzerState::default()),
+        })
+    }
+
+    pub fn initialize(&mut self) -> Result<()> {
+        self.initialized = true;
+        info!("Rust分析器初始化成功");
+        Ok(())
+    }
+
+    pub fn load_data(
+        &self,
+        borrow_paths: Vec<String>,
+        member_path: String,
+        column_mapping: &ColumnMapping,
+        sheet_selection: &SheetSelection,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<()> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        // 验证文件路径
+        if borrow_paths.is_empty() {
+            return Err(AnalyzerError::input_missing("请至少选择一个借阅数据文件", None).into());
+        }
+
+        if member_path.is_empty() {
+            return Err(AnalyzerError::input_missing("请选择读者清单文件", None).into());
+        }
+
+        // 验证文件是否存在
+        for path in &borrow_paths {
+            if !Path::new(path).exists() {
+                return Err(AnalyzerError::input_missing(
+                    format!("借阅数据文件不存在: {}", path),
+                    Some(path),
+                )
+                .into());
+            }
+        }
+
+        if !Path::new(&member_path).exists() {
+            return Err(AnalyzerError::input_missing(
+                format!("读者清单文件不存在: {}", member_path),
+                Some(&member_path),
+            )
+            .into());
+        }
+
+        let member_resolver = ReaderIdentityResolver::new(read_member_file(&member_path, column_mapping)?);
+
+        // 每个选中的工作表作为一个单独的条目
+        let entries = resolve_entries(&borrow_paths, sheet_selection)?;
+        let total_files = entries.len() as u32;
+        let mut file_data_list = Vec::with_capacity(entries.len());
+        let mut file_names = Vec::with_capacity(entries.len());
+        for (i, entry) in entries.into_iter().enumerate() {
+            report_progress(
+                &progress_callback,
+                total_files,
+                i as u32,
+                &format!("正在读取文件 {}", entry.name),
+            );
+            file_data_list.push(read_borrow_file(&entry, column_mapping)?);
+            file_names.push(entry.name);
+        }
+        report_progress(&progress_callback, total_files, total_files, "数据加载完成");
+
+        let readers = group_by_reader(&file_data_list.concat());
+
+        *self.state.borrow_mut() = AnalyzerState {
+            member_resolver: Some(member_resolver),
+            file_data_list,
+            file_names,
+            readers,
+            results: None,
+        };
+
+        info!("数据加载完成");
+        info!("借阅数据文件: {:?}", borrow_paths);
+        info!("读者清单文件: {}", member_path);
+
+        Ok(())
+    }
+
+    pub fn analyze(
+        &self,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let mut state = self.state.borrow_mut();
+        let member_resolver = state
+            .member_resolver
+            .as_ref()
+            .ok_or(AnalyzerError::DataNotLoaded)?;
+
+        // 合并所有文件的数据（用于整体分析）
+        let data: Vec<BorrowRecord> = state.file_data_list.concat();
+        report_progress(&progress_callback, 100, 10, "开始分析数据");
+        let details = analyze_records(&data, member_resolver, as_of)
+            .context("分析数据失败")?;
+        let result = details.to_result();
+        state.results = Some(details);
+        report_progress(&progress_callback, 100, 100, "分析完成");
+
+        info!("数据分析完成");
+        Ok(result)
+    }
+
+    pub fn chart_data(&self) -> Result<ChartData> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let state = self.state.borrow();
+        let details = state.results.as_ref().ok_or(AnalyzerError::DataNotLoaded)?;
+        Ok(details.chart_data())
+    }
+
+    pub fn report_data(&self) -> Result<ReportData> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let state = self.state.borrow();
+        let details = state.results.as_ref().ok_or(AnalyzerError::DataNotLoaded)?;
+        Ok(details.report_data())
+    }
+
+    pub fn analysis_data(&self) -> Result<AnalysisData> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let state = self.state.borrow();
+        let details = state.results.as_ref().ok_or(AnalyzerError::DataNotLoaded)?;
+        Ok(details.analysis_data())
+    }
+
+    pub fn get_file_count(&self) -> Result<u32> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        Ok(self.state.borrow().file_data_list.len() as u32)
+    }
+
+    pub fn get_file_name(&self, file_index: u32) -> Result<String> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let state = self.state.borrow();
+        Ok(state
+            .file_names
+            .get(file_index as usize)
+            .cloned()
+            .unwrap_or_else(|| format!("文件{}", file_index + 1)))
+    }
+
+    pub fn analyze_single_file(
+        &self,
+        file_index: u32,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let mut state = self.state.borrow_mut();
+        let member_resolver = state
+            .member_resolver
+            .as_ref()
+            .ok_or_else(|| anyhow!("无效的文件索引或未加载数据"))?;
+        let data = state
+            .file_data_list
+            .get(file_index as usize)
+            .ok_or_else(|| anyhow!("无效的文件索引或未加载数据"))?;
+
+        report_progress(&progress_callback, 100, 10, "开始分析数据");
+        let details = analyze_records(data, member_resolver, as_of)
+            .context("分析数据失败")?;
+        let result = details.to_result();
+        state.results = Some(details);
+        report_progress(&progress_callback, 100, 100, "分析完成");
+
+        info!("单文件分析完成: 文件索引 {}", file_index);
+        Ok(result)
+    }
+
+    pub fn get_reader_count(&self) -> Result<u32> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        Ok(self.state.borrow().readers.len() as u32)
+    }
+
+    pub fn get_reader_name(&self, reader_index: u32) -> Result<String> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let state = self.state.borrow();
+        Ok(state
+            .readers
+            .get(reader_index as usize)
+            .map(|reader| reader.name.clone())
+            .unwrap_or_else(|| format!("读者{}", reader_index + 1)))
+    }
+
+    pub fn analyze_reader(
+        &self,
+        reader_index: u32,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<AnalysisResult> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let mut state = self.state.borrow_mut();
+        let member_resolver = state
+            .member_resolver
+            .as_ref()
+            .ok_or_else(|| anyhow!("无效的读者索引或未加载数据"))?;
+        let reader = state
+            .readers
+            .get(reader_index as usize)
+            .ok_or_else(|| anyhow!("无效的读者索引或未加载数据"))?;
+
+        report_progress(&progress_callback, 100, 10, "开始分析数据");
+        let details = analyze_records(&reader.records, member_resolver, as_of)
+            .context("分析数据失败")?;
+        let result = details.to_result();
+        state.results = Some(details);
+        report_progress(&progress_callback, 100, 100, "分析完成");
+
+        info!("读者分析完成: 读者索引 {}", reader_index);
+        Ok(result)
+    }
+
+    /// 为每个文件（或每位读者）分别分析并生成 `format` 格式的报告，报告位于 `base_output_dir/名称/名称_analysis.扩展名`
+    #[allow(clippy::too_many_arguments)]
+    pub fn export_reports_for_all_files(
+        &self,
+        base_output_dir: String,
+        grouping: ReportGrouping,
+        format: ReportFormat,
+        as_of: NaiveDate,
+        progress_callback: Option<ProgressCallback>,
+        cancel: Option<CancelFlag>,
+        on_output: Option<OutputCallback>,
+    ) -> Result<Vec<String>> {
+        if !self.initialized {
+            return Err(AnalyzerError::NotInitialized.into());
+        }
+
+        let total = match grouping {
+            ReportGrouping::File => self.get_file_count()?,
+            ReportGrouping::Reader => self.get_reader_count()?,
+        };
+        let mut report_paths = Vec::new();
+
+        for index in 0..total {
+            if cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                report_progress(
+                    &progress_callback,
+                    total,
+                    index,
+                    &format!("已取消，已生成 {} 份报告", report_paths.len()),
+                );
+                return Err(AnalyzerError::Cancelled.into());
+            }
+
+            let name = match grouping {
+                ReportGrouping::File => self.get_file_name(index)?,
+                ReportGrouping::Reader => self.get_reader_name(index)?,
+            };
+            report_progress(&progress_callback, total, index, &format!("正在为 {} 生成报告", name));
+
+            // 为每个文件或读者创建独立的输出目录
+            let output_dir = Path::new(&base_output_dir).join(&name);
+            std::fs::create_dir_all(&output_dir)
+                .with_context(|| format!("无法创建报告目录: {:?}", output_dir))?;
+
+            match grouping {
+                ReportGrouping::File => self.analyze_single_file(index, as_of, None)?,
+                ReportGrouping::Reader => self.analyze_reader(index, as_of, None)?,
+            };
+
+            let report_path = output_dir.join(format!("{}_analysis.{}", name, format.extension()));
+            report::export_report(&self.report_data()?, &report_path, format, None)
+                .with_context(|| format!("为 {} 生成报告失败", name))?;
+            let report_path = report_path.to_string_lossy().to_string();
+            if let Some(on_output) = &on_output {
+                on_output(report_path.clone());
+            }
+            report_paths.push(report_path);
+        }
+
+        report_progress(&progress_callback, total, total, "所有报告生成完成");
+        info!("所有报告生成完成，共 {} 个文件", report_paths.len());
+        Ok(report_paths)
+    }
+}
+
+impl Default for RustAnalyzer {
+    fn default() -> Self {
+        Self::new().unwrap()
+    }
+}
+
+/// 调用进度回调，参数与Python版本的 `progress_callback(total, current, message)` 一致
+fn report_progress(
+    progress_callback: &Option<ProgressCallback>,
+    total: u32,
+    current: u32,
+    message: &str,
+) {
+    if let Some(callback) = progress_callback {
+        callback(total, current, message.to_string());
+    }
+}
+
+/// 按读者分组：有读者证号的记录按证号分组，否则按姓名分组（均去除首尾空白）。
+///
+/// 分组按首次出现的顺序排列；姓名和证号都为空的记录不属于任何读者。
+pub fn group_by_reader(data: &[BorrowRecord]) -> Vec<ReaderGroup> {
+    let mut groups: Vec<ReaderGroup> = Vec::new();
+    let mut positions: HashMap<(bool, &str), usize> = HashMap::new();
+
+    for record in data {
+        let name = record.name.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let card = record
+            .card_number
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        let (key, display) = match (card, name) {
+            (Some(card), Some(name)) => ((true, card), format!("{}_{}", name, card)),
+            (Some(card), None) => ((true, card), card.to_string()),
+            (None, Some(name)) => ((false, name), name.to_string()),
+            (None, None) => continue,
+        };
+
+        match positions.get(&key) {
+            Some(&pos) => groups[pos].records.push(record.clone()),
+            None => {
+                positions.insert(key, groups.len());
+                groups.push(ReaderGroup {
+                    name: display,
+                    records: vec![record.clone()],
+                });
+            }
+        }
+    }
+    groups
+}
+
+/// 对一组借阅记录执行分析，逻辑与 `BorrowingAnalyzer.analyze_with_progress` 一致
+///
+/// `as_of` 为计算会员时长的统计日期，使用相同的日期可以重现之前的结果。
+pub fn analyze_records(
+    data: &[BorrowRecord],
+    member_resolver: &ReaderIdentityResolver,
+    as_of: NaiveDate,
+) -> Result<AnalysisDetails> {
+    if data.is_empty() {
+        return Err(anyhow!("借阅数据为空"));
+    }
+
+    // 借阅频次
+    let borrow_frequency = data.len() as u32;
+
+    // 获取会员时长：按第一条记录的读者证号或姓名在读者清单中查找
+    let reader_identity = member_resolver.resolve_record(&data[0]);
+    let total_duration = MembershipDuration::for_reader(&reader_identity, as_of);
+    let unresolved_readers = member_resolver.unresolved_readers(data);
+
+    // 总阅读时长和定价总价
+    let durations: Vec<Option<i64>> = data.iter().map(BorrowRecord::duration_days).collect();
+    let total_reading_duration: i64 = durations.iter().flatten().sum();
+    let prices: Vec<f64> = data.iter().map(|record| record.price.unwrap_or(0.0)).collect();
+    let total_price = numpy_sum(&prices);
+
+    // 月度数据分析
+    let mut monthly_duration_map: BTreeMap<u32, i64> = BTreeMap::new();
+    let mut monthly_borrow_map: BTreeMap<String, u32> = BTreeMap::new();
+    for (record, duration) in data.iter().zip(&durations) {
+        let month = record.borrow_date.month();
+        *monthly_duration_map.entry(month).or_default() += duration.unwrap_or(0);
+        *monthly_borrow_map.entry(year_month(&record.borrow_date)).or_default() +=
+            u32::from(record.book_title.is_some());
+    }
+    let monthly_duration = sort_desc(monthly_duration_map.into_iter().collect());
+    let monthly_timeline = month_timeline(&monthly_borrow_map)?;
+    let monthly_borrow = sort_desc(monthly_borrow_map.into_iter().collect());
+
+    // 类别分析
+    let categories: Vec<Option<String>> = data
+        .iter()
+        .map(|record| record.category.as_deref().map(categorycode2name))
+        .collect();
+    // 各类别借阅册数（图表数据），对应 `groupby('类别')['书名'].count()`
+    let mut category_count_map: BTreeMap<String, u32> = BTreeMap::new();
+    for (category, record) in categories.iter().zip(data) {
+        if let Some(category) = category {
+            *category_count_map.entry(category.clone()).or_default() +=
+                u32::from(record.book_title.is_some());
+        }
+    }
+    let category_count = category_count_map.into_iter().collect();
+    let book_duration: Vec<(String, i64)> =
+        group_sum(data.iter().map(|r| r.book_title.as_deref()), &durations)
+            .into_iter()
+            .collect();
+    let book_duration_ratio = book_duration
+        .iter()
+        .map(|(book, duration)| {
+            (book.clone(), *duration as f64 / total_reading_duration as f64 * 100.0)
+        })
+        .collect();
+    let sorted_duration = sort_desc(book_duration.clone());
+
+    // 计算每本书被借阅的次数
+    let book_borrow_counts = value_counts(data.iter().map(|r| r.book_title.as_deref()));
+
+    // 阅读时长最长的作者和类别
+    let longest_author = idxmax(group_sum(data.iter().map(|r| r.author.as_deref()), &durations))
+        .ok_or_else(|| anyhow!("缺少作者信息"))?;
+    let longest_category = idxmax(group_sum(categories.iter().map(|c| c.as_deref()), &durations))
+        .ok_or_else(|| anyhow!("缺少类别信息"))?;
+
+    // 借阅次数最多的作者和类别
+    let most_frequent_author = value_counts(data.iter().map(|r| r.author.as_deref()))
+        .first()
+        .map(|(author, _)| author.clone())
+        .ok_or_else(|| anyhow!("缺少作者信息"))?;
+    let most_frequent_category = value_counts(categories.iter().map(|c| c.as_deref()))
+        .first()
+        .map(|(category, _)| category.clone())
+        .ok_or_else(|| anyhow!("缺少类别信息"))?;
+
+    // 借阅高峰期和最多借阅的书：高峰期取时间线上借阅册数最多的年月（并列时取最早的），
+    // `borrow_peak` 为该年月的月份
+    let borrow_peak_yearmonth = sort_desc(monthly_timeline.clone())[0].0.clone();
+    let borrow_peak = parse_year_month(&borrow_peak_yearmonth)?.month() as u8;
+    let (most_borrowed_book, most_borrowed_book_count) = book_borrow_counts
+        .first()
+        .cloned()
+        .ok_or_else(|| anyhow!("缺少书名信息"))?;
+
+    // 全勤月统计
+    let all_recorded_weeks: BTreeSet<String> =
+        data.iter().map(|r| iso_week(&r.borrow_date.date())).collect();
+    let mut weeks_by_month: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for record in data {
+        weeks_by_month
+            .entry(year_month(&record.borrow_date))
+            .or_default()
+            .insert(iso_week(&record.borrow_date.date()));
+    }
+
+    let mut full_attendance = Vec::new();
+    let mut partial_attendance = Vec::new();
+    let mut missing_weeks_info = BTreeMap::new();
+    for (month, recorded_weeks) in &weeks_by_month {
+        let (year, month_num) = month
+            .split_once('-')
+            .and_then(|(y, m)| Some((y.parse::<i32>().ok()?, m.parse::<u32>().ok()?)))
+            .ok_or_else(|| anyhow!("无效的年月: {}", month))?;
+        let (actual_weeks, all_weeks) = calculate_actual_weeks(year, month_num);
+
+        // 对于跨月周的特殊处理
+        // 如果一个周在当月没有记录，但在该周对应的其他月份有记录，则视为有记录
+        let mut corrected_recorded_set = recorded_weeks.clone();
+        for week in &all_weeks {
+            if !recorded_weeks.contains(week) && all_recorded_weeks.contains(week) {
+                corrected_recorded_set.insert(week.clone());
+            }
+        }
+
+        if corrected_recorded_set.len() == actual_weeks {
+            full_attendance.push(month.clone());
+        } else {
+            partial_attendance.push(month.clone());
+            let missing_weeks = all_weeks
+                .iter()
+                .filter(|week| !corrected_recorded_set.contains(*week))
+                .cloned()
+                .collect();
+            missing_weeks_info.insert(
+                month.clone(),
+                MissingWeeksInfo {
+                    recorded_weeks: recorded_weeks.iter().cloned().collect(),
+                    corrected_recorded_weeks: corrected_recorded_set.into_iter().collect(),
+                    missing_weeks,
+                    total_weeks: all_weeks,
+                },
+            );
+        }
+    }
+    let total_full_attendance = full_attendance.len() as u32;
+
+    Ok(AnalysisDetails {
+        borrow_frequency,
+        total_duration,
+        total_reading_duration,
+        total_price,
+        monthly_duration,
+        monthly_borrow,
+        book_duration,
+        book_duration_ratio,
+        sorted_duration,
+        monthly_timeline,
+        category_count,
+        book_borrow_counts,
+        most_frequent_author,
+        most_frequent_category,
+        borrow_peak_yearmonth,
+        partial_attendance,
+        missing_weeks_info,
+        longest_author,
+        longest_category,
+        borrow_peak,
+        most_borrowed_book,
+        most_borrowed_book_count,
+        full_attendance,
+        total_full_attendance,
+        reader_identity,
+        unresolved_readers,
+    })
+}
+
+/// 计算一个月中实际包含的ISO周数及所有周（已排序）
+pub fn calculate_actual_weeks(year: i32, month: u32) -> (usize, Vec<String>) {
+    let mut iso_weeks = BTreeSet::new();
+    let mut day = NaiveDate::from_ymd_opt(year, month, 1);
+    while let Some(date) = day.filter(|d| d.month() == month) {
+        iso_weeks.insert(iso_week(&date));
+        day = date.succ_opt();
+    }
+    (iso_weeks.len(), iso_weeks.into_iter().collect())
+}
+
+/// 将类别代码转换为类别名称
+pub fn categorycode2name(code: &str) -> String {
+    let name = match code {
+        "BLG" => "双语读物",
+        "CCM" => "中文漫画",
+        "CFI" => "中文小说",
+        "CHP" => "中文低幼",
+        "CNF" => "中文科普",
+        "CPB" => "中文绘本",
+        "CPU" => "中文立体读物",
+        "CPY" => "拼音读物",
+        "CRF" => "中文家长用书",
+        "CYX" => "中文音像(书)",
+        "ECM" => "英文漫画",
+        "EER" => "英文分级读物",
+        "EFI" => "英文小说",
+        "EHP" => "英文低幼",
+        "ENF" => "英文科普",
+        "EPB" => "英文绘本",
+        "EPU" => "英文立体读物",
+        "ERF" => "英文家长用书",
+        "EYX" => "英文音像(书)",
+        other => other,
+    };
+    name.to_string()
+}
+
+/// ISO周标识，格式为 `YYYY-WW`
+fn iso_week(date: &NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("{}-{:02}", week.year(), week.week())
+}
+
+/// 年-月标识，格式为 `YYYY-MM`
+fn year_month(datetime: &NaiveDateTime) -> String {
+    datetime.format("%Y-%m").to_string()
+}
+
+/// 解析 `YYYY-MM` 为该月第一天
+fn parse_year_month(month: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| anyhow!("无效的年月: {}", month))
+}
+
+/// 连续的年月时间线：从最早到最晚的月份逐月排列，没有记录的月份补0
+fn month_timeline(counts: &BTreeMap<String, u32>) -> Result<Vec<(String, u32)>> {
+    let (Some(first), Some(last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Ok(Vec::new());
+    };
+    let last = parse_year_month(last)?;
+    let mut month = parse_year_month(first)?;
+    let mut timeline = Vec::new();
+    while month <= last {
+        let key = month.format("%Y-%m").to_string();
+        let count = counts.get(&key).copied().unwrap_or(0);
+        timeline.push((key, count));
+        month = month
+            .checked_add_months(Months::new(1))
+            .ok_or_else(|| anyhow!("年月超出范围: {}", month))?;
+    }
+    Ok(timeline)
+}
+
+/// 按天向下取整，对应 `Timedelta.days`
+fn floor_days(delta: Duration) -> i64 {
+    let days = delta.num_days();
+    if delta < Duration::days(days) {
+        days - 1
+    } else {
+        days
+    }
+}
+
+/// 与 numpy 的 `add.reduce` 相同的求和顺序（首元素 + 成对求和），保证浮点结果一致
+fn numpy_sum(values: &[f64]) -> f64 {
+    match values.split_first() {
+        None => 0.0,
+        Some((first, rest)) => first + pairwise_sum(rest),
+    }
+}
+
+fn pairwise_sum(values: &[f64]) -> f64 {
+    const BLOCK_SIZE: usize = 128;
+    let n = values.len();
+    if n < 8 {
+        values.iter().fold(0.0, |acc, v| acc + v)
+    } else if n <= BLOCK_SIZE {
+        let mut r = [0.0; 8];
+        r.copy_from_slice(&values[..8]);
+        let mut i = 8;
+        while i < n - n % 8 {
+            for (j, acc) in r.iter_mut().enumerate() {
+                *acc += values[i + j];
+            }
+            i += 8;
+        }
+        let mut res = ((r[0] + r[1]) + (r[2] + r[3])) + ((r[4] + r[5]) + (r[6] + r[7]));
+        for value in &values[i..] {
+            res += value;
+        }
+        res
+    } else {
+        let mut n2 = n / 2;
+        n2 -= n2 % 8;
+        pairwise_sum(&values[..n2]) + pairwise_sum(&values[n2..])
+    }
+}
+
+/// 按键分组求和（键升序，跳过空键），对应 `groupby(key)[col].sum()`
+fn group_sum<'a>(
+    keys: impl Iterator<Item = Option<&'a str>>,
+    values: &[Option<i64>],
+) -> BTreeMap<String, i64> {
+    let mut groups = BTreeMap::new();
+    for (key, value) in keys.zip(values) {
+        if let Some(key) = key {
+            *groups.entry(key.to_string()).or_default() += value.unwrap_or(0);
+        }
+    }
+    groups
+}
+
+/// 对应 `groupby(...).idxmax()`：并列时取排序靠前的键
+fn idxmax<V: PartialOrd + Copy>(groups: BTreeMap<String, V>) -> Option<String> {
+    let mut best: Option<(String, V)> = None;
+    for (key, value) in groups {
+        if best.as_ref().is_none_or(|(_, max)| value > *max) {
+            best = Some((key, value));
+        }
+    }
+    best.map(|(key, _)| key)
+}
+
+/// 对应 `value_counts()`：按次数降序，并列时按首次出现的顺序
+fn value_counts<'a>(values: impl Iterator<Item = Option<&'a str>>) -> Vec<(String, u32)> {
+    let mut counts: Vec<(String, u32)> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for value in values.flatten() {
+        match positions.get(value) {
+            Some(&pos) => counts[pos].1 += 1,
+            None => {
+                positions.insert(value, counts.len());
+                counts.push((value.to_string(), 1));
+            }
+        }
+    }
+    sort_desc(counts)
+}
+
+/// 对应 `sort_values(ascending=False)`：按值降序的稳定排序
+fn sort_desc<K, V: PartialOrd>(mut items: Vec<(K, V)>) -> Vec<(K, V)> {
+    items.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    items
+}
+
+/// 按表头映射定位各列，缺少必需列时报错
+fn column_indices(
+    range: &Range<Data>,
+    columns: &[Column],
+    optional: &[Column],
+    column_mapping: &ColumnMapping,
+    path: &str,
+) -> Result<HashMap<Column, usize>> {
+    let headers: Vec<String> = range
+        .rows()
+        .next()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .unwrap_or_default();
+
+    column_mapping
+        .resolve_with_optional(&headers, columns, optional)
+        .with_context(|| format!("文件 {} 表头不符合要求", path))
+}
+
+/// 数据行（跳过表头和整行为空的行）
+pub(crate) fn data_rows(range: &Range<Data>) -> impl Iterator<Item = (usize, &[Data])> {
+    range
+        .rows()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, row)| row.iter().any(|cell| !cell.is_empty()))
+}
+
+fn read_borrow_file(entry: &SheetEntry, column_mapping: &ColumnMapping) -> Result<Vec<BorrowRecord>> {
+    let range = read_sheet(&entry.path, entry.sheet.as_deref())?.range;
+    // 错误信息中的位置：文件路径，Excel文件附带工作表名称
+    let source = match &entry.sheet {
+        Some(sheet) => format!("{} [{}]", entry.path, sheet),
+        None => entry.path.clone(),
+    };
+    let idx = column_indices(
+        &range,
+        &BORROW_COLUMNS,
+        &OPTIONAL_BORROW_COLUMNS,
+        column_mapping,
+        &source,
+    )?;
+
+    data_rows(&range)
+        .map(|(row_index, row)| {
+            let cell = |column: Column| {
+                idx.get(&column)
+                    .and_then(|&i| row.get(i))
+                    .unwrap_or(&Data::Empty)
+            };
+            let borrow_date = cell_to_datetime(cell(Column::BorrowDate)).ok_or_else(|| {
+                anyhow!("文件 {} 第 {} 行的借书日期无法解析", source, row_index + 1)
+            })?;
+            Ok(BorrowRecord {
+                name: cell_to_string(cell(Column::ReaderName)),
+                book_title: cell_to_string(cell(Column::BookTitle)),
+                author: cell_to_string(cell(Column::Author)),
+                category: cell_to_string(cell(Column::Category)),
+                price: cell_to_f64(cell(Column::Price)),
+                borrow_date,
+                return_date: cell_to_datetime(cell(Column::ReturnDate)),
+                card_number: cell_to_string(cell(Column::CardNumber)),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn read_member_file(
+    path: &str,
+    column_mapping: &ColumnMapping,
+) -> Result<Vec<MemberRecord>> {
+    let range = read_first_sheet(path)?.range;
+    let idx = column_indices(
+        &range,
+        &MEMBER_COLUMNS,
+        &OPTIONAL_MEMBER_COLUMNS,
+        column_mapping,
+        path,
+    )?;
+
+    Ok(data_rows(&range)
+        .map(|(row_index, row)| {
+            let cell = |column: Column| {
+                idx.get(&column)
+                    .and_then(|&i| row.get(i))
+                    .unwrap_or(&Data::Empty)
+            };
+            MemberRecord {
+                row: row_index as u32 + 1,
+                name: cell_to_string(cell(Column::ReaderName)),
+                registration_date: cell_to_datetime(cell(Column::RegistrationDate)),
+                card_number: cell_to_string(cell(Column::CardNumber)),
+            }
+        })
+        .collect())
+}
+
+pub(crate) fn cell_to_string(cell: &Data) -> Option<String> {
+    match cell {
+        Data::Empty | Data::Error(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+pub(crate) fn cell_to_f64(cell: &Data) -> Option<f64> {
+    match cell {
+        Data::Int(v) => Some(*v as f64),
+        Data::Float(v) => Some(*v),
+        Data::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn cell_to_datetime(cell: &Data) -> Option<NaiveDateTime> {
+    match cell {
+        Data::DateTime(dt) => dt.as_datetime(),
+        Data::DateTimeIso(s) | Data::String(s) => parse_datetime(s),
+        // 未设置日期格式的单元格按Excel序列号处理
+        Data::Int(_) | Data::Float(_) => {
+            let serial = cell.as_f64()?;
+            let base = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_hms_opt(0, 0, 0)?;
+            base.checked_add_signed(Duration::milliseconds((serial * 86_400_000.0).round() as i64))
+        }
+        _ => None,
+    }
+}
+
+/// 解析常见的日期文本格式
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    const DATETIME_FORMATS: [&str; 4] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ];
+    const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%Y%m%d"];
+
+    let text = text.trim();
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(text, fmt).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|fmt| NaiveDate::parse_from_str(text, fmt).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(text: &str) -> NaiveDateTime {
+        parse_datetime(text).unwrap()
+    }
+
+    fn record(
+        title: &str,
+        author: &str,
+        category: &str,
+        price: Option<f64>,
+        borrow: &str,
+        return_date: Option<&str>,
+    ) -> BorrowRecord {
+        BorrowRecord {
+            name: Some("张三".to_string()),
+            book_title: Some(title.to_string()),
+            author: Some(author.to_string()),
+            category: Some(category.to_string()),
+            price,
+            borrow_date: datetime(borrow),
+            return_date: return_date.map(datetime),
+            card_number: None,
+        }
+    }
+
+    fn weeks(year: i32, month: u32) -> Vec<String> {
+        let (count, weeks) = calculate_actual_weeks(year, month);
+        assert_eq!(count, weeks.len());
+        weeks
+    }
+
+    #[test]
+    fn iso_weeks_cross_month_and_year_boundaries() {
+        // 2024-01-01 为周一，1月31日为第5周
+        assert_eq!(weeks(2024, 1), ["2024-01", "2024-02", "2024-03", "2024-04", "2024-05"]);
+        // 9月1日为周日（第35周），9月30日为周一（第40周）
+        assert_eq!(weeks(2024, 9).len(), 6);
+        // 12月30日、31日属于2025年第1周
+        assert_eq!(
+            weeks(2024, 12),
+            ["2024-48", "2024-49", "2024-50", "2024-51", "2024-52", "2025-01"]
+        );
+        // 2021-01-01 为周五，属于2020年第53周
+        assert_eq!(weeks(2021, 1), ["2020-53", "2021-01", "2021-02", "2021-03", "2021-04"]);
+    }
+
+    #[test]
+    fn floor_days_rounds_towards_negative_infinity() {
+        assert_eq!(floor_days(Duration::hours(47)), 1);
+        assert_eq!(floor_days(Duration::days(2)), 2);
+        assert_eq!(floor_days(Duration::hours(-1)), -1);
+        assert_eq!(floor_days(Duration::days(-2)), -2);
+        assert_eq!(floor_days(Duration::hours(-49)), -3);
+    }
+
+    #[test]
+    fn numpy_sum_uses_pairwise_order() {
+        // 逐个相加为 0.9999999999999999，numpy 的求和顺序得到 1.0
+        let values = [0.1; 10];
+        assert_eq!(values.iter().fold(0.0, |acc, v| acc + v), 0.9999999999999999);
+        assert_eq!(numpy_sum(&values), 1.0);
+        assert_eq!(numpy_sum(&[]), 0.0);
+        assert_eq!(numpy_sum(&[2.5]), 2.5);
+
+        // 超过一个块（128个）时分两半递归
+        let values: Vec<f64> = (1..=300).map(f64::from).collect();
+        assert_eq!(numpy_sum(&values), 45150.0);
+    }
+
+    #[test]
+    fn month_timeline_fills_missing_months_across_years() {
+        let counts = BTreeMap::from([("2023-11".to_string(), 2), ("2024-02".to_string(), 1)]);
+        assert_eq!(
+            month_timeline(&counts).unwrap(),
+            vec![
+                ("2023-11".to_string(), 2),
+                ("2023-12".to_string(), 0),
+                ("2024-01".to_string(), 0),
+                ("2024-02".to_string(), 1),
+            ]
+        );
+        assert!(month_timeline(&BTreeMap::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ties_follow_pandas_order() {
+        // idxmax：并列时取排序靠前的键
+        let groups = BTreeMap::from([("b".to_string(), 3), ("a".to_string(), 3), ("c".to_string(), 1)]);
+        assert_eq!(idxmax(groups).as_deref(), Some("a"));
+        assert_eq!(idxmax(BTreeMap::<String, i64>::new()), None);
+
+        // value_counts：并列时按首次出现的顺序，跳过空值
+        let values = [Some("y"), None, Some("x"), Some("x"), Some("z"), Some("y")];
+        assert_eq!(
+            value_counts(values.into_iter()),
+            vec![("y".to_string(), 2), ("x".to_string(), 2), ("z".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn analyzes_records() {
+        let data = vec![
+            record("A", "X", "CFI", Some(10.0), "2024-01-29", Some("2024-02-05")),
+            record("B", "Y", "EFI", Some(5.0), "2024-02-05", Some("2024-02-06 12:00")),
+            record("A", "X", "CFI", Some(10.0), "2024-02-14", None),
+            record("B", "Y", "EFI", None, "2024-02-21", Some("2024-02-28")),
+            record("C", "Y", "CFI", Some(3.5), "2024-02-26", Some("2024-02-27")),
+        ];
+        let resolver = ReaderIdentityResolver::new(Vec::new());
+        let details = analyze_records(&data, &resolver, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()).unwrap();
+
+        assert_eq!(details.borrow_frequency, 5);
+        assert_eq!(details.total_reading_duration, 16);
+        assert_eq!(details.total_price, 28.5);
+        assert_eq!(details.monthly_duration, vec![(2, 9), (1, 7)]);
+        assert_eq!(
+            details.monthly_timeline,
+            vec![("2024-01".to_string(), 1), ("2024-02".to_string(), 4)]
+        );
+        assert_eq!(details.borrow_peak_yearmonth, "2024-02");
+        assert_eq!(details.borrow_peak, 2);
+        assert_eq!(
+            details.book_duration,
+            vec![("A".to_string(), 7), ("B".to_string(), 8), ("C".to_string(), 1)]
+        );
+        assert_eq!(details.book_duration_ratio[0], ("A".to_string(), 43.75));
+        assert_eq!(details.sorted_duration[0], ("B".to_string(), 8));
+        // A 和 B 都借阅了两次，取先出现的 A
+        assert_eq!((details.most_borrowed_book.as_str(), details.most_borrowed_book_count), ("A", 2));
+        assert_eq!(details.longest_author, "Y");
+        assert_eq!(details.most_frequent_author, "Y");
+        // 两个类别的阅读时长都是8天，取排序靠前的类别
+        assert_eq!(details.longest_category, "中文小说");
+        assert_eq!(details.most_frequent_category, "中文小说");
+
+        // 2月的第5周在1月29日有记录，2月仍为全勤；1月只有第5周有记录
+        assert_eq!(details.full_attendance, vec!["2024-02"]);
+        assert_eq!(details.partial_attendance, vec!["2024-01"]);
+        let january = &details.missing_weeks_info["2024-01"];
+        assert_eq!(january.recorded_weeks, vec!["2024-05"]);
+        assert_eq!(january.missing_weeks, vec!["2024-01", "2024-02", "2024-03", "2024-04"]);
+        assert_eq!(details.total_full_attendance, 1);
+    }
+
+    #[test]
+    fn groups_records_by_card_number_then_name() {
+        let mut first = record("A", "X", "CFI", None, "2024-01-01", None);
+        first.card_number = Some(" 001 ".to_string());
+        let mut second = record("B", "X", "CFI", None, "2024-01-02", None);
+        second.name = Some("張三".to_string());
+        second.card_number = Some("001".to_string());
+        let third = record("C", "X", "CFI", None, "2024-01-03", None);
+
+        let groups = group_by_reader(&[first, second, third]);
+        let summary: Vec<(&str, usize)> =
+            groups.iter().map(|group| (group.name.as_str(), group.records.len())).collect();
+        assert_eq!(summary, vec![("张三_001", 2), ("张三", 1)]);
+    }
+
+    #[test]
+    fn data_accessors_require_analysis() {
+        let mut analyzer = RustAnalyzer::new().unwrap();
+        assert!(matches!(
+            analyzer.chart_data().unwrap_err().downcast::<AnalyzerError>(),
+            Ok(AnalyzerError::NotInitialized)
+        ));
+
+        analyzer.initialize().unwrap();
+        let errors = [
+            analyzer.chart_data().map(|_| ()),
+            analyzer.report_data().map(|_| ()),
+            analyzer.analysis_data().map(|_| ()),
+        ];
+        for error in errors {
+            let error = AnalyzerError::classify(error.unwrap_err(), |message| {
+                AnalyzerError::Internal { message }
+            });
+            assert_eq!(error.code(), "data_not_loaded");
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use anyhow::{anyhow, Context, Result};
+use calamine::{open_workbook, Data, Range, Reader, Sheets};
+use encoding_rs::{Encoding, GB18030, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use tracing::warn;
+
+/// 文本文件的字符编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// 带BOM的UTF-8（Excel另存为CSV时的默认格式）
+    Utf8Bom,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// GBK及其超集GB18030
+    Gb18030,
+}
+
+impl TextEncoding {
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8Bom | TextEncoding::Utf8 => UTF_8,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+            TextEncoding::Gb18030 => GB18030,
+        }
+    }
+
+    /// 对应的Python编码名称
+    fn python_codec(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8Bom => "utf-8-sig",
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => "utf-16",
+            TextEncoding::Gb18030 => "gb18030",
+        }
+    }
+}
+
+/// 输入文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Xlsx,
+    Xlsb,
+    Xls,
+    Ods,
+    /// CSV/TSV等分隔符文本
+    Delimited {
+        delimiter: u8,
+        encoding: TextEncoding,
+    },
+}
+
+impl InputFormat {
+    /// 传给Python端 `pd.read_csv` / `pd.read_excel` 的读取参数
+    pub fn pandas_options(&self) -> HashMap<&'static str, String> {
+        let mut options = HashMap::new();
+        match self {
+            InputFormat::Delimited {
+                delimiter,
+                encoding,
+            } => {
+                options.insert("format", "csv".to_string());
+                options.insert("sep", (*delimiter as char).to_string());
+                options.insert("encoding", encoding.python_codec().to_string());
+            }
+            InputFormat::Xlsx => {
+                options.insert("format", "excel".to_string());
+                options.insert("engine", "openpyxl".to_string());
+            }
+            InputFormat::Xlsb => {
+                options.insert("format", "excel".to_string());
+                options.insert("engine", "pyxlsb".to_string());
+            }
+            InputFormat::Xls => {
+                options.insert("format", "excel".to_string());
+                options.insert("engine", "xlrd".to_string());
+            }
+            InputFormat::Ods => {
+                options.insert("format", "excel".to_string());
+                options.insert("engine", "odf".to_string());
+            }
+        }
+        options
+    }
+}
+
+/// OLE复合文档（.xls）的文件头
+const OLE_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+/// ZIP文件头（.xlsx/.xlsb/.ods）
+const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+/// ODS文件中第一个未压缩的 `mimetype` 条目
+const ODS_MIMETYPE: &[u8] = b"mimetypeapplication/vnd.oasis.opendocument.spreadsheet";
+/// 文本格式嗅探读取的字节数
+const SNIFF_LEN: usize = 64 * 1024;
+
+/// 根据文件内容判断格式，内容无法区分时参考扩展名。
+///
+/// 部分系统导出的 `.xls` 实际上是文本文件，因此以文件头为准。
+pub fn detect_format(path: &str) -> Result<InputFormat> {
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    File::open(path)
+        .with_context(|| format!("无法打开文件: {}", path))?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .with_context(|| format!("无法读取文件: {}", path))?;
+    let extension = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    if head.starts_with(&OLE_MAGIC) {
+        return Ok(InputFormat::Xls);
+    }
+    if head.starts_with(&ZIP_MAGIC) {
+        if head
+            .windows(ODS_MIMETYPE.len())
+            .take(128)
+            .any(|window| window == ODS_MIMETYPE)
+        {
+            return Ok(InputFormat::Ods);
+        }
+        return Ok(if extension == "xlsb" {
+            InputFormat::Xlsb
+        } else {
+            InputFormat::Xlsx
+        });
+    }
+
+    let encoding = detect_encoding(&head);
+    let (text, _) = encoding.encoding().decode_without_bom_handling(strip_bom(&head, encoding));
+    Ok(InputFormat::Delimited {
+        delimiter: detect_delimiter(&text, &extension),
+        encoding,
+    })
+}
+
+/// BOM优先；否则能按UTF-8解码的视为UTF-8，其余按GB18030处理
+fn detect_encoding(head: &[u8]) -> TextEncoding {
+    if head.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return TextEncoding::Utf8Bom;
+    }
+    if head.starts_with(&[0xFF, 0xFE]) {
+        return TextEncoding::Utf16Le;
+    }
+    if head.starts_with(&[0xFE, 0xFF]) {
+        return TextEncoding::Utf16Be;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => TextEncoding::Utf8,
+        // 嗅探长度截断在多字节字符中间时仍视为UTF-8
+        Err(e) if e.error_len().is_none() => TextEncoding::Utf8,
+        Err(_) => TextEncoding::Gb18030,
+    }
+}
+
+fn strip_bom(bytes: &[u8], encoding: TextEncoding) -> &[u8] {
+    let bom_len = match encoding {
+        TextEncoding::Utf8Bom => 3,
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => 2,
+        TextEncoding::Utf8 | TextEncoding::Gb18030 => 0,
+    };
+    &bytes[bom_len.min(bytes.len())..]
+}
+
+/// 扩展名为 `.tsv` 时使用制表符，`.csv` 时使用逗号，否则按表头行中出现次数最多的分隔符
+fn detect_delimiter(text: &str, extension: &str) -> u8 {
+    match extension {
+        "tsv" | "tab" => return b'\t',
+        "csv" => return b',',
+        _ => {}
+    }
+
+    let header = text.lines().next().unwrap_or_default();
+    [b'\t', b',', b';', b'|']
+        .into_iter()
+        .max_by_key(|&delimiter| header.matches(delimiter as char).count())
+        .filter(|&delimiter| header.contains(delimiter as char))
+        .unwrap_or(b',')
+}
+
+/// 工作表名称及其单元格
+pub struct Sheet {
+    /// 文本文件没有工作表名称，使用文件名（不含扩展名）
+    pub name: String,
+    pub range: Range<Data>,
+}
+
+/// 未单独指定工作表的文件读取哪些工作表
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetScope {
+    /// 只读取第一个工作表（与 `pd.read_excel(path)` 一致）
+    #[default]
+    First,
+    /// 读取所有非空工作表
+    All,
+}
+
+/// 借阅数据文件的工作表选择
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SheetSelection {
+    pub scope: SheetScope,
+    /// 按文件路径指定要读取的工作表名称，优先于 `scope`
+    pub files: HashMap<String, Vec<String>>,
+}
+
+/// 一个待分析的工作表，`get_file_count`/`get_file_name` 按条目计数
+#[derive(Debug, Clone, Serialize)]
+pub struct SheetEntry {
+    pub path: String,
+    /// 工作表名称，文本文件为 `None`
+    pub sheet: Option<String>,
+    /// 显示名称：文件只取一个工作表时为文件名，否则为 `文件名_工作表名`
+    pub name: String,
+}
+
+type Workbook = Sheets<BufReader<File>>;
+
+/// 打开Excel/ODS工作簿，文本文件返回 `None`
+fn open_excel(path: &str, format: InputFormat) -> Result<Option<Workbook>> {
+    let context = || format!("无法打开Excel文件: {}", path);
+    let workbook = match format {
+        InputFormat::Xlsx => Sheets::Xlsx(open_workbook(path).with_context(context)?),
+        InputFormat::Xlsb => Sheets::Xlsb(open_workbook(path).with_context(context)?),
+        InputFormat::Xls => Sheets::Xls(open_workbook(path).with_context(context)?),
+        InputFormat::Ods => Sheets::Ods(open_workbook(path).with_context(context)?),
+        InputFormat::Delimited { .. } => return Ok(None),
+    };
+    Ok(Some(workbook))
+}
+
+/// 列出文件中的工作表名称，文本文件没有工作表，返回空列表
+pub fn sheet_names(path: &str) -> Result<Vec<String>> {
+    Ok(open_excel(path, detect_format(path)?)?
+        .map(|workbook| workbook.sheet_names())
+        .unwrap_or_default())
+}
+
+/// 读取指定的工作表，`sheet` 为 `None` 时读取第一个工作表；文本文件忽略 `sheet`
+pub fn read_sheet(path: &str, sheet: Option<&str>) -> Result<Sheet> {
+    let format = detect_format(path)?;
+    let Some(mut workbook) = open_excel(path, format)? else {
+        let InputFormat::Delimited {
+            delimiter,
+            encoding,
+        } = format
+        else {
+            unreachable!("只有文本文件没有工作簿");
+        };
+        return Ok(Sheet {
+            name: file_stem(path),
+            range: read_delimited(path, delimiter, encoding)?,
+        });
+    };
+
+    let names = workbook.sheet_names();
+    let name = match sheet {
+        Some(sheet) if names.iter().any(|name| name == sheet) => sheet.to_string(),
+        Some(sheet) => return Err(anyhow!("文件 {} 中没有工作表 {}", path, sheet)),
+        None => names
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("Excel文件中没有工作表: {}", path))?,
+    };
+    let range = workbook
+        .worksheet_range(&name)
+        .with_context(|| format!("读取工作表失败: {} [{}]", path, name))?;
+    Ok(Sheet { name, range })
+}
+
+/// 读取文件的第一个工作表（文本文件视为只有一个工作表），对应 `pd.read_excel(path)`
+pub fn read_first_sheet(path: &str) -> Result<Sheet> {
+    read_sheet(path, None)
+}
+
+/// 按工作表选择展开借阅数据文件，每个工作表一个条目
+pub fn resolve_entries(paths: &[String], selection: &SheetSelection) -> Result<Vec<SheetEntry>> {
+    let mut entries = Vec::new();
+
+    for path in paths {
+        let stem = file_stem(path);
+        let Some(mut workbook) = open_excel(path, detect_format(path)?)? else {
+            entries.push(SheetEntry {
+                path: path.clone(),
+                sheet: None,
+                name: stem,
+            });
+            continue;
+        };
+
+        let names = workbook.sheet_names();
+        let sheets: Vec<String> = match (selection.files.get(path), selection.scope) {
+            (Some(chosen), _) => {
+                if let Some(missing) = chosen.iter().find(|sheet| !names.contains(sheet)) {
+                    return Err(anyhow!("文件 {} 中没有工作表 {}", path, missing));
+                }
+                chosen.clone()
+            }
+            (None, SheetScope::First) => names.into_iter().take(1).collect(),
+            (None, SheetScope::All) => names
+                .into_iter()
+                .filter(|name| {
+                    workbook
+                        .worksheet_range(name)
+                        .map(|range| range.used_cells().next().is_some())
+                        .unwrap_or(true)
+                })
+                .collect(),
+        };
+        if sheets.is_empty() {
+            return Err(anyhow!("文件 {} 中没有可读取的工作表", path));
+        }
+
+        let single = sheets.len() == 1;
+        entries.extend(sheets.into_iter().map(|sheet| SheetEntry {
+            path: path.clone(),
+            name: if single {
+                stem.clone()
+            } else {
+                format!("{}_{}", stem, sheet)
+            },
+            sheet: Some(sheet),
+        }));
+    }
+
+    Ok(entries)
+}
+
+/// 提取文件名（不含扩展名）
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// 读取分隔符文本文件，所有单元格按文本保存，空单元格为 `Data::Empty`
+fn read_delimited(path: &str, delimiter: u8, encoding: TextEncoding) -> Result<Range<Data>> {
+    let bytes = std::fs::read(path).with_context(|| format!("无法读取文件: {}", path))?;
+    let (text, had_errors) = encoding
+        .encoding()
+        .decode_without_bom_handling(strip_bom(&bytes, encoding));
+    if had_errors {
+        warn!("文件 {} 中有无法按 {:?} 解码的字符，已替换", path, encoding);
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut rows: Vec<Vec<Data>> = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record =
+            record.with_context(|| format!("文件 {} 第 {} 行格式错误", path, index + 1))?;
+        rows.push(
+            record
+                .iter()
+                .map(|field| match field.trim() {
+                    "" => Data::Empty,
+                    value => Data::String(value.to_string()),
+                })
+                .collect(),
+        );
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if rows.is_empty() || width == 0 {
+        return Ok(Range::empty());
+    }
+
+    let mut range = Range::new((0, 0), (rows.len() as u32 - 1, width as u32 - 1));
+    for (row_index, row) in rows.into_iter().enumerate() {
+        for (column_index, value) in row.into_iter().enumerate() {
+            range.set_value((row_index as u32, column_index as u32), value);
+        }
+    }
+    Ok(range)
+}
+
+/// 读取第一个工作表的表头行
+pub fn read_headers(path: &str) -> Result<Vec<String>> {
+    read_sheet_headers(path, None)
+}
+
+/// 读取指定工作表的表头行
+pub fn read_sheet_headers(path: &str, sheet: Option<&str>) -> Result<Vec<String>> {
+    Ok(read_sheet(path, sheet)?
+        .range
+        .rows()
+        .next()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("readerreport-spreadsheet-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn cells(path: &Path) -> Vec<Vec<String>> {
+        read_sheet(path.to_str().unwrap(), None)
+            .unwrap()
+            .range
+            .rows()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn detects_bom_and_gbk_encodings() {
+        assert_eq!(detect_encoding("姓名,书名".as_bytes()), TextEncoding::Utf8);
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFname"), TextEncoding::Utf8Bom);
+        assert_eq!(detect_encoding(b"\xFF\xFEn\0"), TextEncoding::Utf16Le);
+        let (gbk, _, _) = GB18030.encode("姓名,书名");
+        assert_eq!(detect_encoding(&gbk), TextEncoding::Gb18030);
+        // 嗅探长度截断在多字节字符中间
+        let utf8 = "姓名".as_bytes();
+        assert_eq!(detect_encoding(&utf8[..utf8.len() - 1]), TextEncoding::Utf8);
+    }
+
+    #[test]
+    fn detects_delimiters() {
+        assert_eq!(detect_delimiter("a;b;c", "tsv"), b'\t');
+        assert_eq!(detect_delimiter("a\tb\tc", "csv"), b',');
+        assert_eq!(detect_delimiter("a\tb\tc,d", "txt"), b'\t');
+        assert_eq!(detect_delimiter("a|b|c", "xls"), b'|');
+        assert_eq!(detect_delimiter("abc", "txt"), b',');
+    }
+
+    #[test]
+    fn reads_gbk_and_utf8_bom_text_files() {
+        let (gbk, _, _) = GB18030.encode("姓名,书名\n张三,红楼梦\n");
+        let path = temp_file("gbk.csv", &gbk);
+        assert_eq!(
+            detect_format(path.to_str().unwrap()).unwrap(),
+            InputFormat::Delimited { delimiter: b',', encoding: TextEncoding::Gb18030 }
+        );
+        assert_eq!(cells(&path), vec![vec!["姓名", "书名"], vec!["张三", "红楼梦"]]);
+
+        let path = temp_file("bom.tsv", "\u{FEFF}姓名\t书名\n李四\t\n".as_bytes());
+        assert_eq!(
+            detect_format(path.to_str().unwrap()).unwrap(),
+            InputFormat::Delimited { delimiter: b'\t', encoding: TextEncoding::Utf8Bom }
+        );
+        assert_eq!(cells(&path), vec![vec!["姓名", "书名"], vec!["李四", ""]]);
+        assert_eq!(read_headers(path.to_str().unwrap()).unwrap(), vec!["姓名", "书名"]);
+    }
+
+    #[test]
+    fn text_disguised_as_xls_is_read_as_text() {
+        let path = temp_file("export.xls", "姓名\t书名\n张三\t红楼梦\n".as_bytes());
+        assert_eq!(
+            detect_format(path.to_str().unwrap()).unwrap(),
+            InputFormat::Delimited { delimiter: b'\t', encoding: TextEncoding::Utf8 }
+        );
+        assert_eq!(cells(&path)[1], vec!["张三", "红楼梦"]);
+    }
+}
@@ -0,0 +1,1199 @@
+//! 报告模板：Tera 风格的模板语法的一个子集。
+//!
+//! - `{{ 表达式 }}` 输出值，`{# 注释 #}`；`{%-`、`-%}`、`{{-`、`-}}` 去掉标签一侧的空白
+//! - `{% if %}`/`{% elif %}`/`{% else %}`/`{% endif %}`，`{% for x in 列表 %}`/`{% for k, v in 对象 %}`/`{% endfor %}`
+//!   （循环中可以使用 `loop.index`、`loop.index0`、`loop.first`、`loop.last`、`loop.length`），`{% set x = 表达式 %}`
+//! - 表达式：字面量、变量（`a.b`、`a.0`、`a["b"]`）、`+ - * / %`、`~`（字符串拼接）、比较、`in`、`and`/`or`/`not`
+//! - 过滤器：`escape`、`safe`、`default(value=)`、`join(sep=)`、`length`、`upper`、`lower`、`trim`、`first`、`last`、
+//!   `replace(from=, to=)`、`truncate(length=)`、`round(precision=)`、`fixed(precision=)`（保留固定位数的小数）
+//!
+//! 输出时按模板格式自动转义：`.html` 模板转义HTML特殊字符，`.md` 模板转义Markdown特殊字符，`safe` 过滤器取消转义。
+//!
+//! 没有使用 `tera` 库本身：它只提供HTML转义，而Markdown模板的渲染结果还要解析为Word/PDF的表格和标题，
+//! 插入的值必须按Markdown转义（包括行首的列表、标题标记），否则书名中的 `|`、`#` 会改变报告结构；
+//! 错误信息也需要中文的行号提示。报告模板只用到上面这些功能，自己实现的代码量不大，也不增加依赖。
+//! 语法保持为Tera的严格子集，用户模板以后可以直接换用 `tera` 渲染。
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{Map, Number, Value};
+use std::path::Path;
+
+/// 输出时的自动转义方式，由模板文件扩展名决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    Html,
+    Markdown,
+    None,
+}
+
+impl Escape {
+    fn from_name(name: &str) -> Self {
+        let extension = Path::new(name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "html" | "htm" => Escape::Html,
+            "md" => Escape::Markdown,
+            _ => Escape::None,
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Escape::Html => {
+                let mut escaped = String::with_capacity(text.len());
+                for c in text.chars() {
+                    match c {
+                        '&' => escaped.push_str("&amp;"),
+                        '<' => escaped.push_str("&lt;"),
+                        '>' => escaped.push_str("&gt;"),
+                        '"' => escaped.push_str("&quot;"),
+                        '\'' => escaped.push_str("&#39;"),
+                        c => escaped.push(c),
+                    }
+                }
+                escaped
+            }
+            Escape::Markdown => {
+                // 值位于行首时，开头的 `-`、`+`、`=`、`~` 和 `1.`、`1)` 会被解析为列表、分隔线或标题下划线
+                let start = text.len() - text.trim_start().len();
+                let digits = text[start..].chars().take_while(char::is_ascii_digit).count();
+                let mut escaped = String::with_capacity(text.len());
+                for (index, c) in text.char_indices() {
+                    let block_marker = (index == start && matches!(c, '-' | '+' | '=' | '~'))
+                        || (digits > 0 && index == start + digits && matches!(c, '.' | ')'));
+                    if block_marker
+                        || matches!(c, '\\' | '|' | '*' | '_' | '`' | '#' | '[' | ']' | '<' | '>' | '!')
+                    {
+                        escaped.push('\\');
+                    }
+                    // 换行会破坏表格和标题，替换为空格
+                    escaped.push(if c == '\n' { ' ' } else { c });
+                }
+                escaped
+            }
+            Escape::None => text.to_string(),
+        }
+    }
+}
+
+/// 解析后的模板
+#[derive(Debug, Clone)]
+pub struct Template {
+    name: String,
+    escape: Escape,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Output(Expr, usize),
+    If {
+        branches: Vec<(Expr, Vec<Node>)>,
+        otherwise: Vec<Node>,
+        line: usize,
+    },
+    For {
+        key: Option<String>,
+        value: String,
+        iterable: Expr,
+        body: Vec<Node>,
+        line: usize,
+    },
+    Set(String, Expr, usize),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Variable(String),
+    List(Vec<Expr>),
+    /// `a.b`、`a.0`、`a["b"]`
+    Attribute(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Filter {
+        input: Box<Expr>,
+        name: String,
+        args: Vec<(String, Expr)>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    In,
+    Concat,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+impl Template {
+    /// 解析模板，`name` 用于错误信息和决定自动转义方式
+    pub fn parse(name: &str, source: &str) -> Result<Self> {
+        let segments = split(source).with_context(|| format!("模板 {} 解析失败", name))?;
+        let mut parser = Parser {
+            segments: segments.into_iter(),
+        };
+        let (nodes, _) = parser
+            .nodes(&[])
+            .with_context(|| format!("模板 {} 解析失败", name))?;
+        Ok(Self {
+            name: name.to_string(),
+            escape: Escape::from_name(name),
+            nodes,
+        })
+    }
+
+    /// 从文件读取并解析模板
+    pub fn load(path: &Path) -> Result<Self> {
+        let source =
+            std::fs::read_to_string(path).with_context(|| format!("无法读取模板: {:?}", path))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self::parse(&name, &source)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 用 `context`（JSON对象）中的变量渲染模板
+    pub fn render(&self, context: &Value) -> Result<String> {
+        let mut renderer = Renderer {
+            escape: self.escape,
+            globals: context,
+            scopes: vec![Map::new()],
+            output: String::new(),
+        };
+        renderer
+            .nodes(&self.nodes)
+            .with_context(|| format!("模板 {} 渲染失败", self.name))?;
+        Ok(renderer.output)
+    }
+}
+
+/// 模板源码中的片段
+enum Segment {
+    Text(String),
+    Output(String, usize),
+    Tag(String, usize),
+}
+
+/// 把模板源码拆分为文本、`{{ }}` 和 `{% %}`，去掉注释并处理空白控制
+fn split(source: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+    let mut trim_next = false;
+    loop {
+        let Some(start) = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|open| rest.find(open))
+            .min()
+        else {
+            let text = if trim_next { rest.trim_start() } else { rest };
+            if !text.is_empty() {
+                segments.push(Segment::Text(text.to_string()));
+            }
+            return Ok(segments);
+        };
+
+        let open = &rest[start..start + 2];
+        let close = match open {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let body_start = start + 2;
+        let Some(length) = rest[body_start..].find(close) else {
+            bail!("第 {} 行: {} 没有对应的 {}", line + rest[..start].matches('\n').count(), open, close);
+        };
+        let body = &rest[body_start..body_start + length];
+        let trim_before = body.starts_with('-');
+        let trim_after = body.ends_with('-') && body.len() > 1;
+
+        let mut text = &rest[..start];
+        if trim_next {
+            text = text.trim_start();
+        }
+        if trim_before {
+            text = text.trim_end();
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text.to_string()));
+        }
+        line += rest[..start].matches('\n').count();
+
+        let inner = body.strip_prefix('-').unwrap_or(body);
+        let inner = if trim_after { &inner[..inner.len() - 1] } else { inner };
+        match open {
+            "{{" => segments.push(Segment::Output(inner.trim().to_string(), line)),
+            "{%" => segments.push(Segment::Tag(inner.trim().to_string(), line)),
+            _ => {}
+        }
+        line += body.matches('\n').count();
+        trim_next = trim_after;
+        rest = &rest[body_start + length + 2..];
+    }
+}
+
+/// 结束一段节点的标签（标签内容，行号）
+type EndTag = Option<(String, usize)>;
+
+struct Parser {
+    segments: std::vec::IntoIter<Segment>,
+}
+
+impl Parser {
+    /// 解析到 `ends` 中的某个标签（或模板末尾）为止，返回节点和结束标签
+    fn nodes(&mut self, ends: &[&str]) -> Result<(Vec<Node>, EndTag)> {
+        let mut nodes = Vec::new();
+        while let Some(segment) = self.segments.next() {
+            match segment {
+                Segment::Text(text) => nodes.push(Node::Text(text)),
+                Segment::Output(source, line) => {
+                    nodes.push(Node::Output(parse_expr(&source, line)?, line))
+                }
+                Segment::Tag(tag, line) => {
+                    let keyword = tag.split_whitespace().next().unwrap_or_default();
+                    if ends.contains(&keyword) {
+                        return Ok((nodes, Some((tag, line))));
+                    }
+                    nodes.push(self.tag(&tag, keyword, line)?);
+                }
+            }
+        }
+        if !ends.is_empty() {
+            bail!("模板结束前缺少 {{% {} %}}", ends.last().unwrap());
+        }
+        Ok((nodes, None))
+    }
+
+    fn tag(&mut self, tag: &str, keyword: &str, line: usize) -> Result<Node> {
+        let rest = tag[keyword.len()..].trim();
+        match keyword {
+            "if" => {
+                let mut branches = vec![];
+                let mut condition = parse_expr(rest, line)?;
+                loop {
+                    let (body, end) = self.nodes(&["elif", "else", "endif"])?;
+                    let (end, end_line) = end.unwrap();
+                    branches.push((condition, body));
+                    if let Some(next) = end.strip_prefix("elif") {
+                        condition = parse_expr(next.trim(), end_line)?;
+                        continue;
+                    }
+                    let otherwise = if end == "else" {
+                        self.nodes(&["endif"])?.0
+                    } else {
+                        Vec::new()
+                    };
+                    return Ok(Node::If {
+                        branches,
+                        otherwise,
+                        line,
+                    });
+                }
+            }
+            "for" => {
+                let (names, iterable) = rest
+                    .split_once(" in ")
+                    .ok_or_else(|| anyhow!("第 {} 行: for 标签应为 {{% for x in 列表 %}}", line))?;
+                let names: Vec<&str> = names.split(',').map(str::trim).collect();
+                let (key, value) = match names.as_slice() {
+                    [value] => (None, value.to_string()),
+                    [key, value] => (Some(key.to_string()), value.to_string()),
+                    _ => bail!("第 {} 行: for 标签最多有两个变量", line),
+                };
+                for name in key.iter().chain([&value]) {
+                    if !is_identifier(name) {
+                        bail!("第 {} 行: 无效的变量名 {:?}", line, name);
+                    }
+                }
+                let iterable = parse_expr(iterable, line)?;
+                let (body, _) = self.nodes(&["endfor"])?;
+                Ok(Node::For {
+                    key,
+                    value,
+                    iterable,
+                    body,
+                    line,
+                })
+            }
+            "set" => {
+                let (name, expr) = rest
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("第 {} 行: set 标签应为 {{% set x = 值 %}}", line))?;
+                let name = name.trim();
+                if !is_identifier(name) {
+                    bail!("第 {} 行: 无效的变量名 {:?}", line, name);
+                }
+                Ok(Node::Set(name.to_string(), parse_expr(expr, line)?, line))
+            }
+            "elif" | "else" | "endif" | "endfor" => bail!("第 {} 行: 多余的 {{% {} %}}", line, tag),
+            _ => bail!("第 {} 行: 未知的标签 {{% {} %}}", line, tag),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Literal(Value),
+    Symbol(&'static str),
+}
+
+/// 运算符和标点，较长的在前；`=` 只用于过滤器参数
+const SYMBOLS: [&str; 20] = [
+    "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "~", "|", "(", ")", ",", ".", "[", "]", "=",
+];
+
+fn tokenize(source: &str, line: usize) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        if c.is_whitespace() {
+            index += 1;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = index;
+            while index < chars.len() && (chars[index].is_ascii_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            let word: String = chars[start..index].iter().collect();
+            tokens.push(match word.as_str() {
+                "true" => Token::Literal(Value::Bool(true)),
+                "false" => Token::Literal(Value::Bool(false)),
+                "none" | "null" => Token::Literal(Value::Null),
+                _ => Token::Identifier(word),
+            });
+        } else if c.is_ascii_digit() {
+            let start = index;
+            while index < chars.len()
+                && (chars[index].is_ascii_digit()
+                    || (chars[index] == '.' && chars.get(index + 1).is_some_and(char::is_ascii_digit)))
+            {
+                index += 1;
+            }
+            let number: String = chars[start..index].iter().collect();
+            let value = match number.parse::<i64>() {
+                Ok(integer) => Value::from(integer),
+                Err(_) => number
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map(Value::Number)
+                    .ok_or_else(|| anyhow!("第 {} 行: 无效的数字 {}", line, number))?,
+            };
+            tokens.push(Token::Literal(value));
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            index += 1;
+            loop {
+                match chars.get(index) {
+                    None => bail!("第 {} 行: 字符串缺少结束的引号", line),
+                    Some(&quote) if quote == c => break,
+                    Some('\\') if chars.get(index + 1).is_some() => {
+                        index += 1;
+                        text.push(match chars[index] {
+                            'n' => '\n',
+                            't' => '\t',
+                            other => other,
+                        });
+                    }
+                    Some(&other) => text.push(other),
+                }
+                index += 1;
+            }
+            index += 1;
+            tokens.push(Token::Literal(Value::String(text)));
+        } else {
+            let rest: String = chars[index..chars.len().min(index + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(**symbol))
+                .ok_or_else(|| anyhow!("第 {} 行: 无法识别的字符 {:?}", line, c))?;
+            tokens.push(Token::Symbol(symbol));
+            index += symbol.chars().count();
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_expr(source: &str, line: usize) -> Result<Expr> {
+    let mut parser = ExprParser {
+        tokens: tokenize(source, line)?,
+        position: 0,
+        line,
+    };
+    let expr = parser.or()?;
+    if parser.position < parser.tokens.len() {
+        bail!("第 {} 行: 表达式 {:?} 中有多余的内容", line, source);
+    }
+    Ok(expr)
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    position: usize,
+    line: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Identifier(word)) if word == keyword) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            bail!("第 {} 行: 缺少 {:?}", self.line, symbol)
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => bail!("第 {} 行: 缺少名称", self.line),
+        }
+    }
+
+    fn binary(&mut self, ops: &[(&str, BinaryOp)], next: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        let mut left = next(self)?;
+        'outer: loop {
+            for (text, op) in ops {
+                let matched = if text.chars().all(|c| c.is_ascii_alphabetic()) {
+                    self.eat_keyword(text)
+                } else {
+                    self.eat_symbol(text)
+                };
+                if matched {
+                    let right = next(self)?;
+                    left = Expr::Binary(*op, Box::new(left), Box::new(right));
+                    continue 'outer;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        self.binary(&[("or", BinaryOp::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        self.binary(&[("and", BinaryOp::And)], Self::not)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        self.binary(
+            &[
+                ("==", BinaryOp::Equal),
+                ("!=", BinaryOp::NotEqual),
+                ("<=", BinaryOp::LessEqual),
+                (">=", BinaryOp::GreaterEqual),
+                ("<", BinaryOp::Less),
+                (">", BinaryOp::Greater),
+                ("in", BinaryOp::In),
+            ],
+            Self::concat,
+        )
+    }
+
+    fn concat(&mut self) -> Result<Expr> {
+        self.binary(&[("~", BinaryOp::Concat)], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<Expr> {
+        self.binary(
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+            Self::multiplicative,
+        )
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr> {
+        self.binary(
+            &[
+                ("*", BinaryOp::Multiply),
+                ("/", BinaryOp::Divide),
+                ("%", BinaryOp::Modulo),
+            ],
+            Self::filtered,
+        )
+    }
+
+    fn filtered(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.eat_symbol("|") {
+            let name = self.identifier()?;
+            let mut args = Vec::new();
+            if self.eat_symbol("(") {
+                while !self.eat_symbol(")") {
+                    let arg = self.identifier()?;
+                    if !self.eat_symbol("=") {
+                        bail!("第 {} 行: 过滤器参数应为 name=值", self.line);
+                    }
+                    args.push((arg, self.or()?));
+                    if !self.eat_symbol(",") {
+                        self.expect_symbol(")")?;
+                        break;
+                    }
+                }
+            }
+            expr = Expr::Filter {
+                input: Box::new(expr),
+                name,
+                args,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat_symbol("-") {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat_symbol(".") {
+                let key = match self.peek().cloned() {
+                    Some(Token::Identifier(name)) => Value::String(name),
+                    Some(Token::Literal(value @ Value::Number(_))) => value,
+                    _ => bail!("第 {} 行: \".\" 后缺少属性名", self.line),
+                };
+                self.position += 1;
+                expr = Expr::Attribute(Box::new(expr), Box::new(Expr::Literal(key)));
+            } else if self.eat_symbol("[") {
+                let key = self.or()?;
+                self.expect_symbol("]")?;
+                expr = Expr::Attribute(Box::new(expr), Box::new(key));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| anyhow!("第 {} 行: 表达式不完整", self.line))?;
+        self.position += 1;
+        match token {
+            Token::Literal(value) => Ok(Expr::Literal(value)),
+            Token::Identifier(name) => Ok(Expr::Variable(name)),
+            Token::Symbol("(") => {
+                let expr = self.or()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            Token::Symbol("[") => {
+                let mut items = Vec::new();
+                while !self.eat_symbol("]") {
+                    items.push(self.or()?);
+                    if !self.eat_symbol(",") {
+                        self.expect_symbol("]")?;
+                        break;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            Token::Symbol(symbol) => bail!("第 {} 行: 意外的 {:?}", self.line, symbol),
+        }
+    }
+}
+
+/// 渲染结果中的值：`safe` 表示输出时不再转义
+struct Rendered {
+    value: Value,
+    safe: bool,
+}
+
+impl From<Value> for Rendered {
+    fn from(value: Value) -> Self {
+        Self { value, safe: false }
+    }
+}
+
+struct Renderer<'a> {
+    escape: Escape,
+    globals: &'a Value,
+    /// 循环和 `set` 定义的局部变量，后面的作用域优先
+    scopes: Vec<Map<String, Value>>,
+    output: String,
+}
+
+impl Renderer<'_> {
+    fn nodes(&mut self, nodes: &[Node]) -> Result<()> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.output.push_str(text),
+                Node::Output(expr, line) => {
+                    let rendered = self.eval(expr).with_context(|| format!("第 {} 行", line))?;
+                    let text = display(&rendered.value);
+                    if rendered.safe {
+                        self.output.push_str(&text);
+                    } else {
+                        self.output.push_str(&self.escape.apply(&text));
+                    }
+                }
+                Node::If {
+                    branches,
+                    otherwise,
+                    line,
+                } => {
+                    let mut matched = None;
+                    for (condition, body) in branches {
+                        let value = self.eval(condition).with_context(|| format!("第 {} 行", line))?;
+                        if truthy(&value.value) {
+                            matched = Some(body);
+                            break;
+                        }
+                    }
+                    self.nodes(matched.unwrap_or(otherwise))?;
+                }
+                Node::For {
+                    key,
+                    value,
+                    iterable,
+                    body,
+                    line,
+                } => {
+                    let items: Vec<(Value, Value)> =
+                        match self.eval(iterable).with_context(|| format!("第 {} 行", line))?.value {
+                            Value::Array(items) => items
+                                .into_iter()
+                                .enumerate()
+                                .map(|(index, item)| (Value::from(index), item))
+                                .collect(),
+                            Value::Object(map) => map
+                                .into_iter()
+                                .map(|(key, item)| (Value::String(key), item))
+                                .collect(),
+                            Value::Null => Vec::new(),
+                            other => bail!("第 {} 行: 不能遍历 {}", line, type_name(&other)),
+                        };
+                    let length = items.len();
+                    for (index, (item_key, item)) in items.into_iter().enumerate() {
+                        let mut scope = Map::new();
+                        if let Some(key) = key {
+                            scope.insert(key.clone(), item_key);
+                        }
+                        scope.insert(value.clone(), item);
+                        scope.insert(
+                            "loop".to_string(),
+                            serde_json::json!({
+                                "index": index + 1,
+                                "index0": index,
+                                "first": index == 0,
+                                "last": index + 1 == length,
+                                "length": length,
+                            }),
+                        );
+                        self.scopes.push(scope);
+                        let result = self.nodes(body);
+                        self.scopes.pop();
+                        result?;
+                    }
+                }
+                Node::Set(name, expr, line) => {
+                    let value = self.eval(expr).with_context(|| format!("第 {} 行", line))?.value;
+                    self.scopes.last_mut().unwrap().insert(name.clone(), value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Option<Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.globals.get(name))
+            .cloned()
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Rendered> {
+        Ok(match expr {
+            Expr::Literal(value) => value.clone().into(),
+            Expr::Variable(name) => self
+                .lookup(name)
+                .ok_or_else(|| anyhow!("变量 {} 未定义", name))?
+                .into(),
+            Expr::List(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.eval(item).map(|rendered| rendered.value))
+                    .collect::<Result<_>>()?,
+            )
+            .into(),
+            Expr::Attribute(target, key) => {
+                let target = self.eval(target)?.value;
+                let key = self.eval(key)?.value;
+                attribute(&target, &key)
+                    .ok_or_else(|| anyhow!("{}没有属性 {}", type_name(&target), display(&key)))?
+                    .into()
+            }
+            Expr::Not(inner) => Value::Bool(!truthy(&self.eval(inner)?.value)).into(),
+            Expr::Negate(inner) => {
+                arithmetic(BinaryOp::Subtract, &Value::from(0), &self.eval(inner)?.value)?.into()
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left)?.value;
+                // `and`/`or` 短路求值
+                match op {
+                    BinaryOp::And if !truthy(&left) => return Ok(Value::Bool(false).into()),
+                    BinaryOp::Or if truthy(&left) => return Ok(Value::Bool(true).into()),
+                    _ => {}
+                }
+                let right = self.eval(right)?.value;
+                binary(*op, &left, &right)?.into()
+            }
+            Expr::Filter { input, name, args } => {
+                let input = match (name.as_str(), self.eval(input)) {
+                    // 输入未定义时使用默认值
+                    ("default", Err(_)) => Value::Null.into(),
+                    (_, input) => input?,
+                };
+                let args = args
+                    .iter()
+                    .map(|(name, expr)| Ok((name.as_str(), self.eval(expr)?.value)))
+                    .collect::<Result<Vec<_>>>()?;
+                self.filter(name, input, &args)
+                    .with_context(|| format!("过滤器 {} 执行失败", name))?
+            }
+        })
+    }
+
+    fn filter(&self, name: &str, input: Rendered, args: &[(&str, Value)]) -> Result<Rendered> {
+        let arg = |key: &str| args.iter().find(|(name, _)| *name == key).map(|(_, value)| value);
+        let required = |key: &str| arg(key).ok_or_else(|| anyhow!("缺少参数 {}", key));
+        let value = input.value;
+        let text = || display(&value);
+        Ok(match name {
+            "safe" => Rendered { value, safe: true },
+            "escape" | "e" => Rendered {
+                value: Value::String(self.escape.apply(&text())),
+                safe: true,
+            },
+            "default" => {
+                if value.is_null() {
+                    Rendered {
+                        value: required("value")?.clone(),
+                        safe: input.safe,
+                    }
+                } else {
+                    Rendered { value, safe: input.safe }
+                }
+            }
+            "join" => {
+                let separator = arg("sep").map(display).unwrap_or_default();
+                match &value {
+                    Value::Array(items) => {
+                        Value::String(items.iter().map(display).collect::<Vec<_>>().join(&separator)).into()
+                    }
+                    other => bail!("不能连接 {}", type_name(other)),
+                }
+            }
+            "length" => Value::from(match &value {
+                Value::Array(items) => items.len(),
+                Value::Object(map) => map.len(),
+                Value::String(text) => text.chars().count(),
+                other => bail!("{} 没有长度", type_name(other)),
+            })
+            .into(),
+            "upper" => Value::String(text().to_uppercase()).into(),
+            "lower" => Value::String(text().to_lowercase()).into(),
+            "trim" => Value::String(text().trim().to_string()).into(),
+            "first" | "last" => match &value {
+                Value::Array(items) => {
+                    let item = if name == "first" { items.first() } else { items.last() };
+                    item.cloned().unwrap_or(Value::Null).into()
+                }
+                other => bail!("{} 不是列表", type_name(other)),
+            },
+            "replace" => Value::String(
+                text().replace(&display(required("from")?), &display(required("to")?)),
+            )
+            .into(),
+            "truncate" => {
+                let length = required("length")?
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("length 应为非负整数"))? as usize;
+                let text = text();
+                if text.chars().count() > length {
+                    Value::String(text.chars().take(length).collect::<String>() + "…").into()
+                } else {
+                    Value::String(text).into()
+                }
+            }
+            "round" | "fixed" => {
+                let number = value
+                    .as_f64()
+                    .ok_or_else(|| anyhow!("{} 不是数字", type_name(&value)))?;
+                let precision = match arg("precision") {
+                    Some(precision) => precision
+                        .as_u64()
+                        .ok_or_else(|| anyhow!("precision 应为非负整数"))?
+                        as usize,
+                    None => 0,
+                };
+                if name == "fixed" {
+                    Value::String(format!("{:.*}", precision, number)).into()
+                } else {
+                    let scale = 10f64.powi(precision as i32);
+                    number_value((number * scale).round() / scale).into()
+                }
+            }
+            _ => bail!("未知的过滤器"),
+        })
+    }
+}
+
+fn attribute(target: &Value, key: &Value) -> Option<Value> {
+    match (target, key) {
+        (Value::Object(map), key) => map.get(&display(key)).cloned(),
+        (Value::Array(items), Value::Number(index)) => {
+            index.as_u64().and_then(|index| items.get(index as usize)).cloned()
+        }
+        _ => None,
+    }
+}
+
+fn binary(op: BinaryOp, left: &Value, right: &Value) -> Result<Value> {
+    Ok(match op {
+        BinaryOp::And | BinaryOp::Or => Value::Bool(truthy(right)),
+        BinaryOp::Equal => Value::Bool(equal(left, right)),
+        BinaryOp::NotEqual => Value::Bool(!equal(left, right)),
+        BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => {
+            let ordering = match (left, right) {
+                (Value::String(a), Value::String(b)) => a.cmp(b),
+                _ => match (left.as_f64(), right.as_f64()) {
+                    (Some(a), Some(b)) => a
+                        .partial_cmp(&b)
+                        .ok_or_else(|| anyhow!("无法比较 {} 和 {}", a, b))?,
+                    _ => bail!("无法比较 {} 和 {}", type_name(left), type_name(right)),
+                },
+            };
+            Value::Bool(match op {
+                BinaryOp::Less => ordering.is_lt(),
+                BinaryOp::LessEqual => ordering.is_le(),
+                BinaryOp::Greater => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+        BinaryOp::In => Value::Bool(match right {
+            Value::Array(items) => items.iter().any(|item| equal(item, left)),
+            Value::Object(map) => map.contains_key(&display(left)),
+            Value::String(text) => text.contains(&display(left)),
+            other => bail!("不能在 {} 中查找", type_name(other)),
+        }),
+        BinaryOp::Concat => Value::String(display(left) + &display(right)),
+        _ => arithmetic(op, left, right)?,
+    })
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> Result<Value> {
+    if let (Some(a), Some(b)) = (left.as_i64(), right.as_i64()) {
+        let result = match op {
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Subtract => a.checked_sub(b),
+            BinaryOp::Multiply => a.checked_mul(b),
+            BinaryOp::Modulo => a.checked_rem(b),
+            _ => None,
+        };
+        if let Some(result) = result {
+            return Ok(Value::from(result));
+        }
+    }
+    let (Some(a), Some(b)) = (left.as_f64(), right.as_f64()) else {
+        bail!("{} 和 {} 不能进行算术运算", type_name(left), type_name(right));
+    };
+    let result = match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Subtract => a - b,
+        BinaryOp::Multiply => a * b,
+        BinaryOp::Divide if b == 0.0 => bail!("除数为0"),
+        BinaryOp::Divide => a / b,
+        BinaryOp::Modulo if b == 0.0 => bail!("除数为0"),
+        _ => a % b,
+    };
+    Ok(number_value(result))
+}
+
+/// 整数值的浮点数转换为整数，使输出中没有多余的 `.0`
+fn number_value(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        Value::from(number as i64)
+    } else {
+        Number::from_f64(number).map(Value::Number).unwrap_or(Value::Null)
+    }
+}
+
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => left == right,
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64() != Some(0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+/// 值的输出文本：字符串不加引号，`null` 为空，列表和对象为JSON
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "空值",
+        Value::Bool(_) => "布尔值",
+        Value::Number(_) => "数字",
+        Value::String(_) => "字符串",
+        Value::Array(_) => "列表",
+        Value::Object(_) => "对象",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(name: &str, source: &str, context: Value) -> Result<String> {
+        Template::parse(name, source)?.render(&context)
+    }
+
+    #[test]
+    fn renders_loops_and_conditionals() {
+        let source = "{% for book in books -%}\n\
+                      {{ loop.index }}. {{ book.title }}{% if not loop.last %}, {% endif %}\n\
+                      {%- endfor %}\n\
+                      {% if count > 2 %}多{% elif count == 2 %}二{% else %}少{% endif %}";
+        let context = json!({ "books": [{ "title": "红楼梦" }, { "title": "水浒传" }], "count": 2 });
+        assert_eq!(render("report.txt", source, context).unwrap(), "1. 红楼梦, 2. 水浒传\n二");
+
+        let source = "{% for key, value in counts %}{{ key }}={{ value }};{% endfor %}{% set total = 1 + 2 * 3 %}{{ total }}";
+        let context = json!({ "counts": { "a": 1, "b": 2 } });
+        assert_eq!(render("report.txt", source, context).unwrap(), "a=1;b=2;7");
+    }
+
+    #[test]
+    fn applies_filters() {
+        let source = "{{ missing | default(value=\"无\") }} {{ names | join(sep=\"、\") }} \
+                      {{ price | fixed(precision=2) }} {{ title | truncate(length=2) }}";
+        let context = json!({ "names": ["张三", "李四"], "price": 12.5, "title": "红楼梦" });
+        assert_eq!(render("report.txt", source, context).unwrap(), "无 张三、李四 12.50 红楼…");
+    }
+
+    #[test]
+    fn escapes_by_template_format() {
+        let context = json!({ "title": "<b>|*x*|</b>" });
+        assert_eq!(
+            render("report.html", "{{ title }} {{ title | safe }}", context.clone()).unwrap(),
+            "&lt;b&gt;|*x*|&lt;/b&gt; <b>|*x*|</b>"
+        );
+        assert_eq!(
+            render("report.md", "{{ title }}", context.clone()).unwrap(),
+            "\\<b\\>\\|\\*x\\*\\|\\</b\\>"
+        );
+        assert_eq!(render("report.txt", "{{ title }}", context).unwrap(), "<b>|*x*|</b>");
+
+        let context = json!({ "items": ["- a-b", " 12. x.y", "1) 2)", "===", "2024-01"] });
+        assert_eq!(
+            render("report.md", "{% for item in items %}{{ item }};{% endfor %}", context).unwrap(),
+            "\\- a-b; 12\\. x.y;1\\) 2);\\===;2024-01;"
+        );
+    }
+
+    #[test]
+    fn loop_variables_and_scopes() {
+        let source = "{% for row in rows %}{% for cell in row %}{{ loop.index0 }}/{{ loop.length }}:{{ cell }}\
+                      {% if loop.first %}<{% endif %}{% endfor %};{% endfor %}";
+        let context = json!({ "rows": [["a", "b"], ["c"]] });
+        assert_eq!(render("report.txt", source, context).unwrap(), "0/2:a<1/2:b;0/1:c<;");
+
+        // 循环内的 set 和循环变量不影响循环外；遍历空值不输出
+        let source = "{% set x = 1 %}{% for x in xs %}{% set y = x %}{{ x }}{% endfor %}{{ x }}\
+                      {% for item in nothing %}不应输出{% endfor %}{{ y | default(value=\"-\") }}";
+        assert_eq!(render("report.txt", source, json!({ "xs": [7, 8], "nothing": null })).unwrap(), "781-");
+
+        let error = render("report.txt", "\n{% for c in count %}{% endfor %}", json!({ "count": 3 })).unwrap_err();
+        assert!(format!("{:#}", error).contains("第 2 行: 不能遍历 数字"));
+    }
+
+    #[test]
+    fn evaluates_conditions_and_expressions() {
+        let context = json!({
+            "tags": ["CFI", "CNF"],
+            "reader": { "name": "张三", "books": [{ "title": "红楼梦" }] },
+            "count": 7,
+            "price": 2.5,
+            "empty": "",
+        });
+        let cases = [
+            ("{% if \"CFI\" in tags and not (\"X\" in tags) %}是{% endif %}", "是"),
+            ("{% if \"name\" in reader and \"三\" in reader.name %}是{% endif %}", "是"),
+            // and/or 短路求值，右侧未定义的变量不会报错
+            ("{% if empty and missing %}是{% elif count >= 7 or missing %}否{% endif %}", "否"),
+            ("{% if \"b\" < \"c\" and count != 8 %}是{% else %}否{% endif %}", "是"),
+            ("{{ reader.books.0.title }}{{ reader[\"name\"] }}{{ tags[1] }}", "红楼梦张三CNF"),
+            ("{{ count + 1 }} {{ count / 2 }} {{ count % 4 }} {{ price * 2 }} {{ -count }}", "8 3.5 3 5 -7"),
+            ("{{ \"共\" ~ count ~ \"册\" }} {{ [1, 2] | length }}", "共7册 2"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(render("report.txt", source, context.clone()).unwrap(), expected, "{}", source);
+        }
+
+        let error = render("report.txt", "{{ count / 0 }}", context.clone()).unwrap_err();
+        assert!(format!("{:#}", error).contains("除数为0"));
+        let error = render("report.txt", "{{ reader + 1 }}", context).unwrap_err();
+        assert!(format!("{:#}", error).contains("对象 和 数字 不能进行算术运算"));
+    }
+
+    #[test]
+    fn applies_text_and_list_filters() {
+        let context = json!({ "name": "  Alice Smith ", "books": ["甲", "乙", "丙"], "counts": { "a": 1 }, "ratio": 0.12345 });
+        let cases = [
+            ("{{ name | trim | upper }}|{{ name | trim | lower }}", "ALICE SMITH|alice smith"),
+            ("{{ books | first }}{{ books | last }} {{ books | length }} {{ counts | length }} {{ \"红楼梦\" | length }}", "甲丙 3 1 3"),
+            ("{{ name | trim | replace(from=\"Smith\", to=\"S.\") }}", "Alice S."),
+            ("{{ ratio | round(precision=2) }} {{ ratio | round }} {{ 2.5 | fixed }} {{ ratio | fixed(precision=3) }}", "0.12 0 2 0.123"),
+            ("{{ \"红楼梦\" | truncate(length=5) }}", "红楼梦"),
+            ("{{ name | default(value=\"无\") | trim }}", "Alice Smith"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(render("report.txt", source, context.clone()).unwrap(), expected, "{}", source);
+        }
+
+        let errors = [
+            ("{{ name | join(sep=\",\") }}", "不能连接 字符串"),
+            ("{{ name | round }}", "字符串 不是数字"),
+            ("{{ name | truncate }}", "缺少参数 length"),
+            ("{{ name | shout }}", "过滤器 shout 执行失败: 未知的过滤器"),
+        ];
+        for (source, expected) in errors {
+            let message = format!("{:#}", render("report.txt", source, context.clone()).unwrap_err());
+            assert!(message.contains(expected), "{}: {}", source, message);
+        }
+    }
+
+    #[test]
+    fn escape_filter_and_whitespace_control() {
+        let context = json!({ "title": "A & B <\"C\"> 'D'", "note": "第一行\n# 第二行" });
+        // escape 之后的值视为安全，不会被再次转义
+        assert_eq!(
+            render("report.html", "{{ title }}|{{ title | escape }}|{{ title | e | safe }}", context.clone()).unwrap(),
+            "A &amp; B &lt;&quot;C&quot;&gt; &#39;D&#39;|A &amp; B &lt;&quot;C&quot;&gt; &#39;D&#39;|A &amp; B &lt;&quot;C&quot;&gt; &#39;D&#39;"
+        );
+        // Markdown 中的换行替换为空格，值中的标题标记被转义
+        assert_eq!(render("report.md", "| {{ note }} |", context.clone()).unwrap(), "| 第一行 \\# 第二行 |");
+        // 不转义的格式中 escape 不改变文本
+        assert_eq!(render("report.txt", "{{ title | escape }}", context.clone()).unwrap(), "A & B <\"C\"> 'D'");
+
+        let source = "<ul>\n  {%- for i in items %}\n  <li>{{- i -}}</li>\n  {%- endfor %}\n</ul>";
+        assert_eq!(
+            render("report.html", source, json!({ "items": [1, 2] })).unwrap(),
+            "<ul>\n  <li>1</li>\n  <li>2</li>\n</ul>"
+        );
+    }
+
+    #[test]
+    fn missing_variables_report_their_line() {
+        let error = render("report.md", "标题\n\n{{ reader.name }}\n{{ missing }}", json!({ "reader": { "name": "张三" } }))
+            .unwrap_err();
+        let message = format!("{:#}", error);
+        assert!(message.contains("第 4 行"), "{}", message);
+        assert!(message.contains("变量 missing 未定义"), "{}", message);
+
+        let error = render("report.md", "{{ reader.age }}", json!({ "reader": {} })).unwrap_err();
+        assert!(format!("{:#}", error).contains("没有属性 age"));
+    }
+
+    #[test]
+    fn syntax_errors_report_their_line() {
+        let cases = [
+            ("a\n{% if x %}\nb", "缺少 {% endif %}"),
+            ("a\n\n{{ x ", "第 3 行: {{ 没有对应的 }}"),
+            ("{# 注释\n #}\n{% endfor %}", "第 3 行: 多余的 {% endfor %}"),
+            ("\n{% unknown %}", "第 2 行: 未知的标签"),
+            ("{% for 1x in xs %}{% endfor %}", "第 1 行: 无效的变量名"),
+        ];
+        for (source, expected) in cases {
+            let message = format!("{:#}", Template::parse("report.md", source).unwrap_err());
+            assert!(message.contains(expected), "{:?}: {}", source, message);
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// 默认的报告标题
+pub const DEFAULT_TITLE: &str = "读书报告分析结果";
+/// 默认配色（与 matplotlib 默认配色一致）
+const DEFAULT_PALETTE: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+/// 图表分辨率上限（DPI）
+const MAX_CHART_DPI: u32 = 600;
+/// 图表图片宽度和高度的上限（像素），避免配置错误时生成过大的图片耗尽内存
+const MAX_CHART_PIXELS: u32 = 8000;
+
+/// 页面除去页边距后至少保留的宽度和高度（毫米）
+const MIN_CONTENT_MM: f64 = 50.0;
+
+static CURRENT: Mutex<Option<ReportTheme>> = Mutex::new(None);
+
+/// 报告的纸张大小
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    /// 自定义尺寸（毫米）
+    Custom { width_mm: f64, height_mm: f64 },
+}
+
+impl PageSize {
+    /// 纸张宽度和高度（毫米）
+    pub fn dimensions_mm(self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (210.0, 297.0),
+            PageSize::A5 => (148.0, 210.0),
+            PageSize::Letter => (215.9, 279.4),
+            PageSize::Custom { width_mm, height_mm } => (width_mm, height_mm),
+        }
+    }
+}
+
+/// 页边距（毫米）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageMargins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Default for PageMargins {
+    fn default() -> Self {
+        Self {
+            top: 25.4,
+            right: 25.4,
+            bottom: 25.4,
+            left: 25.4,
+        }
+    }
+}
+
+/// 报告主题：标题、页眉页脚、标志、配色、字体和图表尺寸。
+/// 图表和各种格式的报告导出都使用同一个主题，配置在 `config.json` 的 `report_theme` 中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportTheme {
+    /// 报告标题
+    pub title: String,
+    /// 页眉文字
+    pub header_text: Option<String>,
+    /// 页脚文字
+    pub footer_text: Option<String>,
+    /// 页眉中的标志图片（PNG/JPEG），相对路径相对于配置文件所在目录
+    pub logo: Option<PathBuf>,
+    /// 标志图片高度（厘米）
+    pub logo_height_cm: f64,
+    /// 配色（`#RRGGBB`），图表依次使用；第一个颜色同时用于报告标题和小标题
+    pub palette: Vec<String>,
+    /// 报告正文字体名称，如 `Microsoft YaHei`；未设置时使用文档默认字体
+    pub report_font: Option<String>,
+    /// 图表字体文件（TTF/OTF/TTC），需包含中文；未设置时使用打包字体或系统字体
+    pub chart_font: Option<PathBuf>,
+    /// 条形图和折线图的尺寸（英寸）
+    pub chart_width: f64,
+    pub chart_height: f64,
+    /// 饼图的边长（英寸）
+    pub pie_chart_size: f64,
+    /// 图表分辨率，图片像素 = 尺寸 × 分辨率，文字和线条随分辨率缩放；最大为 `MAX_CHART_DPI`
+    pub chart_dpi: u32,
+    /// Word和PDF报告的纸张大小，HTML报告打印时也使用该纸张大小
+    pub page_size: PageSize,
+    /// Word和PDF报告的页边距（毫米），HTML报告打印时也使用该页边距
+    pub page_margins_mm: PageMargins,
+    /// HTML报告使用可交互的SVG图表（悬停时显示数值），否则嵌入PNG图片
+    pub interactive_charts: bool,
+}
+
+impl Default for ReportTheme {
+    fn default() -> Self {
+        Self {
+            title: DEFAULT_TITLE.to_string(),
+            header_text: None,
+            footer_text: None,
+            logo: None,
+            logo_height_cm: 1.5,
+            palette: DEFAULT_PALETTE.iter().map(|color| color.to_string()).collect(),
+            report_font: None,
+            chart_font: None,
+            chart_width: 10.0,
+            chart_height: 6.0,
+            pie_chart_size: 8.0,
+            chart_dpi: 150,
+            page_size: PageSize::default(),
+            page_margins_mm: PageMargins::default(),
+            interactive_charts: false,
+        }
+    }
+}
+
+impl ReportTheme {
+    /// 修正无效的配置（尺寸、分辨率、配色），并把相对路径解析为相对于 `base_dir` 的路径
+    pub fn normalize(mut self, base_dir: Option<&Path>) -> Self {
+        let default = Self::default();
+        if self.title.trim().is_empty() {
+            self.title = default.title;
+        }
+        for (name, value, fallback) in [
+            ("chart_width", &mut self.chart_width, default.chart_width),
+            ("chart_height", &mut self.chart_height, default.chart_height),
+            ("pie_chart_size", &mut self.pie_chart_size, default.pie_chart_size),
+            ("logo_height_cm", &mut self.logo_height_cm, default.logo_height_cm),
+        ] {
+            if !(value.is_finite() && *value > 0.0) {
+                warn!("报告主题的 {} 无效 ({})，使用默认值 {}", name, value, fallback);
+                *value = fallback;
+            }
+        }
+        if self.chart_dpi == 0 {
+            warn!("报告主题的 chart_dpi 无效，使用默认值 {}", default.chart_dpi);
+            self.chart_dpi = default.chart_dpi;
+        } else if self.chart_dpi > MAX_CHART_DPI {
+            warn!("报告主题的 chart_dpi 过大 ({})，使用 {}", self.chart_dpi, MAX_CHART_DPI);
+            self.chart_dpi = MAX_CHART_DPI;
+        }
+        let max_inches = MAX_CHART_PIXELS as f64 / self.chart_dpi as f64;
+        for (name, value) in [
+            ("chart_width", &mut self.chart_width),
+            ("chart_height", &mut self.chart_height),
+            ("pie_chart_size", &mut self.pie_chart_size),
+        ] {
+            if *value > max_inches {
+                warn!(
+                    "报告主题的 {} 过大 ({} 英寸，超过 {} 像素)，使用 {} 英寸",
+                    name, value, MAX_CHART_PIXELS, max_inches
+                );
+                *value = max_inches;
+            }
+        }
+
+        let (page_width, page_height) = self.page_size.dimensions_mm();
+        if !(page_width.is_finite() && page_height.is_finite())
+            || page_width < MIN_CONTENT_MM
+            || page_height < MIN_CONTENT_MM
+        {
+            warn!("报告主题的纸张大小无效 ({} × {} 毫米)，使用 A4", page_width, page_height);
+            self.page_size = default.page_size;
+        }
+        let (page_width, page_height) = self.page_size.dimensions_mm();
+        let margins = self.page_margins_mm;
+        let valid_margins = [margins.top, margins.right, margins.bottom, margins.left]
+            .iter()
+            .all(|margin| margin.is_finite() && *margin >= 0.0)
+            && page_width - margins.left - margins.right >= MIN_CONTENT_MM
+            && page_height - margins.top - margins.bottom >= MIN_CONTENT_MM;
+        if !valid_margins {
+            warn!("报告主题的页边距无效 ({:?})，使用默认页边距", margins);
+            self.page_margins_mm = default.page_margins_mm;
+        }
+
+        self.palette.retain(|color| {
+            let valid = parse_color(color).is_some();
+            if !valid {
+                warn!("忽略报告主题中无效的颜色: {}", color);
+            }
+            valid
+        });
+        if self.palette.is_empty() {
+            self.palette = default.palette;
+        }
+
+        if let Some(base_dir) = base_dir {
+            for path in [&mut self.logo, &mut self.chart_font].into_iter().flatten() {
+                if path.is_relative() {
+                    *path = base_dir.join(&*path);
+                }
+            }
+        }
+        self
+    }
+
+    /// 配色的 RGB 值
+    pub fn palette_rgb(&self) -> Vec<(u8, u8, u8)> {
+        let colors: Vec<(u8, u8, u8)> = self.palette.iter().filter_map(|color| parse_color(color)).collect();
+        if colors.is_empty() {
+            DEFAULT_PALETTE.iter().filter_map(|color| parse_color(color)).collect()
+        } else {
+            colors
+        }
+    }
+
+    /// 报告的主题色（配色中的第一个颜色）
+    pub fn accent_rgb(&self) -> (u8, u8, u8) {
+        self.palette_rgb()[0]
+    }
+
+    /// 英寸换算为图片像素，不超过 `MAX_CHART_PIXELS`
+    pub fn chart_pixels(&self, inches: f64) -> u32 {
+        let pixels = (inches * self.chart_dpi as f64).round();
+        if pixels.is_nan() {
+            return 1;
+        }
+        pixels.clamp(1.0, MAX_CHART_PIXELS as f64) as u32
+    }
+}
+
+/// 解析 `#RRGGBB` 颜色
+fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// 设置当前使用的报告主题，应用启动时根据配置调用
+pub fn set_current(theme: ReportTheme) {
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(theme);
+}
+
+/// 当前使用的报告主题，未设置时为默认主题
+pub fn current() -> ReportTheme {
+    CURRENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_clamps_chart_resolution_and_size() {
+        let theme = ReportTheme {
+            chart_dpi: 100_000,
+            chart_width: 1e9,
+            chart_height: 6.0,
+            pie_chart_size: -1.0,
+            ..Default::default()
+        }
+        .normalize(None);
+
+        assert_eq!(theme.chart_dpi, MAX_CHART_DPI);
+        assert_eq!(theme.chart_pixels(theme.chart_width), MAX_CHART_PIXELS);
+        assert_eq!(theme.chart_pixels(theme.chart_height), 3600);
+        assert_eq!(theme.pie_chart_size, ReportTheme::default().pie_chart_size);
+
+        let theme = ReportTheme {
+            chart_dpi: 0,
+            ..Default::default()
+        }
+        .normalize(None);
+        assert_eq!(theme.chart_dpi, ReportTheme::default().chart_dpi);
+        assert_eq!(theme.chart_width, ReportTheme::default().chart_width);
+    }
+
+    #[test]
+    fn chart_pixels_stay_in_range_without_normalize() {
+        let theme = ReportTheme {
+            chart_dpi: u32::MAX,
+            ..Default::default()
+        };
+        assert_eq!(theme.chart_pixels(10.0), MAX_CHART_PIXELS);
+        assert_eq!(theme.chart_pixels(f64::INFINITY), MAX_CHART_PIXELS);
+        assert_eq!(theme.chart_pixels(0.0), 1);
+        assert_eq!(theme.chart_pixels(f64::NAN), 1);
+    }
+
+    #[test]
+    fn normalize_resolves_relative_paths_and_drops_invalid_colors() {
+        let theme = ReportTheme {
+            title: "  ".to_string(),
+            palette: vec!["#zzzzzz".to_string(), "#00ff00".to_string()],
+            logo: Some(PathBuf::from("logo.png")),
+            ..Default::default()
+        }
+        .normalize(Some(Path::new("/config")));
+
+        assert_eq!(theme.title, DEFAULT_TITLE);
+        assert_eq!(theme.palette, ["#00ff00"]);
+        assert_eq!(theme.accent_rgb(), (0, 255, 0));
+        assert_eq!(theme.logo, Some(Path::new("/config").join("logo.png")));
+    }
+}
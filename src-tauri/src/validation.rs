@@ -0,0 +1,445 @@
+use crate::column_mapping::{Column, ColumnMapping, BORROW_COLUMNS, MEMBER_COLUMNS};
+use crate::rust_analyzer::{cell_to_datetime, cell_to_f64, data_rows};
+use crate::spreadsheet::{read_first_sheet, read_sheet, resolve_entries, Sheet, SheetSelection};
+use calamine::{Data, DataType, Range};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// 文件无法打开或没有工作表
+    FileUnreadable,
+    /// 缺少必需的列
+    MissingColumn,
+    /// 必填单元格为空
+    MissingValue,
+    /// 日期无法解析
+    InvalidDate,
+    /// 定价不是数字
+    InvalidPrice,
+    /// 定价为负数
+    NegativePrice,
+    /// 还书日期早于借书日期
+    ReturnBeforeBorrow,
+    /// 与前面的行完全重复
+    DuplicateRow,
+}
+
+/// 问题严重程度：`Error` 会导致分析失败或结果错误，`Warning` 仅供参考
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 单个问题，行号为Excel中的行号（从1开始，表头为第1行）
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationIssue {
+    pub file: String,
+    pub sheet: String,
+    pub row: Option<u32>,
+    pub column: Option<String>,
+    pub kind: IssueKind,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// 输入文件校验报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    /// 没有 `Error` 级别的问题
+    pub valid: bool,
+    pub files_checked: u32,
+    pub rows_checked: u32,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// 读取表格的类型
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SheetKind {
+    Borrow,
+    Member,
+}
+
+/// 在分析之前检查读者清单和所有借阅数据文件（按工作表选择逐个检查工作表）
+pub fn validate_inputs(
+    borrow_paths: &[String],
+    member_path: &str,
+    column_mapping: &ColumnMapping,
+    sheet_selection: &SheetSelection,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    // 读者清单只读取第一个工作表
+    report.files_checked += 1;
+    validate_sheet(
+        member_path,
+        read_first_sheet(member_path),
+        SheetKind::Member,
+        column_mapping,
+        &mut report,
+    );
+
+    for path in borrow_paths {
+        report.files_checked += 1;
+        match resolve_entries(std::slice::from_ref(path), sheet_selection) {
+            Ok(entries) => {
+                for entry in entries {
+                    validate_sheet(
+                        path,
+                        read_sheet(path, entry.sheet.as_deref()),
+                        SheetKind::Borrow,
+                        column_mapping,
+                        &mut report,
+                    );
+                }
+            }
+            Err(e) => report.issues.push(unreadable(path, e)),
+        }
+    }
+
+    report.valid = report
+        .issues
+        .iter()
+        .all(|issue| issue.severity != Severity::Error);
+    report
+}
+
+fn unreadable(path: &str, error: anyhow::Error) -> ValidationIssue {
+    ValidationIssue {
+        file: path.to_string(),
+        sheet: String::new(),
+        row: None,
+        column: None,
+        kind: IssueKind::FileUnreadable,
+        severity: Severity::Error,
+        message: format!("{:#}", error),
+    }
+}
+
+fn validate_sheet(
+    path: &str,
+    sheet: anyhow::Result<Sheet>,
+    kind: SheetKind,
+    column_mapping: &ColumnMapping,
+    report: &mut ValidationReport,
+) {
+    let sheet = match sheet {
+        Ok(sheet) => sheet,
+        Err(e) => {
+            report.issues.push(unreadable(path, e));
+            return;
+        }
+    };
+
+    SheetValidator {
+        path,
+        sheet: &sheet.name,
+        kind,
+        report,
+    }
+    .validate(&sheet.range, column_mapping);
+}
+
+struct SheetValidator<'a> {
+    path: &'a str,
+    sheet: &'a str,
+    kind: SheetKind,
+    report: &'a mut ValidationReport,
+}
+
+impl SheetValidator<'_> {
+    fn issue(
+        &mut self,
+        row: Option<u32>,
+        column: Option<&str>,
+        kind: IssueKind,
+        severity: Severity,
+        message: String,
+    ) {
+        self.report.issues.push(ValidationIssue {
+            file: self.path.to_string(),
+            sheet: self.sheet.to_string(),
+            row,
+            column: column.map(str::to_string),
+            kind,
+            severity,
+            message,
+        });
+    }
+
+    fn validate(&mut self, range: &Range<Data>, column_mapping: &ColumnMapping) {
+        let headers: Vec<String> = range
+            .rows()
+            .next()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .unwrap_or_default();
+        let columns: &[Column] = match self.kind {
+            SheetKind::Borrow => &BORROW_COLUMNS,
+            SheetKind::Member => &MEMBER_COLUMNS,
+        };
+
+        let (idx, missing) = column_mapping.resolve_partial(&headers, columns);
+        for column in missing {
+            self.issue(
+                Some(1),
+                Some(column.canonical()),
+                IssueKind::MissingColumn,
+                Severity::Error,
+                format!(
+                    "缺少列 {}（可接受的表头: {}）",
+                    column.canonical(),
+                    column_mapping.candidates(column).join(", ")
+                ),
+            );
+        }
+
+        let mut seen_rows: HashMap<Vec<String>, u32> = HashMap::new();
+        for (row_index, row) in data_rows(range) {
+            self.report.rows_checked += 1;
+            let row_number = row_index as u32 + 1;
+            let cell = |column: Column| {
+                idx.get(&column)
+                    .map(|&i| (headers[i].as_str(), row.get(i).unwrap_or(&Data::Empty)))
+            };
+
+            match self.kind {
+                SheetKind::Borrow => {
+                    let borrow_date =
+                        self.check_date(row_number, cell(Column::BorrowDate), Some(Severity::Error));
+                    // 还书日期为空表示尚未归还
+                    let return_date = self.check_date(row_number, cell(Column::ReturnDate), None);
+                    if let (Some(borrow_date), Some(return_date)) = (borrow_date, return_date) {
+                        if return_date < borrow_date {
+                            let header = cell(Column::ReturnDate).map(|(header, _)| header);
+                            self.issue(
+                                Some(row_number),
+                                header,
+                                IssueKind::ReturnBeforeBorrow,
+                                Severity::Error,
+                                format!(
+                                    "还书日期 {} 早于借书日期 {}",
+                                    return_date.format("%Y-%m-%d"),
+                                    borrow_date.format("%Y-%m-%d")
+                                ),
+                            );
+                        }
+                    }
+                    self.check_price(row_number, cell(Column::Price));
+                }
+                SheetKind::Member => {
+                    // 办卡日期为空时该读者的会员时长为未知，不影响分析
+                    self.check_date(row_number, cell(Column::RegistrationDate), Some(Severity::Warning));
+                }
+            }
+
+            // 重复行：所有已识别列的值都与前面某行相同
+            let key: Vec<String> = columns
+                .iter()
+                .filter_map(|&column| cell(column).map(|(_, value)| value.to_string()))
+                .collect();
+            if self.kind == SheetKind::Borrow && !key.is_empty() {
+                if let Some(&first_row) = seen_rows.get(&key) {
+                    self.issue(
+                        Some(row_number),
+                        None,
+                        IssueKind::DuplicateRow,
+                        Severity::Warning,
+                        format!("与第 {} 行完全重复", first_row),
+                    );
+                } else {
+                    seen_rows.insert(key, row_number);
+                }
+            }
+        }
+    }
+
+    /// 检查日期单元格；`empty` 为单元格为空时的问题严重程度，`None` 表示允许为空
+    fn check_date(
+        &mut self,
+        row: u32,
+        cell: Option<(&str, &Data)>,
+        empty: Option<Severity>,
+    ) -> Option<chrono::NaiveDateTime> {
+        let (header, value) = cell?;
+        if value.is_empty() {
+            if let Some(severity) = empty {
+                self.issue(
+                    Some(row),
+                    Some(header),
+                    IssueKind::MissingValue,
+                    severity,
+                    format!("{}为空", header),
+                );
+            }
+            return None;
+        }
+
+        let date = cell_to_datetime(value);
+        if date.is_none() {
+            self.issue(
+                Some(row),
+                Some(header),
+                IssueKind::InvalidDate,
+                Severity::Error,
+                format!("无法解析的日期: {}", value),
+            );
+        }
+        date
+    }
+
+    fn check_price(&mut self, row: u32, cell: Option<(&str, &Data)>) {
+        let Some((header, value)) = cell else {
+            return;
+        };
+
+        // 定价为空时不计入总价值
+        if value.is_empty() {
+            self.issue(
+                Some(row),
+                Some(header),
+                IssueKind::MissingValue,
+                Severity::Warning,
+                format!("{}为空，不计入总价值", header),
+            );
+            return;
+        }
+
+        match cell_to_f64(value) {
+            None => self.issue(
+                Some(row),
+                Some(header),
+                IssueKind::InvalidPrice,
+                Severity::Error,
+                format!("定价不是数字: {}", value),
+            ),
+            Some(price) if price < 0.0 => self.issue(
+                Some(row),
+                Some(header),
+                IssueKind::NegativePrice,
+                Severity::Error,
+                format!("定价为负数: {}", price),
+            ),
+            Some(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const MEMBERS: &str = "姓名,办卡日期\n张三,2023-01-01\n";
+    const BORROW_HEADER: &str = "姓名,书名,作者,类别,定价,借书日期,还书日期\n";
+
+    fn temp_file(name: &str, content: &str) -> String {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("readerreport-validation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn validate(name: &str, members: &str, borrow: &str) -> ValidationReport {
+        let member_path = temp_file(&format!("{}_member.csv", name), members);
+        let borrow_path = temp_file(&format!("{}_borrow.csv", name), borrow);
+        validate_inputs(&[borrow_path], &member_path, &ColumnMapping::default(), &SheetSelection::default())
+    }
+
+    fn kinds(report: &ValidationReport) -> Vec<(Option<u32>, IssueKind, Severity)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.row, issue.kind, issue.severity))
+            .collect()
+    }
+
+    #[test]
+    fn valid_input_has_no_issues() {
+        let borrow = format!("{}张三,红楼梦,曹雪芹,I,35.5,2024-01-02,2024-01-20\n", BORROW_HEADER);
+        let report = validate("valid", MEMBERS, &borrow);
+        assert!(report.valid, "{:?}", report.issues);
+        assert!(report.issues.is_empty());
+        assert_eq!(report.files_checked, 2);
+        assert_eq!(report.rows_checked, 2);
+    }
+
+    #[test]
+    fn unreturned_books_and_missing_optional_values_still_pass() {
+        let borrow = format!("{}张三,红楼梦,曹雪芹,I,,2024-01-02,\n", BORROW_HEADER);
+        let report = validate("optional", "姓名,办卡日期\n张三,\n", &borrow);
+        assert!(report.valid, "{:?}", report.issues);
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (Some(2), IssueKind::MissingValue, Severity::Warning),
+                (Some(2), IssueKind::MissingValue, Severity::Warning),
+            ]
+        );
+        assert_eq!(report.issues[0].column.as_deref(), Some("办卡日期"));
+        assert_eq!(report.issues[1].column.as_deref(), Some("定价"));
+    }
+
+    #[test]
+    fn missing_column_is_an_error() {
+        let report = validate("missing", MEMBERS, "姓名,书名,作者,类别,定价,借书日期\n");
+        assert!(!report.valid);
+        assert_eq!(kinds(&report), vec![(Some(1), IssueKind::MissingColumn, Severity::Error)]);
+        assert_eq!(report.issues[0].column.as_deref(), Some("还书日期"));
+    }
+
+    #[test]
+    fn invalid_dates_and_prices_are_errors() {
+        let borrow = format!(
+            "{}张三,红楼梦,曹雪芹,I,abc,2024-13-45,2024-01-20\n张三,水浒传,施耐庵,I,-1,,2024-01-20\n",
+            BORROW_HEADER
+        );
+        let report = validate("invalid", MEMBERS, &borrow);
+        assert!(!report.valid);
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (Some(2), IssueKind::InvalidDate, Severity::Error),
+                (Some(2), IssueKind::InvalidPrice, Severity::Error),
+                (Some(3), IssueKind::MissingValue, Severity::Error),
+                (Some(3), IssueKind::NegativePrice, Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn return_before_borrow_is_an_error() {
+        let borrow = format!("{}张三,红楼梦,曹雪芹,I,10,2024-02-01,2024-01-20\n", BORROW_HEADER);
+        let report = validate("order", MEMBERS, &borrow);
+        assert!(!report.valid);
+        assert_eq!(kinds(&report), vec![(Some(2), IssueKind::ReturnBeforeBorrow, Severity::Error)]);
+    }
+
+    #[test]
+    fn duplicate_rows_are_warnings() {
+        let row = "张三,红楼梦,曹雪芹,I,10,2024-01-02,2024-01-20\n";
+        let borrow = format!("{}{}{}{}", BORROW_HEADER, row, "张三,水浒传,施耐庵,I,10,2024-01-02,2024-01-20\n", row);
+        let report = validate("duplicate", MEMBERS, &borrow);
+        assert!(report.valid);
+        assert_eq!(kinds(&report), vec![(Some(4), IssueKind::DuplicateRow, Severity::Warning)]);
+        assert_eq!(report.issues[0].message, "与第 2 行完全重复");
+    }
+
+    #[test]
+    fn unreadable_files_are_errors() {
+        let report = validate_inputs(
+            &["/nonexistent/borrow.csv".to_string()],
+            "/nonexistent/member.csv",
+            &ColumnMapping::default(),
+            &SheetSelection::default(),
+        );
+        assert!(!report.valid);
+        assert!(report.issues.iter().all(|issue| issue.kind == IssueKind::FileUnreadable));
+        assert_eq!(report.issues.len(), 2);
+    }
+}
@@ -0,0 +1,414 @@
+use crate::docx::{escape, XML_DECLARATION};
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const NAMESPACE_MAIN: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const NAMESPACE_RELATIONSHIPS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+const REL_OFFICE_DOCUMENT: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument";
+const REL_WORKSHEET: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet";
+const REL_STYLES: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles";
+
+const CONTENT_TYPE_WORKBOOK: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml";
+const CONTENT_TYPE_WORKSHEET: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml";
+const CONTENT_TYPE_STYLES: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml";
+
+/// 样式表：默认样式和表头使用的粗体样式（`cellXfs` 中的第1项）
+const STYLES: &str = concat!(
+    r#"<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts>"#,
+    r#"<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>"#,
+    r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>"#,
+    r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>"#,
+    r#"<cellXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/></cellXfs>"#,
+    r#"<cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles>"#,
+);
+const HEADER_STYLE: u32 = 1;
+
+/// 工作表名称的最大长度
+const MAX_SHEET_NAME_CHARS: usize = 31;
+/// 列宽（字符数）的范围，按列中最长的内容估算
+const MIN_COLUMN_WIDTH: f64 = 8.0;
+const MAX_COLUMN_WIDTH: f64 = 60.0;
+
+/// 单元格的值
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl Cell {
+    /// 估算显示宽度（字符数），全角字符按两个字符计算
+    fn width(&self) -> f64 {
+        let text = match self {
+            Cell::Empty => return 0.0,
+            Cell::Text(text) => text.clone(),
+            Cell::Number(value) => value.to_string(),
+            Cell::Bool(value) => value.to_string().to_uppercase(),
+        };
+        text.chars().map(|c| if c.is_ascii() { 1.0 } else { 2.0 }).sum()
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<u32> for Cell {
+    fn from(value: u32) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(value: bool) -> Self {
+        Cell::Bool(value)
+    }
+}
+
+/// 工作表：第一行为表头，冻结在顶部
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+/// 将工作表依次写入Excel工作簿（`.xlsx`），文字使用内联字符串，不依赖共享字符串表
+pub fn write_xlsx(sheets: &[Sheet], output_path: &Path) -> Result<()> {
+    if sheets.is_empty() {
+        return Err(anyhow!("工作簿中至少需要一个工作表"));
+    }
+
+    let mut names = HashSet::new();
+    for sheet in sheets {
+        validate_sheet_name(&sheet.name)?;
+        if !names.insert(sheet.name.to_lowercase()) {
+            return Err(anyhow!("工作表名称重复: {}", sheet.name));
+        }
+    }
+
+    let file = File::create(output_path)
+        .with_context(|| format!("无法创建文件: {:?}", output_path))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    let mut content_types = format!(
+        concat!(
+            r#"{}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+            r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+            r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+            r#"<Override PartName="/xl/workbook.xml" ContentType="{}"/>"#,
+            r#"<Override PartName="/xl/styles.xml" ContentType="{}"/>"#
+        ),
+        XML_DECLARATION, CONTENT_TYPE_WORKBOOK, CONTENT_TYPE_STYLES
+    );
+    for index in 1..=sheets.len() {
+        content_types.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="{}"/>"#,
+            index, CONTENT_TYPE_WORKSHEET
+        ));
+    }
+    content_types.push_str("</Types>");
+    zip.start_file("[Content_Types].xml", options)?;
+    zip.write_all(content_types.as_bytes())?;
+
+    zip.start_file("_rels/.rels", options)?;
+    zip.write_all(
+        relationships(&[(REL_OFFICE_DOCUMENT, "xl/workbook.xml".to_string())]).as_bytes(),
+    )?;
+
+    let mut workbook = format!(
+        r#"{}<workbook xmlns="{}" xmlns:r="{}"><sheets>"#,
+        XML_DECLARATION, NAMESPACE_MAIN, NAMESPACE_RELATIONSHIPS
+    );
+    let mut workbook_rels = Vec::new();
+    for (index, sheet) in sheets.iter().enumerate() {
+        let id = index + 1;
+        workbook.push_str(&format!(
+            r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
+            escape(&sheet.name),
+            id,
+            id
+        ));
+        workbook_rels.push((REL_WORKSHEET, format!("worksheets/sheet{}.xml", id)));
+    }
+    workbook.push_str("</sheets></workbook>");
+    workbook_rels.push((REL_STYLES, "styles.xml".to_string()));
+
+    zip.start_file("xl/workbook.xml", options)?;
+    zip.write_all(workbook.as_bytes())?;
+    zip.start_file("xl/_rels/workbook.xml.rels", options)?;
+    zip.write_all(relationships(&workbook_rels).as_bytes())?;
+    zip.start_file("xl/styles.xml", options)?;
+    zip.write_all(
+        format!(r#"{}<styleSheet xmlns="{}">{}</styleSheet>"#, XML_DECLARATION, NAMESPACE_MAIN, STYLES)
+            .as_bytes(),
+    )?;
+
+    for (index, sheet) in sheets.iter().enumerate() {
+        zip.start_file(format!("xl/worksheets/sheet{}.xml", index + 1), options)?;
+        zip.write_all(worksheet(sheet).as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// 工作表名称不能为空、不能超过31个字符，也不能包含 `[]:*?/\`
+fn validate_sheet_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() || name.chars().count() > MAX_SHEET_NAME_CHARS {
+        return Err(anyhow!("无效的工作表名称: {:?}", name));
+    }
+    if name.contains(['[', ']', ':', '*', '?', '/', '\\']) || name.starts_with('\'') {
+        return Err(anyhow!("工作表名称包含不允许的字符: {:?}", name));
+    }
+    Ok(())
+}
+
+fn relationships(targets: &[(&str, String)]) -> String {
+    let mut xml = format!(
+        r#"{}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        XML_DECLARATION
+    );
+    for (index, (kind, target)) in targets.iter().enumerate() {
+        xml.push_str(&format!(
+            r#"<Relationship Id="rId{}" Type="{}" Target="{}"/>"#,
+            index + 1,
+            kind,
+            escape(target)
+        ));
+    }
+    xml.push_str("</Relationships>");
+    xml
+}
+
+fn worksheet(sheet: &Sheet) -> String {
+    let columns = sheet
+        .rows
+        .iter()
+        .map(Vec::len)
+        .chain([sheet.headers.len()])
+        .max()
+        .unwrap_or(0);
+
+    let mut xml = format!(
+        concat!(
+            r#"{}<worksheet xmlns="{}" xmlns:r="{}">"#,
+            r#"<sheetViews><sheetView workbookViewId="0">"#,
+            r#"<pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/>"#,
+            r#"</sheetView></sheetViews>"#
+        ),
+        XML_DECLARATION, NAMESPACE_MAIN, NAMESPACE_RELATIONSHIPS
+    );
+
+    if columns > 0 {
+        xml.push_str("<cols>");
+        for column in 0..columns {
+            let width = sheet
+                .rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .map(Cell::width)
+                .chain(sheet.headers.get(column).map(|header| Cell::from(header.as_str()).width()))
+                .fold(MIN_COLUMN_WIDTH, f64::max)
+                .min(MAX_COLUMN_WIDTH);
+            xml.push_str(&format!(
+                r#"<col min="{0}" max="{0}" width="{1:.1}" customWidth="1"/>"#,
+                column + 1,
+                width + 2.0
+            ));
+        }
+        xml.push_str("</cols>");
+    }
+
+    xml.push_str("<sheetData>");
+    let header_cells: Vec<Cell> = sheet.headers.iter().map(|header| Cell::from(header.as_str())).collect();
+    xml.push_str(&row(1, &header_cells, Some(HEADER_STYLE)));
+    for (index, cells) in sheet.rows.iter().enumerate() {
+        xml.push_str(&row(index as u32 + 2, cells, None));
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn row(number: u32, cells: &[Cell], style: Option<u32>) -> String {
+    let mut xml = format!(r#"<row r="{}">"#, number);
+    let style = style.map(|style| format!(r#" s="{}""#, style)).unwrap_or_default();
+    for (column, cell) in cells.iter().enumerate() {
+        let reference = format!("{}{}", column_name(column), number);
+        match cell {
+            Cell::Empty => {}
+            Cell::Text(text) => xml.push_str(&format!(
+                r#"<c r="{}"{} t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                reference,
+                style,
+                escape(text)
+            )),
+            Cell::Number(value) if value.is_finite() => xml.push_str(&format!(
+                r#"<c r="{}"{}><v>{}</v></c>"#,
+                reference, style, value
+            )),
+            // Excel不支持NaN和无穷大，以文字写入
+            Cell::Number(value) => xml.push_str(&format!(
+                r#"<c r="{}"{} t="inlineStr"><is><t>{}</t></is></c>"#,
+                reference, style, value
+            )),
+            Cell::Bool(value) => xml.push_str(&format!(
+                r#"<c r="{}"{} t="b"><v>{}</v></c>"#,
+                reference,
+                style,
+                u8::from(*value)
+            )),
+        }
+    }
+    xml.push_str("</row>");
+    xml
+}
+
+/// 列号（从0开始）对应的列名：A、B、…、Z、AA、AB、…
+fn column_name(mut column: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (column % 26) as u8);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{open_workbook, Data, Reader, Xlsx};
+    use std::io::Read;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("readerreport-xlsx-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn column_names_continue_past_z() {
+        let names: Vec<String> = [0, 1, 25, 26, 27, 51, 52, 701, 702, 16383]
+            .into_iter()
+            .map(column_name)
+            .collect();
+        assert_eq!(names, ["A", "B", "Z", "AA", "AB", "AZ", "BA", "ZZ", "AAA", "XFD"]);
+    }
+
+    #[test]
+    fn worksheet_escapes_text_and_writes_non_finite_numbers_as_text() {
+        let sheet = Sheet {
+            name: "data".to_string(),
+            headers: vec!["title".to_string(), "percent".to_string()],
+            rows: vec![
+                vec![Cell::from("<汤姆 & 杰瑞>"), Cell::Number(f64::NAN)],
+                vec![Cell::from("\"引号\""), Cell::Number(f64::INFINITY)],
+                vec![Cell::Empty, Cell::Number(12.5)],
+            ],
+        };
+        let xml = worksheet(&sheet);
+
+        assert!(xml.contains("&lt;汤姆 &amp; 杰瑞&gt;"));
+        assert!(!xml.contains("<汤姆"));
+        assert!(xml.contains(r#"<c r="B2" t="inlineStr"><is><t>NaN</t></is></c>"#));
+        assert!(xml.contains(r#"<c r="B3" t="inlineStr"><is><t>inf</t></is></c>"#));
+        assert!(xml.contains(r#"<c r="B4"><v>12.5</v></c>"#));
+        assert!(!xml.contains(r#"r="A4""#), "空单元格不应写入");
+        assert!(xml.contains(r#"<c r="A1" s="1" t="inlineStr">"#), "表头应使用表头样式");
+    }
+
+    #[test]
+    fn written_workbook_reads_back_with_the_same_values() {
+        let path = temp_path("roundtrip.xlsx");
+        let sheets = [
+            Sheet {
+                name: "summary".to_string(),
+                headers: vec!["field".to_string(), "value".to_string()],
+                rows: vec![
+                    vec![Cell::from("a<b>&c"), Cell::from(3_u32)],
+                    vec![Cell::from("ratio"), Cell::Number(f64::NAN)],
+                    vec![Cell::from("flag"), Cell::from(true)],
+                ],
+            },
+            Sheet {
+                name: "wide".to_string(),
+                headers: (0..30).map(|index| format!("c{}", index)).collect(),
+                rows: vec![(0..30).map(|index| Cell::from(index as u32)).collect()],
+            },
+        ];
+        write_xlsx(&sheets, &path).unwrap();
+
+        let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+        assert_eq!(workbook.sheet_names(), ["summary", "wide"]);
+
+        let summary = workbook.worksheet_range("summary").unwrap();
+        assert_eq!(summary.get_value((1, 0)), Some(&Data::String("a<b>&c".to_string())));
+        assert_eq!(summary.get_value((1, 1)), Some(&Data::Float(3.0)));
+        assert_eq!(summary.get_value((2, 1)), Some(&Data::String("NaN".to_string())));
+        assert_eq!(summary.get_value((3, 1)), Some(&Data::Bool(true)));
+
+        let wide = workbook.worksheet_range("wide").unwrap();
+        assert_eq!(wide.width(), 30);
+        assert_eq!(wide.get_value((0, 29)), Some(&Data::String("c29".to_string())));
+        assert_eq!(wide.get_value((1, 29)), Some(&Data::Float(29.0)));
+
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut xml = String::new();
+        archive.by_name("xl/worksheets/sheet2.xml").unwrap().read_to_string(&mut xml).unwrap();
+        assert!(xml.contains(r#"<c r="AD2"><v>29</v></c>"#));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn rejects_invalid_and_duplicate_sheet_names() {
+        let path = temp_path("invalid.xlsx");
+        let named = |name: &str| Sheet {
+            name: name.to_string(),
+            headers: Vec::new(),
+            rows: Vec::new(),
+        };
+        assert!(write_xlsx(&[], &path).is_err());
+        assert!(write_xlsx(&[named("a/b")], &path).is_err());
+        assert!(write_xlsx(&[named(&"长".repeat(32))], &path).is_err());
+        assert!(write_xlsx(&[named("Data"), named("data")], &path).is_err());
+        assert!(!path.exists());
+    }
+}
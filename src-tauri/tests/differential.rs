@@ -0,0 +1,279 @@
+//! Python/Rust 分析结果差分测试
+//!
+//! 读取 `tests/fixtures/differential` 下的读者清单 `member.xlsx` 和其余借阅数据文件（xlsx/csv），
+//! 分别交给Python后端和Rust后端分析（整体分析 + 每个工作表单独分析 + 每位读者单独分析），
+//! 逐字段比较 `AnalysisResult`（整体分析和单个工作表分析还比较图表数据、报告数据和完整分析结果），
+//! 有差异时输出完整的字段对照报告。
+//! 会员时长使用固定的统计日期。
+//!
+//! 读者匹配（`reader_identity`、`unresolved_readers`）、会员时长（`total_duration`）、
+//! 图表渲染和报告写入在两种后端中都调用同一份Rust实现（Python后端通过回调使用），
+//! 这些字段的比较结果必然一致，不在差分测试的覆盖范围内，由各模块的单元测试覆盖。
+//! `chart_paths` 在分析结果中始终为空。
+//!
+//! 两种后端分别计算、差分测试真正比较的是：
+//! - `AnalysisResult` 的 `borrow_frequency`、`total_reading_duration`、`total_price`、`monthly_borrow`、
+//!   `monthly_timeline`、`most_frequent_author`、`most_frequent_category`、`book_borrow_counts`、
+//!   `borrow_peak_yearmonth`、`longest_author`、`longest_category`、`borrow_peak`、`most_borrowed_book`、
+//!   `most_borrowed_book_count`、`full_attendance`、`total_full_attendance`；
+//! - 图表数据、报告数据和完整分析结果（Python端由 `analyzer.py` 的 `chart_data`、`report_data`、
+//!   `analysis_data` 生成），其中的会员时长和读者匹配同样来自共用的Rust实现。
+//!
+//! Python后端依赖 pandas 等库，测试默认忽略，在安装了这些依赖的环境（如CI）中用
+//! `cargo test --test differential -- --ignored` 运行（见 `.github/workflows/tests.yml`）；
+//! 运行时Python后端不可用视为失败，不会跳过。
+//! 未启用 `python` 功能时不编译。
+
+#![cfg(feature = "python")]
+
+use chrono::NaiveDate;
+use readerreport_lib::analyzer_backend::{create_backend, AnalyzerBackend, BackendKind};
+use readerreport_lib::column_mapping::ColumnMapping;
+use readerreport_lib::analyzer_backend::AnalysisResult;
+use readerreport_lib::spreadsheet::{SheetScope, SheetSelection};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+const MEMBER_FILE: &str = "member.xlsx";
+
+fn as_of() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 10, 31).unwrap()
+}
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("differential")
+}
+
+/// 夹具目录中的读者清单和借阅数据文件（按文件名排序）
+fn fixture_files() -> (String, Vec<String>) {
+    let dir = fixture_dir();
+    let mut borrow_paths: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("无法读取夹具目录 {:?}: {}", dir, e))
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xlsx" || ext == "csv"))
+        .filter(|path| path.file_name().is_some_and(|name| name != MEMBER_FILE))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    borrow_paths.sort();
+
+    let member_path = dir.join(MEMBER_FILE).to_string_lossy().to_string();
+    (member_path, borrow_paths)
+}
+
+fn to_fields(result: &AnalysisResult) -> Map<String, Value> {
+    let Value::Object(fields) = serde_json::to_value(result).expect("序列化分析结果失败") else {
+        unreachable!("AnalysisResult 应序列化为对象");
+    };
+    fields
+}
+
+/// 逐字段比较，字典类型字段逐键比较
+fn diff_results(
+    scope: &str,
+    python: &AnalysisResult,
+    rust: &AnalysisResult,
+    report: &mut Vec<String>,
+) {
+    let python = to_fields(python);
+    let rust = to_fields(rust);
+
+    for (field, python_value) in &python {
+        let rust_value = rust.get(field).unwrap_or(&Value::Null);
+        match (python_value, rust_value) {
+            (Value::Object(python_map), Value::Object(rust_map)) => {
+                let mut keys: Vec<&String> = python_map.keys().chain(rust_map.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    let python_entry = python_map.get(key).unwrap_or(&Value::Null);
+                    let rust_entry = rust_map.get(key).unwrap_or(&Value::Null);
+                    if python_entry != rust_entry {
+                        report.push(format!(
+                            "[{}] {}[{}]: python = {}, rust = {}",
+                            scope, field, key, python_entry, rust_entry
+                        ));
+                    }
+                }
+            }
+            _ if python_value != rust_value => {
+                report.push(format!(
+                    "[{}] {}: python = {}, rust = {}",
+                    scope, field, python_value, rust_value
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 比较最近一次分析的图表数据
+fn diff_chart_data(
+    scope: &str,
+    python: &dyn AnalyzerBackend,
+    rust: &dyn AnalyzerBackend,
+    report: &mut Vec<String>,
+) {
+    match (python.chart_data(), rust.chart_data()) {
+        (Ok(python_data), Ok(rust_data)) if python_data == rust_data => {}
+        (python_data, rust_data) => report.push(format!(
+            "[{}] chart_data: python = {:?}, rust = {:?}",
+            scope,
+            python_data.map_err(|e| format!("{:#}", e)),
+            rust_data.map_err(|e| format!("{:#}", e)),
+        )),
+    }
+}
+
+/// 比较最近一次分析的报告数据
+fn diff_report_data(
+    scope: &str,
+    python: &dyn AnalyzerBackend,
+    rust: &dyn AnalyzerBackend,
+    report: &mut Vec<String>,
+) {
+    match (python.report_data(), rust.report_data()) {
+        (Ok(python_data), Ok(rust_data)) if python_data == rust_data => {}
+        (python_data, rust_data) => report.push(format!(
+            "[{}] report_data: python = {:?}, rust = {:?}",
+            scope,
+            python_data.map_err(|e| format!("{:#}", e)),
+            rust_data.map_err(|e| format!("{:#}", e)),
+        )),
+    }
+}
+
+/// 逐字段比较最近一次分析的完整结果（导出数据）
+fn diff_analysis_data(
+    scope: &str,
+    python: &dyn AnalyzerBackend,
+    rust: &dyn AnalyzerBackend,
+    report: &mut Vec<String>,
+) {
+    let (python_data, rust_data) = match (python.analysis_data(), rust.analysis_data()) {
+        (Ok(python_data), Ok(rust_data)) => (python_data, rust_data),
+        (python_data, rust_data) => {
+            report.push(format!(
+                "[{}] analysis_data: python = {:?}, rust = {:?}",
+                scope,
+                python_data.map(|_| "成功").map_err(|e| format!("{:#}", e)),
+                rust_data.map(|_| "成功").map_err(|e| format!("{:#}", e)),
+            ));
+            return;
+        }
+    };
+
+    let (Ok(Value::Object(python_fields)), Ok(Value::Object(rust_fields))) =
+        (serde_json::to_value(&python_data), serde_json::to_value(&rust_data))
+    else {
+        unreachable!("AnalysisData 应序列化为对象");
+    };
+    for (field, python_value) in &python_fields {
+        let rust_value = rust_fields.get(field).unwrap_or(&Value::Null);
+        if python_value != rust_value {
+            report.push(format!(
+                "[{}] analysis_data.{}: python = {}, rust = {}",
+                scope, field, python_value, rust_value
+            ));
+        }
+    }
+}
+
+fn python_backend() -> Box<dyn AnalyzerBackend> {
+    create_backend(BackendKind::Python, None)
+        .unwrap_or_else(|e| panic!("Python分析器不可用（需要安装 python/requirements.txt 中的依赖）: {:#}", e))
+}
+
+#[test]
+#[ignore = "需要安装了 pandas 等依赖的Python环境，使用 --ignored 运行"]
+fn python_and_rust_backends_agree_on_fixtures() {
+    let python = python_backend();
+    let rust = create_backend(BackendKind::Rust, None).expect("创建Rust分析器失败");
+
+    let (member_path, borrow_paths) = fixture_files();
+    assert!(!borrow_paths.is_empty(), "夹具目录中没有借阅数据文件");
+
+    // 读取所有工作表，多工作表文件的每个工作表单独计数
+    let sheet_selection = SheetSelection {
+        scope: SheetScope::All,
+        ..Default::default()
+    };
+    for backend in [&python, &rust] {
+        backend
+            .load_data(
+                borrow_paths.clone(),
+                member_path.clone(),
+                &ColumnMapping::default(),
+                &sheet_selection,
+                None,
+            )
+            .unwrap_or_else(|e| panic!("{} 加载数据失败: {:#}", backend.name(), e));
+    }
+
+    let mut report = Vec::new();
+
+    let python_result = python.analyze(as_of(), None).expect("Python分析失败");
+    let rust_result = rust.analyze(as_of(), None).expect("Rust分析失败");
+    diff_results("全部文件", &python_result, &rust_result, &mut report);
+    diff_chart_data("全部文件", python.as_ref(), rust.as_ref(), &mut report);
+    diff_report_data("全部文件", python.as_ref(), rust.as_ref(), &mut report);
+    diff_analysis_data("全部文件", python.as_ref(), rust.as_ref(), &mut report);
+
+    let file_count = python.get_file_count().expect("获取文件数量失败");
+    assert_eq!(file_count, rust.get_file_count().expect("获取文件数量失败"));
+
+    for index in 0..file_count {
+        let name = python.get_file_name(index).expect("获取文件名失败");
+        assert_eq!(name, rust.get_file_name(index).expect("获取文件名失败"));
+
+        match (
+            python.analyze_single_file(index, as_of(), None),
+            rust.analyze_single_file(index, as_of(), None),
+        ) {
+            (Ok(python_result), Ok(rust_result)) => {
+                diff_results(&name, &python_result, &rust_result, &mut report);
+                diff_chart_data(&name, python.as_ref(), rust.as_ref(), &mut report);
+                diff_report_data(&name, python.as_ref(), rust.as_ref(), &mut report);
+                diff_analysis_data(&name, python.as_ref(), rust.as_ref(), &mut report);
+            }
+            (python_result, rust_result) => report.push(format!(
+                "[{}] 分析结果不一致: python = {:?}, rust = {:?}",
+                name,
+                python_result.map(|_| "成功").map_err(|e| format!("{:#}", e)),
+                rust_result.map(|_| "成功").map_err(|e| format!("{:#}", e)),
+            )),
+        }
+    }
+
+    let reader_count = python.get_reader_count().expect("获取读者数量失败");
+    assert_eq!(reader_count, rust.get_reader_count().expect("获取读者数量失败"));
+
+    for index in 0..reader_count {
+        let name = python.get_reader_name(index).expect("获取读者名称失败");
+        assert_eq!(name, rust.get_reader_name(index).expect("获取读者名称失败"));
+
+        match (
+            python.analyze_reader(index, as_of(), None),
+            rust.analyze_reader(index, as_of(), None),
+        ) {
+            (Ok(python_result), Ok(rust_result)) => {
+                diff_results(&format!("读者 {}", name), &python_result, &rust_result, &mut report)
+            }
+            (python_result, rust_result) => report.push(format!(
+                "[读者 {}] 分析结果不一致: python = {:?}, rust = {:?}",
+                name,
+                python_result.map(|_| "成功").map_err(|e| format!("{:#}", e)),
+                rust_result.map(|_| "成功").map_err(|e| format!("{:#}", e)),
+            )),
+        }
+    }
+
+    assert!(
+        report.is_empty(),
+        "Python与Rust分析结果存在 {} 处差异:\n{}",
+        report.len(),
+        report.join("\n")
+    );
+}
@@ -0,0 +1,226 @@
+"""
+生成差分测试使用的夹具文件
+
+只依赖Python标准库，直接写出最小的 .xlsx 结构，日期单元格使用Excel日期格式，
+与图书馆管理系统导出的文件一致（pandas 读取为 datetime，calamine 读取为 DateTime）。
+另有一个GBK编码的CSV文件，对应分馆系统的文本导出；以及一个每月一个工作表的工作簿。
+
+用法: python generate_fixtures.py
+"""
+import csv
+import datetime
+import io
+import os
+import zipfile
+from xml.sax.saxutils import escape
+
+FIXTURE_DIR = os.path.join(os.path.dirname(os.path.abspath(__file__)), "differential")
+
+BORROW_HEADER = ["姓名", "书名", "作者", "类别", "定价", "借书日期", "还书日期"]
+MEMBER_HEADER = ["姓名", "办卡日期", "读者证号"]
+
+CONTENT_TYPES = """<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+{sheet_overrides}
+<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>
+</Types>"""
+
+ROOT_RELS = """<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"""
+
+WORKBOOK = """<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<sheets>{sheets}</sheets>
+</workbook>"""
+
+WORKBOOK_RELS = """<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+{sheet_rels}
+<Relationship Id="rIdStyles" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+</Relationships>"""
+
+# 样式1为日期格式 (numFmtId=14)
+STYLES = """<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+<fonts count="1"><font><sz val="11"/><name val="Calibri"/></font></fonts>
+<fills count="1"><fill><patternFill patternType="none"/></fill></fills>
+<borders count="1"><border/></borders>
+<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>
+<cellXfs count="2">
+<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>
+<xf numFmtId="14" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
+</cellXfs>
+</styleSheet>"""
+
+EXCEL_EPOCH = datetime.date(1899, 12, 30)
+
+
+def column_name(index: int) -> str:
+    name = ""
+    index += 1
+    while index:
+        index, rem = divmod(index - 1, 26)
+        name = chr(ord("A") + rem) + name
+    return name
+
+
+def cell_xml(ref: str, value) -> str:
+    if value is None:
+        return ""
+    if isinstance(value, datetime.date):
+        serial = (value - EXCEL_EPOCH).days
+        return f'<c r="{ref}" s="1"><v>{serial}</v></c>'
+    if isinstance(value, (int, float)):
+        return f'<c r="{ref}"><v>{value}</v></c>'
+    return f'<c r="{ref}" t="inlineStr"><is><t>{escape(str(value))}</t></is></c>'
+
+
+def sheet_xml(rows: list[list]) -> str:
+    row_xml = []
+    for r, row in enumerate(rows, start=1):
+        cells = "".join(cell_xml(f"{column_name(c)}{r}", v) for c, v in enumerate(row))
+        row_xml.append(f'<row r="{r}">{cells}</row>')
+    return (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+        '<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">'
+        f'<sheetData>{"".join(row_xml)}</sheetData></worksheet>'
+    )
+
+
+def write_workbook(path: str, sheets: dict[str, list[list]]) -> None:
+    """写出包含多个工作表的工作簿，工作表按字典顺序排列"""
+    numbers = range(1, len(sheets) + 1)
+    content_types = CONTENT_TYPES.format(sheet_overrides="\n".join(
+        f'<Override PartName="/xl/worksheets/sheet{i}.xml" '
+        'ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>'
+        for i in numbers))
+    workbook = WORKBOOK.format(sheets="".join(
+        f'<sheet name="{escape(name)}" sheetId="{i}" r:id="rId{i}"/>'
+        for i, name in zip(numbers, sheets)))
+    workbook_rels = WORKBOOK_RELS.format(sheet_rels="\n".join(
+        f'<Relationship Id="rId{i}" '
+        'Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" '
+        f'Target="worksheets/sheet{i}.xml"/>'
+        for i in numbers))
+
+    with zipfile.ZipFile(path, "w", zipfile.ZIP_DEFLATED) as z:
+        z.writestr("[Content_Types].xml", content_types)
+        z.writestr("_rels/.rels", ROOT_RELS)
+        z.writestr("xl/workbook.xml", workbook)
+        z.writestr("xl/_rels/workbook.xml.rels", workbook_rels)
+        z.writestr("xl/styles.xml", STYLES)
+        for i, rows in zip(numbers, sheets.values()):
+            z.writestr(f"xl/worksheets/sheet{i}.xml", sheet_xml(rows))
+
+
+def write_xlsx(path: str, rows: list[list]) -> None:
+    write_workbook(path, {"Sheet1": rows})
+
+
+def write_csv(path: str, rows: list[list], encoding: str) -> None:
+    buffer = io.StringIO()
+    writer = csv.writer(buffer, lineterminator="\r\n")
+    for row in rows:
+        writer.writerow([v.strftime("%Y/%m/%d") if isinstance(v, datetime.date) else v for v in row])
+    with open(path, "wb") as f:
+        f.write(buffer.getvalue().encode(encoding))
+
+
+def d(year: int, month: int, day: int) -> datetime.date:
+    return datetime.date(year, month, day)
+
+
+def borrow(name, title, author, category, price, borrowed, returned):
+    return [name, title, author, category, price, borrowed, returned]
+
+
+def main() -> None:
+    os.makedirs(FIXTURE_DIR, exist_ok=True)
+
+    # 同名读者按读者证号区分；两位没有证号的孙七无法区分
+    write_xlsx(os.path.join(FIXTURE_DIR, "member.xlsx"), [
+        MEMBER_HEADER,
+        ["张三", d(2023, 9, 1), None],
+        ["王五", d(2024, 2, 29), None],
+        ["赵六", d(2022, 5, 20), "000123"],
+        ["赵六", d(2024, 11, 3), "000456"],
+        ["孙七", d(2021, 1, 4), None],
+        ["孙七", d(2023, 6, 15), None],
+    ])
+
+    # 跨月ISO周：2025-03-31 属于 2025-W14，该周同时跨越三月和四月；
+    # 2024-12-30 属于 2025-W01，跨越年份
+    write_xlsx(os.path.join(FIXTURE_DIR, "cross_month_weeks.xlsx"), [
+        BORROW_HEADER,
+        borrow("张三", "小王子", "圣埃克苏佩里", "CFI", 25.5, d(2025, 3, 31), d(2025, 4, 14)),
+        borrow("张三", "昆虫记", "法布尔", "CNF", 39.8, d(2025, 4, 7), d(2025, 4, 20)),
+        borrow("张三", "小王子", "圣埃克苏佩里", "CFI", 25.5, d(2025, 4, 15), d(2025, 4, 22)),
+        borrow("张三", "猜猜我有多爱你", "山姆·麦克布雷尼", "CPB", 12.0, d(2025, 4, 23), d(2025, 4, 30)),
+        borrow("张三", "Goodnight Moon", "Margaret Wise Brown", "EPB", 8.99, d(2025, 4, 28), d(2025, 5, 6)),
+        borrow("张三", "夏洛的网", "E·B·怀特", "CFI", 28.0, d(2024, 12, 30), d(2025, 1, 13)),
+        borrow("张三", "昆虫记", "法布尔", "CNF", 39.8, d(2025, 1, 6), d(2025, 1, 20)),
+    ])
+
+    # 读者清单中不存在的读者
+    write_xlsx(os.path.join(FIXTURE_DIR, "missing_member.xlsx"), [
+        BORROW_HEADER,
+        borrow("李四", "西游记", "吴承恩", "CFI", 45.0, d(2025, 2, 3), d(2025, 2, 17)),
+        borrow("李四", "三国演义", "罗贯中", "CFI", 52.3, d(2025, 2, 10), d(2025, 2, 11)),
+        borrow("李四", "西游记", "吴承恩", "CFI", 45.0, d(2025, 2, 24), d(2025, 3, 3)),
+    ])
+
+    # 未知的类别代码原样保留
+    write_xlsx(os.path.join(FIXTURE_DIR, "unknown_category.xlsx"), [
+        BORROW_HEADER,
+        borrow("王五", "神奇校车", "乔安娜·柯尔", "XYZ", 18.8, d(2025, 5, 5), d(2025, 5, 19)),
+        borrow("王五", "神奇校车", "乔安娜·柯尔", "XYZ", 18.8, d(2025, 5, 12), d(2025, 5, 13)),
+        borrow("王五", "Oxford Reading Tree", "Roderick Hunt", "EER", 6.5, d(2025, 5, 19), d(2025, 6, 2)),
+        borrow("王五", "十万个为什么", "韩启德", "Q99", 30.1, d(2025, 5, 26), d(2025, 6, 9)),
+    ])
+
+    # 每月一个工作表；差分测试读取所有工作表，每个工作表单独计数
+    write_workbook(os.path.join(FIXTURE_DIR, "monthly_sheets.xlsx"), {
+        "八月": [
+            BORROW_HEADER,
+            borrow("王五", "窗边的小豆豆", "黑柳彻子", "CFI", 25.0, d(2025, 8, 4), d(2025, 8, 18)),
+            borrow("王五", "城南旧事", "林海音", "CFI", 19.8, d(2025, 8, 11), d(2025, 8, 25)),
+        ],
+        "九月": [
+            BORROW_HEADER,
+            borrow("王五", "城南旧事", "林海音", "CFI", 19.8, d(2025, 9, 1), d(2025, 9, 15)),
+        ],
+        "空白": [],
+    })
+
+    # 全馆导出：多位读者混在同一文件中，同名读者按读者证号区分
+    write_xlsx(os.path.join(FIXTURE_DIR, "library_export.xlsx"), [
+        BORROW_HEADER + ["读者证号"],
+        borrow("赵六", "小王子", "圣埃克苏佩里", "CFI", 25.5, d(2025, 3, 3), d(2025, 3, 17)) + ["000123"],
+        borrow("赵六", "昆虫记", "法布尔", "CNF", 39.8, d(2025, 3, 4), d(2025, 3, 10)) + ["000456"],
+        borrow("张三", "草房子", "曹文轩", "CFI", 22.0, d(2025, 3, 5), d(2025, 3, 19)) + [None],
+        borrow("赵六 ", "夏洛的网", "E·B·怀特", "CFI", 28.0, d(2025, 3, 10), d(2025, 3, 24)) + ["000123"],
+    ])
+
+    # 姓名写法不同：繁体、全角空格；孙七在读者清单中有两位
+    write_xlsx(os.path.join(FIXTURE_DIR, "identity_variants.xlsx"), [
+        BORROW_HEADER,
+        borrow("張\u3000三", "城南旧事", "林海音", "CFI", 19.8, d(2025, 10, 6), d(2025, 10, 20)),
+        borrow("孫七", "稻草人", "叶圣陶", "CFI", 15.0, d(2025, 10, 8), d(2025, 10, 22)),
+    ])
+
+    # GBK编码的CSV导出，日期为文本
+    write_csv(os.path.join(FIXTURE_DIR, "gbk_export.csv"), [
+        BORROW_HEADER,
+        borrow("张三", "草房子", "曹文轩", "CFI", 22.0, d(2025, 6, 2), d(2025, 6, 16)),
+        borrow("张三", "草房子", "曹文轩", "CFI", 22.0, d(2025, 6, 20), d(2025, 6, 27)),
+        borrow("张三", "寄小读者", "冰心", "CNF", 16.5, d(2025, 7, 1), d(2025, 7, 15)),
+    ], "gbk")
+
+
+if __name__ == "__main__":
+    main()
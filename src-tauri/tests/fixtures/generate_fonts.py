@@ -0,0 +1,290 @@
+"""
+生成PDF字体嵌入测试使用的最小字体
+
+只依赖Python标准库，字形都是简单的多边形，不含任何第三方字体数据：
+- fonts/test-glyf.ttf：TrueType轮廓，`中` 是引用 `B` 和 `C` 的复合字形
+- fonts/test-cff.otf：CID-keyed 的CFF轮廓，两个FD各有本地子程序，另有全局子程序；
+  字形编号与CID不同，`中` 的 hintmask 需要两个掩码字节（第二个字节恰好是 callgsubr 的操作码）
+
+两个字体都包含 .notdef、A、B、中、文、C 六个字形，字形编号依次为 0~5。
+
+用法: python generate_fonts.py
+"""
+import os
+import struct
+
+FONT_DIR = os.path.join(os.path.dirname(os.path.abspath(__file__)), "fonts")
+
+UNITS_PER_EM = 1000
+ASCENDER = 880
+DESCENDER = -120
+# 字符到字形编号
+CMAP = {"A": 1, "B": 2, "中": 3, "文": 4, "C": 5}
+ADVANCES = [500, 600, 620, 1000, 1000, 640]
+# CFF字体中字形编号对应的CID（0 号字形的CID为0）
+CIDS = [0, 10, 11, 20, 21, 12]
+
+
+def checksum(data: bytes) -> int:
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(f">{len(data) // 4}I", data)) & 0xFFFFFFFF
+
+
+def build_sfnt(version: bytes, tables: dict[bytes, bytes]) -> bytes:
+    tags = sorted(tables)
+    count = len(tags)
+    entry_selector = count.bit_length() - 1
+    search_range = (1 << entry_selector) * 16
+    font = version + struct.pack(">HHHH", count, search_range, entry_selector, count * 16 - search_range)
+    offset = 12 + count * 16
+    body = b""
+    for tag in tags:
+        data = tables[tag]
+        font += tag + struct.pack(">III", checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+    return font + body
+
+
+def head_table(bbox, long_loca: int) -> bytes:
+    return struct.pack(
+        ">IIIIHHqqhhhhHHhhh",
+        0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0, UNITS_PER_EM, 0, 0,
+        *bbox, 0, 8, 2, long_loca, 0,
+    )
+
+
+def hhea_table() -> bytes:
+    return struct.pack(
+        ">IhhhHhhhhhhhhhhhH",
+        0x00010000, ASCENDER, DESCENDER, 0, max(ADVANCES), 0, 0, max(ADVANCES),
+        1, 0, 0, 0, 0, 0, 0, 0, len(ADVANCES),
+    )
+
+
+def hmtx_table() -> bytes:
+    return b"".join(struct.pack(">Hh", advance, 0) for advance in ADVANCES)
+
+
+def cmap_table() -> bytes:
+    # 格式4，每个字符一个区间，最后是 0xFFFF 结束区间
+    codes = sorted(ord(c) for c in CMAP)
+    glyphs = {ord(c): glyph for c, glyph in CMAP.items()}
+    segments = [(code, code, (glyphs[code] - code) % 0x10000) for code in codes] + [(0xFFFF, 0xFFFF, 1)]
+    count = len(segments)
+    entry_selector = count.bit_length() - 1
+    search_range = (1 << entry_selector) * 2
+    subtable = struct.pack(">HHHHHHH", 4, 0, 0, count * 2, search_range, entry_selector, count * 2 - search_range)
+    subtable += b"".join(struct.pack(">H", end) for _, end, _ in segments) + b"\0\0"
+    subtable += b"".join(struct.pack(">H", start) for start, _, _ in segments)
+    subtable += b"".join(struct.pack(">H", delta) for _, _, delta in segments)
+    subtable += b"\0\0" * count
+    subtable = subtable[:2] + struct.pack(">H", len(subtable)) + subtable[4:]
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+
+def name_table(postscript_name: str) -> bytes:
+    value = postscript_name.encode("utf-16-be")
+    return struct.pack(">HHHHHHHHH", 0, 1, 18, 3, 1, 0x409, 6, len(value), 0) + value
+
+
+def post_table() -> bytes:
+    return struct.pack(">IIhhIIIII", 0x00030000, 0, -100, 50, 0, 0, 0, 0, 0)
+
+
+# ---------- TrueType ----------
+
+def simple_glyph(points: list[tuple[int, int]]) -> bytes:
+    xs = [x for x, _ in points]
+    ys = [y for _, y in points]
+    data = struct.pack(">hhhhh", 1, min(xs), min(ys), max(xs), max(ys))
+    data += struct.pack(">HH", len(points) - 1, 0)
+    data += bytes([0x01] * len(points))
+    previous = (0, 0)
+    deltas_x, deltas_y = b"", b""
+    for x, y in points:
+        deltas_x += struct.pack(">h", x - previous[0])
+        deltas_y += struct.pack(">h", y - previous[1])
+        previous = (x, y)
+    return data + deltas_x + deltas_y
+
+
+def composite_glyph(bbox, components: list[tuple[int, int, int]]) -> bytes:
+    data = struct.pack(">hhhhh", -1, *bbox)
+    for i, (glyph, dx, dy) in enumerate(components):
+        # ARG_1_AND_2_ARE_WORDS | ARGS_ARE_XY_VALUES，除最后一个外加 MORE_COMPONENTS
+        flags = 0x0001 | 0x0002 | (0x0020 if i + 1 < len(components) else 0)
+        data += struct.pack(">HHhh", flags, glyph, dx, dy)
+    return data
+
+
+def write_glyf_font(path: str) -> None:
+    glyphs = [
+        simple_glyph([(50, 0), (50, 700), (450, 700), (450, 0)]),
+        simple_glyph([(100, 0), (300, 700), (500, 0)]),
+        simple_glyph([(100, 0), (100, 700), (500, 700), (500, 0)]),
+        composite_glyph((100, 0, 960, 700), [(2, 0, 0), (5, 400, 0)]),
+        simple_glyph([(100, 0), (500, 400), (100, 800)]),
+        simple_glyph([(100, 0), (100, 600), (560, 600)]),
+    ]
+    glyf, loca = b"", b""
+    for glyph in glyphs:
+        glyph += b"\0" * (-len(glyph) % 2)
+        loca += struct.pack(">H", len(glyf) // 2)
+        glyf += glyph
+    loca += struct.pack(">H", len(glyf) // 2)
+
+    maxp = struct.pack(">IHHHHHHHHHHHHHH", 0x00010000, len(glyphs), 4, 1, 8, 2, 2, 0, 0, 0, 0, 0, 0, 2, 1)
+    tables = {
+        b"head": head_table((50, 0, 960, 800), 0),
+        b"hhea": hhea_table(),
+        b"maxp": maxp,
+        b"hmtx": hmtx_table(),
+        b"cmap": cmap_table(),
+        b"loca": loca,
+        b"glyf": glyf,
+        b"name": name_table("RRTestGlyf"),
+        b"post": post_table(),
+    }
+    with open(path, "wb") as f:
+        f.write(build_sfnt(b"\0\1\0\0", tables))
+
+
+# ---------- CFF ----------
+
+def number(value: int) -> bytes:
+    """Type 2 charstring / DICT 中的整数（-107~107 为单字节，其余用三字节形式）"""
+    if -107 <= value <= 107:
+        return bytes([value + 139])
+    return b"\x1c" + struct.pack(">h", value)
+
+
+def offset_number(value: int) -> bytes:
+    return b"\x1d" + struct.pack(">i", value)
+
+
+def program(*items) -> bytes:
+    """整数为操作数，bytes 为操作符或原始字节"""
+    return b"".join(number(item) if isinstance(item, int) else item for item in items)
+
+
+def index(items: list[bytes]) -> bytes:
+    if not items:
+        return b"\0\0"
+    offsets = [1]
+    for item in items:
+        offsets.append(offsets[-1] + len(item))
+    off_size = 1 if offsets[-1] < 0x100 else 2
+    data = struct.pack(">HB", len(items), off_size)
+    data += b"".join(offset.to_bytes(off_size, "big") for offset in offsets)
+    return data + b"".join(items)
+
+
+RMOVETO, RLINETO, HSTEM, HSTEMHM, HINTMASK = b"\x15", b"\x05", b"\x01", b"\x12", b"\x13"
+CALLSUBR, CALLGSUBR, RETURN, ENDCHAR = b"\x0a", b"\x1d", b"\x0b", b"\x0e"
+# 子程序数量少于1240时编号的偏置为107
+BIAS = 107
+
+
+def write_cff_font(path: str) -> None:
+    global_subrs = [
+        program(-100, 100, RLINETO, RETURN),
+        program(0, 600, RLINETO, 300, 0, RLINETO, RETURN),
+        # 全局子程序调用当前字形所属FD的本地子程序
+        program(0 - BIAS, CALLSUBR, RETURN),
+    ]
+    local_subrs = [
+        [
+            program(200, 700, RLINETO, 200, -700, RLINETO, RETURN),
+            program(0, 700, RLINETO, 400, 0, RLINETO, RETURN),
+        ],
+        [
+            program(500, 0, RLINETO, 0, 500, RLINETO, RETURN),
+            program(300, 300, RLINETO, -300, 300, RLINETO, RETURN),
+        ],
+    ]
+    hstems = [0, 10, 20, 10, 20, 10, 20, 10, 20, 10, 20, 10, 20, 10, 20, 10]
+    charstrings = [
+        program(50, 0, RMOVETO, 0, 700, RLINETO, 400, 0, RLINETO, ENDCHAR),
+        program(100, 0, RMOVETO, 0 - BIAS, CALLSUBR, 0 - BIAS, CALLGSUBR, ENDCHAR),
+        program(0, 50, HSTEM, 100, 0, RMOVETO, 1 - BIAS, CALLSUBR, ENDCHAR),
+        # 8 个 hstemhm 加上 hintmask 前隐含的 1 个 vstem，共 9 个提示，掩码为两个字节
+        program(*hstems, HSTEMHM, 50, 60, HINTMASK, b"\xff\x1d", 100, 100, RMOVETO, 2 - BIAS, CALLGSUBR, ENDCHAR),
+        program(100, 0, RMOVETO, 1 - BIAS, CALLSUBR, ENDCHAR),
+        program(100, 0, RMOVETO, 1 - BIAS, CALLGSUBR, ENDCHAR),
+    ]
+
+    strings = [b"Adobe", b"Identity", b"RRTestCFF"]
+    header = bytes([1, 0, 4, 4])
+    name_index = index([b"RRTestCFF"])
+    string_index = index(strings)
+    global_subr_index = index(global_subrs)
+    # 字符集格式0：依次列出 1 号字形之后各字形的CID
+    charset = b"\0" + b"".join(struct.pack(">H", cid) for cid in CIDS[1:])
+    # FDSelect格式3：0~2 号字形属于FD0，3~5 号字形属于FD1
+    fd_select = struct.pack(">BHHBHBH", 3, 2, 0, 0, 3, 1, len(charstrings))
+    charstring_index = index(charstrings)
+
+    privates = []
+    for subrs in local_subrs:
+        # Subrs 的偏移量相对于 Private DICT，本地子程序紧跟在 DICT 之后
+        dict_without_subrs = program(500, b"\x14")
+        length = len(dict_without_subrs) + 2
+        privates.append(dict_without_subrs + program(length, b"\x13") + index(subrs))
+    private_lengths = [len(program(500, b"\x14")) + 2 for _ in local_subrs]
+
+    def top_dict(charset_offset, fd_select_offset, charstrings_offset, fd_array_offset):
+        return (
+            program(391, 392, 0, b"\x0c\x1e")
+            + program(-100, -150, 1000, 900, b"\x05")
+            + program(len(CIDS) + 20, b"\x0c\x22")
+            + offset_number(charset_offset) + b"\x0f"
+            + offset_number(fd_select_offset) + b"\x0c\x25"
+            + offset_number(charstrings_offset) + b"\x11"
+            + offset_number(fd_array_offset) + b"\x0c\x24"
+        )
+
+    def fd_dict(size, offset):
+        return offset_number(size) + offset_number(offset) + b"\x12"
+
+    top_length = len(index([top_dict(0, 0, 0, 0)]))
+    fd_array_length = len(index([fd_dict(0, 0) for _ in privates]))
+    charset_offset = len(header) + len(name_index) + top_length + len(string_index) + len(global_subr_index)
+    fd_select_offset = charset_offset + len(charset)
+    charstrings_offset = fd_select_offset + len(fd_select)
+    fd_array_offset = charstrings_offset + len(charstring_index)
+    private_offset = fd_array_offset + fd_array_length
+
+    fd_dicts = []
+    for private, length in zip(privates, private_lengths):
+        fd_dicts.append(fd_dict(length, private_offset))
+        private_offset += len(private)
+
+    cff = (
+        header + name_index
+        + index([top_dict(charset_offset, fd_select_offset, charstrings_offset, fd_array_offset)])
+        + string_index + global_subr_index + charset + fd_select + charstring_index
+        + index(fd_dicts) + b"".join(privates)
+    )
+
+    tables = {
+        b"head": head_table((-100, -150, 1000, 900), 0),
+        b"hhea": hhea_table(),
+        b"maxp": struct.pack(">IH", 0x00005000, len(charstrings)),
+        b"hmtx": hmtx_table(),
+        b"cmap": cmap_table(),
+        b"CFF ": cff,
+        b"name": name_table("RRTestCFF"),
+        b"post": post_table(),
+    }
+    with open(path, "wb") as f:
+        f.write(build_sfnt(b"OTTO", tables))
+
+
+def main() -> None:
+    os.makedirs(FONT_DIR, exist_ok=True)
+    write_glyf_font(os.path.join(FONT_DIR, "test-glyf.ttf"))
+    write_cff_font(os.path.join(FONT_DIR, "test-cff.otf"))
+
+
+if __name__ == "__main__":
+    main()
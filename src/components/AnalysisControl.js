@@ -54,6 +54,17 @@ export class AnalysisControl {
         }
     }
 
+    // 订阅后端的 analysis://progress 事件；只显示指定任务的进度，jobId 为空时显示所有任务
+    listenProgress(jobId = null) {
+        return window.__TAURI__.event.listen('analysis://progress', ({ payload }) => {
+            if (jobId && payload.job_id !== jobId) {
+                return;
+            }
+            const progress = payload.total > 0 ? Math.round(payload.current / payload.total * 100) : 0;
+            this.updateProgress(progress, `${payload.message} (${payload.current}/${payload.total})`);
+        });
+    }
+
     mount(parent) {
         parent.appendChild(this.element);
     }
@@ -65,8 +65,8 @@ export class FileSelector {
             const selected = await this.openDialog({
                 multiple: true,
                 filters: [{
-                    name: 'Excel文件',
-                    extensions: ['xlsx', 'xls']
+                    name: '表格文件',
+                    extensions: ['xlsx', 'xls', 'xlsb', 'ods', 'csv', 'tsv']
                 }],
                 title: '选择借阅数据文件'
             });
@@ -93,8 +93,8 @@ export class FileSelector {
             const selected = await this.openDialog({
                 multiple: false,
                 filters: [{
-                    name: 'Excel文件',
-                    extensions: ['xlsx', 'xls']
+                    name: '表格文件',
+                    extensions: ['xlsx', 'xls', 'xlsb', 'ods', 'csv', 'tsv']
                 }],
                 title: '选择读者清单文件'
             });
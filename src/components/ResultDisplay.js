@@ -1,7 +1,27 @@
+// 会员时长的文字描述
+function formatMembershipDuration(duration) {
+    if (!duration || duration.status !== 'known') {
+        return '未知';
+    }
+    return `${duration.years}年${duration.months}个月${duration.days}天（共 ${duration.total_days} 天）`;
+}
+
+const SVG_NS = 'http://www.w3.org/2000/svg';
+// 与静态图表一致的配色
+const CHART_COLORS = ['#1f77b4', '#ff7f0e', '#2ca02c', '#d62728', '#9467bd',
+    '#8c564b', '#e377c2', '#7f7f7f', '#bcbd22', '#17becf'];
+
+function svgElement(tag, attributes) {
+    const element = document.createElementNS(SVG_NS, tag);
+    Object.entries(attributes).forEach(([name, value]) => element.setAttribute(name, value));
+    return element;
+}
+
 export class ResultDisplay {
     constructor() {
         this.element = this._createUI();
         this.onExportReportCallback = null;
+        this.onChartTopNChangeCallback = null;
     }
 
     _createUI() {
@@ -56,12 +76,12 @@ export class ResultDisplay {
         // 显示基础统计信息
         const stats = [
             ['借阅频次', `${analysisResult.borrow_frequency} 次`],
-            ['会员时长', analysisResult.total_duration],
+            ['会员时长', formatMembershipDuration(analysisResult.total_duration)],
             ['总阅读时长', `${analysisResult.total_reading_duration} 天`],
             ['图书总价值', `¥${analysisResult.total_price.toFixed(2)}`],
             ['最喜欢的作者', analysisResult.longest_author],
             ['最喜欢的类别', analysisResult.longest_category],
-            ['借阅高峰期', `${analysisResult.borrow_peak} 月`],
+            ['借阅高峰期', analysisResult.borrow_peak_yearmonth],
             ['最常借阅的书', `${analysisResult.most_borrowed_book} (${analysisResult.most_borrowed_book_count}次)`],
             ['全勤月总数', `${analysisResult.total_full_attendance} 个月`]
         ];
@@ -127,11 +147,225 @@ export class ResultDisplay {
         });
     }
 
+    // 交互图表：series 为 get_chart_data 返回的数据序列
+    displayChartData(series) {
+        this.chartSection.innerHTML = '';
+
+        // 多条折线的图表（逐年对比）数据在 groups 中
+        const hasData = item => item.values.length > 0 || item.groups.length > 0;
+        if (!series || !series.some(hasData)) {
+            const noChartsMsg = document.createElement('div');
+            noChartsMsg.className = 'no-charts';
+            noChartsMsg.textContent = '暂无图表数据';
+            this.chartSection.appendChild(noChartsMsg);
+            return;
+        }
+
+        const chartTitle = document.createElement('h3');
+        chartTitle.textContent = '数据可视化';
+        chartTitle.className = 'chart-title';
+        this.chartSection.appendChild(chartTitle);
+
+        series.forEach(item => {
+            const chartContainer = document.createElement('div');
+            chartContainer.className = 'chart-container';
+
+            const chartLabel = document.createElement('div');
+            chartLabel.className = 'chart-label';
+            chartLabel.textContent = item.title;
+            chartContainer.appendChild(chartLabel);
+
+            // 点击数据项时在图表下方显示详情
+            const detail = document.createElement('div');
+            detail.className = 'chart-detail';
+            const showDetail = (index, group) => {
+                const values = group ? group.values : item.values;
+                const total = values.reduce((sum, value) => sum + (value || 0), 0);
+                const ratio = total > 0 ? (values[index] / total * 100).toFixed(1) : '0.0';
+                const prefix = group ? `${group.name}年 ` : '';
+                detail.textContent = `${prefix}${item.label_name}：${item.labels[index]}，` +
+                    `${item.value_name}：${values[index]} ${item.unit}（占 ${ratio}%）`;
+            };
+
+            if (!hasData(item)) {
+                const empty = document.createElement('div');
+                empty.className = 'chart-placeholder';
+                empty.textContent = '暂无数据';
+                chartContainer.appendChild(empty);
+            } else if (item.chart_type === 'bar') {
+                chartContainer.appendChild(this._createBarChart(item, showDetail));
+            } else if (item.chart_type === 'line') {
+                chartContainer.appendChild(this._createLineChart(item, showDetail));
+            } else {
+                chartContainer.appendChild(this._createPieChart(item, showDetail));
+            }
+
+            // 阅读时长图表只显示前 N 本书，可以展开全部
+            if (item.key === 'duration' && item.total > item.values.length) {
+                const moreButton = document.createElement('button');
+                moreButton.className = 'chart-more-button';
+                moreButton.textContent = `显示全部 ${item.total} 本`;
+                moreButton.addEventListener('click', () => this._handleChartTopNChange(item.total));
+                chartContainer.appendChild(moreButton);
+            }
+
+            chartContainer.appendChild(detail);
+            this.chartSection.appendChild(chartContainer);
+        });
+    }
+
+    // 横向条形图，数值最大的在最上方
+    _createBarChart(item, onSelect) {
+        const rowHeight = 28;
+        const labelWidth = 200;
+        const width = 720;
+        const height = item.values.length * rowHeight + 20;
+        const max = Math.max(...item.values, 1);
+        const svg = svgElement('svg', { viewBox: `0 0 ${width} ${height}`, class: 'interactive-chart' });
+
+        item.values.forEach((value, index) => {
+            const y = 10 + index * rowHeight;
+            const label = svgElement('text', {
+                x: labelWidth - 8, y: y + rowHeight / 2, 'text-anchor': 'end', 'dominant-baseline': 'middle'
+            });
+            const name = item.labels[index];
+            label.textContent = name.length > 12 ? name.slice(0, 12) + '…' : name;
+            svg.appendChild(label);
+
+            const bar = svgElement('rect', {
+                x: labelWidth, y: y + 4, height: rowHeight - 8,
+                width: Math.max(value / max * (width - labelWidth - 60), 1),
+                fill: CHART_COLORS[0], class: 'chart-mark'
+            });
+            this._attachTooltip(bar, item, index, onSelect);
+            svg.appendChild(bar);
+
+            const valueText = svgElement('text', {
+                x: labelWidth + value / max * (width - labelWidth - 60) + 6,
+                y: y + rowHeight / 2, 'dominant-baseline': 'middle'
+            });
+            valueText.textContent = value;
+            svg.appendChild(valueText);
+        });
+        return svg;
+    }
+
+    // 折线图，每个数据点可以单独悬停和点击
+    // 有 groups 时每组一条折线（逐年对比），缺少的数据点不绘制
+    _createLineChart(item, onSelect) {
+        const width = 720;
+        const height = 300;
+        const padding = 40;
+        const groups = item.groups.length > 0 ? item.groups : [null];
+        const valuesOf = group => (group ? group.values : item.values);
+        const max = Math.max(...groups.flatMap(group => valuesOf(group).filter(value => value !== null)), 1);
+        const count = item.labels.length;
+        const step = count > 1 ? (width - padding * 2) / (count - 1) : 0;
+        const x = index => (count > 1 ? padding + index * step : width / 2);
+        const y = value => height - padding - value / max * (height - padding * 2);
+        const svg = svgElement('svg', { viewBox: `0 0 ${width} ${height}`, class: 'interactive-chart' });
+
+        // 月份较多时间隔显示标签
+        const labelStep = Math.ceil(count / 12);
+        item.labels.forEach((text, index) => {
+            if (index % labelStep !== 0) return;
+            const label = svgElement('text', { x: x(index), y: height - padding + 20, 'text-anchor': 'middle' });
+            label.textContent = text;
+            svg.appendChild(label);
+        });
+
+        groups.forEach((group, groupIndex) => {
+            const color = CHART_COLORS[groupIndex % CHART_COLORS.length];
+            const points = valuesOf(group)
+                .map((value, index) => (value === null ? null : [index, value]))
+                .filter(point => point !== null);
+            svg.appendChild(svgElement('polyline', {
+                points: points.map(([index, value]) => `${x(index)},${y(value)}`).join(' '),
+                fill: 'none', stroke: color, 'stroke-width': 2
+            }));
+            points.forEach(([index, value]) => {
+                const point = svgElement('circle', { cx: x(index), cy: y(value), r: 6, fill: color, class: 'chart-mark' });
+                this._attachTooltip(point, item, index, onSelect, group);
+                svg.appendChild(point);
+            });
+
+            if (group) {
+                const legendY = 16 + groupIndex * 20;
+                svg.appendChild(svgElement('rect', { x: width - 90, y: legendY - 10, width: 12, height: 12, fill: color }));
+                const legend = svgElement('text', { x: width - 72, y: legendY });
+                legend.textContent = group.name;
+                svg.appendChild(legend);
+            }
+        });
+        return svg;
+    }
+
+    // 饼图，图例列出各类别
+    _createPieChart(item, onSelect) {
+        const size = 300;
+        const radius = 120;
+        const center = size / 2;
+        const total = item.values.reduce((sum, value) => sum + value, 0);
+        const svg = svgElement('svg', { viewBox: `0 0 ${size + 220} ${size}`, class: 'interactive-chart' });
+
+        let angle = -Math.PI / 2;
+        item.values.forEach((value, index) => {
+            if (value <= 0) return;
+            const sweep = value / total * Math.PI * 2;
+            const color = CHART_COLORS[index % CHART_COLORS.length];
+            let slice;
+            if (sweep >= Math.PI * 2 - 1e-6) {
+                slice = svgElement('circle', { cx: center, cy: center, r: radius, fill: color });
+            } else {
+                const start = [center + radius * Math.cos(angle), center + radius * Math.sin(angle)];
+                const end = [center + radius * Math.cos(angle + sweep), center + radius * Math.sin(angle + sweep)];
+                const largeArc = sweep > Math.PI ? 1 : 0;
+                slice = svgElement('path', {
+                    d: `M ${center} ${center} L ${start.join(' ')} A ${radius} ${radius} 0 ${largeArc} 1 ${end.join(' ')} Z`,
+                    fill: color
+                });
+            }
+            slice.setAttribute('class', 'chart-mark');
+            this._attachTooltip(slice, item, index, onSelect);
+            svg.appendChild(slice);
+            angle += sweep;
+
+            const legendY = 20 + index * 24;
+            svg.appendChild(svgElement('rect', { x: size + 20, y: legendY - 12, width: 14, height: 14, fill: color }));
+            const legend = svgElement('text', { x: size + 42, y: legendY, 'dominant-baseline': 'auto' });
+            legend.textContent = `${item.labels[index]} (${value})`;
+            svg.appendChild(legend);
+        });
+        return svg;
+    }
+
+    // 悬停显示标签和数值，点击显示详情
+    _attachTooltip(element, item, index, onSelect, group = null) {
+        const tooltip = svgElement('title', {});
+        const value = group ? group.values[index] : item.values[index];
+        const prefix = group ? `${group.name}年 ` : '';
+        tooltip.textContent = `${prefix}${item.labels[index]}：${value} ${item.unit}`;
+        element.appendChild(tooltip);
+        element.addEventListener('click', () => onSelect(index, group));
+    }
+
+    _handleChartTopNChange(topN) {
+        if (this.onChartTopNChangeCallback) {
+            this.onChartTopNChangeCallback(topN);
+        }
+    }
+
+    // 回调参数为阅读时长图表需要显示的书目数量，调用方重新获取 get_chart_data 后再调用 displayChartData
+    onChartTopNChange(callback) {
+        this.onChartTopNChangeCallback = callback;
+    }
+
     _getChartLabel(chartName) {
         const labels = {
             'duration': '阅读时长分布',
             'monthly': '月度借阅趋势',
-            'category': '类别阅读比例'
+            'category': '类别阅读比例',
+            'year_over_year': '逐年借阅对比'
         };
         return labels[chartName] || chartName;
     }
@@ -26,6 +26,8 @@ document.addEventListener('DOMContentLoaded', async () => {
     createFileSelector();
     createAnalysisControl();
     createResultDisplay();
+    listenAnalysisProgress();
+    listenJobEvents();
 });
 
 // 创建文件选择器
@@ -59,18 +61,58 @@ function createAnalysisControl() {
 
     container.innerHTML = `
         <div class="analysis-control">
+            <label class="as-of-label">
+                统计日期
+                <input type="date" id="asOfDate" class="as-of-input">
+            </label>
             <button id="analyzeButton" class="analyze-button">开始分析</button>
+            <button id="diagnosticsButton" class="diagnostics-button" title="出现问题时导出日志和运行环境，发送给开发者">导出诊断包</button>
             <div id="progressContainer" class="progress-container" style="display: none;">
                 <div class="progress-bar">
                     <div id="progressFill" class="progress-fill" style="width: 0%;"></div>
                 </div>
                 <div id="progressText" class="progress-text">准备中...</div>
+                <button id="cancelJobButton" class="cancel-job-button" style="display: none;">取消</button>
             </div>
+            <details id="logPanel" class="log-panel">
+                <summary>运行日志</summary>
+                <div class="log-actions">
+                    <button id="refreshLogButton" class="log-button">刷新</button>
+                    <button id="openLogButton" class="log-button">打开日志文件</button>
+                </div>
+                <pre id="logContent" class="log-content"></pre>
+            </details>
         </div>
     `;
 
+    // 默认以当天为统计日期；指定日期后，之后重新生成的报告会员时长保持一致
+    const today = new Date();
+    today.setMinutes(today.getMinutes() - today.getTimezoneOffset());
+    document.getElementById('asOfDate').value = today.toISOString().slice(0, 10);
+
     // 添加事件监听器
     document.getElementById('analyzeButton').addEventListener('click', startAnalysis);
+    document.getElementById('cancelJobButton').addEventListener('click', cancelCurrentJob);
+    document.getElementById('diagnosticsButton').addEventListener('click', exportDiagnostics);
+    document.getElementById('logPanel').addEventListener('toggle', (event) => {
+        if (event.target.open) refreshLog();
+    });
+    document.getElementById('refreshLogButton').addEventListener('click', refreshLog);
+    document.getElementById('openLogButton').addEventListener('click', openLog);
+}
+
+// 会员时长的统计日期 (YYYY-MM-DD)，未填写时由后端使用当天
+function getAsOfDate() {
+    const input = document.getElementById('asOfDate');
+    return (input && input.value) || null;
+}
+
+// 会员时长的文字描述
+function formatMembershipDuration(duration) {
+    if (!duration || duration.status !== 'known') {
+        return '未知';
+    }
+    return `${duration.years}年${duration.months}个月${duration.days}天（共 ${duration.total_days} 天）`;
 }
 
 // 创建结果显示器
@@ -83,18 +125,22 @@ function createResultDisplay() {
             <div class="result-header">
                 <h3>分析结果</h3>
                 <button id="selectFileButton" class="select-file-button" style="display: none;">选择文件预览</button>
+                <button id="selectReaderButton" class="select-file-button" style="display: none;">选择读者预览</button>
             </div>
             <div id="statsCards" class="stats-cards"></div>
             <div id="chartsList" class="charts-list"></div>
             <div class="result-actions">
                 <button id="exportButton" class="export-button">导出报告</button>
+                <button id="exportDataButton" class="export-button">导出数据</button>
             </div>
         </div>
     `;
 
     // 添加事件监听器
     document.getElementById('exportButton').addEventListener('click', exportReport);
-    document.getElementById('selectFileButton').addEventListener('click', showFileSelector);
+    document.getElementById('exportDataButton').addEventListener('click', exportData);
+    document.getElementById('selectFileButton').addEventListener('click', () => showFileSelector('file'));
+    document.getElementById('selectReaderButton').addEventListener('click', () => showFileSelector('reader'));
 }
 
 // 全局变量存储文件信息
@@ -107,17 +153,24 @@ async function selectBorrowFiles() {
         const selected = await window.__TAURI__.dialog.open({
             multiple: true,
             filters: [{
-                name: 'Excel文件',
-                extensions: ['xlsx', 'xls']
+                name: '表格文件',
+                extensions: ['xlsx', 'xls', 'xlsb', 'ods', 'csv', 'tsv']
             }],
             title: '选择借阅数据文件'
         });
 
         if (selected && selected.length > 0) {
-            borrowFiles = selected.map(path => ({
-                name: path.split(/[/\\]/).pop(),
-                path: path
-            }));
+            // 列出各文件的工作表，默认只选中第一个工作表
+            const sheetMap = await window.__TAURI__.core.invoke('list_sheets', { paths: selected });
+            borrowFiles = selected.map(path => {
+                const sheets = sheetMap[path] || [];
+                return {
+                    name: path.split(/[/\\]/).pop(),
+                    path: path,
+                    sheets: sheets,
+                    selectedSheets: sheets.slice(0, 1)
+                };
+            });
             updateFileList();
         }
     } catch (error) {
@@ -132,8 +185,8 @@ async function selectReaderFile() {
         const selected = await window.__TAURI__.dialog.open({
             multiple: false,
             filters: [{
-                name: 'Excel文件',
-                extensions: ['xlsx', 'xls']
+                name: '表格文件',
+                extensions: ['xlsx', 'xls', 'xlsb', 'ods', 'csv', 'tsv']
             }],
             title: '选择读者清单文件'
         });
@@ -168,6 +221,9 @@ function updateFileList() {
             const li = document.createElement('li');
             li.textContent = file.name;
             li.title = file.path;
+            if (file.sheets.length > 1) {
+                li.appendChild(createSheetList(file));
+            }
             borrowList.appendChild(li);
         });
         fileList.appendChild(borrowList);
@@ -187,6 +243,46 @@ function updateFileList() {
     }
 }
 
+// 多工作表文件的工作表勾选列表，每个选中的工作表单独分析
+function createSheetList(file) {
+    const sheetList = document.createElement('div');
+    sheetList.className = 'sheet-list';
+
+    const selectAll = document.createElement('button');
+    selectAll.className = 'sheet-select-all';
+    selectAll.textContent = '全选';
+    selectAll.addEventListener('click', () => {
+        file.selectedSheets = file.sheets.slice();
+        updateFileList();
+    });
+    sheetList.appendChild(selectAll);
+
+    file.sheets.forEach(sheet => {
+        const label = document.createElement('label');
+        const checkbox = document.createElement('input');
+        checkbox.type = 'checkbox';
+        checkbox.checked = file.selectedSheets.includes(sheet);
+        checkbox.addEventListener('change', () => {
+            file.selectedSheets = file.sheets.filter(name =>
+                name === sheet ? checkbox.checked : file.selectedSheets.includes(name)
+            );
+        });
+        label.appendChild(checkbox);
+        label.appendChild(document.createTextNode(sheet));
+        sheetList.appendChild(label);
+    });
+    return sheetList;
+}
+
+// 工作表选择：按文件列出选中的工作表，文本文件不需要指定
+function buildSheetSelection() {
+    const files = {};
+    borrowFiles
+        .filter(file => file.sheets.length > 0)
+        .forEach(file => { files[file.path] = file.selectedSheets; });
+    return { scope: 'first', files };
+}
+
 // 开始分析
 async function startAnalysis() {
     // 验证文件选择
@@ -194,21 +290,45 @@ async function startAnalysis() {
         alert('请选择读者清单文件和至少一个借阅数据文件');
         return;
     }
+    const noSheet = borrowFiles.find(file => file.sheets.length > 0 && file.selectedSheets.length === 0);
+    if (noSheet) {
+        alert(`请为 ${noSheet.name} 至少选择一个工作表`);
+        return;
+    }
+    const sheetSelection = buildSheetSelection();
 
     try {
         // 显示进度条
         document.getElementById('progressContainer').style.display = 'block';
         document.getElementById('analyzeButton').disabled = true;
 
+        // 校验输入文件
+        updateProgress(5, '校验输入文件...');
+        const report = await window.__TAURI__.core.invoke('validate_inputs', {
+            readerListPath: readerListFile.path,
+            borrowPaths: borrowFiles.map(f => f.path),
+            sheetSelection
+        });
+        if (report.issues.length > 0) {
+            console.warn('输入文件校验问题:', report.issues);
+        }
+        if (!report.valid) {
+            alert('输入文件存在问题，请修正后重试：\n' + formatValidationIssues(report.issues));
+            updateProgress(0, '输入文件校验未通过');
+            return;
+        }
+
         // 初始化分析器
         updateProgress(10, '初始化分析器...');
         await window.__TAURI__.core.invoke('initialize_analyzer');
 
         // 开始分析
         updateProgress(30, '开始分析数据...');
-        const result = await window.__TAURI__.core.invoke('analyze_files', {
+        const result = await runJob('analyze_files', {
             readerListPath: readerListFile.path,
-            borrowPaths: borrowFiles.map(f => f.path)
+            borrowPaths: borrowFiles.map(f => f.path),
+            sheetSelection,
+            asOf: getAsOfDate()
         });
 
         // 显示结果
@@ -220,7 +340,7 @@ async function startAnalysis() {
         const outputDir = await selectOutputDirectory();
         if (outputDir) {
             updateProgress(90, '生成图表...');
-            const chartPaths = await window.__TAURI__.core.invoke('generate_charts', { outputDir });
+            const chartPaths = await runJob('generate_charts', { outputDir });
             displayCharts(chartPaths);
         }
         
@@ -233,19 +353,160 @@ async function startAnalysis() {
             selectFileButton.style.display = 'inline-block';
             selectFileButton.textContent = `选择文件预览 (共 ${fileCount} 个文件)`;
         }
+
+        // 合并数据中有多位读者时显示读者选择按钮
+        const readerCount = await window.__TAURI__.core.invoke('get_reader_count');
+        const selectReaderButton = document.getElementById('selectReaderButton');
+        if (selectReaderButton && readerCount > 1) {
+            selectReaderButton.style.display = 'inline-block';
+            selectReaderButton.textContent = `选择读者预览 (共 ${readerCount} 位读者)`;
+        }
         
         // 显示结果区域
         document.querySelector('.result-display').style.display = 'block';
         
     } catch (error) {
         console.error('分析过程中出现错误:', error);
-        alert('分析过程中出现错误: ' + error);
+        alert('分析过程中出现错误: ' + formatError(error));
         updateProgress(0, '分析失败');
     } finally {
         document.getElementById('analyzeButton').disabled = false;
     }
 }
 
+// 格式化校验问题（只显示前20条错误）
+function formatValidationIssues(issues) {
+    const errors = issues.filter(issue => issue.severity === 'error');
+    const lines = errors.slice(0, 20).map(issue => {
+        const fileName = issue.file.split(/[/\\]/).pop();
+        const location = [
+            fileName,
+            issue.sheet,
+            issue.row ? `第${issue.row}行` : '',
+            issue.column || ''
+        ].filter(Boolean).join(' / ');
+        return `${location}: ${issue.message}`;
+    });
+    if (errors.length > lines.length) {
+        lines.push(`……共 ${errors.length} 个问题`);
+    }
+    return lines.join('\n');
+}
+
+// 进度事件中各阶段的名称
+const PROGRESS_STAGES = {
+    load_data: '加载数据',
+    analyze: '分析',
+    generate_charts: '生成图表',
+    export_report: '导出报告',
+    export_reports: '批量导出',
+    export_data: '导出数据'
+};
+
+// 订阅后端的进度事件，按 current/total 更新进度条
+function listenAnalysisProgress() {
+    window.__TAURI__.event.listen('analysis://progress', ({ payload }) => {
+        const percent = payload.total > 0 ? Math.round(payload.current / payload.total * 100) : 0;
+        const container = document.getElementById('progressContainer');
+        if (container) container.style.display = 'block';
+
+        const stage = PROGRESS_STAGES[payload.stage];
+        const message = stage ? `[${stage}] ${payload.message}` : payload.message;
+        // 阶段完成时不自动隐藏进度条，由调用方在整个流程结束后更新
+        const progressFill = document.getElementById('progressFill');
+        const progressText = document.getElementById('progressText');
+        if (progressFill) progressFill.style.width = percent + '%';
+        if (progressText) progressText.textContent = `${message} (${payload.current}/${payload.total})`;
+    });
+}
+
+// 已结束但尚未被 runJob 取走的任务，以及等待任务结束的回调
+const finishedJobs = new Map();
+const jobWaiters = new Map();
+let currentJobId = null;
+
+// 订阅任务结束事件；事件可能先于命令返回任务ID到达，此时先保存起来
+function listenJobEvents() {
+    window.__TAURI__.event.listen('analysis://job', ({ payload }) => {
+        const resolve = jobWaiters.get(payload.id);
+        if (resolve) {
+            jobWaiters.delete(payload.id);
+            resolve(payload);
+        } else {
+            finishedJobs.set(payload.id, payload);
+        }
+    });
+}
+
+// 启动后台任务并等待其结束，返回命令的结果；任务失败或被取消时抛出错误
+async function runJob(command, args = {}) {
+    const jobId = await window.__TAURI__.core.invoke(command, args);
+    currentJobId = jobId;
+    const cancelButton = document.getElementById('cancelJobButton');
+    if (cancelButton) cancelButton.style.display = 'block';
+
+    try {
+        const job = finishedJobs.get(jobId)
+            ?? await new Promise(resolve => jobWaiters.set(jobId, resolve));
+        finishedJobs.delete(jobId);
+
+        if (job.status === 'completed') {
+            return job.result;
+        }
+        if (job.status === 'cancelled') {
+            const outputs = job.outputs.map(path => path.split(/[/\\]/).pop()).join('\n');
+            throw {
+                code: 'cancelled',
+                message: outputs ? `任务已取消，已生成的文件：\n${outputs}` : '任务已取消'
+            };
+        }
+        throw job.error;
+    } finally {
+        if (currentJobId === jobId) {
+            currentJobId = null;
+            if (cancelButton) cancelButton.style.display = 'none';
+        }
+    }
+}
+
+// 取消当前任务，任务在当前文件或阶段完成后停止
+async function cancelCurrentJob() {
+    if (!currentJobId) return;
+    try {
+        await window.__TAURI__.core.invoke('cancel_job', { jobId: currentJobId });
+        updateProgress(0, '正在取消...');
+    } catch (error) {
+        console.error('取消任务失败:', error);
+    }
+}
+
+// 各错误代码对应的处理建议，错误代码见 src-tauri/src/error.rs
+const ERROR_HINTS = {
+    not_initialized: '请重新点击"开始分析"',
+    data_not_loaded: '请先完成一次分析',
+    input_missing: '请检查文件是否存在，并重新选择文件',
+    schema_error: '请检查表头，或在表头映射配置中为缺少的列添加别名',
+    config_error: '请检查表头映射配置文件的格式',
+    python_exception: '请检查数据内容，或点击"导出诊断包"并发送给开发者',
+    internal: '请点击"导出诊断包"并发送给开发者',
+    io_error: '请检查文件是否被其他程序占用，以及是否有读写权限',
+    export_error: '请检查输出位置是否可写',
+    job_not_found: '任务记录已过期，请重新执行'
+};
+
+// 将后端返回的错误（{code, message, ...}）格式化为提示文字
+function formatError(error) {
+    if (!error || typeof error !== 'object') {
+        return String(error);
+    }
+    if (error instanceof Error) {
+        return error.message;
+    }
+    const text = error.message || error.code;
+    const hint = ERROR_HINTS[error.code];
+    return hint ? `${text}\n\n${hint}` : text;
+}
+
 // 更新进度
 function updateProgress(percent, message) {
     const progressFill = document.getElementById('progressFill');
@@ -273,8 +534,8 @@ function displayStats(stats) {
             <p>${stats.borrow_frequency} 次</p>
         </div>
         <div class="stat-card">
-            <h4>总借阅时长</h4>
-            <p>${stats.total_duration}</p>
+            <h4>会员时长</h4>
+            <p>${formatMembershipDuration(stats.total_duration)}</p>
         </div>
         <div class="stat-card">
             <h4>总价值</h4>
@@ -309,6 +570,35 @@ function displayStats(stats) {
             <p>${stats.longest_category}</p>
         </div>
     `;
+
+    // 未能在读者清单中唯一匹配的读者
+    const unresolved = stats.unresolved_readers || [];
+    if (unresolved.length > 0) {
+        // 姓名和证号来自表格数据，只作为文本插入
+        const card = document.createElement('div');
+        card.className = 'stat-card reader-warning';
+        const title = document.createElement('h4');
+        title.textContent = `读者清单匹配问题 (${unresolved.length})`;
+        card.appendChild(title);
+        unresolved.forEach(identity => {
+            const line = document.createElement('p');
+            line.textContent = describeReaderIdentity(identity);
+            card.appendChild(line);
+        });
+        container.appendChild(card);
+    }
+}
+
+// 描述读者匹配结果，返回纯文本（以 textContent 插入页面）
+function describeReaderIdentity(identity) {
+    const reader = identity.card_number
+        ? `${identity.name || ''}（证号 ${identity.card_number}）`
+        : (identity.name || '未填写姓名');
+    if (identity.status === 'ambiguous') {
+        const rows = identity.candidates.map(candidate => `第${candidate.row}行`).join('、');
+        return `${reader}：读者清单中有多名读者符合（${rows}），无法确定会员时长`;
+    }
+    return `${reader}：读者清单中没有该读者`;
 }
 
 // 显示图表
@@ -344,6 +634,13 @@ async function selectOutputDirectory() {
 // 导出报告 - 增强版支持多文件
 async function exportReport() {
     try {
+        // 检查是否有多位读者
+        const readerCount = await window.__TAURI__.core.invoke('get_reader_count');
+        if (readerCount > 1 && confirm(`检测到 ${readerCount} 位读者。\n\n点击"确定"为每位读者单独生成报告\n点击"取消"按文件生成报告`)) {
+            await exportMultipleReports('reader');
+            return;
+        }
+
         // 检查是否有多个文件
         const fileCount = await window.__TAURI__.core.invoke('get_file_count');
         
@@ -363,7 +660,50 @@ async function exportReport() {
         }
     } catch (error) {
         console.error('导出报告失败:', error);
-        alert('导出报告失败: ' + error);
+        alert('导出报告失败: ' + formatError(error));
+    }
+}
+
+// 导出诊断包：日志、运行环境、最近的错误和匿名化的输入表头
+async function exportDiagnostics() {
+    try {
+        const savePath = await window.__TAURI__.dialog.save({
+            filters: [{
+                name: 'ZIP压缩包',
+                extensions: ['zip']
+            }],
+            defaultPath: '诊断包.zip',
+            title: '保存诊断包'
+        });
+
+        if (savePath) {
+            await window.__TAURI__.core.invoke('export_diagnostics', { outputPath: savePath });
+            alert('诊断包导出成功: ' + savePath);
+        }
+    } catch (error) {
+        console.error('导出诊断包失败:', error);
+        alert('导出诊断包失败: ' + formatError(error));
+    }
+}
+
+// 显示最新日志的最后若干行
+async function refreshLog() {
+    const content = document.getElementById('logContent');
+    try {
+        content.textContent = await window.__TAURI__.core.invoke('tail_log', { lines: 200 });
+        content.scrollTop = content.scrollHeight;
+    } catch (error) {
+        content.textContent = '读取日志失败: ' + formatError(error);
+    }
+}
+
+// 用系统默认程序打开日志文件
+async function openLog() {
+    try {
+        await window.__TAURI__.core.invoke('open_log');
+    } catch (error) {
+        console.error('打开日志失败:', error);
+        alert('打开日志失败: ' + formatError(error));
     }
 }
 
@@ -374,23 +714,60 @@ async function exportSingleReport() {
             filters: [{
                 name: 'Word文档',
                 extensions: ['docx']
+            }, {
+                name: 'PDF文档',
+                extensions: ['pdf']
+            }, {
+                name: '网页',
+                extensions: ['html']
+            }, {
+                name: 'Markdown',
+                extensions: ['md']
             }],
             defaultPath: '读书报告.docx',
             title: '保存报告'
         });
         
         if (savePath) {
-            await window.__TAURI__.core.invoke('export_report', { outputPath: savePath });
+            // 报告格式由扩展名决定（.docx、.pdf、.html 或 .md）
+            await runJob('export_report', { outputPath: savePath });
             alert('报告导出成功: ' + savePath);
         }
     } catch (error) {
         console.error('导出单个报告失败:', error);
-        alert('导出单个报告失败: ' + error);
+        alert('导出单个报告失败: ' + formatError(error));
+    }
+}
+
+// 导出最近一次分析的完整结果（JSON 或 Excel 工作簿），供其他工具读取
+async function exportData() {
+    try {
+        const savePath = await window.__TAURI__.dialog.save({
+            filters: [{
+                name: 'JSON',
+                extensions: ['json']
+            }, {
+                name: 'Excel工作簿',
+                extensions: ['xlsx']
+            }],
+            defaultPath: '分析结果.json',
+            title: '保存分析结果'
+        });
+
+        if (savePath) {
+            // 导出格式由扩展名决定（.json 或 .xlsx）
+            await runJob('export_data', { outputPath: savePath });
+            alert('数据导出成功: ' + savePath);
+        }
+    } catch (error) {
+        console.error('导出数据失败:', error);
+        alert('导出数据失败: ' + formatError(error));
     }
 }
 
-// 导出多个报告
-async function exportMultipleReports() {
+// 导出多个报告，groupBy 为 'file'（每个文件一份）或 'reader'（每位读者一份），
+// format 为报告格式（docx/pdf/html/markdown）
+async function exportMultipleReports(groupBy = 'file', format = 'docx') {
     try {
         const outputDir = await window.__TAURI__.dialog.open({
             directory: true,
@@ -400,8 +777,11 @@ async function exportMultipleReports() {
         if (outputDir) {
             updateProgress(10, '开始批量生成报告...');
             
-            const reportPaths = await window.__TAURI__.core.invoke('export_reports_for_all_files', {
-                baseOutputDir: outputDir
+            const reportPaths = await runJob('export_reports_for_all_files', {
+                baseOutputDir: outputDir,
+                groupBy,
+                format,
+                asOf: getAsOfDate()
             });
             
             updateProgress(100, '所有报告生成完成');
@@ -411,14 +791,21 @@ async function exportMultipleReports() {
         }
     } catch (error) {
         console.error('导出多个报告失败:', error);
-        alert('导出多个报告失败: ' + error);
+        alert('导出多个报告失败: ' + formatError(error));
     }
 }
 
-// 新增：显示文件选择器，支持单文件分析预览
-async function showFileSelector() {
+// 文件/读者选择器使用的命令和文案
+const SELECTOR_MODES = {
+    file: { count: 'get_file_count', name: 'get_file_name', label: '文件' },
+    reader: { count: 'get_reader_count', name: 'get_reader_name', label: '读者' }
+};
+
+// 新增：显示文件选择器，支持单文件（或单个读者）分析预览
+async function showFileSelector(mode = 'file') {
+    const selectorMode = SELECTOR_MODES[mode];
     try {
-        const fileCount = await window.__TAURI__.core.invoke('get_file_count');
+        const fileCount = await window.__TAURI__.core.invoke(selectorMode.count);
         
         if (fileCount <= 1) {
             return; // 只有一个文件，不需要选择器
@@ -429,7 +816,7 @@ async function showFileSelector() {
         modal.className = 'file-selector-modal';
         modal.innerHTML = `
             <div class="modal-content">
-                <h3>选择要预览的文件</h3>
+                <h3>选择要预览的${selectorMode.label}</h3>
                 <div id="fileOptions" class="file-options"></div>
                 <div class="modal-buttons">
                     <button id="previewSelected" class="preview-button">预览选中文件</button>
@@ -477,7 +864,8 @@ async function showFileSelector() {
         // 填充文件选项
         const fileOptionsContainer = modal.querySelector('#fileOptions');
         for (let i = 0; i < fileCount; i++) {
-            const fileName = await window.__TAURI__.core.invoke('get_file_name', { fileIndex: i });
+            const fileName = await window.__TAURI__.core.invoke(selectorMode.name,
+                mode === 'reader' ? { readerIndex: i } : { fileIndex: i });
             const option = document.createElement('div');
             option.className = 'file-option';
             option.innerHTML = `
@@ -496,9 +884,13 @@ async function showFileSelector() {
             const selected = modal.querySelector('input[name="selectedFile"]:checked');
             if (selected) {
                 const fileIndex = parseInt(selected.value);
-                await previewSingleFile(fileIndex);
+                if (mode === 'reader') {
+                    await previewReader(fileIndex);
+                } else {
+                    await previewSingleFile(fileIndex);
+                }
             } else {
-                alert('请选择一个文件');
+                alert(`请选择一个${selectorMode.label}`);
             }
         });
         
@@ -509,7 +901,23 @@ async function showFileSelector() {
         
     } catch (error) {
         console.error('显示文件选择器失败:', error);
-        alert('显示文件选择器失败: ' + error);
+        alert('显示文件选择器失败: ' + formatError(error));
+    }
+}
+
+// 预览单个读者的分析结果
+async function previewReader(readerIndex) {
+    try {
+        updateProgress(10, '分析单个读者...');
+
+        const result = await runJob('analyze_reader', { readerIndex, asOf: getAsOfDate() });
+        const readerName = await window.__TAURI__.core.invoke('get_reader_name', { readerIndex });
+        showPreviewResult(result, readerName);
+
+        updateProgress(100, '读者分析完成');
+    } catch (error) {
+        console.error('预览读者失败:', error);
+        alert('预览读者失败: ' + formatError(error));
     }
 }
 
@@ -518,37 +926,41 @@ async function previewSingleFile(fileIndex) {
     try {
         updateProgress(10, '分析单个文件...');
         
-        const result = await window.__TAURI__.core.invoke('analyze_single_file', { fileIndex });
+        const result = await runJob('analyze_single_file', { fileIndex, asOf: getAsOfDate() });
         const fileName = await window.__TAURI__.core.invoke('get_file_name', { fileIndex });
-        
-        updateProgress(50, '显示分析结果...');
-        
-        // 更新结果显示区域标题
-        const resultDisplay = document.querySelector('.result-display h3');
-        if (resultDisplay) {
-            resultDisplay.textContent = `分析结果 - ${fileName}`;
-        }
-        
-        displayStats(result);
+        showPreviewResult(result, fileName);
         
         updateProgress(100, '单文件分析完成');
         
-        // 关闭模态框
-        const modal = document.querySelector('.file-selector-modal');
-        if (modal) {
-            document.body.removeChild(modal);
-            // 移除样式
-            const style = document.querySelector('style');
-            if (style && style.textContent.includes('.file-selector-modal')) {
-                document.head.removeChild(style);
-            }
-        }
-        
-        // 显示结果区域
-        document.querySelector('.result-display').style.display = 'block';
-        
     } catch (error) {
         console.error('预览单个文件失败:', error);
-        alert('预览单个文件失败: ' + error);
+        alert('预览单个文件失败: ' + formatError(error));
+    }
+}
+
+// 显示单个文件或读者的分析结果并关闭选择器
+function showPreviewResult(result, title) {
+    updateProgress(50, '显示分析结果...');
+    
+    // 更新结果显示区域标题
+    const resultDisplay = document.querySelector('.result-display h3');
+    if (resultDisplay) {
+        resultDisplay.textContent = `分析结果 - ${title}`;
+    }
+    
+    displayStats(result);
+    
+    // 关闭模态框
+    const modal = document.querySelector('.file-selector-modal');
+    if (modal) {
+        document.body.removeChild(modal);
+        // 移除样式
+        const style = document.querySelector('style');
+        if (style && style.textContent.includes('.file-selector-modal')) {
+            document.head.removeChild(style);
+        }
     }
+    
+    // 显示结果区域
+    document.querySelector('.result-display').style.display = 'block';
 }